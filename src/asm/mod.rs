@@ -0,0 +1,734 @@
+use std::{
+    collections::HashMap,
+    fmt,
+};
+
+use crate::{
+    core::{
+        cpu::{AddressingMode, CpuVariant},
+        opcodes::{self, Mnemonic, Opcode},
+    },
+    disasm::SymbolTable,
+};
+
+/// Address programs are assembled at without an `.org` (where `CPU::load_program` puts them)
+pub const DEFAULT_ORIGIN: u16 = 0x0600;
+
+/// Output of the assembler
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Assembled {
+    /// Address of the first byte
+    pub origin: u16,
+    /// Contiguous image starting at `origin` (gaps between `.org`s are filled with zeros)
+    pub bytes: Vec<u8>,
+    /// Labels and constants; local labels are named `global@local`
+    pub symbols: HashMap<String, u16>,
+}
+
+impl Assembled {
+    /// Return the value of a label or constant
+    pub fn symbol(&self, name: &str) -> Option<u16> {
+        self.symbols.get(name).copied()
+    }
+
+    /// Return the symbols as a `SymbolTable` for the disassembler
+    pub fn symbol_table(&self) -> SymbolTable {
+        let mut table = SymbolTable::new();
+        for (name, value) in &self.symbols {
+            table.insert(*value, name);
+        }
+        table
+    }
+}
+
+/// Assembler error, with the (1-based) line it occurred on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AsmError {
+    pub line: usize,
+    pub kind: AsmErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AsmErrorKind {
+    /// The line couldn't be parsed
+    Syntax(String),
+    UnknownInstruction(String),
+    UnknownDirective(String),
+    /// The instruction doesn't support the operand's addressing mode
+    InvalidAddressingMode(String),
+    UndefinedSymbol(String),
+    DuplicateSymbol(String),
+    /// A value doesn't fit its operand
+    ValueOutOfRange(i32),
+    /// A branch target is further than -128..=127 bytes away
+    BranchOutOfRange(i32),
+    /// An `.org` points before code that was already assembled
+    OrgBackwards(u16),
+}
+
+impl fmt::Display for AsmErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AsmErrorKind::Syntax(message) => write!(f, "syntax error: {}", message),
+            AsmErrorKind::UnknownInstruction(name) => write!(f, "unknown instruction {}", name),
+            AsmErrorKind::UnknownDirective(name) => write!(f, "unknown directive {}", name),
+            AsmErrorKind::InvalidAddressingMode(name) => write!(f, "invalid addressing mode for {}", name),
+            AsmErrorKind::UndefinedSymbol(name) => write!(f, "undefined symbol {}", name),
+            AsmErrorKind::DuplicateSymbol(name) => write!(f, "symbol {} is already defined", name),
+            AsmErrorKind::ValueOutOfRange(value) => write!(f, "value {} is out of range", value),
+            AsmErrorKind::BranchOutOfRange(offset) => write!(f, "branch offset {} is out of range", offset),
+            AsmErrorKind::OrgBackwards(addr) => write!(f, ".org ${:04X} is before the current address", addr),
+        }
+    }
+}
+
+impl fmt::Display for AsmError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.kind)
+    }
+}
+
+impl std::error::Error for AsmError {}
+
+/// Assemble `source` for the NMOS 6502
+///
+/// Supports labels (`label:`), local labels scoped to the previous label (`@loop:`), constants (`name = expr`),
+/// `.org`, `.byte`, `.word` and `.res`, and expressions with `+ - * / & | ^`, parentheses and the `<`/`>` (low/high byte) operators.
+/// `*` is the current address. Numbers are decimal, `$hex`, `%binary` or `'c'` characters.
+pub fn assemble(source: &str) -> Result<Assembled, AsmError> {
+    assemble_for(source, CpuVariant::default())
+}
+
+/// Assemble `source` with the instruction set of `variant`
+pub fn assemble_for(source: &str, variant: CpuVariant) -> Result<Assembled, AsmError> {
+    Assembler::new(variant).run(source)
+}
+
+/// Parsed expression
+#[derive(Debug, Clone)]
+enum Expr {
+    Number(i32),
+    Symbol(String),
+    Pc,
+    Unary(char, Box<Expr>),
+    Binary(char, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Index {
+    None,
+    X,
+    Y,
+}
+
+#[derive(Debug, Clone)]
+enum Operand {
+    None,
+    Accumulator,
+    Immediate(Expr),
+    Direct(Expr, Index),
+    Indirect(Expr),
+    IndirectX(Expr),
+    IndirectY(Expr),
+    /// Zero page address and branch target of BBR/BBS
+    BitBranch(Expr, Expr),
+}
+
+#[derive(Debug, Clone)]
+enum Statement {
+    Instruction {
+        name: String,
+        mnemonic: Mnemonic,
+        bit: Option<u8>,
+        operand: Operand,
+    },
+    Org(Expr),
+    Byte(Vec<ByteItem>),
+    Word(Vec<Expr>),
+    Res(Expr, Option<Expr>),
+}
+
+#[derive(Debug, Clone)]
+enum ByteItem {
+    Expr(Expr),
+    Text(Vec<u8>),
+}
+
+/// A parsed line
+struct Line {
+    number: usize,
+    label: Option<String>,
+    constant: Option<(String, Expr)>,
+    statement: Option<Statement>,
+}
+
+struct Assembler {
+    table: &'static [Opcode; 256],
+    symbols: HashMap<String, u16>,
+}
+
+impl Assembler {
+    fn new(variant: CpuVariant) -> Self {
+        Assembler {
+            table: opcodes::table(variant),
+            symbols: HashMap::new(),
+        }
+    }
+
+    fn run(mut self, source: &str) -> Result<Assembled, AsmError> {
+        let mut lines = Vec::new();
+        let mut scope = String::new();
+        for (i, text) in source.lines().enumerate() {
+            let line = self.parse_line(text, i + 1, &mut scope)
+                .map_err(|kind| AsmError { line: i + 1, kind })?;
+            lines.push(line);
+        }
+
+        // Pass 1: assign addresses to labels and pick addressing modes
+        let mut modes = Vec::with_capacity(lines.len());
+        let mut pc = DEFAULT_ORIGIN;
+        let mut origin = None;
+        for line in &lines {
+            let result = self.first_pass(line, &mut pc, &mut origin);
+            modes.push(result.map_err(|kind| AsmError { line: line.number, kind })?);
+        }
+
+        // Pass 2: emit bytes with every symbol known
+        let origin = origin.unwrap_or(DEFAULT_ORIGIN);
+        let mut bytes = Vec::new();
+        let mut pc = origin;
+        for (line, mode) in lines.iter().zip(modes) {
+            self.second_pass(line, mode, origin, &mut pc, &mut bytes)
+                .map_err(|kind| AsmError { line: line.number, kind })?;
+        }
+
+        Ok(Assembled {
+            origin,
+            bytes,
+            symbols: self.symbols,
+        })
+    }
+
+    fn define(&mut self, name: &str, value: u16) -> Result<(), AsmErrorKind> {
+        if self.symbols.insert(name.to_string(), value).is_some() {
+            return Err(AsmErrorKind::DuplicateSymbol(name.to_string()));
+        }
+        Ok(())
+    }
+
+    fn first_pass(&mut self, line: &Line, pc: &mut u16, origin: &mut Option<u16>) -> Result<Option<AddressingMode>, AsmErrorKind> {
+        if let Some(label) = &line.label {
+            self.define(label, *pc)?;
+        }
+        if let Some((name, expr)) = &line.constant {
+            let value = self.eval(expr, *pc)?;
+            self.define(name, value as u16)?;
+        }
+
+        let mut mode = None;
+        let size = match &line.statement {
+            None => 0,
+            Some(Statement::Instruction { name, mnemonic, bit, operand }) => {
+                let selected = self.select_mode(*mnemonic, *bit, operand, *pc)
+                    .ok_or_else(|| AsmErrorKind::InvalidAddressingMode(name.clone()))?;
+                mode = Some(selected);
+                self.table[self.find(*mnemonic, selected, *bit).unwrap() as usize].len as u16
+            },
+            Some(Statement::Org(expr)) => {
+                let addr = self.eval_u16(expr, *pc)?;
+                match origin {
+                    None => *origin = Some(addr),
+                    Some(origin) if addr < *origin || addr < *pc => return Err(AsmErrorKind::OrgBackwards(addr)),
+                    _ => (),
+                }
+                *pc = addr;
+                0
+            },
+            Some(Statement::Byte(items)) => items.iter().map(|item| match item {
+                ByteItem::Expr(_) => 1,
+                ByteItem::Text(text) => text.len() as u16,
+            }).sum(),
+            Some(Statement::Word(exprs)) => 2 * exprs.len() as u16,
+            Some(Statement::Res(count, _)) => self.eval_u16(count, *pc)?,
+        };
+
+        // Anything emitted before the first .org is placed at the default origin
+        if size > 0 && origin.is_none() {
+            *origin = Some(*pc);
+        }
+        *pc = pc.wrapping_add(size);
+        Ok(mode)
+    }
+
+    fn second_pass(&self, line: &Line, mode: Option<AddressingMode>, origin: u16, pc: &mut u16, bytes: &mut Vec<u8>) -> Result<(), AsmErrorKind> {
+        let start = bytes.len();
+
+        match &line.statement {
+            None => (),
+            Some(Statement::Instruction { mnemonic, bit, operand, .. }) => {
+                let mode = mode.unwrap();
+                bytes.push(self.find(*mnemonic, mode, *bit).unwrap());
+                self.encode(operand, mode, *pc, bytes)?;
+            },
+            Some(Statement::Org(expr)) => {
+                let addr = self.eval_u16(expr, *pc)?;
+                bytes.resize((addr - origin) as usize, 0x00);
+                *pc = addr;
+            },
+            Some(Statement::Byte(items)) => {
+                for item in items {
+                    match item {
+                        ByteItem::Expr(expr) => bytes.push(self.eval_u8(expr, *pc)?),
+                        ByteItem::Text(text) => bytes.extend_from_slice(text),
+                    }
+                }
+            },
+            Some(Statement::Word(exprs)) => {
+                for expr in exprs {
+                    bytes.extend_from_slice(&self.eval_u16(expr, *pc)?.to_le_bytes());
+                }
+            },
+            Some(Statement::Res(count, fill)) => {
+                let count = self.eval_u16(count, *pc)?;
+                let fill = match fill {
+                    Some(fill) => self.eval_u8(fill, *pc)?,
+                    None => 0x00,
+                };
+                bytes.extend(std::iter::repeat_n(fill, count as usize));
+            },
+        }
+
+        *pc = pc.wrapping_add((bytes.len() - start) as u16);
+        Ok(())
+    }
+
+    /// Write the operand bytes of an instruction at `pc`
+    fn encode(&self, operand: &Operand, mode: AddressingMode, pc: u16, bytes: &mut Vec<u8>) -> Result<(), AsmErrorKind> {
+        let expr = match operand {
+            Operand::None | Operand::Accumulator => return Ok(()),
+            Operand::Immediate(expr) => return self.eval_u8(expr, pc).map(|value| bytes.push(value)),
+            Operand::BitBranch(zp, target) => {
+                bytes.push(self.eval_zp(zp, pc)?);
+                bytes.push(self.branch_offset(target, pc.wrapping_add(3))?);
+                return Ok(());
+            },
+            Operand::Direct(expr, _) | Operand::Indirect(expr) | Operand::IndirectX(expr) | Operand::IndirectY(expr) => expr,
+        };
+
+        match mode {
+            AddressingMode::REL => bytes.push(self.branch_offset(expr, pc.wrapping_add(2))?),
+            AddressingMode::ZP0 | AddressingMode::ZPX | AddressingMode::ZPY |
+            AddressingMode::IDX | AddressingMode::IDY | AddressingMode::ZPI => bytes.push(self.eval_zp(expr, pc)?),
+            _ => bytes.extend_from_slice(&self.eval_u16(expr, pc)?.to_le_bytes()),
+        }
+        Ok(())
+    }
+
+    fn branch_offset(&self, target: &Expr, next: u16) -> Result<u8, AsmErrorKind> {
+        let offset = self.eval_u16(target, next)? as i32 - next as i32;
+        if !(-128..=127).contains(&offset) {
+            return Err(AsmErrorKind::BranchOutOfRange(offset));
+        }
+        Ok(offset as u8)
+    }
+
+    /// Pick the addressing mode for an operand, preferring zero page when the address is already known to fit
+    fn select_mode(&self, mnemonic: Mnemonic, bit: Option<u8>, operand: &Operand, pc: u16) -> Option<AddressingMode> {
+        use AddressingMode as A;
+
+        let zero_page = |expr: &Expr| matches!(self.eval(expr, pc), Ok(0..=0xFF));
+        let sized = |expr: &Expr, zp: AddressingMode, abs: AddressingMode| {
+            if zero_page(expr) { vec![zp, abs] } else { vec![abs, zp] }
+        };
+
+        let candidates = match operand {
+            Operand::None => vec![A::IMP, A::ACC],
+            Operand::Accumulator => vec![A::ACC],
+            Operand::Immediate(_) => vec![A::IMM],
+            Operand::Direct(expr, Index::None) => {
+                let mut modes = vec![A::REL];
+                modes.extend(sized(expr, A::ZP0, A::ABS));
+                modes
+            },
+            Operand::Direct(expr, Index::X) => sized(expr, A::ZPX, A::ABX),
+            Operand::Direct(expr, Index::Y) => sized(expr, A::ZPY, A::ABY),
+            Operand::Indirect(expr) => sized(expr, A::ZPI, A::IND),
+            Operand::IndirectX(expr) => sized(expr, A::IDX, A::IAX),
+            Operand::IndirectY(_) => vec![A::IDY],
+            Operand::BitBranch(_, _) => vec![A::ZPR],
+        };
+
+        candidates.into_iter().find(|mode| self.find(mnemonic, *mode, bit).is_some())
+    }
+
+    /// Find the opcode of an instruction, preferring documented opcodes
+    fn find(&self, mnemonic: Mnemonic, mode: AddressingMode, bit: Option<u8>) -> Option<u8> {
+        let matching = |illegal: bool| {
+            self.table.iter().enumerate().position(|(opcode, op)| {
+                op.mnemonic == mnemonic && op.mode == mode && op.illegal == illegal
+                    && bit.is_none_or(|bit| (opcode as u8 >> 4) & 0x07 == bit)
+            })
+        };
+        matching(false).or_else(|| matching(true)).map(|opcode| opcode as u8)
+    }
+
+    /// Look up a mnemonic by name, splitting off the bit number of RMB, SMB, BBR and BBS
+    fn mnemonic(&self, name: &str) -> Option<(Mnemonic, Option<u8>)> {
+        let upper = name.to_ascii_uppercase();
+        let (base, bit) = match upper.as_bytes() {
+            [.., digit @ b'0'..=b'7'] if upper.len() == 4 => (&upper[..3], Some(digit - b'0')),
+            _ => (upper.as_str(), None),
+        };
+
+        let mnemonic = self.table.iter()
+            .map(|op| op.mnemonic)
+            .find(|mnemonic| format!("{:?}", mnemonic) == base)?;
+
+        let has_bit = matches!(mnemonic, Mnemonic::RMB | Mnemonic::SMB | Mnemonic::BBR | Mnemonic::BBS);
+        (has_bit == bit.is_some()).then_some((mnemonic, bit))
+    }
+
+    fn eval(&self, expr: &Expr, pc: u16) -> Result<i32, AsmErrorKind> {
+        Ok(match expr {
+            Expr::Number(value) => *value,
+            Expr::Pc => pc as i32,
+            Expr::Symbol(name) => match self.symbols.get(name) {
+                Some(value) => *value as i32,
+                None => return Err(AsmErrorKind::UndefinedSymbol(name.clone())),
+            },
+            Expr::Unary(op, expr) => {
+                let value = self.eval(expr, pc)?;
+                match op {
+                    '-' => value.wrapping_neg(),
+                    '<' => value & 0xFF,
+                    '>' => (value >> 8) & 0xFF,
+                    _ => unreachable!(),
+                }
+            },
+            Expr::Binary(op, left, right) => {
+                let (left, right) = (self.eval(left, pc)?, self.eval(right, pc)?);
+                match op {
+                    '+' => left.wrapping_add(right),
+                    '-' => left.wrapping_sub(right),
+                    '*' => left.wrapping_mul(right),
+                    '/' => left.checked_div(right).ok_or_else(|| AsmErrorKind::Syntax("division by zero".to_string()))?,
+                    '&' => left & right,
+                    '|' => left | right,
+                    '^' => left ^ right,
+                    _ => unreachable!(),
+                }
+            },
+        })
+    }
+
+    fn eval_range(&self, expr: &Expr, pc: u16, min: i32, max: i32) -> Result<i32, AsmErrorKind> {
+        let value = self.eval(expr, pc)?;
+        if value < min || value > max {
+            return Err(AsmErrorKind::ValueOutOfRange(value));
+        }
+        Ok(value)
+    }
+
+    /// Evaluate a byte, allowing negative numbers
+    fn eval_u8(&self, expr: &Expr, pc: u16) -> Result<u8, AsmErrorKind> {
+        self.eval_range(expr, pc, -128, 0xFF).map(|value| value as u8)
+    }
+
+    fn eval_zp(&self, expr: &Expr, pc: u16) -> Result<u8, AsmErrorKind> {
+        self.eval_range(expr, pc, 0, 0xFF).map(|value| value as u8)
+    }
+
+    fn eval_u16(&self, expr: &Expr, pc: u16) -> Result<u16, AsmErrorKind> {
+        self.eval_range(expr, pc, 0, 0xFFFF).map(|value| value as u16)
+    }
+
+    fn parse_line(&self, text: &str, number: usize, scope: &mut String) -> Result<Line, AsmErrorKind> {
+        let mut rest = strip_comment(text).trim();
+        let mut line = Line { number, label: None, constant: None, statement: None };
+
+        // Label or constant
+        let name_len = identifier_len(rest);
+        if name_len > 0 {
+            let (name, after) = rest.split_at(name_len);
+            let after = after.trim_start();
+            if let Some(after) = after.strip_prefix(':') {
+                line.label = Some(scoped(name, scope));
+                if !name.starts_with('@') {
+                    *scope = name.to_string();
+                }
+                rest = after.trim();
+            } else if let Some(value) = after.strip_prefix('=') {
+                line.constant = Some((scoped(name, scope), parse_expr(value, scope)?));
+                return Ok(line);
+            }
+        }
+
+        if rest.is_empty() {
+            return Ok(line);
+        }
+
+        let (word, operand) = match rest.find(char::is_whitespace) {
+            Some(i) => (&rest[..i], rest[i..].trim()),
+            None => (rest, ""),
+        };
+
+        line.statement = Some(if word.starts_with('.') {
+            self.parse_directive(word, operand, scope)?
+        } else {
+            let (mnemonic, bit) = self.mnemonic(word)
+                .ok_or_else(|| AsmErrorKind::UnknownInstruction(word.to_string()))?;
+            Statement::Instruction {
+                name: word.to_ascii_uppercase(),
+                mnemonic,
+                bit,
+                operand: parse_operand(operand, scope)?,
+            }
+        });
+        Ok(line)
+    }
+
+    fn parse_directive(&self, word: &str, operand: &str, scope: &str) -> Result<Statement, AsmErrorKind> {
+        let args = split_args(operand);
+        let exprs = || args.iter().map(|arg| parse_expr(arg, scope)).collect::<Result<Vec<_>, _>>();
+
+        Ok(match word.to_ascii_lowercase().as_str() {
+            ".org" => Statement::Org(parse_expr(operand, scope)?),
+            ".byte" => Statement::Byte(args.iter().map(|arg| {
+                match arg.strip_prefix('"').and_then(|arg| arg.strip_suffix('"')) {
+                    Some(text) => Ok(ByteItem::Text(text.as_bytes().to_vec())),
+                    None => parse_expr(arg, scope).map(ByteItem::Expr),
+                }
+            }).collect::<Result<_, _>>()?),
+            ".word" => Statement::Word(exprs()?),
+            ".res" => {
+                let mut exprs = exprs()?.into_iter();
+                match (exprs.next(), exprs.next(), exprs.next()) {
+                    (Some(count), fill, None) => Statement::Res(count, fill),
+                    _ => return Err(AsmErrorKind::Syntax(".res takes a count and an optional fill value".to_string())),
+                }
+            },
+            _ => return Err(AsmErrorKind::UnknownDirective(word.to_string())),
+        })
+    }
+}
+
+/// Qualify local labels with the label they belong to
+fn scoped(name: &str, scope: &str) -> String {
+    if name.starts_with('@') {
+        format!("{}{}", scope, name)
+    } else {
+        name.to_string()
+    }
+}
+
+/// Remove a `;` comment, ignoring semicolons in quotes
+fn strip_comment(text: &str) -> &str {
+    let mut quote = None;
+    for (i, c) in text.char_indices() {
+        match (c, quote) {
+            ('"' | '\'', None) => quote = Some(c),
+            (c, Some(q)) if c == q => quote = None,
+            (';', None) => return &text[..i],
+            _ => (),
+        }
+    }
+    text
+}
+
+/// Return the length of the identifier (or local `@identifier`) at the start of `text`
+fn identifier_len(text: &str) -> usize {
+    let body = text.strip_prefix('@').unwrap_or(text);
+    if !body.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        return 0;
+    }
+    let len = body.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_')).unwrap_or(body.len());
+    len + text.len() - body.len()
+}
+
+/// Split comma separated arguments, ignoring commas in parentheses and quotes
+fn split_args(text: &str) -> Vec<&str> {
+    let mut args = Vec::new();
+    let mut depth = 0;
+    let mut quote = None;
+    let mut start = 0;
+    for (i, c) in text.char_indices() {
+        match (c, quote) {
+            ('"' | '\'', None) => quote = Some(c),
+            (c, Some(q)) if c == q => quote = None,
+            ('(', None) => depth += 1,
+            (')', None) => depth -= 1,
+            (',', None) if depth == 0 => {
+                args.push(text[start..i].trim());
+                start = i + 1;
+            },
+            _ => (),
+        }
+    }
+    if !text.trim().is_empty() {
+        args.push(text[start..].trim());
+    }
+    args
+}
+
+/// Return the contents of `text` if it's entirely wrapped in one pair of parentheses
+fn parenthesized(text: &str) -> Option<&str> {
+    let inner = text.strip_prefix('(')?.strip_suffix(')')?;
+    let mut depth = 0;
+    for c in inner.chars() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return None,
+            ')' => depth -= 1,
+            _ => (),
+        }
+    }
+    Some(inner)
+}
+
+fn parse_index(text: &str) -> Option<Index> {
+    match text.to_ascii_uppercase().as_str() {
+        "X" => Some(Index::X),
+        "Y" => Some(Index::Y),
+        _ => None,
+    }
+}
+
+fn parse_operand(text: &str, scope: &str) -> Result<Operand, AsmErrorKind> {
+    if text.is_empty() {
+        return Ok(Operand::None);
+    }
+    if text.eq_ignore_ascii_case("a") {
+        return Ok(Operand::Accumulator);
+    }
+    if let Some(value) = text.strip_prefix('#') {
+        return Ok(Operand::Immediate(parse_expr(value, scope)?));
+    }
+
+    let expr = |text| parse_expr(text, scope);
+    let args = split_args(text);
+    match args.as_slice() {
+        [base, index] => match (parenthesized(base), parse_index(index)) {
+            (Some(inner), Some(Index::Y)) => Ok(Operand::IndirectY(expr(inner)?)),
+            (_, Some(index)) => Ok(Operand::Direct(expr(base)?, index)),
+            (_, None) => Ok(Operand::BitBranch(expr(base)?, expr(index)?)),
+        },
+        [single] => match parenthesized(single) {
+            Some(inner) => match split_args(inner).as_slice() {
+                [base, index] if parse_index(index) == Some(Index::X) => Ok(Operand::IndirectX(expr(base)?)),
+                [_] => Ok(Operand::Indirect(expr(inner)?)),
+                _ => Err(AsmErrorKind::Syntax(format!("invalid operand {}", text))),
+            },
+            None => Ok(Operand::Direct(expr(single)?, Index::None)),
+        },
+        _ => Err(AsmErrorKind::Syntax(format!("invalid operand {}", text))),
+    }
+}
+
+fn parse_expr(text: &str, scope: &str) -> Result<Expr, AsmErrorKind> {
+    let mut parser = ExprParser { chars: text.trim().chars().collect(), pos: 0, scope };
+    let expr = parser.binary(0)?;
+    parser.skip_whitespace();
+    if parser.pos < parser.chars.len() {
+        return Err(AsmErrorKind::Syntax(format!("unexpected {:?} in expression", parser.chars[parser.pos])));
+    }
+    Ok(expr)
+}
+
+/// Binary operators by increasing precedence
+const PRECEDENCE: [&[char]; 5] = [&['|'], &['^'], &['&'], &['+', '-'], &['*', '/']];
+
+/// Recursive descent expression parser
+struct ExprParser<'a> {
+    chars: Vec<char>,
+    pos: usize,
+    scope: &'a str,
+}
+
+impl ExprParser<'_> {
+    fn skip_whitespace(&mut self) {
+        while self.chars.get(self.pos).is_some_and(|c| c.is_whitespace()) {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_whitespace();
+        self.chars.get(self.pos).copied()
+    }
+
+    fn binary(&mut self, level: usize) -> Result<Expr, AsmErrorKind> {
+        if level == PRECEDENCE.len() {
+            return self.unary();
+        }
+
+        let mut left = self.binary(level + 1)?;
+        while let Some(op) = self.peek().filter(|c| PRECEDENCE[level].contains(c)) {
+            self.pos += 1;
+            let right = self.binary(level + 1)?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Expr, AsmErrorKind> {
+        match self.peek() {
+            Some(op @ ('-' | '<' | '>')) => {
+                self.pos += 1;
+                Ok(Expr::Unary(op, Box::new(self.unary()?)))
+            },
+            _ => self.primary(),
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr, AsmErrorKind> {
+        self.skip_whitespace();
+        let start = self.pos;
+        let c = self.peek().ok_or_else(|| AsmErrorKind::Syntax("expected expression".to_string()))?;
+        self.pos += 1;
+
+        match c {
+            '*' => Ok(Expr::Pc),
+            '(' => {
+                let expr = self.binary(0)?;
+                if self.peek() != Some(')') {
+                    return Err(AsmErrorKind::Syntax("expected )".to_string()));
+                }
+                self.pos += 1;
+                Ok(expr)
+            },
+            '\'' => match (self.chars.get(self.pos), self.chars.get(self.pos + 1)) {
+                (Some(c), Some('\'')) => {
+                    self.pos += 2;
+                    Ok(Expr::Number(*c as i32))
+                },
+                _ => Err(AsmErrorKind::Syntax("invalid character literal".to_string())),
+            },
+            '$' => self.number(16),
+            '%' => self.number(2),
+            '0'..='9' => {
+                self.pos -= 1;
+                self.number(10)
+            },
+            _ => {
+                let rest: String = self.chars[start..].iter().collect();
+                let len = identifier_len(&rest);
+                if len == 0 {
+                    return Err(AsmErrorKind::Syntax(format!("unexpected {:?} in expression", c)));
+                }
+                self.pos = start + len;
+                Ok(Expr::Symbol(scoped(&rest[..len], self.scope)))
+            },
+        }
+    }
+
+    fn number(&mut self, radix: u32) -> Result<Expr, AsmErrorKind> {
+        let start = self.pos;
+        while self.chars.get(self.pos).is_some_and(|c| c.is_digit(radix)) {
+            self.pos += 1;
+        }
+        let digits: String = self.chars[start..self.pos].iter().collect();
+        i32::from_str_radix(&digits, radix)
+            .map(Expr::Number)
+            .map_err(|_| AsmErrorKind::Syntax(format!("invalid number {:?}", digits)))
+    }
+}
@@ -1,42 +1,64 @@
+use crate::core::{
+    memory::Memory,
+    state::{StateError, StateReader, StateWriter},
+};
+
+/// Flat 64KB of RAM, the default `Memory` of the CPU
 pub struct Bus {
     ram: [u8; 64 * 1024],
 }
 
 impl Bus {
     pub fn new() -> Self {
+        Self::filled(0x00)
+    }
+
+    // Construct bus with every byte of RAM set to value
+    pub fn filled(value: u8) -> Self {
         Bus {
-            ram: [0; 64 * 1024],
+            ram: [value; 64 * 1024],
         }
     }
 
+    // Construct bus with random RAM contents, like real hardware at power-on
+    pub fn randomized() -> Self {
+        let mut bus = Self::new();
+        rand::Rng::fill(&mut rand::thread_rng(), &mut bus.ram[..]);
+        bus
+    }
+
+    // Return entire RAM
+    pub fn get_ram(&self) -> [u8; 64 * 1024] {
+        self.ram
+    }
+}
+
+impl Memory for Bus {
+    // Read from RAM at addr
+    fn read(&mut self, addr: u16) -> u8 {
+        self.ram[addr as usize]
+    }
+
     // Write data to addr in RAM
-    pub fn write(&mut self, addr: u16, data: u8) {
+    fn write(&mut self, addr: u16, data: u8) {
         self.ram[addr as usize] = data;
     }
 
-    // Read from RAM at addr
-    pub fn read(&mut self, addr: u16) -> u8 {
+    fn peek(&self, addr: u16) -> u8 {
         self.ram[addr as usize]
     }
 
-    // Write u16 data (little endian)
-    pub fn write_u16(&mut self, addr: u16, data: u16) {
-        let hi = (data >> 8) as u8;
-        let lo = (data & 0xff) as u8;
-        self.write(addr, lo);
-        self.write(addr + 1, hi);
+    fn poke(&mut self, addr: u16, data: u8) -> bool {
+        self.ram[addr as usize] = data;
+        true
     }
 
-    // Read u16 data (little endian)
-    pub fn read_u16(&mut self, addr: u16) -> u16 {
-        let lo = self.read(addr) as u16;
-        let hi = self.read(addr + 1) as u16;
-        (hi << 8) | lo
+    fn save_state(&self, writer: &mut StateWriter) {
+        writer.write_bytes(&self.ram);
     }
 
-    // Return entire RAM
-    pub fn get_ram(&self) -> [u8; 64 * 1024] {
-        self.ram
+    fn load_state(&mut self, reader: &mut StateReader) -> Result<(), StateError> {
+        reader.read_bytes_into(&mut self.ram)
     }
 }
 
@@ -1,76 +1,298 @@
-use crate::core::bus::Bus;
+use std::{collections::HashMap, fmt, ops::RangeInclusive};
+
+use crate::{
+    core::{
+        bus::Bus,
+        debug::{Access, Breakpoint, StopReason, Watchpoint},
+        memory::Memory,
+        opcodes::{self, Mnemonic},
+        rewind::{CoreState, Rewind, RewindConfig},
+        state::{StateError, StateReader, StateWriter},
+        trace::{Registers, TraceEntry, Tracer},
+    },
+    disasm::Instruction,
+};
 
-#[allow(dead_code)]
 const NMI_VECTOR: u16 = 0xFFFA;
 const RESET_VECTOR: u16 = 0xFFFC;
 const IRQ_VECTOR: u16 = 0xFFFE;
 
+/// 6502 CPU, generic over the `Memory` it is connected to
 #[allow(clippy::upper_case_acronyms)]
-pub struct CPU {
-    a: u8,          // accumulator
-    x: u8,          // X register
-    y: u8,          // Y register
-    sp: u8,         // stack pointer
-    pc: u16,        // program counter
-    sr: u8,         // status register
-    opcode: u8,     // current opcode
-    bus: Bus,       // memory bus
+pub struct CPU<M: Memory = Bus> {
+    a: u8,                     // accumulator
+    x: u8,                     // X register
+    y: u8,                     // Y register
+    sp: u8,                    // stack pointer
+    pc: u16,                   // program counter
+    sr: u8,                    // status register
+    opcode: u8,                // current opcode
+    bus: M,                    // memory bus
+    cycles: u64,               // total cycles executed
+    page_crossed: bool,        // whether the current instruction crossed a page boundary
+    extra_cycles: u8,          // penalty cycles charged by the current instruction (e.g. taken branches)
+    jammed: bool,              // whether a JAM opcode has halted the CPU
+    stopped: bool,             // whether an STP opcode has stopped the clock
+    waiting: bool,             // whether a WAI opcode is waiting for an interrupt
+    irq_lines: u8,             // IRQ sources currently asserting the line, one bit each
+    nmi_pending: bool,         // whether an NMI edge has been latched and not yet serviced
+    irq_masked: bool,          // interrupt disable flag, as seen by the poll at the end of the last instruction
+    instruction_pc: u16,       // address of the current instruction
+    fault: Option<CpuError>,   // error raised while executing the current instruction
+    breakpoints: HashMap<u16, Breakpoint>,    // breakpoints that stop `step` before executing their address
+    resume_pc: Option<u16>,                   // breakpoint that was just reported, and is skipped by the next step
+    watchpoints: Vec<Watchpoint>,             // memory ranges that stop `step` after an instruction accesses them
+    watch_hit: Option<(u16, Access, u8)>,     // first watched access (address, kind, value) of the current step
+    tracer: Option<Tracer>,                   // records executed instructions
+    rewind: Option<Rewind>,                   // history of executed instructions, for `step_back`
+    config: CpuConfig,                        // configuration
 }
 
+/// Processor variant, selecting the instruction set and its quirks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CpuVariant {
+    /// Original NMOS 6502, including undocumented opcodes and the JMP indirect page wrap bug
+    #[default]
+    Nmos6502,
+    /// Ricoh 2A03 (NES): an NMOS 6502 without decimal mode
+    Ricoh2A03,
+    /// CMOS 65C02: new instructions and addressing modes, bugs fixed, unused opcodes are NOPs
+    Cmos65C02,
+    /// Rockwell R65C02: 65C02 with RMB, SMB, BBR and BBS
+    Rockwell65C02,
+    /// WDC W65C02S: Rockwell instructions plus WAI and STP
+    Wdc65C02,
+}
+
+impl CpuVariant {
+    /// Return whether this is one of the CMOS variants
+    pub fn is_cmos(&self) -> bool {
+        matches!(self, CpuVariant::Cmos65C02 | CpuVariant::Rockwell65C02 | CpuVariant::Wdc65C02)
+    }
+}
+
+/// CPU configuration
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CpuConfig {
+    /// Trap the NMOS's undocumented opcodes instead of executing them
+    ///
+    /// The 65C02's unused opcodes are documented NOPs, so they're always executed.
+    pub strict: bool,
+    /// Whether the decimal flag enables BCD arithmetic (always disabled on the 2A03)
+    pub decimal_mode: bool,
+    /// Processor variant
+    pub variant: CpuVariant,
+    /// Register contents set by `CPU::power_on`
+    pub power_on: PowerOnState,
+    /// Let the stack pointer wrap around like real hardware, instead of reporting `CpuError::StackWrap`
+    pub allow_stack_wrap: bool,
+}
+
+/// Register contents at power-on, before the reset sequence runs
+///
+/// RAM contents are up to the bus (see `Bus::randomized` and `Bus::filled`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PowerOnState {
+    /// All registers cleared, so SP ends up at 0xFD after reset
+    #[default]
+    Zeroed,
+    /// Random register contents, like real hardware
+    Randomized,
+    /// Fixed register contents
+    Fixed { a: u8, x: u8, y: u8, sp: u8, sr: u8 },
+}
+
+impl Default for CpuConfig {
+    fn default() -> Self {
+        CpuConfig {
+            strict: false,
+            decimal_mode: true,
+            variant: CpuVariant::default(),
+            power_on: PowerOnState::default(),
+            allow_stack_wrap: true,
+        }
+    }
+}
+
+/// Result of a successful `CPU::step`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepOutcome {
+    /// An instruction was executed, taking `cycles`
+    Executed { cycles: u8 },
+    /// An interrupt was serviced instead of executing an instruction
+    Interrupt { cycles: u8 },
+    /// WAI is waiting for an interrupt, nothing was executed
+    Waiting,
+    /// STP has stopped the clock until reset, nothing was executed
+    Stopped,
+}
+
+impl StepOutcome {
+    /// Return the number of cycles taken
+    pub fn cycles(&self) -> u8 {
+        match self {
+            StepOutcome::Executed { cycles } | StepOutcome::Interrupt { cycles } => *cycles,
+            StepOutcome::Waiting | StepOutcome::Stopped => 0,
+        }
+    }
+}
+
+/// Execution errors reported by `CPU::step`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CpuError {
+    /// An undocumented opcode was trapped in strict mode; PC is left on it and nothing was executed
+    IllegalOpcode { pc: u16, opcode: u8 },
+    /// A JAM opcode has halted the CPU, only a reset recovers it
+    Jammed { pc: u16 },
+    /// PC reached a breakpoint; the next step executes the instruction there
+    BreakpointHit { pc: u16 },
+    /// The instruction at `pc` pushed or pulled past the end of the stack (see `CpuConfig::allow_stack_wrap`)
+    StackWrap { pc: u16, sp: u8 },
+    /// The instruction at `pc` accessed a watched address; it was fully executed
+    WatchpointHit { pc: u16, addr: u16, access: Access, value: u8 },
+}
+
+impl fmt::Display for CpuError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CpuError::IllegalOpcode { pc, opcode } => write!(f, "illegal opcode 0x{:02X} at 0x{:04X}", opcode, pc),
+            CpuError::Jammed { pc } => write!(f, "CPU jammed at 0x{:04X}", pc),
+            CpuError::BreakpointHit { pc } => write!(f, "breakpoint hit at 0x{:04X}", pc),
+            CpuError::StackWrap { pc, sp } => write!(f, "stack wrapped (SP 0x{:02X}) at 0x{:04X}", sp, pc),
+            CpuError::WatchpointHit { pc, addr, access, value } => {
+                write!(f, "watchpoint hit at 0x{:04X}: {:?} 0x{:02X} at 0x{:04X}", pc, access, value, addr)
+            },
+        }
+    }
+}
+
+impl std::error::Error for CpuError {}
+
 /// Implement CPU's core functionality
-impl CPU {
-    pub fn new(bus: Bus) -> Self {
+impl<M: Memory> CPU<M> {
+    pub fn new(bus: M) -> Self {
+        Self::with_config(bus, CpuConfig::default())
+    }
+
+    /// Construct CPU of a specific `CpuVariant`
+    pub fn with_variant(bus: M, variant: CpuVariant) -> Self {
+        Self::with_config(bus, CpuConfig { variant, ..CpuConfig::default() })
+    }
+
+    /// Construct CPU with a custom `CpuConfig`
+    pub fn with_config(bus: M, config: CpuConfig) -> Self {
         CPU {
             a: 0x00,
             x: 0x00,
             y: 0x00,
-            sp: 0xFF,
+            sp: 0x00,
             pc: 0x0000,
             sr: 0x00,
             opcode: 0x00,
             bus,
+            cycles: 0,
+            page_crossed: false,
+            extra_cycles: 0,
+            jammed: false,
+            stopped: false,
+            waiting: false,
+            irq_lines: 0,
+            nmi_pending: false,
+            irq_masked: false,
+            instruction_pc: 0x0000,
+            fault: None,
+            breakpoints: HashMap::new(),
+            resume_pc: None,
+            watchpoints: Vec::new(),
+            watch_hit: None,
+            tracer: None,
+            rewind: None,
+            config,
         }
     }
 
     /// Write `u8` value to `u16` address
     pub fn write(&mut self, addr: u16, data: u8) {
+        self.record_write(addr);
         self.bus.write(addr, data);
+        if !self.watchpoints.is_empty() {
+            self.watch(addr, Access::Write, data);
+        }
     }
 
     /// Read `u8` value from `u16` address
     pub fn read(&mut self, addr: u16) -> u8 {
-        self.bus.read(addr)
+        let data = self.bus.read(addr);
+        if !self.watchpoints.is_empty() {
+            self.watch(addr, Access::Read, data);
+        }
+        data
     }
 
     /// Write `u16` data from `u16` address (little endian)
     pub fn write_u16(&mut self, addr: u16, data: u16) {
+        self.record_write(addr);
+        self.record_write(addr.wrapping_add(1));
         self.bus.write_u16(addr, data);
+        if !self.watchpoints.is_empty() {
+            self.watch(addr, Access::Write, data as u8);
+            self.watch(addr.wrapping_add(1), Access::Write, (data >> 8) as u8);
+        }
     }
 
     /// Read `u16` data from `u16` address (little endian)
     pub fn read_u16(&mut self, addr: u16) -> u16 {
-        self.bus.read_u16(addr)
+        let data = self.bus.read_u16(addr);
+        if !self.watchpoints.is_empty() {
+            self.watch(addr, Access::Read, data as u8);
+            self.watch(addr.wrapping_add(1), Access::Read, (data >> 8) as u8);
+        }
+        data
+    }
+
+    // Count the access against every matching watchpoint, and remember the first one
+    fn watch(&mut self, addr: u16, access: Access, value: u8) {
+        for watchpoint in &mut self.watchpoints {
+            if watchpoint.range.contains(&addr) && watchpoint.kind.matches(access) {
+                watchpoint.hits += 1;
+                self.watch_hit.get_or_insert((addr, access, value));
+            }
+        }
     }
 
     /// Push `u8` value to stack
+    ///
+    /// The stack pointer points to the next free slot, so write first and decrement after.
     pub fn push(&mut self, data: u8) {
-        self.sp -= 0x01;
         self.write(0x0100 + (self.sp as u16), data);
+        if self.sp == 0x00 {
+            self.stack_wrapped();
+        }
+        self.sp = self.sp.wrapping_sub(1);
     }
 
-    /// Push `u16` value to stack
+    /// Push `u16` value to stack (high byte first)
     pub fn push_u16(&mut self, data: u16) {
         let lo = data as u8;
         let hi = (data >> 8) as u8;
-        self.push(lo);
         self.push(hi);
+        self.push(lo);
     }
 
     /// Pop `u8` value off stack
     pub fn pop(&mut self) -> u8 {
-        let data = self.read(0x0100 + (self.sp as u16));
-        self.sp += 1;
-        data
+        if self.sp == 0xFF {
+            self.stack_wrapped();
+        }
+        self.sp = self.sp.wrapping_add(1);
+        self.read(0x0100 + (self.sp as u16))
+    }
+
+    // Report the stack pointer wrapping around, unless allowed by the configuration
+    fn stack_wrapped(&mut self) {
+        if !self.config.allow_stack_wrap && self.fault.is_none() {
+            self.fault = Some(CpuError::StackWrap { pc: self.instruction_pc, sp: self.sp });
+        }
     }
 
     /// Pop `u16` value off stack (little endian)
@@ -105,112 +327,604 @@ impl CPU {
         self.set_flag(Flags::N, (value & 0x80) == 0x80);
     }
     
-    /// Reset CPU
+    /// Power on CPU: set registers according to `CpuConfig::power_on`, then reset
+    pub fn power_on(&mut self) {
+        let (a, x, y, sp, sr) = match self.config.power_on {
+            PowerOnState::Zeroed => (0, 0, 0, 0, 0),
+            PowerOnState::Randomized => rand::random(),
+            PowerOnState::Fixed { a, x, y, sp, sr } => (a, x, y, sp, sr),
+        };
+        self.a = a;
+        self.x = x;
+        self.y = y;
+        self.sp = sp;
+        self.sr = sr;
+        self.cycles = 0;
+
+        self.reset();
+    }
+
+    /// Reset CPU (warm reset)
+    ///
+    /// Runs the 7 cycle reset sequence. This is an interrupt sequence whose stack writes are turned into reads, so SP
+    /// drops by 3 without anything being written. A, X and Y are left alone, and PC is loaded from the reset vector.
     pub fn reset(&mut self) {
-        self.pc = RESET_VECTOR;
-
-        // Reset all registers (except program counter)
-        self.a = 0;
-        self.x = 0;
-        self.y = 0;
-        self.sp = 0xFF;
-        self.sr = 0;
+        self.sp = self.sp.wrapping_sub(3);
+        self.set_flag(Flags::I, true);
+        self.set_flag(Flags::U, true);
+
+        // The 65C02 also leaves decimal mode
+        if self.config.variant.is_cmos() {
+            self.set_flag(Flags::D, false);
+        }
+
+        self.pc = self.read_u16(RESET_VECTOR);
         self.opcode = 0;
+        self.jammed = false;
+        self.stopped = false;
+        self.waiting = false;
+        self.nmi_pending = false;
+        self.irq_masked = true;
+        self.cycles += 7;
     }
 
     /// Start clock loop
-    pub fn clock(&mut self) {
-        // CPU starts from the 16-bit reset vector at `0xFFFC` (`RESET_VECTOR`)
-        if self.pc == RESET_VECTOR {
-            let program_start: u16 = self.read_u16(self.pc);
-            self.pc = program_start;
-        }
-
+    ///
+    /// Runs until a BRK is fetched (without executing it), WAI or STP, or an error.
+    pub fn clock(&mut self) -> Result<(), CpuError> {
         loop {
-            self.opcode = self.read(self.pc);
-            self.pc += 1;
-
-            if self.opcode == 0x00 { break }
-
-            match self.opcode {
-                0x00 => self.BRK(AddressingMode::IMP), 0x01 => self.ORA(AddressingMode::ZPX), 0x05 => self.ORA(AddressingMode::ZP0), 0x06 => self.ASL(AddressingMode::ZP0), 0x08 => self.PHP(AddressingMode::IMP), 0x09 => self.ORA(AddressingMode::IMM), 0x0A => self.ASL(AddressingMode::ACC), 0x0D => self.ORA(AddressingMode::ABS), 0x0E => self.ASL(AddressingMode::ABS),
-                0x10 => self.BPL(AddressingMode::REL), 0x11 => self.ORA(AddressingMode::ZPY), 0x15 => self.ORA(AddressingMode::ZPX), 0x16 => self.ASL(AddressingMode::ZPX), 0x18 => self.CLC(AddressingMode::IMP), 0x1D => self.ORA(AddressingMode::ABX), 0x1E => self.ASL(AddressingMode::ABX),
-                0x20 => self.JSR(AddressingMode::ABS), 0x21 => self.AND(AddressingMode::ZPX), 0x24 => self.BIT(AddressingMode::ZP0), 0x25 => self.AND(AddressingMode::ZP0), 0x26 => self.ROL(AddressingMode::ZP0), 0x28 => self.PLP(AddressingMode::IMP), 0x29 => self.AND(AddressingMode::IMM), 0x2A => self.ROL(AddressingMode::ACC), 0x2C => self.BIT(AddressingMode::ABS), 0x2D => self.AND(AddressingMode::ABS), 0x2E => self.ROL(AddressingMode::ABS),
-                0x30 => self.BMI(AddressingMode::REL), 0x31 => self.AND(AddressingMode::ZPX), 0x35 => self.AND(AddressingMode::ZPX), 0x36 => self.ROL(AddressingMode::ZPX), 0x38 => self.SEC(AddressingMode::IMP), 0x39 => self.AND(AddressingMode::ABY), 0x3D => self.AND(AddressingMode::ABX), 0x3E => self.ROL(AddressingMode::ABX),
-                0x40 => self.RTI(AddressingMode::IMP), 0x41 => self.EOR(AddressingMode::ZPX), 0x45 => self.EOR(AddressingMode::ZP0), 0x46 => self.LSR(AddressingMode::ZP0), 0x48 => self.PHA(AddressingMode::IMP), 0x49 => self.EOR(AddressingMode::IMM), 0x4A => self.LSR(AddressingMode::ACC), 0x4C => self.JMP(AddressingMode::ABS), 0x4D => self.EOR(AddressingMode::ABS), 0x4E => self.LSR(AddressingMode::ABS),
-                0x50 => self.BVC(AddressingMode::REL), 0x51 => self.EOR(AddressingMode::ZPY), 0x55 => self.EOR(AddressingMode::ZPY), 0x56 => self.LSR(AddressingMode::ZPX), 0x58 => self.CLI(AddressingMode::IMP), 0x59 => self.EOR(AddressingMode::ABY), 0x5D => self.EOR(AddressingMode::ABX), 0x5E => self.LSR(AddressingMode::ABX),
-                0x60 => self.RTS(AddressingMode::IMP), 0x61 => self.ADC(AddressingMode::ZPX), 0x65 => self.ADC(AddressingMode::ZP0), 0x66 => self.ROR(AddressingMode::ZP0), 0x68 => self.PLA(AddressingMode::IMP), 0x69 => self.ADC(AddressingMode::IMM), 0x6A => self.ROR(AddressingMode::ACC), 0x6C => self.JMP(AddressingMode::IND), 0x6D => self.ADC(AddressingMode::ABS), 0x6E => self.ROR(AddressingMode::ABS),
-                0x70 => self.BVS(AddressingMode::REL), 0x71 => self.ADC(AddressingMode::ZPY), 0x75 => self.ADC(AddressingMode::ZPX), 0x78 => self.SEI(AddressingMode::IMP), 0x79 => self.ADC(AddressingMode::ABY), 0x7D => self.ADC(AddressingMode::ABX), 0x7E => self.ROR(AddressingMode::ABX),
-                0x80 => self.NOP(AddressingMode::IMM), 0x81 => self.STA(AddressingMode::ZPX), 0x84 => self.STY(AddressingMode::ZP0), 0x85 => self.STA(AddressingMode::ZP0), 0x86 => self.STX(AddressingMode::ZP0), 0x88 => self.DEY(AddressingMode::IMP), 0x8A => self.TXA(AddressingMode::IMP), 0x8C => self.STY(AddressingMode::ABS), 0x8D => self.STA(AddressingMode::ABS), 0x8E => self.STX(AddressingMode::ABS),
-                0x90 => self.BCC(AddressingMode::REL), 0x91 => self.STA(AddressingMode::IDY), 0x94 => self.STY(AddressingMode::ZPX), 0x95 => self.STA(AddressingMode::ZPX), 0x96 => self.STX(AddressingMode::ZPY), 0x98 => self.TYA(AddressingMode::IMP), 0x99 => self.STA(AddressingMode::ABY), 0x9A => self.TXS(AddressingMode::IMP), 0x9D => self.STA(AddressingMode::ABX),
-                0xA0 => self.LDY(AddressingMode::IMM), 0xA1 => self.LDA(AddressingMode::ZPX), 0xA2 => self.LDX(AddressingMode::IMM), 0xA4 => self.LDY(AddressingMode::ZP0), 0xA5 => self.LDA(AddressingMode::ZP0), 0xA6 => self.LDX(AddressingMode::ZP0), 0xA8 => self.TAY(AddressingMode::IMP), 0xA9 => self.LDA(AddressingMode::IMM), 0xAA => self.TAX(AddressingMode::IMP), 0xAC => self.LDY(AddressingMode::ABS), 0xAD => self.LDA(AddressingMode::ABS), 0xAE => self.LDX(AddressingMode::ABS),
-                0xB0 => self.BCS(AddressingMode::REL), 0xB1 => self.LDA(AddressingMode::ZPY), 0xB4 => self.LDY(AddressingMode::ZPX), 0xB5 => self.LDA(AddressingMode::ZPX), 0xB6 => self.LDX(AddressingMode::ZPY), 0xB8 => self.CLV(AddressingMode::IMP), 0xB9 => self.LDA(AddressingMode::ABY), 0xBA => self.TSX(AddressingMode::IMP), 0xBC => self.LDY(AddressingMode::ABX), 0xBD => self.LDA(AddressingMode::ABX), 0xBE => self.LDX(AddressingMode::ABY),
-                0xC0 => self.CPY(AddressingMode::IMM), 0xC1 => self.CMP(AddressingMode::ZPX), 0xC4 => self.CPY(AddressingMode::ZP0), 0xC5 => self.CMP(AddressingMode::ZP0), 0xC6 => self.DEC(AddressingMode::ZP0), 0xC8 => self.CLV(AddressingMode::IMP), 0xC9 => self.CMP(AddressingMode::IMM), 0xCA => self.DEX(AddressingMode::IMP), 0xCC => self.CPY(AddressingMode::ABS), 0xCD => self.CMP(AddressingMode::ABS), 0xCE => self.DEC(AddressingMode::ABS),
-                0xD0 => self.BNE(AddressingMode::REL), 0xD1 => self.CMP(AddressingMode::ZPY), 0xD5 => self.CMP(AddressingMode::ZPX), 0xD6 => self.DEC(AddressingMode::ZPX), 0xD8 => self.CLD(AddressingMode::IMP), 0xD9 => self.CMP(AddressingMode::ABY), 0xDD => self.CMP(AddressingMode::ABX), 0xDE => self.DEC(AddressingMode::ABX),
-                0xE0 => self.CPX(AddressingMode::IMM), 0xE1 => self.SBC(AddressingMode::ZPX), 0xE4 => self.CPX(AddressingMode::ZP0), 0xE5 => self.SBC(AddressingMode::ZP0), 0xE6 => self.INC(AddressingMode::ZP0), 0xE8 => self.INX(AddressingMode::IMP), 0xE9 => self.SBC(AddressingMode::IMM), 0xEA => self.NOP(AddressingMode::IMP), 0xEC => self.CPX(AddressingMode::ABS), 0xED => self.SBC(AddressingMode::ABS), 0xEE => self.INC(AddressingMode::ABS),
-                0xF0 => self.BEQ(AddressingMode::REL), 0xF1 => self.SBC(AddressingMode::ZPY), 0xF5 => self.SBC(AddressingMode::ZPX), 0xF6 => self.INC(AddressingMode::ZPX), 0xF8 => self.SED(AddressingMode::IMP), 0xF9 => self.SBC(AddressingMode::ABY), 0xFD => self.SBC(AddressingMode::ABX), 0xFE => self.INC(AddressingMode::ABX),
-                _ => self.XXX(AddressingMode::IMP),
+            if self.at_brk() {
+                self.fetch();
+                return Ok(());
+            }
+
+            match self.step()? {
+                StepOutcome::Waiting | StepOutcome::Stopped => return Ok(()),
+                StepOutcome::Executed { .. } | StepOutcome::Interrupt { .. } => {},
             }
         }
     }
 
     /// Advance CPU by 1 'step' (=/= 1 clock cycle)
-    pub fn advance(&mut self) {
-        // CPU starts from the 16-bit reset vector at 0xFFFC
-        if self.pc == RESET_VECTOR {
-            let program_start: u16 = self.read_u16(self.pc);
-            self.pc = program_start;
-        } else {
-            self.opcode = self.read(self.pc);
-            self.pc += 1;
+    ///
+    /// Like `step`, except that a BRK is fetched but not executed.
+    pub fn advance(&mut self) -> Result<(), CpuError> {
+        if self.at_brk() {
+            self.fetch();
+            return Ok(());
+        }
+
+        self.step().map(|_| ())
+    }
+
+    /// Execute a single instruction, or service a pending interrupt instead
+    ///
+    /// A waiting or stopped CPU does nothing and takes no cycles.
+    pub fn step(&mut self) -> Result<StepOutcome, CpuError> {
+        if self.rewind.is_none() {
+            return self.step_unrecorded();
+        }
 
-            if self.opcode == 0x00 {
-                return
+        let snapshot = self.rewind.as_ref().is_some_and(Rewind::needs_snapshot).then(|| self.save_state());
+        let before = self.core_state();
+        if let Some(rewind) = &mut self.rewind {
+            rewind.begin(before, snapshot);
+        }
+
+        let outcome = self.step_unrecorded();
+        let after = self.core_state();
+        if let Some(rewind) = &mut self.rewind {
+            rewind.commit(after);
+        }
+        outcome
+    }
+
+    fn step_unrecorded(&mut self) -> Result<StepOutcome, CpuError> {
+        if self.jammed {
+            return Err(CpuError::Jammed { pc: self.pc });
+        }
+        if self.stopped {
+            return Ok(StepOutcome::Stopped);
+        }
+
+        let cycles = self.poll_interrupts();
+        if cycles > 0 {
+            return Ok(StepOutcome::Interrupt { cycles });
+        }
+        if self.waiting {
+            return Ok(StepOutcome::Waiting);
+        }
+
+        if self.resume_pc != Some(self.pc) && self.breakpoints.contains_key(&self.pc) {
+            let registers = self.registers();
+            let stop = self.breakpoints.get_mut(&self.pc).is_some_and(|breakpoint| breakpoint.hit(&registers));
+            if stop {
+                self.resume_pc = Some(self.pc);
+                return Err(CpuError::BreakpointHit { pc: self.pc });
             }
+        }
+        self.resume_pc = None;
+        self.watch_hit = None;
+
+        let traced = match &self.tracer {
+            Some(tracer) if tracer.traces(self.pc) => {
+                Some((Instruction::decode_at(&self.bus, self.pc, self.config.variant), self.registers(), self.cycles))
+            },
+            _ => None,
+        };
 
-            match self.opcode {
-                0x00 => self.BRK(AddressingMode::IMP), 0x01 => self.ORA(AddressingMode::ZPX), 0x05 => self.ORA(AddressingMode::ZP0), 0x06 => self.ASL(AddressingMode::ZP0), 0x08 => self.PHP(AddressingMode::IMP), 0x09 => self.ORA(AddressingMode::IMM), 0x0A => self.ASL(AddressingMode::ACC), 0x0D => self.ORA(AddressingMode::ABS), 0x0E => self.ASL(AddressingMode::ABS),
-                0x10 => self.BPL(AddressingMode::REL), 0x11 => self.ORA(AddressingMode::ZPY), 0x15 => self.ORA(AddressingMode::ZPX), 0x16 => self.ASL(AddressingMode::ZPX), 0x18 => self.CLC(AddressingMode::IMP), 0x1D => self.ORA(AddressingMode::ABX), 0x1E => self.ASL(AddressingMode::ABX),
-                0x20 => self.JSR(AddressingMode::ABS), 0x21 => self.AND(AddressingMode::ZPX), 0x24 => self.BIT(AddressingMode::ZP0), 0x25 => self.AND(AddressingMode::ZP0), 0x26 => self.ROL(AddressingMode::ZP0), 0x28 => self.PLP(AddressingMode::IMP), 0x29 => self.AND(AddressingMode::IMM), 0x2A => self.ROL(AddressingMode::ACC), 0x2C => self.BIT(AddressingMode::ABS), 0x2D => self.AND(AddressingMode::ABS), 0x2E => self.ROL(AddressingMode::ABS),
-                0x30 => self.BMI(AddressingMode::REL), 0x31 => self.AND(AddressingMode::ZPX), 0x35 => self.AND(AddressingMode::ZPX), 0x36 => self.ROL(AddressingMode::ZPX), 0x38 => self.SEC(AddressingMode::IMP), 0x39 => self.AND(AddressingMode::ABY), 0x3D => self.AND(AddressingMode::ABX), 0x3E => self.ROL(AddressingMode::ABX),
-                0x40 => self.RTI(AddressingMode::IMP), 0x41 => self.EOR(AddressingMode::ZPX), 0x45 => self.EOR(AddressingMode::ZP0), 0x46 => self.LSR(AddressingMode::ZP0), 0x48 => self.PHA(AddressingMode::IMP), 0x49 => self.EOR(AddressingMode::IMM), 0x4A => self.LSR(AddressingMode::ACC), 0x4C => self.JMP(AddressingMode::ABS), 0x4D => self.EOR(AddressingMode::ABS), 0x4E => self.LSR(AddressingMode::ABS),
-                0x50 => self.BVC(AddressingMode::REL), 0x51 => self.EOR(AddressingMode::ZPY), 0x55 => self.EOR(AddressingMode::ZPY), 0x56 => self.LSR(AddressingMode::ZPX), 0x58 => self.CLI(AddressingMode::IMP), 0x59 => self.EOR(AddressingMode::ABY), 0x5D => self.EOR(AddressingMode::ABX), 0x5E => self.LSR(AddressingMode::ABX),
-                0x60 => self.RTS(AddressingMode::IMP), 0x61 => self.ADC(AddressingMode::ZPX), 0x65 => self.ADC(AddressingMode::ZP0), 0x66 => self.ROR(AddressingMode::ZP0), 0x68 => self.PLA(AddressingMode::IMP), 0x69 => self.ADC(AddressingMode::IMM), 0x6A => self.ROR(AddressingMode::ACC), 0x6C => self.JMP(AddressingMode::IND), 0x6D => self.ADC(AddressingMode::ABS), 0x6E => self.ROR(AddressingMode::ABS),
-                0x70 => self.BVS(AddressingMode::REL), 0x71 => self.ADC(AddressingMode::ZPY), 0x75 => self.ADC(AddressingMode::ZPX), 0x78 => self.SEI(AddressingMode::IMP), 0x79 => self.ADC(AddressingMode::ABY), 0x7D => self.ADC(AddressingMode::ABX), 0x7E => self.ROR(AddressingMode::ABX),
-                0x80 => self.NOP(AddressingMode::IMM), 0x81 => self.STA(AddressingMode::ZPX), 0x84 => self.STY(AddressingMode::ZP0), 0x85 => self.STA(AddressingMode::ZP0), 0x86 => self.STX(AddressingMode::ZP0), 0x88 => self.DEY(AddressingMode::IMP), 0x8A => self.TXA(AddressingMode::IMP), 0x8C => self.STY(AddressingMode::ABS), 0x8D => self.STA(AddressingMode::ABS), 0x8E => self.STX(AddressingMode::ABS),
-                0x90 => self.BCC(AddressingMode::REL), 0x91 => self.STA(AddressingMode::IDY), 0x94 => self.STY(AddressingMode::ZPX), 0x95 => self.STA(AddressingMode::ZPX), 0x96 => self.STX(AddressingMode::ZPY), 0x98 => self.TYA(AddressingMode::IMP), 0x99 => self.STA(AddressingMode::ABY), 0x9A => self.TXS(AddressingMode::IMP), 0x9D => self.STA(AddressingMode::ABX),
-                0xA0 => self.LDY(AddressingMode::IMM), 0xA1 => self.LDA(AddressingMode::ZPX), 0xA2 => self.LDX(AddressingMode::IMM), 0xA4 => self.LDY(AddressingMode::ZP0), 0xA5 => self.LDA(AddressingMode::ZP0), 0xA6 => self.LDX(AddressingMode::ZP0), 0xA8 => self.TAY(AddressingMode::IMP), 0xA9 => self.LDA(AddressingMode::IMM), 0xAA => self.TAX(AddressingMode::IMP), 0xAC => self.LDY(AddressingMode::ABS), 0xAD => self.LDA(AddressingMode::ABS), 0xAE => self.LDX(AddressingMode::ABS),
-                0xB0 => self.BCS(AddressingMode::REL), 0xB1 => self.LDA(AddressingMode::ZPY), 0xB4 => self.LDY(AddressingMode::ZPX), 0xB5 => self.LDA(AddressingMode::ZPX), 0xB6 => self.LDX(AddressingMode::ZPY), 0xB8 => self.CLV(AddressingMode::IMP), 0xB9 => self.LDA(AddressingMode::ABY), 0xBA => self.TSX(AddressingMode::IMP), 0xBC => self.LDY(AddressingMode::ABX), 0xBD => self.LDA(AddressingMode::ABX), 0xBE => self.LDX(AddressingMode::ABY),
-                0xC0 => self.CPY(AddressingMode::IMM), 0xC1 => self.CMP(AddressingMode::ZPX), 0xC4 => self.CPY(AddressingMode::ZP0), 0xC5 => self.CMP(AddressingMode::ZP0), 0xC6 => self.DEC(AddressingMode::ZP0), 0xC8 => self.CLV(AddressingMode::IMP), 0xC9 => self.CMP(AddressingMode::IMM), 0xCA => self.DEX(AddressingMode::IMP), 0xCC => self.CPY(AddressingMode::ABS), 0xCD => self.CMP(AddressingMode::ABS), 0xCE => self.DEC(AddressingMode::ABS),
-                0xD0 => self.BNE(AddressingMode::REL), 0xD1 => self.CMP(AddressingMode::ZPY), 0xD5 => self.CMP(AddressingMode::ZPX), 0xD6 => self.DEC(AddressingMode::ZPX), 0xD8 => self.CLD(AddressingMode::IMP), 0xD9 => self.CMP(AddressingMode::ABY), 0xDD => self.CMP(AddressingMode::ABX), 0xDE => self.DEC(AddressingMode::ABX),
-                0xE0 => self.CPX(AddressingMode::IMM), 0xE1 => self.SBC(AddressingMode::ZPX), 0xE4 => self.CPX(AddressingMode::ZP0), 0xE5 => self.SBC(AddressingMode::ZP0), 0xE6 => self.INC(AddressingMode::ZP0), 0xE8 => self.INX(AddressingMode::IMP), 0xE9 => self.SBC(AddressingMode::IMM), 0xEA => self.NOP(AddressingMode::IMP), 0xEC => self.CPX(AddressingMode::ABS), 0xED => self.SBC(AddressingMode::ABS), 0xEE => self.INC(AddressingMode::ABS),
-                0xF0 => self.BEQ(AddressingMode::REL), 0xF1 => self.SBC(AddressingMode::ZPY), 0xF5 => self.SBC(AddressingMode::ZPX), 0xF6 => self.INC(AddressingMode::ZPX), 0xF8 => self.SED(AddressingMode::IMP), 0xF9 => self.SBC(AddressingMode::ABY), 0xFD => self.SBC(AddressingMode::ABX), 0xFE => self.INC(AddressingMode::ABX),
-                _ => self.XXX(AddressingMode::IMP),
+        self.fetch();
+        let outcome = self.execute();
+
+        if let (Some((instruction, before, total_cycles)), Ok(StepOutcome::Executed { cycles })) = (traced, &outcome) {
+            let entry = TraceEntry { instruction, before, after: self.registers(), cycles: *cycles, total_cycles };
+            if let Some(tracer) = &mut self.tracer {
+                tracer.record(entry);
             }
         }
+
+        match (outcome, self.watch_hit.take()) {
+            (Ok(StepOutcome::Executed { .. }), Some((addr, access, value))) => {
+                Err(CpuError::WatchpointHit { pc: self.instruction_pc, addr, access, value })
+            },
+            (outcome, _) => outcome,
+        }
     }
 
-    // TODO: Interrupt request (IRQ)
-    // If interrupt disable flag clear, push PC and SR to stack and get next location from IRQ vector
-    #[allow(unused)]
-    fn irq(&mut self) {
-        if !self.get_flag(Flags::I) {
-            self.push_u16(self.pc);
-            self.push(self.sr);
-            self.pc = self.read_u16(IRQ_VECTOR)
+    /// Step until `predicate` holds before an instruction, or something else stops execution
+    pub fn run_until(&mut self, mut predicate: impl FnMut(&Self) -> bool) -> StopReason {
+        loop {
+            if predicate(self) {
+                return StopReason::Predicate;
+            }
+            if let Some(reason) = self.run_step() {
+                return reason;
+            }
         }
     }
 
-    // TODO: Non-maskeable interrupt (NMI)
-    // Push PC and SR to stack and get next location from NMI vector
-    #[allow(unused)]
-    fn nmi(&mut self) {
-        todo!();
+    /// Step for at least `cycles` cycles, or until something else stops execution
+    pub fn run_for(&mut self, cycles: u64) -> StopReason {
+        let end = self.cycles.saturating_add(cycles);
+        while self.cycles < end {
+            if let Some(reason) = self.run_step() {
+                return reason;
+            }
+        }
+        StopReason::CycleLimit
+    }
+
+    // Step once, returning why execution should stop, if it should
+    fn run_step(&mut self) -> Option<StopReason> {
+        match self.step() {
+            Ok(StepOutcome::Executed { .. } | StepOutcome::Interrupt { .. }) => None,
+            Ok(StepOutcome::Waiting) => Some(StopReason::Waiting),
+            Ok(StepOutcome::Stopped) => Some(StopReason::Stopped),
+            Err(error) => Some(error.into()),
+        }
+    }
+
+    /// Keep a history of executed instructions, so `step_back` and `rewind` can undo them
+    ///
+    /// Any previous history is discarded.
+    pub fn enable_rewind(&mut self, config: RewindConfig) {
+        self.rewind = Some(Rewind::new(config));
+    }
+
+    pub fn disable_rewind(&mut self) {
+        self.rewind = None;
+    }
+
+    pub fn rewind_history(&self) -> Option<&Rewind> {
+        self.rewind.as_ref()
+    }
+
+    /// Undo the last instruction (or interrupt), returning whether there was one in the history that can be undone
+    ///
+    /// Memory is restored by poking the overwritten bytes back (see `Memory::poke`), without side effects. Instructions
+    /// that wrote to anything else, like memory-mapped I/O, can't be stepped back over; `rewind` to a snapshot instead.
+    pub fn step_back(&mut self) -> bool {
+        let Some((before, writes)) = self.rewind.as_mut().and_then(Rewind::pop) else {
+            return false;
+        };
+        for (addr, old) in writes.into_iter().rev() {
+            self.bus.poke(addr, old);
+        }
+        self.restore_core_state(before);
+        // Don't report a breakpoint at the instruction that was stepped back to
+        self.resume_pc = Some(self.pc);
+        true
+    }
+
+    /// Jump back at least `cycles` cycles, to the latest snapshot (see `RewindConfig::interval`) before that point
+    ///
+    /// Returns whether there was such a snapshot in the history.
+    pub fn rewind(&mut self, cycles: u64) -> bool {
+        let target = self.cycles.saturating_sub(cycles);
+        let Some(state) = self.rewind.as_mut().and_then(|rewind| rewind.pop_to(target)) else {
+            return false;
+        };
+        // The snapshot was saved by this CPU, so it always loads
+        let restored = self.restore_state(&state).is_ok();
+        self.resume_pc = Some(self.pc);
+        restored
+    }
+
+    // Record the byte at `addr` before the current instruction overwrites it, if rewind is enabled
+    fn record_write(&mut self, addr: u16) {
+        if let Some(rewind) = &mut self.rewind {
+            let old = self.bus.peek(addr);
+            // Poking the current value back changes nothing, and tells whether the byte can be restored later
+            if self.bus.poke(addr, old) {
+                rewind.record_write(addr, old);
+            } else {
+                rewind.record_device_write();
+            }
+        }
+    }
+
+    fn core_state(&self) -> CoreState {
+        CoreState {
+            a: self.a,
+            x: self.x,
+            y: self.y,
+            sp: self.sp,
+            pc: self.pc,
+            sr: self.sr,
+            opcode: self.opcode,
+            instruction_pc: self.instruction_pc,
+            cycles: self.cycles,
+            jammed: self.jammed,
+            stopped: self.stopped,
+            waiting: self.waiting,
+            irq_lines: self.irq_lines,
+            nmi_pending: self.nmi_pending,
+            irq_masked: self.irq_masked,
+        }
+    }
+
+    fn restore_core_state(&mut self, state: CoreState) {
+        self.a = state.a;
+        self.x = state.x;
+        self.y = state.y;
+        self.sp = state.sp;
+        self.pc = state.pc;
+        self.sr = state.sr;
+        self.opcode = state.opcode;
+        self.instruction_pc = state.instruction_pc;
+        self.cycles = state.cycles;
+        self.jammed = state.jammed;
+        self.stopped = state.stopped;
+        self.waiting = state.waiting;
+        self.irq_lines = state.irq_lines;
+        self.nmi_pending = state.nmi_pending;
+        self.irq_masked = state.irq_masked;
+    }
+
+    /// Add an unconditional breakpoint at `addr`
+    pub fn add_breakpoint(&mut self, addr: u16) {
+        self.set_breakpoint(Breakpoint::new(addr));
+    }
+
+    /// Add `breakpoint`, replacing any previous one at its address
+    pub fn set_breakpoint(&mut self, breakpoint: Breakpoint) {
+        self.breakpoints.insert(breakpoint.addr, breakpoint);
+    }
+
+    /// Remove the breakpoint at `addr`, returning whether there was one
+    pub fn remove_breakpoint(&mut self, addr: u16) -> bool {
+        self.breakpoints.remove(&addr).is_some()
+    }
+
+    pub fn breakpoint(&self, addr: u16) -> Option<&Breakpoint> {
+        self.breakpoints.get(&addr)
+    }
+
+    /// Return the breakpoints, by address
+    pub fn breakpoints(&self) -> &HashMap<u16, Breakpoint> {
+        &self.breakpoints
+    }
+
+    /// Add `watchpoint`, returning its index
+    pub fn add_watchpoint(&mut self, watchpoint: Watchpoint) -> usize {
+        self.watchpoints.push(watchpoint);
+        self.watchpoints.len() - 1
+    }
+
+    /// Remove every watchpoint on exactly `range`, returning whether there were any
+    pub fn remove_watchpoint(&mut self, range: &RangeInclusive<u16>) -> bool {
+        let len = self.watchpoints.len();
+        self.watchpoints.retain(|watchpoint| watchpoint.range != *range);
+        self.watchpoints.len() != len
+    }
+
+    pub fn watchpoints(&self) -> &[Watchpoint] {
+        &self.watchpoints
+    }
+
+    /// Record every executed instruction with `tracer`, replacing any previous one
+    pub fn set_tracer(&mut self, tracer: Tracer) {
+        self.tracer = Some(tracer);
+    }
+
+    pub fn tracer(&self) -> Option<&Tracer> {
+        self.tracer.as_ref()
+    }
+
+    pub fn tracer_mut(&mut self) -> Option<&mut Tracer> {
+        self.tracer.as_mut()
+    }
+
+    /// Stop tracing, returning the tracer
+    pub fn take_tracer(&mut self) -> Option<Tracer> {
+        self.tracer.take()
+    }
+
+    // Whether the next step would execute a BRK instruction (rather than an interrupt)
+    fn at_brk(&mut self) -> bool {
+        let interrupt = self.nmi_pending || (self.irq_line() && !self.irq_masked);
+        !interrupt && !self.jammed && !self.stopped && !self.waiting && self.bus.peek(self.pc) == 0x00
+    }
+
+    /// Drive the shared, level-triggered IRQ line from the default source
+    ///
+    /// Equivalent to `set_irq_source(0, asserted)`.
+    pub fn set_irq_line(&mut self, asserted: bool) {
+        self.set_irq_source(0, asserted);
+    }
+
+    /// Drive the IRQ line from one of 8 sources (`0..8`)
+    ///
+    /// The line is asserted while any source asserts it, so each peripheral can raise and release its own request.
+    pub fn set_irq_source(&mut self, source: u8, asserted: bool) {
+        let bit = 1 << (source & 0x07);
+        if asserted {
+            self.irq_lines |= bit;
+        } else {
+            self.irq_lines &= !bit;
+        }
+    }
+
+    /// Return whether any source is asserting the IRQ line
+    pub fn irq_line(&self) -> bool {
+        self.irq_lines != 0
+    }
+
+    /// Signal a falling edge on the NMI line
+    ///
+    /// The NMI is latched and serviced at the next instruction boundary, regardless of the interrupt disable flag.
+    pub fn trigger_nmi(&mut self) {
+        self.nmi_pending = true;
+    }
+
+    /// Return total number of cycles executed
+    pub fn cycles(&self) -> u64 {
+        self.cycles
+    }
+
+    /// Halt for `cycles` while another device owns the bus (e.g. OAM DMA on the NES)
+    pub fn stall(&mut self, cycles: u64) {
+        self.cycles += cycles;
+    }
+
+    /// Return whether a JAM opcode has halted the CPU (only a reset recovers it)
+    pub fn is_jammed(&self) -> bool {
+        self.jammed
+    }
+
+    /// Return whether an STP opcode has stopped the clock (only a reset recovers it)
+    pub fn is_stopped(&self) -> bool {
+        self.stopped
+    }
+
+    /// Return whether a WAI opcode is waiting for an interrupt
+    pub fn is_waiting(&self) -> bool {
+        self.waiting
+    }
+
+    /// Return the CPU's configuration
+    pub fn config(&self) -> &CpuConfig {
+        &self.config
+    }
+
+    /// Save registers, cycle count, interrupt lines and memory into a save state
+    ///
+    /// Breakpoints and configuration aren't included, except for the variant which has to match when loading.
+    pub fn save_state(&self) -> Vec<u8> {
+        let mut writer = StateWriter::new();
+        writer.write_u8(self.config.variant as u8);
+        writer.write_u8(self.a);
+        writer.write_u8(self.x);
+        writer.write_u8(self.y);
+        writer.write_u8(self.sp);
+        writer.write_u16(self.pc);
+        writer.write_u8(self.sr);
+        writer.write_u8(self.opcode);
+        writer.write_u16(self.instruction_pc);
+        writer.write_u64(self.cycles);
+        writer.write_bool(self.jammed);
+        writer.write_bool(self.stopped);
+        writer.write_bool(self.waiting);
+        writer.write_u8(self.irq_lines);
+        writer.write_bool(self.nmi_pending);
+        writer.write_bool(self.irq_masked);
+        self.bus.save_state(&mut writer);
+        writer.finish()
+    }
+
+    /// Restore a save state made by `save_state`
+    ///
+    /// Nothing is changed unless the whole state loads: if the bus rejects its part, it's put back as it was. The
+    /// rewind history is discarded once the state has loaded.
+    pub fn load_state(&mut self, state: &[u8]) -> Result<(), StateError> {
+        self.restore_state(state)?;
+        if let Some(rewind) = &mut self.rewind {
+            rewind.clear();
+        }
+        Ok(())
+    }
+
+    fn restore_state(&mut self, state: &[u8]) -> Result<(), StateError> {
+        let mut reader = StateReader::new(state)?;
+        if reader.read_u8()? != self.config.variant as u8 {
+            return Err(StateError::Mismatch("CPU variant"));
+        }
+
+        let core = CoreState {
+            a: reader.read_u8()?,
+            x: reader.read_u8()?,
+            y: reader.read_u8()?,
+            sp: reader.read_u8()?,
+            pc: reader.read_u16()?,
+            sr: reader.read_u8()?,
+            opcode: reader.read_u8()?,
+            instruction_pc: reader.read_u16()?,
+            cycles: reader.read_u64()?,
+            jammed: reader.read_bool()?,
+            stopped: reader.read_bool()?,
+            waiting: reader.read_bool()?,
+            irq_lines: reader.read_u8()?,
+            nmi_pending: reader.read_bool()?,
+            irq_masked: reader.read_bool()?,
+        };
+
+        // Devices may fail halfway through loading, so keep a copy of the bus to put back
+        let mut backup = StateWriter::new();
+        self.bus.save_state(&mut backup);
+        let backup = backup.finish();
+        if let Err(error) = self.bus.load_state(&mut reader) {
+            let mut reader = StateReader::new(&backup)?;
+            self.bus.load_state(&mut reader)?;
+            return Err(error);
+        }
+
+        self.restore_core_state(core);
+        self.resume_pc = None;
+        Ok(())
+    }
+
+    /// Read the next opcode into `self.opcode`
+    fn fetch(&mut self) {
+        self.instruction_pc = self.pc;
+        self.opcode = self.read(self.pc);
+        self.pc = self.pc.wrapping_add(1);
+    }
+
+    /// Decode `self.opcode` using the opcode table and execute it
+    ///
+    /// Returns the number of cycles the instruction took, or the error it raised.
+    fn execute(&mut self) -> Result<StepOutcome, CpuError> {
+        let op = &opcodes::table(self.config.variant)[self.opcode as usize];
+        let mode = op.mode;
+
+        self.page_crossed = false;
+        self.extra_cycles = 0;
+        self.fault = None;
+
+        let irq_masked = self.get_flag(Flags::I);
+
+        // Trap undocumented opcodes before they have any effect
+        if op.illegal && self.config.strict && !self.config.variant.is_cmos() {
+            self.pc = self.instruction_pc;
+            return Err(CpuError::IllegalOpcode { pc: self.pc, opcode: self.opcode });
+        }
+
+        match op.mnemonic {
+            Mnemonic::ADC => self.ADC(mode), Mnemonic::AND => self.AND(mode), Mnemonic::ASL => self.ASL(mode), Mnemonic::BCC => self.BCC(mode),
+            Mnemonic::BCS => self.BCS(mode), Mnemonic::BEQ => self.BEQ(mode), Mnemonic::BIT => self.BIT(mode), Mnemonic::BMI => self.BMI(mode),
+            Mnemonic::BNE => self.BNE(mode), Mnemonic::BPL => self.BPL(mode), Mnemonic::BRK => self.BRK(mode), Mnemonic::BVC => self.BVC(mode),
+            Mnemonic::BVS => self.BVS(mode), Mnemonic::CLC => self.CLC(mode), Mnemonic::CLD => self.CLD(mode), Mnemonic::CLI => self.CLI(mode),
+            Mnemonic::CLV => self.CLV(mode), Mnemonic::CMP => self.CMP(mode), Mnemonic::CPX => self.CPX(mode), Mnemonic::CPY => self.CPY(mode),
+            Mnemonic::DEC => self.DEC(mode), Mnemonic::DEX => self.DEX(mode), Mnemonic::DEY => self.DEY(mode), Mnemonic::EOR => self.EOR(mode),
+            Mnemonic::INC => self.INC(mode), Mnemonic::INX => self.INX(mode), Mnemonic::INY => self.INY(mode), Mnemonic::JMP => self.JMP(mode),
+            Mnemonic::JSR => self.JSR(mode), Mnemonic::LDA => self.LDA(mode), Mnemonic::LDX => self.LDX(mode), Mnemonic::LDY => self.LDY(mode),
+            Mnemonic::LSR => self.LSR(mode), Mnemonic::NOP => self.NOP(mode), Mnemonic::ORA => self.ORA(mode), Mnemonic::PHA => self.PHA(mode),
+            Mnemonic::PHP => self.PHP(mode), Mnemonic::PLA => self.PLA(mode), Mnemonic::PLP => self.PLP(mode), Mnemonic::ROL => self.ROL(mode),
+            Mnemonic::ROR => self.ROR(mode), Mnemonic::RTI => self.RTI(mode), Mnemonic::RTS => self.RTS(mode), Mnemonic::SBC => self.SBC(mode),
+            Mnemonic::SEC => self.SEC(mode), Mnemonic::SED => self.SED(mode), Mnemonic::SEI => self.SEI(mode), Mnemonic::STA => self.STA(mode),
+            Mnemonic::STX => self.STX(mode), Mnemonic::STY => self.STY(mode), Mnemonic::TAX => self.TAX(mode), Mnemonic::TAY => self.TAY(mode),
+            Mnemonic::TSX => self.TSX(mode), Mnemonic::TXA => self.TXA(mode), Mnemonic::TXS => self.TXS(mode), Mnemonic::TYA => self.TYA(mode),
+            Mnemonic::ALR => self.ALR(mode), Mnemonic::ANC => self.ANC(mode), Mnemonic::ANE => self.ANE(mode), Mnemonic::ARR => self.ARR(mode),
+            Mnemonic::DCP => self.DCP(mode), Mnemonic::ISC => self.ISC(mode), Mnemonic::JAM => self.JAM(mode), Mnemonic::LAS => self.LAS(mode),
+            Mnemonic::LAX => self.LAX(mode), Mnemonic::LXA => self.LXA(mode), Mnemonic::RLA => self.RLA(mode), Mnemonic::RRA => self.RRA(mode),
+            Mnemonic::SAX => self.SAX(mode), Mnemonic::SBX => self.SBX(mode), Mnemonic::SHA => self.SHA(mode), Mnemonic::SHX => self.SHX(mode),
+            Mnemonic::SHY => self.SHY(mode), Mnemonic::SLO => self.SLO(mode), Mnemonic::SRE => self.SRE(mode), Mnemonic::TAS => self.TAS(mode),
+            Mnemonic::BBR => self.BBR(mode), Mnemonic::BBS => self.BBS(mode), Mnemonic::BRA => self.BRA(mode), Mnemonic::PHX => self.PHX(mode),
+            Mnemonic::PHY => self.PHY(mode), Mnemonic::PLX => self.PLX(mode), Mnemonic::PLY => self.PLY(mode), Mnemonic::RMB => self.RMB(mode),
+            Mnemonic::SMB => self.SMB(mode), Mnemonic::STP => self.STP(mode), Mnemonic::STZ => self.STZ(mode), Mnemonic::TRB => self.TRB(mode),
+            Mnemonic::TSB => self.TSB(mode), Mnemonic::WAI => self.WAI(mode),
+        }
+
+        // Interrupts are polled before the last cycle, so CLI, SEI and PLP only take effect after the next instruction
+        self.irq_masked = match op.mnemonic {
+            Mnemonic::CLI | Mnemonic::SEI | Mnemonic::PLP => irq_masked,
+            _ => self.get_flag(Flags::I),
+        };
+
+        let mut cycles = op.cycles + self.extra_cycles;
+        if op.page_cross && self.page_crossed {
+            cycles += 1;
+        }
+        self.cycles += cycles as u64;
+
+        if self.jammed {
+            return Err(CpuError::Jammed { pc: self.pc });
+        }
+        if let Some(fault) = self.fault.take() {
+            return Err(fault);
+        }
+
+        Ok(StepOutcome::Executed { cycles })
+    }
+
+    /// Service a pending NMI, or IRQ if it isn't masked, at an instruction boundary
+    ///
+    /// Any interrupt request also wakes up a CPU waiting on WAI, even a masked IRQ. Returns the number of cycles taken
+    /// (0 if no interrupt was serviced).
+    fn poll_interrupts(&mut self) -> u8 {
+        if self.nmi_pending || self.irq_line() {
+            self.waiting = false;
+        }
+
+        if self.nmi_pending {
+            self.interrupt(self.pc, self.sr, NMI_VECTOR);
+        } else if self.irq_line() && !self.irq_masked {
+            self.interrupt(self.pc, self.sr, IRQ_VECTOR);
+        } else {
+            return 0;
+        }
+
+        self.irq_masked = true;
+        self.cycles += 7;
+        7
+    }
+
+    // Interrupt sequence shared by BRK, IRQ and NMI
+    // Push the return address and `sr`, set the interrupt disable flag, and load PC from `vector`
+    // On NMOS, an NMI that is pending by the time the vector is read hijacks a BRK or IRQ
+    fn interrupt(&mut self, pc: u16, sr: u8, vector: u16) {
+        self.push_u16(pc);
+        // The B flag only exists in the pushed copy, and is set for BRK only
+        self.push(sr | Flags::U as u8);
+        self.set_flag(Flags::I, true);
+
+        // The 65C02 also leaves decimal mode
+        if self.config.variant.is_cmos() {
+            self.set_flag(Flags::D, false);
+        }
+
+        let vector = if self.nmi_pending && (vector == NMI_VECTOR || !self.config.variant.is_cmos()) {
+            self.nmi_pending = false;
+            NMI_VECTOR
+        } else {
+            vector
+        };
+        self.pc = self.read_u16(vector);
     }
 }
 
 /// Addressing modes
-#[derive(PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[allow(clippy::upper_case_acronyms, dead_code)]
 pub enum AddressingMode {
     IMM,
@@ -226,48 +940,56 @@ pub enum AddressingMode {
     IDY,
     REL,
     ACC,
+    ZPI,
+    IAX,
+    ZPR,
 }
 
 /// Implement addressing modes
-impl CPU {
+impl<M: Memory> CPU<M> {
     pub fn get_address(&mut self, mode: AddressingMode) -> u16 {
         match mode {
             AddressingMode::IMM => {
                 let addr = self.pc;
-                self.pc += 1;
+                self.pc = self.pc.wrapping_add(1);
                 addr
             },
-            AddressingMode::IMP => {
-                panic!("not supported");
-            },
+            // Implied and accumulator addressing have no operand, so nothing is consumed
+            AddressingMode::IMP | AddressingMode::ACC => self.pc,
             AddressingMode::ZP0 => {
                 let addr = self.read(self.pc) as u16;
-                self.pc += 1;
+                self.pc = self.pc.wrapping_add(1);
                 addr
             },
             AddressingMode::ZPX => {
                 let addr = self.read(self.pc).wrapping_add(self.x) as u16;
-                self.pc += 1;
+                self.pc = self.pc.wrapping_add(1);
                 addr
             },
             AddressingMode::ZPY => {
                 let addr = self.read(self.pc).wrapping_add(self.y) as u16;
-                self.pc += 1;
+                self.pc = self.pc.wrapping_add(1);
                 addr
             },
             AddressingMode::ABS => {
                 let addr = self.read_u16(self.pc);
-                self.pc += 2;
+                self.pc = self.pc.wrapping_add(2);
                 addr
             },
             AddressingMode::ABX => {
-                let addr = self.read_u16(self.pc).wrapping_add(self.x as u16);
-                self.pc += 2;
+                let base = self.read_u16(self.pc);
+                self.pc = self.pc.wrapping_add(2);
+
+                let addr = base.wrapping_add(self.x as u16);
+                self.page_crossed = base & 0xFF00 != addr & 0xFF00;
                 addr
             },
             AddressingMode::ABY => {
-                let addr = self.read_u16(self.pc).wrapping_add(self.y as u16);
-                self.pc += 2;
+                let base = self.read_u16(self.pc);
+                self.pc = self.pc.wrapping_add(2);
+
+                let addr = base.wrapping_add(self.y as u16);
+                self.page_crossed = base & 0xFF00 != addr & 0xFF00;
                 addr
             },
             // Indirect (IND) addressing: the program is supplied with a pointer.
@@ -275,13 +997,21 @@ impl CPU {
             // 
             // E.g. PC is 0x0301. 0x0301 (+1) stores pointer 0x4230. Pointer 0x4230 (+1) stores address 0x04A9.
             // Returns address 0x04A9 because operand is there.
+            //
+            // The NMOS 6502 doesn't carry into the pointer's high byte, so JMP ($10FF) reads its high byte from 0x1000.
             AddressingMode::IND => {
-                let addr = self.read_u16(self.pc);
-                self.pc += 2;
+                let ptr = self.read_u16(self.pc);
+                self.pc = self.pc.wrapping_add(2);
 
-                let ptr = self.read_u16(addr);
-                self.pc += 2;
-                ptr
+                let hi_ptr = if self.config.variant.is_cmos() {
+                    ptr.wrapping_add(1)
+                } else {
+                    (ptr & 0xFF00) | (ptr.wrapping_add(1) & 0x00FF)
+                };
+                let lo = self.read(ptr);
+                let hi = self.read(hi_ptr);
+
+                (hi as u16) << 8 | (lo as u16)
             },
             // Indexed indirect addressing: the program is supplied with a zero-page pointer.
             // The X register is added to that pointer. This points to the address that holds the operand.
@@ -290,59 +1020,253 @@ impl CPU {
             // 0x0033 stores 0xEF. So this returns address 0xEF91 (operand is stored there).
             AddressingMode::IDX => {
                 let base = self.read(self.pc);
+                self.pc = self.pc.wrapping_add(1);
 
-                let ptr = base + self.x;
+                // The pointer never leaves the zero page
+                let ptr = base.wrapping_add(self.x);
                 let lo = self.read(ptr as u16);
-                let hi = self.read((ptr + 1) as u16);
+                let hi = self.read(ptr.wrapping_add(1) as u16);
 
-                self.pc += 2;
                 (hi as u16) << 8 | (lo as u16)
             },
             // Indirect indexed addressing: the program is supplied with a zero-page address.
-            // The value that it reads there + the Y register, is a pointer to the address that holds the operand.
+            // The 16-bit value that it reads there + the Y register, is the address that holds the operand.
             // 
-            // E.g. PC is 0x0301, Y is 0x02. PC stores 0x30. Pointer 0x0030 stores 0x91. Address = 0x91 + 0x02 = 0x93.
-            // Returns address 0x0093 because operand is there.
+            // E.g. PC is 0x0301, Y is 0x02. PC stores 0x30. Pointer 0x0030 (+1) stores 0x0491. Address = 0x0491 + 0x02.
+            // Returns address 0x0493 because operand is there.
             AddressingMode::IDY => {
                 let zp_addr = self.read(self.pc);
-                let base = self.read(zp_addr as u16);
+                self.pc = self.pc.wrapping_add(1);
 
-                let ptr = base + self.y;
+                let lo = self.read(zp_addr as u16);
+                let hi = self.read(zp_addr.wrapping_add(1) as u16);
+                let base = (hi as u16) << 8 | (lo as u16);
 
-                let lo = self.read(ptr as u16);
+                let addr = base.wrapping_add(self.y as u16);
+                self.page_crossed = base & 0xFF00 != addr & 0xFF00;
+                addr
+            },
+            // Relative addressing: the program is supplied with a signed 8-bit offset.
+            // Returns the branch target, relative to the address of the next instruction.
+            AddressingMode::REL => {
+                let offset = self.read(self.pc) as i8;
+                self.pc = self.pc.wrapping_add(1);
+
+                self.pc.wrapping_add(offset as u16)
+            },
+            // Zero page indirect addressing (65C02): like IDY, without adding the Y register.
+            AddressingMode::ZPI => {
+                let zp_addr = self.read(self.pc);
+                self.pc = self.pc.wrapping_add(1);
+
+                let lo = self.read(zp_addr as u16);
+                let hi = self.read(zp_addr.wrapping_add(1) as u16);
+
+                (hi as u16) << 8 | (lo as u16)
+            },
+            // Absolute indexed indirect addressing (65C02, JMP only): the X register is added to the supplied pointer.
+            AddressingMode::IAX => {
+                let base = self.read_u16(self.pc);
+                self.pc = self.pc.wrapping_add(2);
 
-                self.pc += 2;
-                lo as u16
+                self.read_u16(base.wrapping_add(self.x as u16))
             },
-            AddressingMode::REL => todo!(),
-            AddressingMode::ACC => todo!(),
+            // Zero page, relative addressing (BBR and BBS): returns the zero page address.
+            // The branch offset that follows is left for the instruction to read as REL.
+            AddressingMode::ZPR => {
+                let addr = self.read(self.pc) as u16;
+                self.pc = self.pc.wrapping_add(1);
+                addr
+            },
+        }
+    }
+}
+
+/// Implement instructions
+#[allow(non_snake_case)]
+#[allow(unused)]
+impl<M: Memory> CPU<M> {
+    // Shared by all branch instructions: always consume the offset, jump only if `condition` holds
+    // A taken branch costs 1 extra cycle, plus 1 more if the target is on another page
+    fn branch(&mut self, mode: AddressingMode, condition: bool) {
+        let target = self.get_address(mode);
+        if condition {
+            self.extra_cycles += 1;
+            self.page_crossed = self.pc & 0xFF00 != target & 0xFF00;
+            self.pc = target;
+        }
+    }
+
+    // Whether ADC and SBC should use BCD arithmetic
+    fn decimal_enabled(&mut self) -> bool {
+        self.config.variant != CpuVariant::Ricoh2A03 && self.config.decimal_mode && self.get_flag(Flags::D)
+    }
+
+    // Add `operand` and carry to the accumulator, shared by ADC and its undocumented combinations
+    fn add_with_carry(&mut self, operand: u8) {
+        if self.decimal_enabled() {
+            self.add_with_carry_decimal(operand);
+        } else {
+            self.add_with_carry_binary(operand);
+        }
+    }
+
+    // Binary addition of `operand` and carry to the accumulator
+    fn add_with_carry_binary(&mut self, operand: u8) {
+        let result_u16: u16 = self.a as u16 + operand as u16 + (self.sr & 0x01) as u16;
+        let result_u8: u8 = result_u16 as u8;
+
+        // Set carry if unsigned overflow occurs
+        self.set_flag(Flags::C, result_u16 > 0xFF);
+
+        // Set V if signed overflow occurs
+        self.set_flag(Flags::V, self.a & 0x80 == operand & 0x80 && self.a & 0x80 != result_u8 & 0x80);
+
+        // Set accumulator equal to result
+        self.a = result_u8;
+
+        // set zero and negative flags
+        self.set_zero_negative_flags(self.a);
+    }
+
+    // Subtract `operand` and borrow (inverted carry) from the accumulator
+    // In binary mode, A - M - (1 - C) is the same as A + !M + C
+    fn subtract_with_carry(&mut self, operand: u8) {
+        if self.decimal_enabled() {
+            self.subtract_with_carry_decimal(operand);
+        } else {
+            self.add_with_carry_binary(!operand);
+        }
+    }
+
+    // BCD addition, following the algorithms from Bruce Clark's "Decimal Mode" tutorial (appendix A)
+    // On NMOS, Z comes from the binary sum, N and V from the sum before the high nibble is adjusted
+    // The 65C02 takes an extra cycle to set N and Z from the decimal result
+    fn add_with_carry_decimal(&mut self, operand: u8) {
+        let carry = (self.sr & 0x01) as i16;
+        let binary = self.a.wrapping_add(operand).wrapping_add(carry as u8);
+
+        // Add low nibbles, adjusting into the high nibble if the result is not a decimal digit
+        let mut lo = (self.a & 0x0F) as i16 + (operand & 0x0F) as i16 + carry;
+        if lo >= 0x0A {
+            lo = ((lo + 0x06) & 0x0F) + 0x10;
+        }
+
+        // N and V are taken from the signed sum of the unadjusted high nibbles
+        let signed = (self.a & 0xF0) as i8 as i16 + (operand & 0xF0) as i8 as i16 + lo;
+        self.set_flag(Flags::N, signed & 0x80 == 0x80);
+        self.set_flag(Flags::V, !(-128..=127).contains(&signed));
+
+        let mut result = (self.a & 0xF0) as u16 + (operand & 0xF0) as u16 + lo as u16;
+        if result >= 0xA0 {
+            result += 0x60;
+        }
+
+        self.set_flag(Flags::C, result >= 0x100);
+        self.set_flag(Flags::Z, binary == 0x00);
+        self.a = result as u8;
+
+        if self.config.variant.is_cmos() {
+            self.set_zero_negative_flags(self.a);
+            self.extra_cycles += 1;
+        }
+    }
+
+    // BCD subtraction, following the algorithms from Bruce Clark's "Decimal Mode" tutorial (appendix A)
+    // On NMOS, all flags are the same as for binary subtraction
+    // The 65C02 adjusts the whole result at once, and takes an extra cycle to set N and Z from it
+    fn subtract_with_carry_decimal(&mut self, operand: u8) {
+        let carry = (self.sr & 0x01) as i16;
+
+        if self.config.variant.is_cmos() {
+            let lo = (self.a & 0x0F) as i16 - (operand & 0x0F) as i16 + carry - 1;
+            let mut result = self.a as i16 - operand as i16 + carry - 1;
+            if result < 0 {
+                result -= 0x60;
+            }
+            if lo < 0 {
+                result -= 0x06;
+            }
+
+            self.add_with_carry_binary(!operand);
+            self.a = result as u8;
+            self.set_zero_negative_flags(self.a);
+            self.extra_cycles += 1;
+            return;
+        }
+
+        let mut lo = (self.a & 0x0F) as i16 - (operand & 0x0F) as i16 + carry - 1;
+        if lo < 0 {
+            lo = ((lo - 0x06) & 0x0F) - 0x10;
+        }
+
+        let mut result = (self.a & 0xF0) as i16 - (operand & 0xF0) as i16 + lo;
+        if result < 0 {
+            result -= 0x60;
+        }
+
+        // Flags from the binary subtraction
+        self.add_with_carry_binary(!operand);
+        self.a = result as u8;
+    }
+
+    // Compare `register` to `value`, shared by CMP, CPX, CPY and DCP
+    fn compare(&mut self, register: u8, value: u8) {
+        self.set_flag(Flags::C, register >= value);
+        self.set_zero_negative_flags(register.wrapping_sub(value));
+    }
+
+    // Shift left, moving bit 7 into carry
+    fn shift_left(&mut self, value: u8) -> u8 {
+        self.set_flag(Flags::C, value & 0x80 == 0x80);
+        let result = value << 1;
+        self.set_zero_negative_flags(result);
+        result
+    }
+
+    // Shift right, moving bit 0 into carry
+    fn shift_right(&mut self, value: u8) -> u8 {
+        self.set_flag(Flags::C, value & 0x01 == 0x01);
+        let result = value >> 1;
+        self.set_zero_negative_flags(result);
+        result
+    }
+
+    // Shift left, while setting bit 0 equal to carry and setting new carry equal to old bit 7
+    fn rotate_left(&mut self, value: u8) -> u8 {
+        let result = (value << 1) | (self.sr & 0x01);
+        self.set_flag(Flags::C, value & 0x80 == 0x80);
+        self.set_zero_negative_flags(result);
+        result
+    }
+
+    // Shift right, while setting bit 7 equal to carry and setting new carry equal to old bit 0
+    fn rotate_right(&mut self, value: u8) -> u8 {
+        let result = (value >> 1) | ((self.sr & 0x01) << 7);
+        self.set_flag(Flags::C, value & 0x01 == 0x01);
+        self.set_zero_negative_flags(result);
+        result
+    }
+
+    // Read-modify-write: apply `f` to the accumulator or to the value in memory, returning the result
+    fn modify(&mut self, mode: AddressingMode, f: fn(&mut Self, u8) -> u8) -> u8 {
+        if mode == AddressingMode::ACC {
+            self.a = f(self, self.a);
+            self.a
+        } else {
+            let addr: u16 = self.get_address(mode);
+            let value: u8 = self.read(addr);
+            let result = f(self, value);
+            self.write(addr, result);
+            result
         }
     }
-}
 
-/// Implement instructions
-#[allow(non_snake_case)]
-#[allow(unused)]
-impl CPU {
     // Add with carry
     fn ADC(&mut self, mode: AddressingMode) {
         let addr: u16 = self.get_address(mode);
         let operand: u8 = self.read(addr);
-
-        let result_u16: u16 = self.a as u16 + operand as u16 + (self.sr & 0x01) as u16;
-        let result_u8: u8 = result_u16 as u8;
-
-        // Set carry if unsigned overflow occurs
-        self.set_flag(Flags::C, result_u16 > 0xFF);
-        
-        // Set V if signed overflow occurs
-        self.set_flag(Flags::V, self.a & 0x80 == operand & 0x80 && self.a & 0x80 != result_u8 & 0x80);
-
-        // Set accumulator equal to result
-        self.a = result_u8;
-
-        // set zero and negative flags
-        self.set_zero_negative_flags(self.a);
+        self.add_with_carry(operand);
     }
 
     // Logical AND
@@ -355,72 +1279,29 @@ impl CPU {
 
     // Arithmetic shift left
     fn ASL(&mut self, mode: AddressingMode) {
-        println!("ASL");
-        if mode == AddressingMode::ACC {
-            // Move bit 7 to carry
-            self.set_flag(Flags::C, self.a & 0x80 == 0x80);
-
-            // Shift other bits to the left
-            self.a <<= 1;
-
-            self.set_zero_negative_flags(self.a);
-        } else {
-            let addr: u16 = self.get_address(mode);
-            let value: u8 = self.read(addr);
-            
-            // Move bit 7 to carry
-            self.set_flag(Flags::C, value & 0x80 == 0x80);
-
-            // Shift other bits to the left, and set Z and N flags
-            let result = value << 1;
-            self.write(addr, result);
-            self.set_zero_negative_flags(result);
-        }
+        self.modify(mode, Self::shift_left);
     }
 
     // Branch if carry clear
     fn BCC(&mut self, mode: AddressingMode) {
-        if !self.get_flag(Flags::C) {
-            let offset = self.read(self.pc);
-            if offset >= 128 {
-                // offset is negative
-                self.pc -= ((!offset) + 1) as u16;
-            } else {
-                // offset is positive
-                self.pc += offset as u16;
-            }
-        }
+        let condition = !self.get_flag(Flags::C);
+        self.branch(mode, condition);
     }
 
     // Branch if carry set
     fn BCS(&mut self, mode: AddressingMode) {
-        if self.get_flag(Flags::C) {
-            let offset = self.read(self.pc);
-            if offset >= 128 {
-                // offset is negative
-                self.pc -= ((!offset) + 1) as u16;
-            } else {
-                // offset is positive
-                self.pc += offset as u16;
-            }
-        }
+        let condition = self.get_flag(Flags::C);
+        self.branch(mode, condition);
     }
 
     // Branch if equal (zero flag set)
     fn BEQ(&mut self, mode: AddressingMode) {
-        if self.get_flag(Flags::Z) {
-            let offset = self.read(self.pc);
-            if offset >= 128 {
-                // offset is negative
-                self.pc -= ((!offset) + 1) as u16;
-            } else {
-                // offset is positive
-                self.pc += offset as u16;
-            }
-        }
+        let condition = self.get_flag(Flags::Z);
+        self.branch(mode, condition);
     }
 
     // Bit test
+    // The immediate form (65C02) only affects the zero flag
     fn BIT(&mut self, mode: AddressingMode) {
         let addr: u16 = self.get_address(mode);
         let value: u8 = self.read(addr);
@@ -428,92 +1309,48 @@ impl CPU {
         let result = self.a & value;
 
         self.set_flag(Flags::Z, result == 0x00);
+        if mode == AddressingMode::IMM {
+            return;
+        }
         self.set_flag(Flags::V, (value & 0x40) == 0x40);
         self.set_flag(Flags::N, (value & 0x80) == 0x80);
     }
 
     // Branch if minus (negative flag set)
     fn BMI(&mut self, mode: AddressingMode) {
-        if self.get_flag(Flags::N) {
-            let offset = self.read(self.pc);
-            if offset >= 128 {
-                // offset is negative
-                self.pc -= ((!offset) + 1) as u16;
-            } else {
-                // offset is positive
-                self.pc += offset as u16;
-            }
-        }
+        let condition = self.get_flag(Flags::N);
+        self.branch(mode, condition);
     }
 
     // Branch if not equal (zero flag clear)
     fn BNE(&mut self, mode: AddressingMode) {
-        if !self.get_flag(Flags::Z) {
-            let offset = self.read(self.pc);
-            if offset >= 128 {
-                // offset is negative
-                self.pc -= ((!offset) + 1) as u16;
-            } else {
-                // offset is positive
-                self.pc += offset as u16;
-            }
-        }
+        let condition = !self.get_flag(Flags::Z);
+        self.branch(mode, condition);
     }
 
     // Branch if positive (negative flag clear)
     fn BPL(&mut self, mode: AddressingMode) {
-        if !self.get_flag(Flags::N) {
-            let offset = self.read(self.pc);
-            if offset >= 128 {
-                // offset is negative
-                self.pc -= ((!offset) + 1) as u16;
-            } else {
-                // offset is positive
-                self.pc += offset as u16;
-            }
-        }
+        let condition = !self.get_flag(Flags::N);
+        self.branch(mode, condition);
     }
 
     // Force interruption
     fn BRK(&mut self, mode: AddressingMode) {
-        // Push PC to stack
-        self.push_u16(self.pc);
-        // Set break flag to 1
-        self.set_flag(Flags::B, true);
-        // Push SR to stack
-        self.push(self.sr);
-        // Load IRQ interrupt vector at 0xFFFE (+1) into PC
-        self.pc = self.read_u16(IRQ_VECTOR);
-        // Set disable interrupt flag so other interrupts don't happen
-        self.set_flag(Flags::I, true);
+        // BRK is followed by a padding byte, so the return address skips it
+        // Push SR with the break flag set, then jump through the IRQ vector at 0xFFFE (+1)
+        self.interrupt(self.pc.wrapping_add(1), self.sr | Flags::B as u8, IRQ_VECTOR);
     }
 
     // Branch if overflow clear
     fn BVC(&mut self, mode: AddressingMode) {
-        if !self.get_flag(Flags::V) {
-            let offset = self.read(self.pc);
-            if offset >= 128 {
-                // offset is negative
-                self.pc -= ((!offset) + 1) as u16;
-            } else {
-                // offset is positive
-                self.pc += offset as u16;
-            }
-        }
+        let condition = !self.get_flag(Flags::V);
+        self.branch(mode, condition);
     }
 
     // Branch if overflow set
     fn BVS(&mut self, mode: AddressingMode) {
-        if self.get_flag(Flags::V) {
-            let offset = self.read(self.pc);
-            if offset >= 128 {
-                // offset is negative
-                self.pc -= ((!offset) + 1) as u16;
-            } else {
-                // offset is positive
-                self.pc += offset as u16;
-            }
-        }
+        let condition = self.get_flag(Flags::V);
+        self.branch(mode, condition);
     }
 
     // Clear the carry flag to zero
@@ -540,50 +1377,41 @@ impl CPU {
     fn CMP(&mut self, mode: AddressingMode) {
         let addr = self.get_address(mode);
         let value = self.read(addr);
-        let result = self.a - value;
-        self.set_zero_negative_flags(result);
-        self.set_flag(Flags::C, self.a >= value);
-        self.a = result;
+        self.compare(self.a, value);
     }
 
     // Compare X register to memory value
     fn CPX(&mut self, mode: AddressingMode) {
         let addr = self.get_address(mode);
         let value = self.read(addr);
-        let result = self.x - value;
-        self.set_zero_negative_flags(result);
-        self.set_flag(Flags::C, self.x >= value);
-        self.x = result;
+        self.compare(self.x, value);
     }
 
     // Compare Y register to memory value
     fn CPY(&mut self, mode: AddressingMode) {
         let addr = self.get_address(mode);
         let value = self.read(addr);
-        let result = self.y - value;
-        self.set_zero_negative_flags(result);
-        self.set_flag(Flags::C, self.y >= value);
-        self.y = result;
+        self.compare(self.y, value);
     }
 
-    // Decrement memory
+    // Decrement memory (or the accumulator, on the 65C02)
     fn DEC(&mut self, mode: AddressingMode) {
-        let addr: u16 = self.get_address(mode);
-        let mut value: u8 = self.read(addr);
-        value -= 1;
-        self.write(addr, value);
-        self.set_zero_negative_flags(value);
+        self.modify(mode, |cpu, value| {
+            let result = value.wrapping_sub(1);
+            cpu.set_zero_negative_flags(result);
+            result
+        });
     }
 
     // Decrement X
     fn DEX(&mut self, mode: AddressingMode) {
-        self.x -= 1;
+        self.x = self.x.wrapping_sub(1);
         self.set_zero_negative_flags(self.x);
     }
 
     // Decrement Y
     fn DEY(&mut self, mode: AddressingMode) {
-        self.y -= 1;
+        self.y = self.y.wrapping_sub(1);
         self.set_zero_negative_flags(self.y);
     }
 
@@ -595,24 +1423,24 @@ impl CPU {
         self.set_zero_negative_flags(self.a);
     }
 
-    // Increment memory
+    // Increment memory (or the accumulator, on the 65C02)
     fn INC(&mut self, mode: AddressingMode) {
-        let addr: u16 = self.get_address(mode);
-        let mut value: u8 = self.read(addr);
-        value += 1;
-        self.write(addr, value);
-        self.set_zero_negative_flags(value);
+        self.modify(mode, |cpu, value| {
+            let result = value.wrapping_add(1);
+            cpu.set_zero_negative_flags(result);
+            result
+        });
     }
 
     // Increment X
     fn INX(&mut self, mode: AddressingMode) {
-        self.x += 1;
+        self.x = self.x.wrapping_add(1);
         self.set_zero_negative_flags(self.x);
     }
 
     // Increment Y
     fn INY(&mut self, mode: AddressingMode) {
-        self.y += 1;
+        self.y = self.y.wrapping_add(1);
         self.set_zero_negative_flags(self.y);
     }
 
@@ -623,9 +1451,10 @@ impl CPU {
     }
 
     // Jump to subroutine
+    // Pushes the address of the last byte of the JSR instruction, RTS adds 1 to it
     fn JSR(&mut self, mode: AddressingMode) {
-        self.push_u16(self.pc - 1);
         let addr = self.get_address(mode);
+        self.push_u16(self.pc.wrapping_sub(1));
         self.pc = addr;
     }
 
@@ -652,32 +1481,18 @@ impl CPU {
 
     // Logical shift right
     fn LSR(&mut self, mode: AddressingMode) {
-        println!("LSR");
-        if mode == AddressingMode::ACC {
-            // Move bit 0 to carry
-            self.set_flag(Flags::C, self.a & 0x01 == 0x01);
-
-            // Shift other bits to the right
-            self.a >>= 1;
+        self.modify(mode, Self::shift_right);
+    }
 
-            self.set_zero_negative_flags(self.a);
-        } else {
+    // No operation
+    // Undocumented NOPs still read their operand
+    fn NOP(&mut self, mode: AddressingMode) {
+        if mode != AddressingMode::IMP {
             let addr = self.get_address(mode);
-            let value = self.read(addr);
-
-            // Move bit 0 to carry
-            self.set_flag(Flags::C, value & 0x01 == 0x01);
-
-            // Shift other bits to the right, and set Z and N flags
-            let result = value >> 1;
-            self.write(addr, result);
-            self.set_zero_negative_flags(result);
+            self.read(addr);
         }
     }
 
-    // No operation
-    fn NOP(&mut self, mode: AddressingMode) {}
-
     // Inclusive OR
     fn ORA(&mut self, mode: AddressingMode) {
         let addr: u16 = self.get_address(mode);
@@ -692,8 +1507,9 @@ impl CPU {
     }
 
     // Push status register to stack
+    // The pushed copy always has the break and unused flags set
     fn PHP(&mut self, mode: AddressingMode) {
-        self.push(self.sr);
+        self.push(self.sr | Flags::B as u8 | Flags::U as u8);
     }
 
     // Pull accumulator (from stack)
@@ -703,129 +1519,37 @@ impl CPU {
     }
 
     // Pull processor status
+    // The break flag only exists on the stack, the unused flag is always set
     fn PLP(&mut self, mode: AddressingMode) {
-        self.sr = self.pop();
+        self.sr = (self.pop() & !(Flags::B as u8)) | Flags::U as u8;
     }
 
     // Rotate left
-    // Shift left, while setting bit 0 equal to carry and setting new carry equal to old bit 7
     fn ROL(&mut self, mode: AddressingMode) {
-        if mode == AddressingMode::ACC {
-            // Store old bit 7
-            let bit_seven: bool = self.a & 0x80 == 0x80;
-
-            // Shift bits left
-            let mut result = self.a << 1;
-
-            // Set bit 0 equal to old carry
-            result |= self.sr & 0x01;
-
-            // Load result to accumulator
-            self.a = result;
-
-            // Set new carry to old bit 7
-            self.set_flag(Flags::C, bit_seven);
-
-            // Set other flags
-            self.set_zero_negative_flags(self.a);
-        } else {
-            let addr = self.get_address(mode);
-            let value = self.read(addr);
-
-            // Store old bit 7
-            let bit_seven: bool = value & 0x80 == 0x80;
-
-            // Shift bits left
-            let mut result = value << 1;
-
-            // Set bit 0 equal to old carry
-            result |= self.sr & 0x01;
-
-            // Write result
-            self.write(addr, result);
-
-            // Set new carry to old bit 7
-            self.set_flag(Flags::C, bit_seven);
-
-            // Set other flags
-            self.set_zero_negative_flags(result);
-        }
+        self.modify(mode, Self::rotate_left);
     }
 
     // Rotate right
     fn ROR(&mut self, mode: AddressingMode) {
-        if mode == AddressingMode::ACC {
-            // Store old bit 0
-            let bit_zero: bool = self.a & 0x01 == 0x01;
-
-            // Shift bits to the right
-            let mut result = self.a >> 1;
-
-            // Set bit 7 equal to old carry
-            result |= (self.sr & 0x01) << 7;
-
-            // Load result to accumulator
-            self.a = result;
-
-            // Set new carry to old bit 0
-            self.set_flag(Flags::C, bit_zero);
-
-            // Set other flags
-            self.set_zero_negative_flags(self.a);
-        } else {
-            let addr = self.get_address(mode);
-            let value = self.read(addr);
-
-            // Store old bit 0
-            let bit_zero: bool = value & 0x01 == 0x01;
-
-            // Shift bits to the right
-            let mut result = value << 1;
-
-            // Set bit 7 equal to old carry
-            result |= (self.sr & 0x01) << 7;
-
-            // Write result
-            self.write(addr, result);
-
-            // Set new carry to old bit 0
-            self.set_flag(Flags::C, bit_zero);
-
-            // Set other flags
-            self.set_zero_negative_flags(result);
-        }
+        self.modify(mode, Self::rotate_right);
     }
 
     // Return from interrupt
     fn RTI(&mut self, mode: AddressingMode) {
-        self.sr = self.pop();
+        self.sr = (self.pop() & !(Flags::B as u8)) | Flags::U as u8;
         self.pc = self.pop_u16();
     }
 
     // Return from subroutine
     fn RTS(&mut self, mode: AddressingMode) {
-        self.pc = self.pop_u16();
+        self.pc = self.pop_u16().wrapping_add(1);
     }
 
     // Subtract with carry
     fn SBC(&mut self, mode: AddressingMode) {
         let addr = self.get_address(mode);
         let operand = self.read(addr);
-
-        let result_i16: i16 = self.a as i16 - operand as i16 - (self.sr & 0x01) as i16;
-        let result_u8: u8 = result_i16 as u8;
-
-        // TODO: clear C if overflow in bit 7 (???)
-        self.set_flag(Flags::C, result_i16 < 0xFF);
-
-        // Set V if signed overflow occurs
-        self.set_flag(Flags::V, self.a & 0x80 != operand & 0x80 && operand & 0x80 == result_u8 & 0x80);
-
-        // self.a -= operand + (1 - (self.sr & 0x01));
-        self.a = result_u8;
-
-        // Set zero and negative flags
-        self.set_zero_negative_flags(self.a);
+        self.subtract_with_carry(operand);
     }
 
     // Set carry flag to 1
@@ -895,15 +1619,295 @@ impl CPU {
         self.a = self.y;
         self.set_zero_negative_flags(self.a);
     }
+}
+
+/// Implement undocumented (NMOS) instructions
+///
+/// See "No More Secrets" (NMOS 6510 unintended opcodes) for the behaviour of each of these.
+#[allow(non_snake_case)]
+#[allow(unused)]
+impl<M: Memory> CPU<M> {
+    // Magic constant of the unstable ANE and LXA instructions
+    const MAGIC: u8 = 0xEE;
+
+    // Store `value` ANDed with the high byte of the base address + 1, used by SHA, SHX, SHY and TAS
+    // If indexing crosses a page, the high byte of the target address is replaced by the stored value
+    fn store_and_high(&mut self, mode: AddressingMode, index: u8, value: u8) {
+        let addr = self.get_address(mode);
+        let base_hi = (addr.wrapping_sub(index as u16) >> 8) as u8;
+        let result = value & base_hi.wrapping_add(1);
+
+        let addr = if self.page_crossed {
+            (result as u16) << 8 | (addr & 0x00FF)
+        } else {
+            addr
+        };
+        self.write(addr, result);
+    }
+
+    // AND immediate, then shift right
+    fn ALR(&mut self, mode: AddressingMode) {
+        let addr = self.get_address(mode);
+        let value = self.a & self.read(addr);
+        self.a = self.shift_right(value);
+    }
+
+    // AND immediate, then copy bit 7 into carry
+    fn ANC(&mut self, mode: AddressingMode) {
+        self.AND(mode);
+        self.set_flag(Flags::C, self.a & 0x80 == 0x80);
+    }
+
+    // Unstable: A = (A | MAGIC) & X & immediate
+    fn ANE(&mut self, mode: AddressingMode) {
+        let addr = self.get_address(mode);
+        let value = self.read(addr);
+        self.a = (self.a | Self::MAGIC) & self.x & value;
+        self.set_zero_negative_flags(self.a);
+    }
+
+    // AND immediate, then rotate right
+    // C is taken from bit 6 of the result, V from bit 6 XOR bit 5
+    // In decimal mode the result is additionally BCD-fixed per nibble, and C comes from the high nibble fixup
+    fn ARR(&mut self, mode: AddressingMode) {
+        let addr = self.get_address(mode);
+        let value = self.a & self.read(addr);
+        self.a = (value >> 1) | ((self.sr & 0x01) << 7);
+        self.set_zero_negative_flags(self.a);
+
+        if self.decimal_enabled() {
+            self.set_flag(Flags::V, (value ^ self.a) & 0x40 == 0x40);
+
+            if (value & 0x0F) + (value & 0x01) > 0x05 {
+                self.a = (self.a & 0xF0) | (self.a.wrapping_add(0x06) & 0x0F);
+            }
+
+            let fixup = (value & 0xF0) as u16 + (value & 0x10) as u16 > 0x50;
+            if fixup {
+                self.a = (self.a & 0x0F) | (self.a.wrapping_add(0x60) & 0xF0);
+            }
+            self.set_flag(Flags::C, fixup);
+        } else {
+            self.set_flag(Flags::C, self.a & 0x40 == 0x40);
+            self.set_flag(Flags::V, ((self.a >> 6) ^ (self.a >> 5)) & 0x01 == 0x01);
+        }
+    }
+
+    // Decrement memory, then compare with accumulator
+    fn DCP(&mut self, mode: AddressingMode) {
+        let addr = self.get_address(mode);
+        let value = self.read(addr).wrapping_sub(1);
+        self.write(addr, value);
+        self.compare(self.a, value);
+    }
+
+    // Increment memory, then subtract from accumulator
+    fn ISC(&mut self, mode: AddressingMode) {
+        let addr = self.get_address(mode);
+        let value = self.read(addr).wrapping_add(1);
+        self.write(addr, value);
+        self.subtract_with_carry(value);
+    }
+
+    // Halt the CPU until reset, leaving PC on the JAM opcode
+    fn JAM(&mut self, mode: AddressingMode) {
+        self.pc = self.pc.wrapping_sub(1);
+        self.jammed = true;
+    }
+
+    // AND memory with stack pointer, store result in A, X and SP
+    fn LAS(&mut self, mode: AddressingMode) {
+        let addr = self.get_address(mode);
+        let value = self.read(addr) & self.sp;
+        self.a = value;
+        self.x = value;
+        self.sp = value;
+        self.set_zero_negative_flags(value);
+    }
+
+    // Load accumulator and X register
+    fn LAX(&mut self, mode: AddressingMode) {
+        let addr = self.get_address(mode);
+        self.a = self.read(addr);
+        self.x = self.a;
+        self.set_zero_negative_flags(self.a);
+    }
+
+    // Unstable: A = X = (A | MAGIC) & immediate
+    fn LXA(&mut self, mode: AddressingMode) {
+        let addr = self.get_address(mode);
+        let value = self.read(addr);
+        self.a = (self.a | Self::MAGIC) & value;
+        self.x = self.a;
+        self.set_zero_negative_flags(self.a);
+    }
+
+    // Rotate memory left, then AND with accumulator
+    fn RLA(&mut self, mode: AddressingMode) {
+        let result = self.modify(mode, Self::rotate_left);
+        self.a &= result;
+        self.set_zero_negative_flags(self.a);
+    }
+
+    // Rotate memory right, then add to accumulator
+    fn RRA(&mut self, mode: AddressingMode) {
+        let result = self.modify(mode, Self::rotate_right);
+        self.add_with_carry(result);
+    }
+
+    // Store A & X
+    fn SAX(&mut self, mode: AddressingMode) {
+        let addr = self.get_address(mode);
+        self.write(addr, self.a & self.x);
+    }
+
+    // X = (A & X) - immediate, setting flags like CMP
+    fn SBX(&mut self, mode: AddressingMode) {
+        let addr = self.get_address(mode);
+        let value = self.read(addr);
+        let ax = self.a & self.x;
+        self.compare(ax, value);
+        self.x = ax.wrapping_sub(value);
+    }
+
+    // Store A & X & (high byte of address + 1)
+    fn SHA(&mut self, mode: AddressingMode) {
+        self.store_and_high(mode, self.y, self.a & self.x);
+    }
+
+    // Store X & (high byte of address + 1)
+    fn SHX(&mut self, mode: AddressingMode) {
+        self.store_and_high(mode, self.y, self.x);
+    }
+
+    // Store Y & (high byte of address + 1)
+    fn SHY(&mut self, mode: AddressingMode) {
+        self.store_and_high(mode, self.x, self.y);
+    }
+
+    // Shift memory left, then OR with accumulator
+    fn SLO(&mut self, mode: AddressingMode) {
+        let result = self.modify(mode, Self::shift_left);
+        self.a |= result;
+        self.set_zero_negative_flags(self.a);
+    }
+
+    // Shift memory right, then EOR with accumulator
+    fn SRE(&mut self, mode: AddressingMode) {
+        let result = self.modify(mode, Self::shift_right);
+        self.a ^= result;
+        self.set_zero_negative_flags(self.a);
+    }
+
+    // SP = A & X, then store SP & (high byte of address + 1)
+    fn TAS(&mut self, mode: AddressingMode) {
+        self.sp = self.a & self.x;
+        self.store_and_high(mode, self.y, self.sp);
+    }
+}
+
+/// Implement 65C02 instructions
+#[allow(non_snake_case)]
+#[allow(unused)]
+impl<M: Memory> CPU<M> {
+    // Bit number tested or changed by RMB, SMB, BBR and BBS, encoded in the opcode
+    fn opcode_bit(&self) -> u8 {
+        1 << ((self.opcode >> 4) & 0x07)
+    }
+
+    // Branch on bit reset (zero page bit clear)
+    fn BBR(&mut self, mode: AddressingMode) {
+        let addr = self.get_address(mode);
+        let value = self.read(addr);
+        let condition = value & self.opcode_bit() == 0x00;
+        self.branch(AddressingMode::REL, condition);
+    }
+
+    // Branch on bit set (zero page bit set)
+    fn BBS(&mut self, mode: AddressingMode) {
+        let addr = self.get_address(mode);
+        let value = self.read(addr);
+        let condition = value & self.opcode_bit() != 0x00;
+        self.branch(AddressingMode::REL, condition);
+    }
+
+    // Branch always
+    fn BRA(&mut self, mode: AddressingMode) {
+        self.branch(mode, true);
+    }
+
+    // Push X register to stack
+    fn PHX(&mut self, mode: AddressingMode) {
+        self.push(self.x);
+    }
+
+    // Push Y register to stack
+    fn PHY(&mut self, mode: AddressingMode) {
+        self.push(self.y);
+    }
+
+    // Pull X register (from stack)
+    fn PLX(&mut self, mode: AddressingMode) {
+        self.x = self.pop();
+        self.set_zero_negative_flags(self.x);
+    }
+
+    // Pull Y register (from stack)
+    fn PLY(&mut self, mode: AddressingMode) {
+        self.y = self.pop();
+        self.set_zero_negative_flags(self.y);
+    }
+
+    // Reset (clear) memory bit
+    fn RMB(&mut self, mode: AddressingMode) {
+        let addr = self.get_address(mode);
+        let value = self.read(addr) & !self.opcode_bit();
+        self.write(addr, value);
+    }
+
+    // Set memory bit
+    fn SMB(&mut self, mode: AddressingMode) {
+        let addr = self.get_address(mode);
+        let value = self.read(addr) | self.opcode_bit();
+        self.write(addr, value);
+    }
+
+    // Stop the clock until reset
+    fn STP(&mut self, mode: AddressingMode) {
+        self.stopped = true;
+    }
+
+    // Store zero
+    fn STZ(&mut self, mode: AddressingMode) {
+        let addr = self.get_address(mode);
+        self.write(addr, 0x00);
+    }
+
+    // Test and reset bits: clear the accumulator's bits in memory, Z from A & M
+    fn TRB(&mut self, mode: AddressingMode) {
+        let addr = self.get_address(mode);
+        let value = self.read(addr);
+        self.set_flag(Flags::Z, self.a & value == 0x00);
+        self.write(addr, value & !self.a);
+    }
+
+    // Test and set bits: set the accumulator's bits in memory, Z from A & M
+    fn TSB(&mut self, mode: AddressingMode) {
+        let addr = self.get_address(mode);
+        let value = self.read(addr);
+        self.set_flag(Flags::Z, self.a & value == 0x00);
+        self.write(addr, value | self.a);
+    }
 
-    // When an illegal opcode is passed, XXX() is run
-    fn XXX(&mut self, mode: AddressingMode) {
-        panic!("Illegal operation!");
+    // Wait for interrupt
+    fn WAI(&mut self, mode: AddressingMode) {
+        self.waiting = true;
     }
 }
 
 /// Flags (in order from least to most significant bit)
 #[allow(unused)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Flags {
     /// Carry
     C = 0b0000_0001,
@@ -911,7 +1915,7 @@ pub enum Flags {
     Z = 0b0000_0010,
     /// Disable interrupt
     I = 0b0000_0100,
-    /// Decimal mode (not implemented)
+    /// Decimal mode
     D = 0b0000_1000,
     /// Break
     B = 0b0001_0000,
@@ -941,7 +1945,7 @@ impl Flags {
 }
 
 /// Debugging/testing functions
-impl CPU {
+impl<M: Memory> CPU<M> {
     /// Write program defined as `Vec<u8>` to memory
     pub fn load_program(&mut self, program: Vec<u8>) {
         // Point to program start address
@@ -955,12 +1959,14 @@ impl CPU {
         }
     }
 
-    /// Write program to memory, reset, and start clock
+    /// Write program to memory, power on, and start clock
+    ///
+    /// Errors stop the clock, leaving the CPU in the state they happened in.
     #[allow(unused)]
     pub fn quick_start(&mut self, program: Vec<u8>) {
         self.load_program(program);
-        self.reset();
-        self.clock();
+        self.power_on();
+        let _ = self.clock();
     }
 
     // Get registers
@@ -972,6 +1978,24 @@ impl CPU {
     pub fn get_y(&self) -> u8 { self.y }
     pub fn get_opcode(&self) -> u8 { self.opcode }
 
+    // Set registers
+    /// Jump to `pc` without going through the reset vector
+    pub fn set_pc(&mut self, pc: u16) { self.pc = pc; }
+    pub fn set_sp(&mut self, sp: u8) { self.sp = sp; }
+    pub fn set_a(&mut self, a: u8) { self.a = a; }
+    pub fn set_x(&mut self, x: u8) { self.x = x; }
+    pub fn set_y(&mut self, y: u8) { self.y = y; }
+    /// Set the status register, masking IRQs right away if I is set
+    pub fn set_sr(&mut self, sr: u8) {
+        self.sr = sr;
+        self.irq_masked = sr & Flags::I as u8 != 0;
+    }
+
+    /// Return all registers
+    pub fn registers(&self) -> Registers {
+        Registers { a: self.a, x: self.x, y: self.y, sp: self.sp, pc: self.pc, sr: self.sr }
+    }
+
     /// Return all registers as single `Vec<u16>`
     pub fn get_state(&self) -> Vec<u16> {
         vec![
@@ -985,14 +2009,28 @@ impl CPU {
         ]
     }
 
-    /// Get entirety of memory
+    /// Get entirety of memory (without side effects)
     pub fn get_memory(&self) -> [u8; 64 * 1024] {
-        self.bus.get_ram()
+        let mut memory = [0; 64 * 1024];
+        for (addr, byte) in memory.iter_mut().enumerate() {
+            *byte = self.bus.peek(addr as u16);
+        }
+        memory
+    }
+
+    /// Return the memory the CPU is connected to
+    pub fn bus(&self) -> &M {
+        &self.bus
+    }
+
+    /// Return the memory the CPU is connected to, mutably
+    pub fn bus_mut(&mut self) -> &mut M {
+        &mut self.bus
     }
 
     /// Construct CPU with custom values
     #[allow(clippy::too_many_arguments, unused)]
-    pub fn custom(a: u8, x: u8, y: u8, sp: u8, pc: u16, sr: u8, opcode: u8, bus: Bus,) -> Self {
+    pub fn custom(a: u8, x: u8, y: u8, sp: u8, pc: u16, sr: u8, opcode: u8, bus: M,) -> Self {
         CPU {
             a,
             x,
@@ -1002,6 +2040,24 @@ impl CPU {
             sr,
             opcode,
             bus,
+            cycles: 0,
+            page_crossed: false,
+            extra_cycles: 0,
+            jammed: false,
+            stopped: false,
+            waiting: false,
+            irq_lines: 0,
+            nmi_pending: false,
+            irq_masked: sr & Flags::I as u8 != 0,
+            instruction_pc: pc,
+            fault: None,
+            breakpoints: HashMap::new(),
+            resume_pc: None,
+            watchpoints: Vec::new(),
+            watch_hit: None,
+            tracer: None,
+            rewind: None,
+            config: CpuConfig::default(),
         }
     }
 }
@@ -0,0 +1,325 @@
+use std::{fmt, ops::RangeInclusive, str::FromStr};
+
+use crate::core::{
+    cpu::{CpuError, Flags},
+    trace::Registers,
+};
+
+/// Kind of memory access
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    Read,
+    Write,
+}
+
+/// Which accesses a watchpoint stops on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchKind {
+    Read,
+    Write,
+    /// Reads and writes
+    Access,
+}
+
+impl WatchKind {
+    pub fn matches(self, access: Access) -> bool {
+        match self {
+            WatchKind::Read => access == Access::Read,
+            WatchKind::Write => access == Access::Write,
+            WatchKind::Access => true,
+        }
+    }
+}
+
+/// Stops execution after an instruction accesses memory in `range`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Watchpoint {
+    pub range: RangeInclusive<u16>,
+    pub kind: WatchKind,
+    /// Number of times the watchpoint was triggered
+    pub hits: u64,
+}
+
+impl Watchpoint {
+    pub fn new(range: RangeInclusive<u16>, kind: WatchKind) -> Self {
+        Watchpoint { range, kind, hits: 0 }
+    }
+}
+
+/// Stops execution before the instruction at `addr`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Breakpoint {
+    pub addr: u16,
+    /// Only count a hit when the condition holds
+    pub condition: Option<Condition>,
+    /// Only stop from this hit on (1 stops on the first)
+    pub stop_at: u64,
+    /// Number of times PC reached `addr` with the condition holding
+    pub hits: u64,
+}
+
+impl Breakpoint {
+    pub fn new(addr: u16) -> Self {
+        Breakpoint { addr, condition: None, stop_at: 1, hits: 0 }
+    }
+
+    /// Only stop when `condition` holds
+    pub fn when(mut self, condition: Condition) -> Self {
+        self.condition = Some(condition);
+        self
+    }
+
+    /// Only stop from the `n`th hit on
+    pub fn after_hits(mut self, n: u64) -> Self {
+        self.stop_at = n;
+        self
+    }
+
+    /// Count a hit if the condition holds, returning whether execution should stop
+    pub fn hit(&mut self, registers: &Registers) -> bool {
+        if self.condition.as_ref().is_some_and(|condition| !condition.evaluate(registers)) {
+            return false;
+        }
+        self.hits += 1;
+        self.hits >= self.stop_at
+    }
+}
+
+/// Why `CPU::run_until` or `CPU::run_for` stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+    Breakpoint { pc: u16 },
+    Watchpoint { pc: u16, addr: u16, access: Access, value: u8 },
+    /// The predicate of `run_until` returned true
+    Predicate,
+    /// `run_for` ran out of cycles
+    CycleLimit,
+    /// WAI is waiting for an interrupt
+    Waiting,
+    /// STP stopped the clock
+    Stopped,
+    Error(CpuError),
+}
+
+impl From<CpuError> for StopReason {
+    fn from(error: CpuError) -> Self {
+        match error {
+            CpuError::BreakpointHit { pc } => StopReason::Breakpoint { pc },
+            CpuError::WatchpointHit { pc, addr, access, value } => StopReason::Watchpoint { pc, addr, access, value },
+            error => StopReason::Error(error),
+        }
+    }
+}
+
+/// Register or flag used in a `Condition`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operand {
+    A,
+    X,
+    Y,
+    SP,
+    PC,
+    P,
+    /// A single flag, 0 or 1
+    Flag(Flags),
+    Value(u16),
+}
+
+impl Operand {
+    fn value(self, registers: &Registers) -> u16 {
+        match self {
+            Operand::A => registers.a as u16,
+            Operand::X => registers.x as u16,
+            Operand::Y => registers.y as u16,
+            Operand::SP => registers.sp as u16,
+            Operand::PC => registers.pc,
+            Operand::P => registers.sr as u16,
+            Operand::Flag(flag) => (registers.sr & flag as u8 != 0) as u16,
+            Operand::Value(value) => value,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+/// Condition on the registers, like `A == $FF && X > 3`
+///
+/// Operands are the registers `A`, `X`, `Y`, `SP`, `PC` and `P`, the flags `C`, `Z`, `I`, `D`, `V` and `N` (0 or 1),
+/// and numbers (decimal, `$hex` or `%binary`). Comparisons are combined with `&&` and `||` (which binds weaker),
+/// and grouped with parentheses.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Condition {
+    Compare(Operand, Comparison, Operand),
+    And(Box<Condition>, Box<Condition>),
+    Or(Box<Condition>, Box<Condition>),
+}
+
+impl Condition {
+    pub fn evaluate(&self, registers: &Registers) -> bool {
+        match self {
+            Condition::Compare(left, comparison, right) => {
+                let (left, right) = (left.value(registers), right.value(registers));
+                match comparison {
+                    Comparison::Eq => left == right,
+                    Comparison::Ne => left != right,
+                    Comparison::Lt => left < right,
+                    Comparison::Le => left <= right,
+                    Comparison::Gt => left > right,
+                    Comparison::Ge => left >= right,
+                }
+            },
+            Condition::And(left, right) => left.evaluate(registers) && right.evaluate(registers),
+            Condition::Or(left, right) => left.evaluate(registers) || right.evaluate(registers),
+        }
+    }
+}
+
+/// A condition that couldn't be parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConditionError(pub String);
+
+impl fmt::Display for ConditionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid condition: {}", self.0)
+    }
+}
+
+impl std::error::Error for ConditionError {}
+
+impl FromStr for Condition {
+    type Err = ConditionError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let tokens = tokenize(text)?;
+        let mut parser = ConditionParser { tokens: &tokens, pos: 0 };
+        let condition = parser.or()?;
+        match parser.tokens.get(parser.pos) {
+            None => Ok(condition),
+            Some(token) => Err(ConditionError(format!("unexpected {}", token))),
+        }
+    }
+}
+
+fn tokenize(text: &str) -> Result<Vec<String>, ConditionError> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {},
+            '(' | ')' => tokens.push(c.to_string()),
+            '=' | '!' | '<' | '>' | '&' | '|' => {
+                let mut token = c.to_string();
+                if let Some(next) = chars.next_if(|next| matches!(next, '=' | '&' | '|')) {
+                    token.push(next);
+                }
+                tokens.push(token);
+            },
+            c if c.is_ascii_alphanumeric() || c == '$' || c == '%' => {
+                let mut token = c.to_string();
+                while let Some(next) = chars.next_if(char::is_ascii_alphanumeric) {
+                    token.push(next);
+                }
+                tokens.push(token);
+            },
+            c => return Err(ConditionError(format!("unexpected {}", c))),
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct ConditionParser<'a> {
+    tokens: &'a [String],
+    pos: usize,
+}
+
+impl ConditionParser<'_> {
+    fn next(&mut self) -> Result<&str, ConditionError> {
+        let token = self.tokens.get(self.pos).ok_or_else(|| ConditionError("unexpected end".to_string()))?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn accept(&mut self, token: &str) -> bool {
+        if self.tokens.get(self.pos).is_some_and(|next| next == token) {
+            self.pos += 1;
+            return true;
+        }
+        false
+    }
+
+    fn or(&mut self) -> Result<Condition, ConditionError> {
+        let mut condition = self.and()?;
+        while self.accept("||") {
+            condition = Condition::Or(Box::new(condition), Box::new(self.and()?));
+        }
+        Ok(condition)
+    }
+
+    fn and(&mut self) -> Result<Condition, ConditionError> {
+        let mut condition = self.comparison()?;
+        while self.accept("&&") {
+            condition = Condition::And(Box::new(condition), Box::new(self.comparison()?));
+        }
+        Ok(condition)
+    }
+
+    fn comparison(&mut self) -> Result<Condition, ConditionError> {
+        if self.accept("(") {
+            let condition = self.or()?;
+            if !self.accept(")") {
+                return Err(ConditionError("expected )".to_string()));
+            }
+            return Ok(condition);
+        }
+
+        let left = self.operand()?;
+        let comparison = match self.next()? {
+            "==" => Comparison::Eq,
+            "!=" => Comparison::Ne,
+            "<" => Comparison::Lt,
+            "<=" => Comparison::Le,
+            ">" => Comparison::Gt,
+            ">=" => Comparison::Ge,
+            token => return Err(ConditionError(format!("expected a comparison, found {}", token))),
+        };
+        let right = self.operand()?;
+        Ok(Condition::Compare(left, comparison, right))
+    }
+
+    fn operand(&mut self) -> Result<Operand, ConditionError> {
+        let token = self.next()?;
+        let number = |digits: &str, radix| {
+            u16::from_str_radix(digits, radix).map_err(|_| ConditionError(format!("invalid number {}", token)))
+        };
+
+        Ok(match token.to_ascii_uppercase().as_str() {
+            "A" => Operand::A,
+            "X" => Operand::X,
+            "Y" => Operand::Y,
+            "SP" => Operand::SP,
+            "PC" => Operand::PC,
+            "P" => Operand::P,
+            "C" => Operand::Flag(Flags::C),
+            "Z" => Operand::Flag(Flags::Z),
+            "I" => Operand::Flag(Flags::I),
+            "D" => Operand::Flag(Flags::D),
+            "V" => Operand::Flag(Flags::V),
+            "N" => Operand::Flag(Flags::N),
+            _ => match (token.strip_prefix('$'), token.strip_prefix('%')) {
+                (Some(hex), _) => Operand::Value(number(hex, 16)?),
+                (_, Some(binary)) => Operand::Value(number(binary, 2)?),
+                _ => Operand::Value(number(token, 10)?),
+            },
+        })
+    }
+}
@@ -0,0 +1,279 @@
+use crate::core::state::{StateError, StateReader, StateWriter};
+
+/// Anything the CPU can read from and write to: RAM, ROM, memory-mapped I/O, or a whole address space
+pub trait Memory {
+    /// Read `u8` value from `u16` address (reads may have side effects, e.g. clearing a status register)
+    fn read(&mut self, addr: u16) -> u8;
+
+    /// Write `u8` value to `u16` address
+    fn write(&mut self, addr: u16, data: u8);
+
+    /// Read `u8` value from `u16` address without side effects, for debuggers and displays
+    fn peek(&self, addr: u16) -> u8;
+
+    /// Write `u8` value to `u16` address without side effects, if it's plain memory (e.g. to undo a write)
+    ///
+    /// Returns whether it was; anything else, like memory-mapped I/O, is left untouched. By default, nothing is.
+    fn poke(&mut self, _addr: u16, _data: u8) -> bool {
+        false
+    }
+
+    /// Read `u16` data from `u16` address (little endian)
+    fn read_u16(&mut self, addr: u16) -> u16 {
+        let lo = self.read(addr) as u16;
+        let hi = self.read(addr.wrapping_add(1)) as u16;
+        (hi << 8) | lo
+    }
+
+    /// Write `u16` data to `u16` address (little endian)
+    fn write_u16(&mut self, addr: u16, data: u16) {
+        self.write(addr, data as u8);
+        self.write(addr.wrapping_add(1), (data >> 8) as u8);
+    }
+
+    /// Save the device's state (by default, there is none)
+    fn save_state(&self, _writer: &mut StateWriter) {}
+
+    /// Restore the device's state, as written by `save_state`
+    fn load_state(&mut self, _reader: &mut StateReader) -> Result<(), StateError> {
+        Ok(())
+    }
+}
+
+impl<M: Memory + ?Sized> Memory for Box<M> {
+    fn read(&mut self, addr: u16) -> u8 {
+        (**self).read(addr)
+    }
+
+    fn write(&mut self, addr: u16, data: u8) {
+        (**self).write(addr, data);
+    }
+
+    fn peek(&self, addr: u16) -> u8 {
+        (**self).peek(addr)
+    }
+
+    fn poke(&mut self, addr: u16, data: u8) -> bool {
+        (**self).poke(addr, data)
+    }
+
+    fn save_state(&self, writer: &mut StateWriter) {
+        (**self).save_state(writer);
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader) -> Result<(), StateError> {
+        (**self).load_state(reader)
+    }
+}
+
+/// Read/write memory of a fixed size
+///
+/// Addresses past the end wrap around, so a small RAM mapped to a larger region is mirrored. Empty RAM reads 0.
+pub struct Ram {
+    data: Vec<u8>,
+}
+
+impl Ram {
+    /// Construct `size` bytes of zeroed RAM
+    pub fn new(size: usize) -> Self {
+        Ram { data: vec![0; size] }
+    }
+}
+
+impl Memory for Ram {
+    fn read(&mut self, addr: u16) -> u8 {
+        self.peek(addr)
+    }
+
+    fn write(&mut self, addr: u16, data: u8) {
+        let len = self.data.len();
+        if len > 0 {
+            self.data[addr as usize % len] = data;
+        }
+    }
+
+    fn peek(&self, addr: u16) -> u8 {
+        match self.data.len() {
+            0 => 0,
+            len => self.data[addr as usize % len],
+        }
+    }
+
+    fn poke(&mut self, addr: u16, data: u8) -> bool {
+        self.write(addr, data);
+        true
+    }
+
+    fn save_state(&self, writer: &mut StateWriter) {
+        writer.write_bytes(&self.data);
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader) -> Result<(), StateError> {
+        reader.read_bytes_into(&mut self.data)
+    }
+}
+
+/// Read-only memory, initialised from a byte vector
+///
+/// Writes are ignored. Like `Ram`, addresses past the end wrap around and empty ROM reads 0. Contents aren't part of
+/// save states.
+pub struct Rom {
+    data: Vec<u8>,
+}
+
+impl Rom {
+    pub fn new(data: Vec<u8>) -> Self {
+        Rom { data }
+    }
+}
+
+impl Memory for Rom {
+    fn read(&mut self, addr: u16) -> u8 {
+        self.peek(addr)
+    }
+
+    fn write(&mut self, _addr: u16, _data: u8) {}
+
+    fn peek(&self, addr: u16) -> u8 {
+        match self.data.len() {
+            0 => 0,
+            len => self.data[addr as usize % len],
+        }
+    }
+
+    // Writes never change ROM, so there's nothing to restore
+    fn poke(&mut self, _addr: u16, _data: u8) -> bool {
+        true
+    }
+}
+
+/// A device mapped to a range of addresses in a `MemoryMap`
+pub struct Mapping {
+    start: u16,
+    end: u16,
+    mask: u16,
+    read_only: bool,
+    device: Box<dyn Memory>,
+}
+
+impl Mapping {
+    /// Mirror the device across the region: the device sees `(addr - start) & mask`
+    pub fn mirror(&mut self, mask: u16) -> &mut Self {
+        self.mask = mask;
+        self
+    }
+
+    /// Ignore writes to the region
+    pub fn read_only(&mut self) -> &mut Self {
+        self.read_only = true;
+        self
+    }
+
+    // Return the device address for `addr`, if it falls in this region
+    fn translate(&self, addr: u16) -> Option<u16> {
+        if (self.start..=self.end).contains(&addr) {
+            Some((addr - self.start) & self.mask)
+        } else {
+            None
+        }
+    }
+}
+
+/// Address space made of devices mapped to address ranges
+///
+/// Regions are searched in the order they were mapped, so earlier mappings take precedence where they overlap.
+/// Reads from unmapped addresses return the last value read (open bus), writes to them are ignored.
+///
+/// ```
+/// use emulatorr::core::memory::{MemoryMap, Ram, Rom};
+///
+/// let mut map = MemoryMap::new();
+/// // 2KB of RAM, mirrored 4 times
+/// map.map(0x0000, 0x1FFF, Ram::new(0x0800)).mirror(0x07FF);
+/// // 32KB of ROM
+/// map.map(0x8000, 0xFFFF, Rom::new(vec![0xEA; 0x8000]));
+/// ```
+#[derive(Default)]
+pub struct MemoryMap {
+    mappings: Vec<Mapping>,
+    open_bus: u8,
+}
+
+impl MemoryMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Map `device` to `start..=end`, returning the mapping so it can be mirrored or made read-only
+    ///
+    /// The device sees addresses relative to `start`.
+    pub fn map<D: Memory + 'static>(&mut self, start: u16, end: u16, device: D) -> &mut Mapping {
+        self.mappings.push(Mapping {
+            start,
+            end,
+            mask: 0xFFFF,
+            read_only: false,
+            device: Box::new(device),
+        });
+        self.mappings.last_mut().unwrap()
+    }
+
+    // Return the mapping containing `addr` and the device address, if any
+    fn find(&self, addr: u16) -> Option<(usize, u16)> {
+        self.mappings
+            .iter()
+            .enumerate()
+            .find_map(|(i, mapping)| mapping.translate(addr).map(|device_addr| (i, device_addr)))
+    }
+}
+
+impl Memory for MemoryMap {
+    fn read(&mut self, addr: u16) -> u8 {
+        if let Some((i, device_addr)) = self.find(addr) {
+            self.open_bus = self.mappings[i].device.read(device_addr);
+        }
+        self.open_bus
+    }
+
+    fn write(&mut self, addr: u16, data: u8) {
+        if let Some((i, device_addr)) = self.find(addr) {
+            if !self.mappings[i].read_only {
+                self.mappings[i].device.write(device_addr, data);
+            }
+        }
+    }
+
+    fn peek(&self, addr: u16) -> u8 {
+        match self.find(addr) {
+            Some((i, device_addr)) => self.mappings[i].device.peek(device_addr),
+            None => self.open_bus,
+        }
+    }
+
+    fn poke(&mut self, addr: u16, data: u8) -> bool {
+        match self.find(addr) {
+            Some((i, device_addr)) if !self.mappings[i].read_only => self.mappings[i].device.poke(device_addr, data),
+            // Writes to read-only and unmapped regions are ignored anyway
+            _ => true,
+        }
+    }
+
+    fn save_state(&self, writer: &mut StateWriter) {
+        writer.write_u8(self.open_bus);
+        writer.write_u32(self.mappings.len() as u32);
+        for mapping in &self.mappings {
+            mapping.device.save_state(writer);
+        }
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader) -> Result<(), StateError> {
+        self.open_bus = reader.read_u8()?;
+        if reader.read_u32()? as usize != self.mappings.len() {
+            return Err(StateError::Mismatch("memory map"));
+        }
+        for mapping in &mut self.mappings {
+            mapping.device.load_state(reader)?;
+        }
+        Ok(())
+    }
+}
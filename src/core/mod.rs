@@ -1,2 +1,9 @@
 pub mod bus;
-pub mod cpu;
\ No newline at end of file
+pub mod cpu;
+pub mod debug;
+pub mod memory;
+pub mod opcodes;
+pub mod rewind;
+pub mod state;
+pub mod timing;
+pub mod trace;
@@ -0,0 +1,1104 @@
+use crate::core::cpu::{AddressingMode, CpuVariant};
+
+/// Instruction mnemonics
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::upper_case_acronyms)]
+pub enum Mnemonic {
+    ADC, AND, ASL, BCC, BCS, BEQ, BIT, BMI, BNE, BPL, BRK, BVC, BVS, CLC,
+    CLD, CLI, CLV, CMP, CPX, CPY, DEC, DEX, DEY, EOR, INC, INX, INY, JMP,
+    JSR, LDA, LDX, LDY, LSR, NOP, ORA, PHA, PHP, PLA, PLP, ROL, ROR, RTI,
+    RTS, SBC, SEC, SED, SEI, STA, STX, STY, TAX, TAY, TSX, TXA, TXS, TYA,
+    // Undocumented (NMOS)
+    ALR, ANC, ANE, ARR, DCP, ISC, JAM, LAS, LAX, LXA, RLA, RRA, SAX, SBX,
+    SHA, SHX, SHY, SLO, SRE, TAS,
+    // 65C02 (RMB, SMB, BBR and BBS take their bit number from the opcode's high nibble)
+    BBR, BBS, BRA, PHX, PHY, PLX, PLY, RMB, SMB, STP, STZ, TRB, TSB, WAI,
+}
+
+/// Static decoding information for a single opcode
+#[derive(Debug, Clone, Copy)]
+pub struct Opcode {
+    /// Instruction to execute
+    pub mnemonic: Mnemonic,
+    /// How the operand is addressed
+    pub mode: AddressingMode,
+    /// Length of the instruction in bytes, including the opcode itself
+    pub len: u8,
+    /// Base number of cycles the instruction takes
+    pub cycles: u8,
+    /// Whether crossing a page boundary costs an extra cycle
+    pub page_cross: bool,
+    /// Whether the opcode is undocumented (or unused, on the 65C02)
+    pub illegal: bool,
+}
+
+const fn op(mnemonic: Mnemonic, mode: AddressingMode, len: u8, cycles: u8, page_cross: bool) -> Opcode {
+    Opcode { mnemonic, mode, len, cycles, page_cross, illegal: false }
+}
+
+const fn ill(mnemonic: Mnemonic, mode: AddressingMode, len: u8, cycles: u8, page_cross: bool) -> Opcode {
+    Opcode { mnemonic, mode, len, cycles, page_cross, illegal: true }
+}
+
+/// Look up the decoding information for `opcode` (NMOS 6502)
+pub fn decode(opcode: u8) -> &'static Opcode {
+    &OPCODES[opcode as usize]
+}
+
+/// Return the opcode table of a `CpuVariant`
+pub fn table(variant: CpuVariant) -> &'static [Opcode; 256] {
+    match variant {
+        CpuVariant::Nmos6502 | CpuVariant::Ricoh2A03 => &OPCODES,
+        CpuVariant::Cmos65C02 => &OPCODES_65C02,
+        CpuVariant::Rockwell65C02 => &OPCODES_R65C02,
+        CpuVariant::Wdc65C02 => &OPCODES_W65C02,
+    }
+}
+
+use Mnemonic as M;
+use AddressingMode as A;
+
+/// Opcode table of the NMOS 6502 (and the 2A03), indexed by opcode
+///
+/// This is the single source of truth for decoding: the CPU, and any tooling built on top of it, should look
+/// instructions up here instead of matching on opcodes directly.
+pub static OPCODES: [Opcode; 256] = [
+    /* 0x00 */ op(M::BRK, A::IMP, 1, 7, false),
+    /* 0x01 */ op(M::ORA, A::IDX, 2, 6, false),
+    /* 0x02 */ ill(M::JAM, A::IMP, 1, 2, false),
+    /* 0x03 */ ill(M::SLO, A::IDX, 2, 8, false),
+    /* 0x04 */ ill(M::NOP, A::ZP0, 2, 3, false),
+    /* 0x05 */ op(M::ORA, A::ZP0, 2, 3, false),
+    /* 0x06 */ op(M::ASL, A::ZP0, 2, 5, false),
+    /* 0x07 */ ill(M::SLO, A::ZP0, 2, 5, false),
+    /* 0x08 */ op(M::PHP, A::IMP, 1, 3, false),
+    /* 0x09 */ op(M::ORA, A::IMM, 2, 2, false),
+    /* 0x0A */ op(M::ASL, A::ACC, 1, 2, false),
+    /* 0x0B */ ill(M::ANC, A::IMM, 2, 2, false),
+    /* 0x0C */ ill(M::NOP, A::ABS, 3, 4, false),
+    /* 0x0D */ op(M::ORA, A::ABS, 3, 4, false),
+    /* 0x0E */ op(M::ASL, A::ABS, 3, 6, false),
+    /* 0x0F */ ill(M::SLO, A::ABS, 3, 6, false),
+    /* 0x10 */ op(M::BPL, A::REL, 2, 2, true),
+    /* 0x11 */ op(M::ORA, A::IDY, 2, 5, true),
+    /* 0x12 */ ill(M::JAM, A::IMP, 1, 2, false),
+    /* 0x13 */ ill(M::SLO, A::IDY, 2, 8, false),
+    /* 0x14 */ ill(M::NOP, A::ZPX, 2, 4, false),
+    /* 0x15 */ op(M::ORA, A::ZPX, 2, 4, false),
+    /* 0x16 */ op(M::ASL, A::ZPX, 2, 6, false),
+    /* 0x17 */ ill(M::SLO, A::ZPX, 2, 6, false),
+    /* 0x18 */ op(M::CLC, A::IMP, 1, 2, false),
+    /* 0x19 */ op(M::ORA, A::ABY, 3, 4, true),
+    /* 0x1A */ ill(M::NOP, A::IMP, 1, 2, false),
+    /* 0x1B */ ill(M::SLO, A::ABY, 3, 7, false),
+    /* 0x1C */ ill(M::NOP, A::ABX, 3, 4, true),
+    /* 0x1D */ op(M::ORA, A::ABX, 3, 4, true),
+    /* 0x1E */ op(M::ASL, A::ABX, 3, 7, false),
+    /* 0x1F */ ill(M::SLO, A::ABX, 3, 7, false),
+    /* 0x20 */ op(M::JSR, A::ABS, 3, 6, false),
+    /* 0x21 */ op(M::AND, A::IDX, 2, 6, false),
+    /* 0x22 */ ill(M::JAM, A::IMP, 1, 2, false),
+    /* 0x23 */ ill(M::RLA, A::IDX, 2, 8, false),
+    /* 0x24 */ op(M::BIT, A::ZP0, 2, 3, false),
+    /* 0x25 */ op(M::AND, A::ZP0, 2, 3, false),
+    /* 0x26 */ op(M::ROL, A::ZP0, 2, 5, false),
+    /* 0x27 */ ill(M::RLA, A::ZP0, 2, 5, false),
+    /* 0x28 */ op(M::PLP, A::IMP, 1, 4, false),
+    /* 0x29 */ op(M::AND, A::IMM, 2, 2, false),
+    /* 0x2A */ op(M::ROL, A::ACC, 1, 2, false),
+    /* 0x2B */ ill(M::ANC, A::IMM, 2, 2, false),
+    /* 0x2C */ op(M::BIT, A::ABS, 3, 4, false),
+    /* 0x2D */ op(M::AND, A::ABS, 3, 4, false),
+    /* 0x2E */ op(M::ROL, A::ABS, 3, 6, false),
+    /* 0x2F */ ill(M::RLA, A::ABS, 3, 6, false),
+    /* 0x30 */ op(M::BMI, A::REL, 2, 2, true),
+    /* 0x31 */ op(M::AND, A::IDY, 2, 5, true),
+    /* 0x32 */ ill(M::JAM, A::IMP, 1, 2, false),
+    /* 0x33 */ ill(M::RLA, A::IDY, 2, 8, false),
+    /* 0x34 */ ill(M::NOP, A::ZPX, 2, 4, false),
+    /* 0x35 */ op(M::AND, A::ZPX, 2, 4, false),
+    /* 0x36 */ op(M::ROL, A::ZPX, 2, 6, false),
+    /* 0x37 */ ill(M::RLA, A::ZPX, 2, 6, false),
+    /* 0x38 */ op(M::SEC, A::IMP, 1, 2, false),
+    /* 0x39 */ op(M::AND, A::ABY, 3, 4, true),
+    /* 0x3A */ ill(M::NOP, A::IMP, 1, 2, false),
+    /* 0x3B */ ill(M::RLA, A::ABY, 3, 7, false),
+    /* 0x3C */ ill(M::NOP, A::ABX, 3, 4, true),
+    /* 0x3D */ op(M::AND, A::ABX, 3, 4, true),
+    /* 0x3E */ op(M::ROL, A::ABX, 3, 7, false),
+    /* 0x3F */ ill(M::RLA, A::ABX, 3, 7, false),
+    /* 0x40 */ op(M::RTI, A::IMP, 1, 6, false),
+    /* 0x41 */ op(M::EOR, A::IDX, 2, 6, false),
+    /* 0x42 */ ill(M::JAM, A::IMP, 1, 2, false),
+    /* 0x43 */ ill(M::SRE, A::IDX, 2, 8, false),
+    /* 0x44 */ ill(M::NOP, A::ZP0, 2, 3, false),
+    /* 0x45 */ op(M::EOR, A::ZP0, 2, 3, false),
+    /* 0x46 */ op(M::LSR, A::ZP0, 2, 5, false),
+    /* 0x47 */ ill(M::SRE, A::ZP0, 2, 5, false),
+    /* 0x48 */ op(M::PHA, A::IMP, 1, 3, false),
+    /* 0x49 */ op(M::EOR, A::IMM, 2, 2, false),
+    /* 0x4A */ op(M::LSR, A::ACC, 1, 2, false),
+    /* 0x4B */ ill(M::ALR, A::IMM, 2, 2, false),
+    /* 0x4C */ op(M::JMP, A::ABS, 3, 3, false),
+    /* 0x4D */ op(M::EOR, A::ABS, 3, 4, false),
+    /* 0x4E */ op(M::LSR, A::ABS, 3, 6, false),
+    /* 0x4F */ ill(M::SRE, A::ABS, 3, 6, false),
+    /* 0x50 */ op(M::BVC, A::REL, 2, 2, true),
+    /* 0x51 */ op(M::EOR, A::IDY, 2, 5, true),
+    /* 0x52 */ ill(M::JAM, A::IMP, 1, 2, false),
+    /* 0x53 */ ill(M::SRE, A::IDY, 2, 8, false),
+    /* 0x54 */ ill(M::NOP, A::ZPX, 2, 4, false),
+    /* 0x55 */ op(M::EOR, A::ZPX, 2, 4, false),
+    /* 0x56 */ op(M::LSR, A::ZPX, 2, 6, false),
+    /* 0x57 */ ill(M::SRE, A::ZPX, 2, 6, false),
+    /* 0x58 */ op(M::CLI, A::IMP, 1, 2, false),
+    /* 0x59 */ op(M::EOR, A::ABY, 3, 4, true),
+    /* 0x5A */ ill(M::NOP, A::IMP, 1, 2, false),
+    /* 0x5B */ ill(M::SRE, A::ABY, 3, 7, false),
+    /* 0x5C */ ill(M::NOP, A::ABX, 3, 4, true),
+    /* 0x5D */ op(M::EOR, A::ABX, 3, 4, true),
+    /* 0x5E */ op(M::LSR, A::ABX, 3, 7, false),
+    /* 0x5F */ ill(M::SRE, A::ABX, 3, 7, false),
+    /* 0x60 */ op(M::RTS, A::IMP, 1, 6, false),
+    /* 0x61 */ op(M::ADC, A::IDX, 2, 6, false),
+    /* 0x62 */ ill(M::JAM, A::IMP, 1, 2, false),
+    /* 0x63 */ ill(M::RRA, A::IDX, 2, 8, false),
+    /* 0x64 */ ill(M::NOP, A::ZP0, 2, 3, false),
+    /* 0x65 */ op(M::ADC, A::ZP0, 2, 3, false),
+    /* 0x66 */ op(M::ROR, A::ZP0, 2, 5, false),
+    /* 0x67 */ ill(M::RRA, A::ZP0, 2, 5, false),
+    /* 0x68 */ op(M::PLA, A::IMP, 1, 4, false),
+    /* 0x69 */ op(M::ADC, A::IMM, 2, 2, false),
+    /* 0x6A */ op(M::ROR, A::ACC, 1, 2, false),
+    /* 0x6B */ ill(M::ARR, A::IMM, 2, 2, false),
+    /* 0x6C */ op(M::JMP, A::IND, 3, 5, false),
+    /* 0x6D */ op(M::ADC, A::ABS, 3, 4, false),
+    /* 0x6E */ op(M::ROR, A::ABS, 3, 6, false),
+    /* 0x6F */ ill(M::RRA, A::ABS, 3, 6, false),
+    /* 0x70 */ op(M::BVS, A::REL, 2, 2, true),
+    /* 0x71 */ op(M::ADC, A::IDY, 2, 5, true),
+    /* 0x72 */ ill(M::JAM, A::IMP, 1, 2, false),
+    /* 0x73 */ ill(M::RRA, A::IDY, 2, 8, false),
+    /* 0x74 */ ill(M::NOP, A::ZPX, 2, 4, false),
+    /* 0x75 */ op(M::ADC, A::ZPX, 2, 4, false),
+    /* 0x76 */ op(M::ROR, A::ZPX, 2, 6, false),
+    /* 0x77 */ ill(M::RRA, A::ZPX, 2, 6, false),
+    /* 0x78 */ op(M::SEI, A::IMP, 1, 2, false),
+    /* 0x79 */ op(M::ADC, A::ABY, 3, 4, true),
+    /* 0x7A */ ill(M::NOP, A::IMP, 1, 2, false),
+    /* 0x7B */ ill(M::RRA, A::ABY, 3, 7, false),
+    /* 0x7C */ ill(M::NOP, A::ABX, 3, 4, true),
+    /* 0x7D */ op(M::ADC, A::ABX, 3, 4, true),
+    /* 0x7E */ op(M::ROR, A::ABX, 3, 7, false),
+    /* 0x7F */ ill(M::RRA, A::ABX, 3, 7, false),
+    /* 0x80 */ ill(M::NOP, A::IMM, 2, 2, false),
+    /* 0x81 */ op(M::STA, A::IDX, 2, 6, false),
+    /* 0x82 */ ill(M::NOP, A::IMM, 2, 2, false),
+    /* 0x83 */ ill(M::SAX, A::IDX, 2, 6, false),
+    /* 0x84 */ op(M::STY, A::ZP0, 2, 3, false),
+    /* 0x85 */ op(M::STA, A::ZP0, 2, 3, false),
+    /* 0x86 */ op(M::STX, A::ZP0, 2, 3, false),
+    /* 0x87 */ ill(M::SAX, A::ZP0, 2, 3, false),
+    /* 0x88 */ op(M::DEY, A::IMP, 1, 2, false),
+    /* 0x89 */ ill(M::NOP, A::IMM, 2, 2, false),
+    /* 0x8A */ op(M::TXA, A::IMP, 1, 2, false),
+    /* 0x8B */ ill(M::ANE, A::IMM, 2, 2, false),
+    /* 0x8C */ op(M::STY, A::ABS, 3, 4, false),
+    /* 0x8D */ op(M::STA, A::ABS, 3, 4, false),
+    /* 0x8E */ op(M::STX, A::ABS, 3, 4, false),
+    /* 0x8F */ ill(M::SAX, A::ABS, 3, 4, false),
+    /* 0x90 */ op(M::BCC, A::REL, 2, 2, true),
+    /* 0x91 */ op(M::STA, A::IDY, 2, 6, false),
+    /* 0x92 */ ill(M::JAM, A::IMP, 1, 2, false),
+    /* 0x93 */ ill(M::SHA, A::IDY, 2, 6, false),
+    /* 0x94 */ op(M::STY, A::ZPX, 2, 4, false),
+    /* 0x95 */ op(M::STA, A::ZPX, 2, 4, false),
+    /* 0x96 */ op(M::STX, A::ZPY, 2, 4, false),
+    /* 0x97 */ ill(M::SAX, A::ZPY, 2, 4, false),
+    /* 0x98 */ op(M::TYA, A::IMP, 1, 2, false),
+    /* 0x99 */ op(M::STA, A::ABY, 3, 5, false),
+    /* 0x9A */ op(M::TXS, A::IMP, 1, 2, false),
+    /* 0x9B */ ill(M::TAS, A::ABY, 3, 5, false),
+    /* 0x9C */ ill(M::SHY, A::ABX, 3, 5, false),
+    /* 0x9D */ op(M::STA, A::ABX, 3, 5, false),
+    /* 0x9E */ ill(M::SHX, A::ABY, 3, 5, false),
+    /* 0x9F */ ill(M::SHA, A::ABY, 3, 5, false),
+    /* 0xA0 */ op(M::LDY, A::IMM, 2, 2, false),
+    /* 0xA1 */ op(M::LDA, A::IDX, 2, 6, false),
+    /* 0xA2 */ op(M::LDX, A::IMM, 2, 2, false),
+    /* 0xA3 */ ill(M::LAX, A::IDX, 2, 6, false),
+    /* 0xA4 */ op(M::LDY, A::ZP0, 2, 3, false),
+    /* 0xA5 */ op(M::LDA, A::ZP0, 2, 3, false),
+    /* 0xA6 */ op(M::LDX, A::ZP0, 2, 3, false),
+    /* 0xA7 */ ill(M::LAX, A::ZP0, 2, 3, false),
+    /* 0xA8 */ op(M::TAY, A::IMP, 1, 2, false),
+    /* 0xA9 */ op(M::LDA, A::IMM, 2, 2, false),
+    /* 0xAA */ op(M::TAX, A::IMP, 1, 2, false),
+    /* 0xAB */ ill(M::LXA, A::IMM, 2, 2, false),
+    /* 0xAC */ op(M::LDY, A::ABS, 3, 4, false),
+    /* 0xAD */ op(M::LDA, A::ABS, 3, 4, false),
+    /* 0xAE */ op(M::LDX, A::ABS, 3, 4, false),
+    /* 0xAF */ ill(M::LAX, A::ABS, 3, 4, false),
+    /* 0xB0 */ op(M::BCS, A::REL, 2, 2, true),
+    /* 0xB1 */ op(M::LDA, A::IDY, 2, 5, true),
+    /* 0xB2 */ ill(M::JAM, A::IMP, 1, 2, false),
+    /* 0xB3 */ ill(M::LAX, A::IDY, 2, 5, true),
+    /* 0xB4 */ op(M::LDY, A::ZPX, 2, 4, false),
+    /* 0xB5 */ op(M::LDA, A::ZPX, 2, 4, false),
+    /* 0xB6 */ op(M::LDX, A::ZPY, 2, 4, false),
+    /* 0xB7 */ ill(M::LAX, A::ZPY, 2, 4, false),
+    /* 0xB8 */ op(M::CLV, A::IMP, 1, 2, false),
+    /* 0xB9 */ op(M::LDA, A::ABY, 3, 4, true),
+    /* 0xBA */ op(M::TSX, A::IMP, 1, 2, false),
+    /* 0xBB */ ill(M::LAS, A::ABY, 3, 4, true),
+    /* 0xBC */ op(M::LDY, A::ABX, 3, 4, true),
+    /* 0xBD */ op(M::LDA, A::ABX, 3, 4, true),
+    /* 0xBE */ op(M::LDX, A::ABY, 3, 4, true),
+    /* 0xBF */ ill(M::LAX, A::ABY, 3, 4, true),
+    /* 0xC0 */ op(M::CPY, A::IMM, 2, 2, false),
+    /* 0xC1 */ op(M::CMP, A::IDX, 2, 6, false),
+    /* 0xC2 */ ill(M::NOP, A::IMM, 2, 2, false),
+    /* 0xC3 */ ill(M::DCP, A::IDX, 2, 8, false),
+    /* 0xC4 */ op(M::CPY, A::ZP0, 2, 3, false),
+    /* 0xC5 */ op(M::CMP, A::ZP0, 2, 3, false),
+    /* 0xC6 */ op(M::DEC, A::ZP0, 2, 5, false),
+    /* 0xC7 */ ill(M::DCP, A::ZP0, 2, 5, false),
+    /* 0xC8 */ op(M::INY, A::IMP, 1, 2, false),
+    /* 0xC9 */ op(M::CMP, A::IMM, 2, 2, false),
+    /* 0xCA */ op(M::DEX, A::IMP, 1, 2, false),
+    /* 0xCB */ ill(M::SBX, A::IMM, 2, 2, false),
+    /* 0xCC */ op(M::CPY, A::ABS, 3, 4, false),
+    /* 0xCD */ op(M::CMP, A::ABS, 3, 4, false),
+    /* 0xCE */ op(M::DEC, A::ABS, 3, 6, false),
+    /* 0xCF */ ill(M::DCP, A::ABS, 3, 6, false),
+    /* 0xD0 */ op(M::BNE, A::REL, 2, 2, true),
+    /* 0xD1 */ op(M::CMP, A::IDY, 2, 5, true),
+    /* 0xD2 */ ill(M::JAM, A::IMP, 1, 2, false),
+    /* 0xD3 */ ill(M::DCP, A::IDY, 2, 8, false),
+    /* 0xD4 */ ill(M::NOP, A::ZPX, 2, 4, false),
+    /* 0xD5 */ op(M::CMP, A::ZPX, 2, 4, false),
+    /* 0xD6 */ op(M::DEC, A::ZPX, 2, 6, false),
+    /* 0xD7 */ ill(M::DCP, A::ZPX, 2, 6, false),
+    /* 0xD8 */ op(M::CLD, A::IMP, 1, 2, false),
+    /* 0xD9 */ op(M::CMP, A::ABY, 3, 4, true),
+    /* 0xDA */ ill(M::NOP, A::IMP, 1, 2, false),
+    /* 0xDB */ ill(M::DCP, A::ABY, 3, 7, false),
+    /* 0xDC */ ill(M::NOP, A::ABX, 3, 4, true),
+    /* 0xDD */ op(M::CMP, A::ABX, 3, 4, true),
+    /* 0xDE */ op(M::DEC, A::ABX, 3, 7, false),
+    /* 0xDF */ ill(M::DCP, A::ABX, 3, 7, false),
+    /* 0xE0 */ op(M::CPX, A::IMM, 2, 2, false),
+    /* 0xE1 */ op(M::SBC, A::IDX, 2, 6, false),
+    /* 0xE2 */ ill(M::NOP, A::IMM, 2, 2, false),
+    /* 0xE3 */ ill(M::ISC, A::IDX, 2, 8, false),
+    /* 0xE4 */ op(M::CPX, A::ZP0, 2, 3, false),
+    /* 0xE5 */ op(M::SBC, A::ZP0, 2, 3, false),
+    /* 0xE6 */ op(M::INC, A::ZP0, 2, 5, false),
+    /* 0xE7 */ ill(M::ISC, A::ZP0, 2, 5, false),
+    /* 0xE8 */ op(M::INX, A::IMP, 1, 2, false),
+    /* 0xE9 */ op(M::SBC, A::IMM, 2, 2, false),
+    /* 0xEA */ op(M::NOP, A::IMP, 1, 2, false),
+    /* 0xEB */ ill(M::SBC, A::IMM, 2, 2, false),
+    /* 0xEC */ op(M::CPX, A::ABS, 3, 4, false),
+    /* 0xED */ op(M::SBC, A::ABS, 3, 4, false),
+    /* 0xEE */ op(M::INC, A::ABS, 3, 6, false),
+    /* 0xEF */ ill(M::ISC, A::ABS, 3, 6, false),
+    /* 0xF0 */ op(M::BEQ, A::REL, 2, 2, true),
+    /* 0xF1 */ op(M::SBC, A::IDY, 2, 5, true),
+    /* 0xF2 */ ill(M::JAM, A::IMP, 1, 2, false),
+    /* 0xF3 */ ill(M::ISC, A::IDY, 2, 8, false),
+    /* 0xF4 */ ill(M::NOP, A::ZPX, 2, 4, false),
+    /* 0xF5 */ op(M::SBC, A::ZPX, 2, 4, false),
+    /* 0xF6 */ op(M::INC, A::ZPX, 2, 6, false),
+    /* 0xF7 */ ill(M::ISC, A::ZPX, 2, 6, false),
+    /* 0xF8 */ op(M::SED, A::IMP, 1, 2, false),
+    /* 0xF9 */ op(M::SBC, A::ABY, 3, 4, true),
+    /* 0xFA */ ill(M::NOP, A::IMP, 1, 2, false),
+    /* 0xFB */ ill(M::ISC, A::ABY, 3, 7, false),
+    /* 0xFC */ ill(M::NOP, A::ABX, 3, 4, true),
+    /* 0xFD */ op(M::SBC, A::ABX, 3, 4, true),
+    /* 0xFE */ op(M::INC, A::ABX, 3, 7, false),
+    /* 0xFF */ ill(M::ISC, A::ABX, 3, 7, false),
+];
+
+/// Opcode table of the CMOS 65C02
+///
+/// Unused opcodes are NOPs of various lengths and timings, instead of the NMOS's undocumented instructions.
+pub static OPCODES_65C02: [Opcode; 256] = [
+    /* 0x00 */ op(M::BRK, A::IMP, 1, 7, false),
+    /* 0x01 */ op(M::ORA, A::IDX, 2, 6, false),
+    /* 0x02 */ ill(M::NOP, A::IMM, 2, 2, false),
+    /* 0x03 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x04 */ op(M::TSB, A::ZP0, 2, 5, false),
+    /* 0x05 */ op(M::ORA, A::ZP0, 2, 3, false),
+    /* 0x06 */ op(M::ASL, A::ZP0, 2, 5, false),
+    /* 0x07 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x08 */ op(M::PHP, A::IMP, 1, 3, false),
+    /* 0x09 */ op(M::ORA, A::IMM, 2, 2, false),
+    /* 0x0A */ op(M::ASL, A::ACC, 1, 2, false),
+    /* 0x0B */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x0C */ op(M::TSB, A::ABS, 3, 6, false),
+    /* 0x0D */ op(M::ORA, A::ABS, 3, 4, false),
+    /* 0x0E */ op(M::ASL, A::ABS, 3, 6, false),
+    /* 0x0F */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x10 */ op(M::BPL, A::REL, 2, 2, true),
+    /* 0x11 */ op(M::ORA, A::IDY, 2, 5, true),
+    /* 0x12 */ op(M::ORA, A::ZPI, 2, 5, false),
+    /* 0x13 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x14 */ op(M::TRB, A::ZP0, 2, 5, false),
+    /* 0x15 */ op(M::ORA, A::ZPX, 2, 4, false),
+    /* 0x16 */ op(M::ASL, A::ZPX, 2, 6, false),
+    /* 0x17 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x18 */ op(M::CLC, A::IMP, 1, 2, false),
+    /* 0x19 */ op(M::ORA, A::ABY, 3, 4, true),
+    /* 0x1A */ op(M::INC, A::ACC, 1, 2, false),
+    /* 0x1B */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x1C */ op(M::TRB, A::ABS, 3, 6, false),
+    /* 0x1D */ op(M::ORA, A::ABX, 3, 4, true),
+    /* 0x1E */ op(M::ASL, A::ABX, 3, 6, true),
+    /* 0x1F */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x20 */ op(M::JSR, A::ABS, 3, 6, false),
+    /* 0x21 */ op(M::AND, A::IDX, 2, 6, false),
+    /* 0x22 */ ill(M::NOP, A::IMM, 2, 2, false),
+    /* 0x23 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x24 */ op(M::BIT, A::ZP0, 2, 3, false),
+    /* 0x25 */ op(M::AND, A::ZP0, 2, 3, false),
+    /* 0x26 */ op(M::ROL, A::ZP0, 2, 5, false),
+    /* 0x27 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x28 */ op(M::PLP, A::IMP, 1, 4, false),
+    /* 0x29 */ op(M::AND, A::IMM, 2, 2, false),
+    /* 0x2A */ op(M::ROL, A::ACC, 1, 2, false),
+    /* 0x2B */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x2C */ op(M::BIT, A::ABS, 3, 4, false),
+    /* 0x2D */ op(M::AND, A::ABS, 3, 4, false),
+    /* 0x2E */ op(M::ROL, A::ABS, 3, 6, false),
+    /* 0x2F */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x30 */ op(M::BMI, A::REL, 2, 2, true),
+    /* 0x31 */ op(M::AND, A::IDY, 2, 5, true),
+    /* 0x32 */ op(M::AND, A::ZPI, 2, 5, false),
+    /* 0x33 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x34 */ op(M::BIT, A::ZPX, 2, 4, false),
+    /* 0x35 */ op(M::AND, A::ZPX, 2, 4, false),
+    /* 0x36 */ op(M::ROL, A::ZPX, 2, 6, false),
+    /* 0x37 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x38 */ op(M::SEC, A::IMP, 1, 2, false),
+    /* 0x39 */ op(M::AND, A::ABY, 3, 4, true),
+    /* 0x3A */ op(M::DEC, A::ACC, 1, 2, false),
+    /* 0x3B */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x3C */ op(M::BIT, A::ABX, 3, 4, true),
+    /* 0x3D */ op(M::AND, A::ABX, 3, 4, true),
+    /* 0x3E */ op(M::ROL, A::ABX, 3, 6, true),
+    /* 0x3F */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x40 */ op(M::RTI, A::IMP, 1, 6, false),
+    /* 0x41 */ op(M::EOR, A::IDX, 2, 6, false),
+    /* 0x42 */ ill(M::NOP, A::IMM, 2, 2, false),
+    /* 0x43 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x44 */ ill(M::NOP, A::ZP0, 2, 3, false),
+    /* 0x45 */ op(M::EOR, A::ZP0, 2, 3, false),
+    /* 0x46 */ op(M::LSR, A::ZP0, 2, 5, false),
+    /* 0x47 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x48 */ op(M::PHA, A::IMP, 1, 3, false),
+    /* 0x49 */ op(M::EOR, A::IMM, 2, 2, false),
+    /* 0x4A */ op(M::LSR, A::ACC, 1, 2, false),
+    /* 0x4B */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x4C */ op(M::JMP, A::ABS, 3, 3, false),
+    /* 0x4D */ op(M::EOR, A::ABS, 3, 4, false),
+    /* 0x4E */ op(M::LSR, A::ABS, 3, 6, false),
+    /* 0x4F */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x50 */ op(M::BVC, A::REL, 2, 2, true),
+    /* 0x51 */ op(M::EOR, A::IDY, 2, 5, true),
+    /* 0x52 */ op(M::EOR, A::ZPI, 2, 5, false),
+    /* 0x53 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x54 */ ill(M::NOP, A::ZPX, 2, 4, false),
+    /* 0x55 */ op(M::EOR, A::ZPX, 2, 4, false),
+    /* 0x56 */ op(M::LSR, A::ZPX, 2, 6, false),
+    /* 0x57 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x58 */ op(M::CLI, A::IMP, 1, 2, false),
+    /* 0x59 */ op(M::EOR, A::ABY, 3, 4, true),
+    /* 0x5A */ op(M::PHY, A::IMP, 1, 3, false),
+    /* 0x5B */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x5C */ ill(M::NOP, A::ABS, 3, 8, false),
+    /* 0x5D */ op(M::EOR, A::ABX, 3, 4, true),
+    /* 0x5E */ op(M::LSR, A::ABX, 3, 6, true),
+    /* 0x5F */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x60 */ op(M::RTS, A::IMP, 1, 6, false),
+    /* 0x61 */ op(M::ADC, A::IDX, 2, 6, false),
+    /* 0x62 */ ill(M::NOP, A::IMM, 2, 2, false),
+    /* 0x63 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x64 */ op(M::STZ, A::ZP0, 2, 3, false),
+    /* 0x65 */ op(M::ADC, A::ZP0, 2, 3, false),
+    /* 0x66 */ op(M::ROR, A::ZP0, 2, 5, false),
+    /* 0x67 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x68 */ op(M::PLA, A::IMP, 1, 4, false),
+    /* 0x69 */ op(M::ADC, A::IMM, 2, 2, false),
+    /* 0x6A */ op(M::ROR, A::ACC, 1, 2, false),
+    /* 0x6B */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x6C */ op(M::JMP, A::IND, 3, 6, false),
+    /* 0x6D */ op(M::ADC, A::ABS, 3, 4, false),
+    /* 0x6E */ op(M::ROR, A::ABS, 3, 6, false),
+    /* 0x6F */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x70 */ op(M::BVS, A::REL, 2, 2, true),
+    /* 0x71 */ op(M::ADC, A::IDY, 2, 5, true),
+    /* 0x72 */ op(M::ADC, A::ZPI, 2, 5, false),
+    /* 0x73 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x74 */ op(M::STZ, A::ZPX, 2, 4, false),
+    /* 0x75 */ op(M::ADC, A::ZPX, 2, 4, false),
+    /* 0x76 */ op(M::ROR, A::ZPX, 2, 6, false),
+    /* 0x77 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x78 */ op(M::SEI, A::IMP, 1, 2, false),
+    /* 0x79 */ op(M::ADC, A::ABY, 3, 4, true),
+    /* 0x7A */ op(M::PLY, A::IMP, 1, 4, false),
+    /* 0x7B */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x7C */ op(M::JMP, A::IAX, 3, 6, false),
+    /* 0x7D */ op(M::ADC, A::ABX, 3, 4, true),
+    /* 0x7E */ op(M::ROR, A::ABX, 3, 6, true),
+    /* 0x7F */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x80 */ op(M::BRA, A::REL, 2, 2, true),
+    /* 0x81 */ op(M::STA, A::IDX, 2, 6, false),
+    /* 0x82 */ ill(M::NOP, A::IMM, 2, 2, false),
+    /* 0x83 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x84 */ op(M::STY, A::ZP0, 2, 3, false),
+    /* 0x85 */ op(M::STA, A::ZP0, 2, 3, false),
+    /* 0x86 */ op(M::STX, A::ZP0, 2, 3, false),
+    /* 0x87 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x88 */ op(M::DEY, A::IMP, 1, 2, false),
+    /* 0x89 */ op(M::BIT, A::IMM, 2, 2, false),
+    /* 0x8A */ op(M::TXA, A::IMP, 1, 2, false),
+    /* 0x8B */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x8C */ op(M::STY, A::ABS, 3, 4, false),
+    /* 0x8D */ op(M::STA, A::ABS, 3, 4, false),
+    /* 0x8E */ op(M::STX, A::ABS, 3, 4, false),
+    /* 0x8F */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x90 */ op(M::BCC, A::REL, 2, 2, true),
+    /* 0x91 */ op(M::STA, A::IDY, 2, 6, false),
+    /* 0x92 */ op(M::STA, A::ZPI, 2, 5, false),
+    /* 0x93 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x94 */ op(M::STY, A::ZPX, 2, 4, false),
+    /* 0x95 */ op(M::STA, A::ZPX, 2, 4, false),
+    /* 0x96 */ op(M::STX, A::ZPY, 2, 4, false),
+    /* 0x97 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x98 */ op(M::TYA, A::IMP, 1, 2, false),
+    /* 0x99 */ op(M::STA, A::ABY, 3, 5, false),
+    /* 0x9A */ op(M::TXS, A::IMP, 1, 2, false),
+    /* 0x9B */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x9C */ op(M::STZ, A::ABS, 3, 4, false),
+    /* 0x9D */ op(M::STA, A::ABX, 3, 5, false),
+    /* 0x9E */ op(M::STZ, A::ABX, 3, 5, false),
+    /* 0x9F */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0xA0 */ op(M::LDY, A::IMM, 2, 2, false),
+    /* 0xA1 */ op(M::LDA, A::IDX, 2, 6, false),
+    /* 0xA2 */ op(M::LDX, A::IMM, 2, 2, false),
+    /* 0xA3 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0xA4 */ op(M::LDY, A::ZP0, 2, 3, false),
+    /* 0xA5 */ op(M::LDA, A::ZP0, 2, 3, false),
+    /* 0xA6 */ op(M::LDX, A::ZP0, 2, 3, false),
+    /* 0xA7 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0xA8 */ op(M::TAY, A::IMP, 1, 2, false),
+    /* 0xA9 */ op(M::LDA, A::IMM, 2, 2, false),
+    /* 0xAA */ op(M::TAX, A::IMP, 1, 2, false),
+    /* 0xAB */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0xAC */ op(M::LDY, A::ABS, 3, 4, false),
+    /* 0xAD */ op(M::LDA, A::ABS, 3, 4, false),
+    /* 0xAE */ op(M::LDX, A::ABS, 3, 4, false),
+    /* 0xAF */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0xB0 */ op(M::BCS, A::REL, 2, 2, true),
+    /* 0xB1 */ op(M::LDA, A::IDY, 2, 5, true),
+    /* 0xB2 */ op(M::LDA, A::ZPI, 2, 5, false),
+    /* 0xB3 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0xB4 */ op(M::LDY, A::ZPX, 2, 4, false),
+    /* 0xB5 */ op(M::LDA, A::ZPX, 2, 4, false),
+    /* 0xB6 */ op(M::LDX, A::ZPY, 2, 4, false),
+    /* 0xB7 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0xB8 */ op(M::CLV, A::IMP, 1, 2, false),
+    /* 0xB9 */ op(M::LDA, A::ABY, 3, 4, true),
+    /* 0xBA */ op(M::TSX, A::IMP, 1, 2, false),
+    /* 0xBB */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0xBC */ op(M::LDY, A::ABX, 3, 4, true),
+    /* 0xBD */ op(M::LDA, A::ABX, 3, 4, true),
+    /* 0xBE */ op(M::LDX, A::ABY, 3, 4, true),
+    /* 0xBF */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0xC0 */ op(M::CPY, A::IMM, 2, 2, false),
+    /* 0xC1 */ op(M::CMP, A::IDX, 2, 6, false),
+    /* 0xC2 */ ill(M::NOP, A::IMM, 2, 2, false),
+    /* 0xC3 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0xC4 */ op(M::CPY, A::ZP0, 2, 3, false),
+    /* 0xC5 */ op(M::CMP, A::ZP0, 2, 3, false),
+    /* 0xC6 */ op(M::DEC, A::ZP0, 2, 5, false),
+    /* 0xC7 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0xC8 */ op(M::INY, A::IMP, 1, 2, false),
+    /* 0xC9 */ op(M::CMP, A::IMM, 2, 2, false),
+    /* 0xCA */ op(M::DEX, A::IMP, 1, 2, false),
+    /* 0xCB */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0xCC */ op(M::CPY, A::ABS, 3, 4, false),
+    /* 0xCD */ op(M::CMP, A::ABS, 3, 4, false),
+    /* 0xCE */ op(M::DEC, A::ABS, 3, 6, false),
+    /* 0xCF */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0xD0 */ op(M::BNE, A::REL, 2, 2, true),
+    /* 0xD1 */ op(M::CMP, A::IDY, 2, 5, true),
+    /* 0xD2 */ op(M::CMP, A::ZPI, 2, 5, false),
+    /* 0xD3 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0xD4 */ ill(M::NOP, A::ZPX, 2, 4, false),
+    /* 0xD5 */ op(M::CMP, A::ZPX, 2, 4, false),
+    /* 0xD6 */ op(M::DEC, A::ZPX, 2, 6, false),
+    /* 0xD7 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0xD8 */ op(M::CLD, A::IMP, 1, 2, false),
+    /* 0xD9 */ op(M::CMP, A::ABY, 3, 4, true),
+    /* 0xDA */ op(M::PHX, A::IMP, 1, 3, false),
+    /* 0xDB */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0xDC */ ill(M::NOP, A::ABS, 3, 4, false),
+    /* 0xDD */ op(M::CMP, A::ABX, 3, 4, true),
+    /* 0xDE */ op(M::DEC, A::ABX, 3, 7, false),
+    /* 0xDF */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0xE0 */ op(M::CPX, A::IMM, 2, 2, false),
+    /* 0xE1 */ op(M::SBC, A::IDX, 2, 6, false),
+    /* 0xE2 */ ill(M::NOP, A::IMM, 2, 2, false),
+    /* 0xE3 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0xE4 */ op(M::CPX, A::ZP0, 2, 3, false),
+    /* 0xE5 */ op(M::SBC, A::ZP0, 2, 3, false),
+    /* 0xE6 */ op(M::INC, A::ZP0, 2, 5, false),
+    /* 0xE7 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0xE8 */ op(M::INX, A::IMP, 1, 2, false),
+    /* 0xE9 */ op(M::SBC, A::IMM, 2, 2, false),
+    /* 0xEA */ op(M::NOP, A::IMP, 1, 2, false),
+    /* 0xEB */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0xEC */ op(M::CPX, A::ABS, 3, 4, false),
+    /* 0xED */ op(M::SBC, A::ABS, 3, 4, false),
+    /* 0xEE */ op(M::INC, A::ABS, 3, 6, false),
+    /* 0xEF */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0xF0 */ op(M::BEQ, A::REL, 2, 2, true),
+    /* 0xF1 */ op(M::SBC, A::IDY, 2, 5, true),
+    /* 0xF2 */ op(M::SBC, A::ZPI, 2, 5, false),
+    /* 0xF3 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0xF4 */ ill(M::NOP, A::ZPX, 2, 4, false),
+    /* 0xF5 */ op(M::SBC, A::ZPX, 2, 4, false),
+    /* 0xF6 */ op(M::INC, A::ZPX, 2, 6, false),
+    /* 0xF7 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0xF8 */ op(M::SED, A::IMP, 1, 2, false),
+    /* 0xF9 */ op(M::SBC, A::ABY, 3, 4, true),
+    /* 0xFA */ op(M::PLX, A::IMP, 1, 4, false),
+    /* 0xFB */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0xFC */ ill(M::NOP, A::ABS, 3, 4, false),
+    /* 0xFD */ op(M::SBC, A::ABX, 3, 4, true),
+    /* 0xFE */ op(M::INC, A::ABX, 3, 7, false),
+    /* 0xFF */ ill(M::NOP, A::IMP, 1, 1, false),
+];
+
+/// Opcode table of the Rockwell R65C02, which adds the bit manipulation instructions (RMB, SMB, BBR, BBS)
+pub static OPCODES_R65C02: [Opcode; 256] = [
+    /* 0x00 */ op(M::BRK, A::IMP, 1, 7, false),
+    /* 0x01 */ op(M::ORA, A::IDX, 2, 6, false),
+    /* 0x02 */ ill(M::NOP, A::IMM, 2, 2, false),
+    /* 0x03 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x04 */ op(M::TSB, A::ZP0, 2, 5, false),
+    /* 0x05 */ op(M::ORA, A::ZP0, 2, 3, false),
+    /* 0x06 */ op(M::ASL, A::ZP0, 2, 5, false),
+    /* 0x07 */ op(M::RMB, A::ZP0, 2, 5, false),
+    /* 0x08 */ op(M::PHP, A::IMP, 1, 3, false),
+    /* 0x09 */ op(M::ORA, A::IMM, 2, 2, false),
+    /* 0x0A */ op(M::ASL, A::ACC, 1, 2, false),
+    /* 0x0B */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x0C */ op(M::TSB, A::ABS, 3, 6, false),
+    /* 0x0D */ op(M::ORA, A::ABS, 3, 4, false),
+    /* 0x0E */ op(M::ASL, A::ABS, 3, 6, false),
+    /* 0x0F */ op(M::BBR, A::ZPR, 3, 5, true),
+    /* 0x10 */ op(M::BPL, A::REL, 2, 2, true),
+    /* 0x11 */ op(M::ORA, A::IDY, 2, 5, true),
+    /* 0x12 */ op(M::ORA, A::ZPI, 2, 5, false),
+    /* 0x13 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x14 */ op(M::TRB, A::ZP0, 2, 5, false),
+    /* 0x15 */ op(M::ORA, A::ZPX, 2, 4, false),
+    /* 0x16 */ op(M::ASL, A::ZPX, 2, 6, false),
+    /* 0x17 */ op(M::RMB, A::ZP0, 2, 5, false),
+    /* 0x18 */ op(M::CLC, A::IMP, 1, 2, false),
+    /* 0x19 */ op(M::ORA, A::ABY, 3, 4, true),
+    /* 0x1A */ op(M::INC, A::ACC, 1, 2, false),
+    /* 0x1B */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x1C */ op(M::TRB, A::ABS, 3, 6, false),
+    /* 0x1D */ op(M::ORA, A::ABX, 3, 4, true),
+    /* 0x1E */ op(M::ASL, A::ABX, 3, 6, true),
+    /* 0x1F */ op(M::BBR, A::ZPR, 3, 5, true),
+    /* 0x20 */ op(M::JSR, A::ABS, 3, 6, false),
+    /* 0x21 */ op(M::AND, A::IDX, 2, 6, false),
+    /* 0x22 */ ill(M::NOP, A::IMM, 2, 2, false),
+    /* 0x23 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x24 */ op(M::BIT, A::ZP0, 2, 3, false),
+    /* 0x25 */ op(M::AND, A::ZP0, 2, 3, false),
+    /* 0x26 */ op(M::ROL, A::ZP0, 2, 5, false),
+    /* 0x27 */ op(M::RMB, A::ZP0, 2, 5, false),
+    /* 0x28 */ op(M::PLP, A::IMP, 1, 4, false),
+    /* 0x29 */ op(M::AND, A::IMM, 2, 2, false),
+    /* 0x2A */ op(M::ROL, A::ACC, 1, 2, false),
+    /* 0x2B */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x2C */ op(M::BIT, A::ABS, 3, 4, false),
+    /* 0x2D */ op(M::AND, A::ABS, 3, 4, false),
+    /* 0x2E */ op(M::ROL, A::ABS, 3, 6, false),
+    /* 0x2F */ op(M::BBR, A::ZPR, 3, 5, true),
+    /* 0x30 */ op(M::BMI, A::REL, 2, 2, true),
+    /* 0x31 */ op(M::AND, A::IDY, 2, 5, true),
+    /* 0x32 */ op(M::AND, A::ZPI, 2, 5, false),
+    /* 0x33 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x34 */ op(M::BIT, A::ZPX, 2, 4, false),
+    /* 0x35 */ op(M::AND, A::ZPX, 2, 4, false),
+    /* 0x36 */ op(M::ROL, A::ZPX, 2, 6, false),
+    /* 0x37 */ op(M::RMB, A::ZP0, 2, 5, false),
+    /* 0x38 */ op(M::SEC, A::IMP, 1, 2, false),
+    /* 0x39 */ op(M::AND, A::ABY, 3, 4, true),
+    /* 0x3A */ op(M::DEC, A::ACC, 1, 2, false),
+    /* 0x3B */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x3C */ op(M::BIT, A::ABX, 3, 4, true),
+    /* 0x3D */ op(M::AND, A::ABX, 3, 4, true),
+    /* 0x3E */ op(M::ROL, A::ABX, 3, 6, true),
+    /* 0x3F */ op(M::BBR, A::ZPR, 3, 5, true),
+    /* 0x40 */ op(M::RTI, A::IMP, 1, 6, false),
+    /* 0x41 */ op(M::EOR, A::IDX, 2, 6, false),
+    /* 0x42 */ ill(M::NOP, A::IMM, 2, 2, false),
+    /* 0x43 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x44 */ ill(M::NOP, A::ZP0, 2, 3, false),
+    /* 0x45 */ op(M::EOR, A::ZP0, 2, 3, false),
+    /* 0x46 */ op(M::LSR, A::ZP0, 2, 5, false),
+    /* 0x47 */ op(M::RMB, A::ZP0, 2, 5, false),
+    /* 0x48 */ op(M::PHA, A::IMP, 1, 3, false),
+    /* 0x49 */ op(M::EOR, A::IMM, 2, 2, false),
+    /* 0x4A */ op(M::LSR, A::ACC, 1, 2, false),
+    /* 0x4B */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x4C */ op(M::JMP, A::ABS, 3, 3, false),
+    /* 0x4D */ op(M::EOR, A::ABS, 3, 4, false),
+    /* 0x4E */ op(M::LSR, A::ABS, 3, 6, false),
+    /* 0x4F */ op(M::BBR, A::ZPR, 3, 5, true),
+    /* 0x50 */ op(M::BVC, A::REL, 2, 2, true),
+    /* 0x51 */ op(M::EOR, A::IDY, 2, 5, true),
+    /* 0x52 */ op(M::EOR, A::ZPI, 2, 5, false),
+    /* 0x53 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x54 */ ill(M::NOP, A::ZPX, 2, 4, false),
+    /* 0x55 */ op(M::EOR, A::ZPX, 2, 4, false),
+    /* 0x56 */ op(M::LSR, A::ZPX, 2, 6, false),
+    /* 0x57 */ op(M::RMB, A::ZP0, 2, 5, false),
+    /* 0x58 */ op(M::CLI, A::IMP, 1, 2, false),
+    /* 0x59 */ op(M::EOR, A::ABY, 3, 4, true),
+    /* 0x5A */ op(M::PHY, A::IMP, 1, 3, false),
+    /* 0x5B */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x5C */ ill(M::NOP, A::ABS, 3, 8, false),
+    /* 0x5D */ op(M::EOR, A::ABX, 3, 4, true),
+    /* 0x5E */ op(M::LSR, A::ABX, 3, 6, true),
+    /* 0x5F */ op(M::BBR, A::ZPR, 3, 5, true),
+    /* 0x60 */ op(M::RTS, A::IMP, 1, 6, false),
+    /* 0x61 */ op(M::ADC, A::IDX, 2, 6, false),
+    /* 0x62 */ ill(M::NOP, A::IMM, 2, 2, false),
+    /* 0x63 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x64 */ op(M::STZ, A::ZP0, 2, 3, false),
+    /* 0x65 */ op(M::ADC, A::ZP0, 2, 3, false),
+    /* 0x66 */ op(M::ROR, A::ZP0, 2, 5, false),
+    /* 0x67 */ op(M::RMB, A::ZP0, 2, 5, false),
+    /* 0x68 */ op(M::PLA, A::IMP, 1, 4, false),
+    /* 0x69 */ op(M::ADC, A::IMM, 2, 2, false),
+    /* 0x6A */ op(M::ROR, A::ACC, 1, 2, false),
+    /* 0x6B */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x6C */ op(M::JMP, A::IND, 3, 6, false),
+    /* 0x6D */ op(M::ADC, A::ABS, 3, 4, false),
+    /* 0x6E */ op(M::ROR, A::ABS, 3, 6, false),
+    /* 0x6F */ op(M::BBR, A::ZPR, 3, 5, true),
+    /* 0x70 */ op(M::BVS, A::REL, 2, 2, true),
+    /* 0x71 */ op(M::ADC, A::IDY, 2, 5, true),
+    /* 0x72 */ op(M::ADC, A::ZPI, 2, 5, false),
+    /* 0x73 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x74 */ op(M::STZ, A::ZPX, 2, 4, false),
+    /* 0x75 */ op(M::ADC, A::ZPX, 2, 4, false),
+    /* 0x76 */ op(M::ROR, A::ZPX, 2, 6, false),
+    /* 0x77 */ op(M::RMB, A::ZP0, 2, 5, false),
+    /* 0x78 */ op(M::SEI, A::IMP, 1, 2, false),
+    /* 0x79 */ op(M::ADC, A::ABY, 3, 4, true),
+    /* 0x7A */ op(M::PLY, A::IMP, 1, 4, false),
+    /* 0x7B */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x7C */ op(M::JMP, A::IAX, 3, 6, false),
+    /* 0x7D */ op(M::ADC, A::ABX, 3, 4, true),
+    /* 0x7E */ op(M::ROR, A::ABX, 3, 6, true),
+    /* 0x7F */ op(M::BBR, A::ZPR, 3, 5, true),
+    /* 0x80 */ op(M::BRA, A::REL, 2, 2, true),
+    /* 0x81 */ op(M::STA, A::IDX, 2, 6, false),
+    /* 0x82 */ ill(M::NOP, A::IMM, 2, 2, false),
+    /* 0x83 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x84 */ op(M::STY, A::ZP0, 2, 3, false),
+    /* 0x85 */ op(M::STA, A::ZP0, 2, 3, false),
+    /* 0x86 */ op(M::STX, A::ZP0, 2, 3, false),
+    /* 0x87 */ op(M::SMB, A::ZP0, 2, 5, false),
+    /* 0x88 */ op(M::DEY, A::IMP, 1, 2, false),
+    /* 0x89 */ op(M::BIT, A::IMM, 2, 2, false),
+    /* 0x8A */ op(M::TXA, A::IMP, 1, 2, false),
+    /* 0x8B */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x8C */ op(M::STY, A::ABS, 3, 4, false),
+    /* 0x8D */ op(M::STA, A::ABS, 3, 4, false),
+    /* 0x8E */ op(M::STX, A::ABS, 3, 4, false),
+    /* 0x8F */ op(M::BBS, A::ZPR, 3, 5, true),
+    /* 0x90 */ op(M::BCC, A::REL, 2, 2, true),
+    /* 0x91 */ op(M::STA, A::IDY, 2, 6, false),
+    /* 0x92 */ op(M::STA, A::ZPI, 2, 5, false),
+    /* 0x93 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x94 */ op(M::STY, A::ZPX, 2, 4, false),
+    /* 0x95 */ op(M::STA, A::ZPX, 2, 4, false),
+    /* 0x96 */ op(M::STX, A::ZPY, 2, 4, false),
+    /* 0x97 */ op(M::SMB, A::ZP0, 2, 5, false),
+    /* 0x98 */ op(M::TYA, A::IMP, 1, 2, false),
+    /* 0x99 */ op(M::STA, A::ABY, 3, 5, false),
+    /* 0x9A */ op(M::TXS, A::IMP, 1, 2, false),
+    /* 0x9B */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x9C */ op(M::STZ, A::ABS, 3, 4, false),
+    /* 0x9D */ op(M::STA, A::ABX, 3, 5, false),
+    /* 0x9E */ op(M::STZ, A::ABX, 3, 5, false),
+    /* 0x9F */ op(M::BBS, A::ZPR, 3, 5, true),
+    /* 0xA0 */ op(M::LDY, A::IMM, 2, 2, false),
+    /* 0xA1 */ op(M::LDA, A::IDX, 2, 6, false),
+    /* 0xA2 */ op(M::LDX, A::IMM, 2, 2, false),
+    /* 0xA3 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0xA4 */ op(M::LDY, A::ZP0, 2, 3, false),
+    /* 0xA5 */ op(M::LDA, A::ZP0, 2, 3, false),
+    /* 0xA6 */ op(M::LDX, A::ZP0, 2, 3, false),
+    /* 0xA7 */ op(M::SMB, A::ZP0, 2, 5, false),
+    /* 0xA8 */ op(M::TAY, A::IMP, 1, 2, false),
+    /* 0xA9 */ op(M::LDA, A::IMM, 2, 2, false),
+    /* 0xAA */ op(M::TAX, A::IMP, 1, 2, false),
+    /* 0xAB */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0xAC */ op(M::LDY, A::ABS, 3, 4, false),
+    /* 0xAD */ op(M::LDA, A::ABS, 3, 4, false),
+    /* 0xAE */ op(M::LDX, A::ABS, 3, 4, false),
+    /* 0xAF */ op(M::BBS, A::ZPR, 3, 5, true),
+    /* 0xB0 */ op(M::BCS, A::REL, 2, 2, true),
+    /* 0xB1 */ op(M::LDA, A::IDY, 2, 5, true),
+    /* 0xB2 */ op(M::LDA, A::ZPI, 2, 5, false),
+    /* 0xB3 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0xB4 */ op(M::LDY, A::ZPX, 2, 4, false),
+    /* 0xB5 */ op(M::LDA, A::ZPX, 2, 4, false),
+    /* 0xB6 */ op(M::LDX, A::ZPY, 2, 4, false),
+    /* 0xB7 */ op(M::SMB, A::ZP0, 2, 5, false),
+    /* 0xB8 */ op(M::CLV, A::IMP, 1, 2, false),
+    /* 0xB9 */ op(M::LDA, A::ABY, 3, 4, true),
+    /* 0xBA */ op(M::TSX, A::IMP, 1, 2, false),
+    /* 0xBB */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0xBC */ op(M::LDY, A::ABX, 3, 4, true),
+    /* 0xBD */ op(M::LDA, A::ABX, 3, 4, true),
+    /* 0xBE */ op(M::LDX, A::ABY, 3, 4, true),
+    /* 0xBF */ op(M::BBS, A::ZPR, 3, 5, true),
+    /* 0xC0 */ op(M::CPY, A::IMM, 2, 2, false),
+    /* 0xC1 */ op(M::CMP, A::IDX, 2, 6, false),
+    /* 0xC2 */ ill(M::NOP, A::IMM, 2, 2, false),
+    /* 0xC3 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0xC4 */ op(M::CPY, A::ZP0, 2, 3, false),
+    /* 0xC5 */ op(M::CMP, A::ZP0, 2, 3, false),
+    /* 0xC6 */ op(M::DEC, A::ZP0, 2, 5, false),
+    /* 0xC7 */ op(M::SMB, A::ZP0, 2, 5, false),
+    /* 0xC8 */ op(M::INY, A::IMP, 1, 2, false),
+    /* 0xC9 */ op(M::CMP, A::IMM, 2, 2, false),
+    /* 0xCA */ op(M::DEX, A::IMP, 1, 2, false),
+    /* 0xCB */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0xCC */ op(M::CPY, A::ABS, 3, 4, false),
+    /* 0xCD */ op(M::CMP, A::ABS, 3, 4, false),
+    /* 0xCE */ op(M::DEC, A::ABS, 3, 6, false),
+    /* 0xCF */ op(M::BBS, A::ZPR, 3, 5, true),
+    /* 0xD0 */ op(M::BNE, A::REL, 2, 2, true),
+    /* 0xD1 */ op(M::CMP, A::IDY, 2, 5, true),
+    /* 0xD2 */ op(M::CMP, A::ZPI, 2, 5, false),
+    /* 0xD3 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0xD4 */ ill(M::NOP, A::ZPX, 2, 4, false),
+    /* 0xD5 */ op(M::CMP, A::ZPX, 2, 4, false),
+    /* 0xD6 */ op(M::DEC, A::ZPX, 2, 6, false),
+    /* 0xD7 */ op(M::SMB, A::ZP0, 2, 5, false),
+    /* 0xD8 */ op(M::CLD, A::IMP, 1, 2, false),
+    /* 0xD9 */ op(M::CMP, A::ABY, 3, 4, true),
+    /* 0xDA */ op(M::PHX, A::IMP, 1, 3, false),
+    /* 0xDB */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0xDC */ ill(M::NOP, A::ABS, 3, 4, false),
+    /* 0xDD */ op(M::CMP, A::ABX, 3, 4, true),
+    /* 0xDE */ op(M::DEC, A::ABX, 3, 7, false),
+    /* 0xDF */ op(M::BBS, A::ZPR, 3, 5, true),
+    /* 0xE0 */ op(M::CPX, A::IMM, 2, 2, false),
+    /* 0xE1 */ op(M::SBC, A::IDX, 2, 6, false),
+    /* 0xE2 */ ill(M::NOP, A::IMM, 2, 2, false),
+    /* 0xE3 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0xE4 */ op(M::CPX, A::ZP0, 2, 3, false),
+    /* 0xE5 */ op(M::SBC, A::ZP0, 2, 3, false),
+    /* 0xE6 */ op(M::INC, A::ZP0, 2, 5, false),
+    /* 0xE7 */ op(M::SMB, A::ZP0, 2, 5, false),
+    /* 0xE8 */ op(M::INX, A::IMP, 1, 2, false),
+    /* 0xE9 */ op(M::SBC, A::IMM, 2, 2, false),
+    /* 0xEA */ op(M::NOP, A::IMP, 1, 2, false),
+    /* 0xEB */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0xEC */ op(M::CPX, A::ABS, 3, 4, false),
+    /* 0xED */ op(M::SBC, A::ABS, 3, 4, false),
+    /* 0xEE */ op(M::INC, A::ABS, 3, 6, false),
+    /* 0xEF */ op(M::BBS, A::ZPR, 3, 5, true),
+    /* 0xF0 */ op(M::BEQ, A::REL, 2, 2, true),
+    /* 0xF1 */ op(M::SBC, A::IDY, 2, 5, true),
+    /* 0xF2 */ op(M::SBC, A::ZPI, 2, 5, false),
+    /* 0xF3 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0xF4 */ ill(M::NOP, A::ZPX, 2, 4, false),
+    /* 0xF5 */ op(M::SBC, A::ZPX, 2, 4, false),
+    /* 0xF6 */ op(M::INC, A::ZPX, 2, 6, false),
+    /* 0xF7 */ op(M::SMB, A::ZP0, 2, 5, false),
+    /* 0xF8 */ op(M::SED, A::IMP, 1, 2, false),
+    /* 0xF9 */ op(M::SBC, A::ABY, 3, 4, true),
+    /* 0xFA */ op(M::PLX, A::IMP, 1, 4, false),
+    /* 0xFB */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0xFC */ ill(M::NOP, A::ABS, 3, 4, false),
+    /* 0xFD */ op(M::SBC, A::ABX, 3, 4, true),
+    /* 0xFE */ op(M::INC, A::ABX, 3, 7, false),
+    /* 0xFF */ op(M::BBS, A::ZPR, 3, 5, true),
+];
+
+/// Opcode table of the WDC W65C02S, which adds WAI and STP on top of the Rockwell instructions
+pub static OPCODES_W65C02: [Opcode; 256] = [
+    /* 0x00 */ op(M::BRK, A::IMP, 1, 7, false),
+    /* 0x01 */ op(M::ORA, A::IDX, 2, 6, false),
+    /* 0x02 */ ill(M::NOP, A::IMM, 2, 2, false),
+    /* 0x03 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x04 */ op(M::TSB, A::ZP0, 2, 5, false),
+    /* 0x05 */ op(M::ORA, A::ZP0, 2, 3, false),
+    /* 0x06 */ op(M::ASL, A::ZP0, 2, 5, false),
+    /* 0x07 */ op(M::RMB, A::ZP0, 2, 5, false),
+    /* 0x08 */ op(M::PHP, A::IMP, 1, 3, false),
+    /* 0x09 */ op(M::ORA, A::IMM, 2, 2, false),
+    /* 0x0A */ op(M::ASL, A::ACC, 1, 2, false),
+    /* 0x0B */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x0C */ op(M::TSB, A::ABS, 3, 6, false),
+    /* 0x0D */ op(M::ORA, A::ABS, 3, 4, false),
+    /* 0x0E */ op(M::ASL, A::ABS, 3, 6, false),
+    /* 0x0F */ op(M::BBR, A::ZPR, 3, 5, true),
+    /* 0x10 */ op(M::BPL, A::REL, 2, 2, true),
+    /* 0x11 */ op(M::ORA, A::IDY, 2, 5, true),
+    /* 0x12 */ op(M::ORA, A::ZPI, 2, 5, false),
+    /* 0x13 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x14 */ op(M::TRB, A::ZP0, 2, 5, false),
+    /* 0x15 */ op(M::ORA, A::ZPX, 2, 4, false),
+    /* 0x16 */ op(M::ASL, A::ZPX, 2, 6, false),
+    /* 0x17 */ op(M::RMB, A::ZP0, 2, 5, false),
+    /* 0x18 */ op(M::CLC, A::IMP, 1, 2, false),
+    /* 0x19 */ op(M::ORA, A::ABY, 3, 4, true),
+    /* 0x1A */ op(M::INC, A::ACC, 1, 2, false),
+    /* 0x1B */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x1C */ op(M::TRB, A::ABS, 3, 6, false),
+    /* 0x1D */ op(M::ORA, A::ABX, 3, 4, true),
+    /* 0x1E */ op(M::ASL, A::ABX, 3, 6, true),
+    /* 0x1F */ op(M::BBR, A::ZPR, 3, 5, true),
+    /* 0x20 */ op(M::JSR, A::ABS, 3, 6, false),
+    /* 0x21 */ op(M::AND, A::IDX, 2, 6, false),
+    /* 0x22 */ ill(M::NOP, A::IMM, 2, 2, false),
+    /* 0x23 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x24 */ op(M::BIT, A::ZP0, 2, 3, false),
+    /* 0x25 */ op(M::AND, A::ZP0, 2, 3, false),
+    /* 0x26 */ op(M::ROL, A::ZP0, 2, 5, false),
+    /* 0x27 */ op(M::RMB, A::ZP0, 2, 5, false),
+    /* 0x28 */ op(M::PLP, A::IMP, 1, 4, false),
+    /* 0x29 */ op(M::AND, A::IMM, 2, 2, false),
+    /* 0x2A */ op(M::ROL, A::ACC, 1, 2, false),
+    /* 0x2B */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x2C */ op(M::BIT, A::ABS, 3, 4, false),
+    /* 0x2D */ op(M::AND, A::ABS, 3, 4, false),
+    /* 0x2E */ op(M::ROL, A::ABS, 3, 6, false),
+    /* 0x2F */ op(M::BBR, A::ZPR, 3, 5, true),
+    /* 0x30 */ op(M::BMI, A::REL, 2, 2, true),
+    /* 0x31 */ op(M::AND, A::IDY, 2, 5, true),
+    /* 0x32 */ op(M::AND, A::ZPI, 2, 5, false),
+    /* 0x33 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x34 */ op(M::BIT, A::ZPX, 2, 4, false),
+    /* 0x35 */ op(M::AND, A::ZPX, 2, 4, false),
+    /* 0x36 */ op(M::ROL, A::ZPX, 2, 6, false),
+    /* 0x37 */ op(M::RMB, A::ZP0, 2, 5, false),
+    /* 0x38 */ op(M::SEC, A::IMP, 1, 2, false),
+    /* 0x39 */ op(M::AND, A::ABY, 3, 4, true),
+    /* 0x3A */ op(M::DEC, A::ACC, 1, 2, false),
+    /* 0x3B */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x3C */ op(M::BIT, A::ABX, 3, 4, true),
+    /* 0x3D */ op(M::AND, A::ABX, 3, 4, true),
+    /* 0x3E */ op(M::ROL, A::ABX, 3, 6, true),
+    /* 0x3F */ op(M::BBR, A::ZPR, 3, 5, true),
+    /* 0x40 */ op(M::RTI, A::IMP, 1, 6, false),
+    /* 0x41 */ op(M::EOR, A::IDX, 2, 6, false),
+    /* 0x42 */ ill(M::NOP, A::IMM, 2, 2, false),
+    /* 0x43 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x44 */ ill(M::NOP, A::ZP0, 2, 3, false),
+    /* 0x45 */ op(M::EOR, A::ZP0, 2, 3, false),
+    /* 0x46 */ op(M::LSR, A::ZP0, 2, 5, false),
+    /* 0x47 */ op(M::RMB, A::ZP0, 2, 5, false),
+    /* 0x48 */ op(M::PHA, A::IMP, 1, 3, false),
+    /* 0x49 */ op(M::EOR, A::IMM, 2, 2, false),
+    /* 0x4A */ op(M::LSR, A::ACC, 1, 2, false),
+    /* 0x4B */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x4C */ op(M::JMP, A::ABS, 3, 3, false),
+    /* 0x4D */ op(M::EOR, A::ABS, 3, 4, false),
+    /* 0x4E */ op(M::LSR, A::ABS, 3, 6, false),
+    /* 0x4F */ op(M::BBR, A::ZPR, 3, 5, true),
+    /* 0x50 */ op(M::BVC, A::REL, 2, 2, true),
+    /* 0x51 */ op(M::EOR, A::IDY, 2, 5, true),
+    /* 0x52 */ op(M::EOR, A::ZPI, 2, 5, false),
+    /* 0x53 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x54 */ ill(M::NOP, A::ZPX, 2, 4, false),
+    /* 0x55 */ op(M::EOR, A::ZPX, 2, 4, false),
+    /* 0x56 */ op(M::LSR, A::ZPX, 2, 6, false),
+    /* 0x57 */ op(M::RMB, A::ZP0, 2, 5, false),
+    /* 0x58 */ op(M::CLI, A::IMP, 1, 2, false),
+    /* 0x59 */ op(M::EOR, A::ABY, 3, 4, true),
+    /* 0x5A */ op(M::PHY, A::IMP, 1, 3, false),
+    /* 0x5B */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x5C */ ill(M::NOP, A::ABS, 3, 8, false),
+    /* 0x5D */ op(M::EOR, A::ABX, 3, 4, true),
+    /* 0x5E */ op(M::LSR, A::ABX, 3, 6, true),
+    /* 0x5F */ op(M::BBR, A::ZPR, 3, 5, true),
+    /* 0x60 */ op(M::RTS, A::IMP, 1, 6, false),
+    /* 0x61 */ op(M::ADC, A::IDX, 2, 6, false),
+    /* 0x62 */ ill(M::NOP, A::IMM, 2, 2, false),
+    /* 0x63 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x64 */ op(M::STZ, A::ZP0, 2, 3, false),
+    /* 0x65 */ op(M::ADC, A::ZP0, 2, 3, false),
+    /* 0x66 */ op(M::ROR, A::ZP0, 2, 5, false),
+    /* 0x67 */ op(M::RMB, A::ZP0, 2, 5, false),
+    /* 0x68 */ op(M::PLA, A::IMP, 1, 4, false),
+    /* 0x69 */ op(M::ADC, A::IMM, 2, 2, false),
+    /* 0x6A */ op(M::ROR, A::ACC, 1, 2, false),
+    /* 0x6B */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x6C */ op(M::JMP, A::IND, 3, 6, false),
+    /* 0x6D */ op(M::ADC, A::ABS, 3, 4, false),
+    /* 0x6E */ op(M::ROR, A::ABS, 3, 6, false),
+    /* 0x6F */ op(M::BBR, A::ZPR, 3, 5, true),
+    /* 0x70 */ op(M::BVS, A::REL, 2, 2, true),
+    /* 0x71 */ op(M::ADC, A::IDY, 2, 5, true),
+    /* 0x72 */ op(M::ADC, A::ZPI, 2, 5, false),
+    /* 0x73 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x74 */ op(M::STZ, A::ZPX, 2, 4, false),
+    /* 0x75 */ op(M::ADC, A::ZPX, 2, 4, false),
+    /* 0x76 */ op(M::ROR, A::ZPX, 2, 6, false),
+    /* 0x77 */ op(M::RMB, A::ZP0, 2, 5, false),
+    /* 0x78 */ op(M::SEI, A::IMP, 1, 2, false),
+    /* 0x79 */ op(M::ADC, A::ABY, 3, 4, true),
+    /* 0x7A */ op(M::PLY, A::IMP, 1, 4, false),
+    /* 0x7B */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x7C */ op(M::JMP, A::IAX, 3, 6, false),
+    /* 0x7D */ op(M::ADC, A::ABX, 3, 4, true),
+    /* 0x7E */ op(M::ROR, A::ABX, 3, 6, true),
+    /* 0x7F */ op(M::BBR, A::ZPR, 3, 5, true),
+    /* 0x80 */ op(M::BRA, A::REL, 2, 2, true),
+    /* 0x81 */ op(M::STA, A::IDX, 2, 6, false),
+    /* 0x82 */ ill(M::NOP, A::IMM, 2, 2, false),
+    /* 0x83 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x84 */ op(M::STY, A::ZP0, 2, 3, false),
+    /* 0x85 */ op(M::STA, A::ZP0, 2, 3, false),
+    /* 0x86 */ op(M::STX, A::ZP0, 2, 3, false),
+    /* 0x87 */ op(M::SMB, A::ZP0, 2, 5, false),
+    /* 0x88 */ op(M::DEY, A::IMP, 1, 2, false),
+    /* 0x89 */ op(M::BIT, A::IMM, 2, 2, false),
+    /* 0x8A */ op(M::TXA, A::IMP, 1, 2, false),
+    /* 0x8B */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x8C */ op(M::STY, A::ABS, 3, 4, false),
+    /* 0x8D */ op(M::STA, A::ABS, 3, 4, false),
+    /* 0x8E */ op(M::STX, A::ABS, 3, 4, false),
+    /* 0x8F */ op(M::BBS, A::ZPR, 3, 5, true),
+    /* 0x90 */ op(M::BCC, A::REL, 2, 2, true),
+    /* 0x91 */ op(M::STA, A::IDY, 2, 6, false),
+    /* 0x92 */ op(M::STA, A::ZPI, 2, 5, false),
+    /* 0x93 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x94 */ op(M::STY, A::ZPX, 2, 4, false),
+    /* 0x95 */ op(M::STA, A::ZPX, 2, 4, false),
+    /* 0x96 */ op(M::STX, A::ZPY, 2, 4, false),
+    /* 0x97 */ op(M::SMB, A::ZP0, 2, 5, false),
+    /* 0x98 */ op(M::TYA, A::IMP, 1, 2, false),
+    /* 0x99 */ op(M::STA, A::ABY, 3, 5, false),
+    /* 0x9A */ op(M::TXS, A::IMP, 1, 2, false),
+    /* 0x9B */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0x9C */ op(M::STZ, A::ABS, 3, 4, false),
+    /* 0x9D */ op(M::STA, A::ABX, 3, 5, false),
+    /* 0x9E */ op(M::STZ, A::ABX, 3, 5, false),
+    /* 0x9F */ op(M::BBS, A::ZPR, 3, 5, true),
+    /* 0xA0 */ op(M::LDY, A::IMM, 2, 2, false),
+    /* 0xA1 */ op(M::LDA, A::IDX, 2, 6, false),
+    /* 0xA2 */ op(M::LDX, A::IMM, 2, 2, false),
+    /* 0xA3 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0xA4 */ op(M::LDY, A::ZP0, 2, 3, false),
+    /* 0xA5 */ op(M::LDA, A::ZP0, 2, 3, false),
+    /* 0xA6 */ op(M::LDX, A::ZP0, 2, 3, false),
+    /* 0xA7 */ op(M::SMB, A::ZP0, 2, 5, false),
+    /* 0xA8 */ op(M::TAY, A::IMP, 1, 2, false),
+    /* 0xA9 */ op(M::LDA, A::IMM, 2, 2, false),
+    /* 0xAA */ op(M::TAX, A::IMP, 1, 2, false),
+    /* 0xAB */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0xAC */ op(M::LDY, A::ABS, 3, 4, false),
+    /* 0xAD */ op(M::LDA, A::ABS, 3, 4, false),
+    /* 0xAE */ op(M::LDX, A::ABS, 3, 4, false),
+    /* 0xAF */ op(M::BBS, A::ZPR, 3, 5, true),
+    /* 0xB0 */ op(M::BCS, A::REL, 2, 2, true),
+    /* 0xB1 */ op(M::LDA, A::IDY, 2, 5, true),
+    /* 0xB2 */ op(M::LDA, A::ZPI, 2, 5, false),
+    /* 0xB3 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0xB4 */ op(M::LDY, A::ZPX, 2, 4, false),
+    /* 0xB5 */ op(M::LDA, A::ZPX, 2, 4, false),
+    /* 0xB6 */ op(M::LDX, A::ZPY, 2, 4, false),
+    /* 0xB7 */ op(M::SMB, A::ZP0, 2, 5, false),
+    /* 0xB8 */ op(M::CLV, A::IMP, 1, 2, false),
+    /* 0xB9 */ op(M::LDA, A::ABY, 3, 4, true),
+    /* 0xBA */ op(M::TSX, A::IMP, 1, 2, false),
+    /* 0xBB */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0xBC */ op(M::LDY, A::ABX, 3, 4, true),
+    /* 0xBD */ op(M::LDA, A::ABX, 3, 4, true),
+    /* 0xBE */ op(M::LDX, A::ABY, 3, 4, true),
+    /* 0xBF */ op(M::BBS, A::ZPR, 3, 5, true),
+    /* 0xC0 */ op(M::CPY, A::IMM, 2, 2, false),
+    /* 0xC1 */ op(M::CMP, A::IDX, 2, 6, false),
+    /* 0xC2 */ ill(M::NOP, A::IMM, 2, 2, false),
+    /* 0xC3 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0xC4 */ op(M::CPY, A::ZP0, 2, 3, false),
+    /* 0xC5 */ op(M::CMP, A::ZP0, 2, 3, false),
+    /* 0xC6 */ op(M::DEC, A::ZP0, 2, 5, false),
+    /* 0xC7 */ op(M::SMB, A::ZP0, 2, 5, false),
+    /* 0xC8 */ op(M::INY, A::IMP, 1, 2, false),
+    /* 0xC9 */ op(M::CMP, A::IMM, 2, 2, false),
+    /* 0xCA */ op(M::DEX, A::IMP, 1, 2, false),
+    /* 0xCB */ op(M::WAI, A::IMP, 1, 3, false),
+    /* 0xCC */ op(M::CPY, A::ABS, 3, 4, false),
+    /* 0xCD */ op(M::CMP, A::ABS, 3, 4, false),
+    /* 0xCE */ op(M::DEC, A::ABS, 3, 6, false),
+    /* 0xCF */ op(M::BBS, A::ZPR, 3, 5, true),
+    /* 0xD0 */ op(M::BNE, A::REL, 2, 2, true),
+    /* 0xD1 */ op(M::CMP, A::IDY, 2, 5, true),
+    /* 0xD2 */ op(M::CMP, A::ZPI, 2, 5, false),
+    /* 0xD3 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0xD4 */ ill(M::NOP, A::ZPX, 2, 4, false),
+    /* 0xD5 */ op(M::CMP, A::ZPX, 2, 4, false),
+    /* 0xD6 */ op(M::DEC, A::ZPX, 2, 6, false),
+    /* 0xD7 */ op(M::SMB, A::ZP0, 2, 5, false),
+    /* 0xD8 */ op(M::CLD, A::IMP, 1, 2, false),
+    /* 0xD9 */ op(M::CMP, A::ABY, 3, 4, true),
+    /* 0xDA */ op(M::PHX, A::IMP, 1, 3, false),
+    /* 0xDB */ op(M::STP, A::IMP, 1, 3, false),
+    /* 0xDC */ ill(M::NOP, A::ABS, 3, 4, false),
+    /* 0xDD */ op(M::CMP, A::ABX, 3, 4, true),
+    /* 0xDE */ op(M::DEC, A::ABX, 3, 7, false),
+    /* 0xDF */ op(M::BBS, A::ZPR, 3, 5, true),
+    /* 0xE0 */ op(M::CPX, A::IMM, 2, 2, false),
+    /* 0xE1 */ op(M::SBC, A::IDX, 2, 6, false),
+    /* 0xE2 */ ill(M::NOP, A::IMM, 2, 2, false),
+    /* 0xE3 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0xE4 */ op(M::CPX, A::ZP0, 2, 3, false),
+    /* 0xE5 */ op(M::SBC, A::ZP0, 2, 3, false),
+    /* 0xE6 */ op(M::INC, A::ZP0, 2, 5, false),
+    /* 0xE7 */ op(M::SMB, A::ZP0, 2, 5, false),
+    /* 0xE8 */ op(M::INX, A::IMP, 1, 2, false),
+    /* 0xE9 */ op(M::SBC, A::IMM, 2, 2, false),
+    /* 0xEA */ op(M::NOP, A::IMP, 1, 2, false),
+    /* 0xEB */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0xEC */ op(M::CPX, A::ABS, 3, 4, false),
+    /* 0xED */ op(M::SBC, A::ABS, 3, 4, false),
+    /* 0xEE */ op(M::INC, A::ABS, 3, 6, false),
+    /* 0xEF */ op(M::BBS, A::ZPR, 3, 5, true),
+    /* 0xF0 */ op(M::BEQ, A::REL, 2, 2, true),
+    /* 0xF1 */ op(M::SBC, A::IDY, 2, 5, true),
+    /* 0xF2 */ op(M::SBC, A::ZPI, 2, 5, false),
+    /* 0xF3 */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0xF4 */ ill(M::NOP, A::ZPX, 2, 4, false),
+    /* 0xF5 */ op(M::SBC, A::ZPX, 2, 4, false),
+    /* 0xF6 */ op(M::INC, A::ZPX, 2, 6, false),
+    /* 0xF7 */ op(M::SMB, A::ZP0, 2, 5, false),
+    /* 0xF8 */ op(M::SED, A::IMP, 1, 2, false),
+    /* 0xF9 */ op(M::SBC, A::ABY, 3, 4, true),
+    /* 0xFA */ op(M::PLX, A::IMP, 1, 4, false),
+    /* 0xFB */ ill(M::NOP, A::IMP, 1, 1, false),
+    /* 0xFC */ ill(M::NOP, A::ABS, 3, 4, false),
+    /* 0xFD */ op(M::SBC, A::ABX, 3, 4, true),
+    /* 0xFE */ op(M::INC, A::ABX, 3, 7, false),
+    /* 0xFF */ op(M::BBS, A::ZPR, 3, 5, true),
+];
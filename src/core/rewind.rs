@@ -0,0 +1,187 @@
+use std::{collections::VecDeque, mem};
+
+/// Configuration of the rewind buffer (see `CPU::enable_rewind`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RewindConfig {
+    /// Instructions between snapshots
+    pub interval: usize,
+    /// Bytes of history to keep; the oldest snapshots are dropped beyond this
+    pub budget: usize,
+}
+
+impl Default for RewindConfig {
+    fn default() -> Self {
+        RewindConfig { interval: 1000, budget: 16 * 1024 * 1024 }
+    }
+}
+
+/// CPU state that isn't in memory, saved before every instruction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct CoreState {
+    pub(crate) a: u8,
+    pub(crate) x: u8,
+    pub(crate) y: u8,
+    pub(crate) sp: u8,
+    pub(crate) pc: u16,
+    pub(crate) sr: u8,
+    pub(crate) opcode: u8,
+    pub(crate) instruction_pc: u16,
+    pub(crate) cycles: u64,
+    pub(crate) jammed: bool,
+    pub(crate) stopped: bool,
+    pub(crate) waiting: bool,
+    pub(crate) irq_lines: u8,
+    pub(crate) nmi_pending: bool,
+    pub(crate) irq_masked: bool,
+}
+
+/// One recorded instruction: the state before it, and the previous value of every byte it wrote
+struct Step {
+    before: CoreState,
+    writes: Vec<(u16, u8)>,
+    // Whether it wrote to something other than plain memory, which can't be undone
+    device: bool,
+}
+
+impl Step {
+    fn size(&self) -> usize {
+        mem::size_of::<Step>() + self.writes.capacity() * mem::size_of::<(u16, u8)>()
+    }
+}
+
+/// A save state, followed by the instructions executed since
+struct Checkpoint {
+    state: Vec<u8>,
+    cycles: u64,
+    steps: Vec<Step>,
+}
+
+/// History of executed instructions, for stepping backwards
+///
+/// A full save state is taken every `RewindConfig::interval` instructions, and every instruction in between records
+/// the registers before it and the bytes it overwrote. Stepping back undoes those writes with `Memory::poke`, so it
+/// stops at instructions that wrote to a device; rewinding further jumps to a snapshot. Devices behind the bus are
+/// only restored exactly at snapshots.
+pub struct Rewind {
+    config: RewindConfig,
+    checkpoints: VecDeque<Checkpoint>,
+    // Instruction being recorded by the current step
+    current: Option<Step>,
+    // Bytes used by `checkpoints`
+    used: usize,
+}
+
+impl Rewind {
+    pub fn new(config: RewindConfig) -> Self {
+        Rewind { config, checkpoints: VecDeque::new(), current: None, used: 0 }
+    }
+
+    pub fn config(&self) -> &RewindConfig {
+        &self.config
+    }
+
+    /// Number of instructions that can be stepped back
+    pub fn len(&self) -> usize {
+        self.checkpoints.iter().map(|checkpoint| checkpoint.steps.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.checkpoints.iter().all(|checkpoint| checkpoint.steps.is_empty())
+    }
+
+    /// Bytes of history currently kept
+    pub fn memory_used(&self) -> usize {
+        self.used
+    }
+
+    /// Forget all history
+    pub fn clear(&mut self) {
+        self.checkpoints.clear();
+        self.current = None;
+        self.used = 0;
+    }
+
+    /// Whether the next instruction starts a new checkpoint, so `begin` needs a save state
+    pub(crate) fn needs_snapshot(&self) -> bool {
+        self.checkpoints.back().is_none_or(|checkpoint| checkpoint.steps.len() >= self.config.interval)
+    }
+
+    /// Start recording an instruction, with a save state of the machine if `needs_snapshot`
+    pub(crate) fn begin(&mut self, before: CoreState, snapshot: Option<Vec<u8>>) {
+        if let Some(state) = snapshot {
+            self.used += state.len();
+            self.checkpoints.push_back(Checkpoint { state, cycles: before.cycles, steps: Vec::new() });
+        }
+        self.current = Some(Step { before, writes: Vec::new(), device: false });
+    }
+
+    /// Record that `addr` held `old` before the current instruction wrote it
+    pub(crate) fn record_write(&mut self, addr: u16, old: u8) {
+        if let Some(step) = &mut self.current {
+            step.writes.push((addr, old));
+        }
+    }
+
+    /// Record that the current instruction wrote to a device, so stepping back can't undo it
+    pub(crate) fn record_device_write(&mut self) {
+        if let Some(step) = &mut self.current {
+            step.device = true;
+        }
+    }
+
+    /// Finish recording the current instruction, dropping it if nothing changed
+    pub(crate) fn commit(&mut self, after: CoreState) {
+        let Some(step) = self.current.take() else {
+            return;
+        };
+        if step.before == after && step.writes.is_empty() && !step.device {
+            return;
+        }
+
+        self.used += step.size();
+        if let Some(checkpoint) = self.checkpoints.back_mut() {
+            checkpoint.steps.push(step);
+        }
+
+        // Keep at least the checkpoint being recorded into
+        while self.used > self.config.budget && self.checkpoints.len() > 1 {
+            if let Some(oldest) = self.checkpoints.pop_front() {
+                self.used -= oldest.state.len() + oldest.steps.iter().map(Step::size).sum::<usize>();
+            }
+        }
+    }
+
+    /// Take the most recent instruction off the history, returning the state before it and the bytes it overwrote
+    ///
+    /// Returns `None`, keeping the history, if there is none or it wrote to a device.
+    pub(crate) fn pop(&mut self) -> Option<(CoreState, Vec<(u16, u8)>)> {
+        self.current = None;
+        let index = self.checkpoints.iter().rposition(|checkpoint| !checkpoint.steps.is_empty())?;
+        if self.checkpoints[index].steps.last()?.device {
+            return None;
+        }
+
+        // The later checkpoints are empty, and their snapshots come after the instruction
+        for checkpoint in self.checkpoints.drain(index + 1..) {
+            self.used -= checkpoint.state.len();
+        }
+        let step = self.checkpoints[index].steps.pop()?;
+        self.used -= step.size();
+        Some((step.before, step.writes))
+    }
+
+    /// Drop all history after the latest snapshot taken at or before `cycles`, returning that snapshot
+    ///
+    /// Returns `None`, keeping the history, if there is no such snapshot.
+    pub(crate) fn pop_to(&mut self, cycles: u64) -> Option<Vec<u8>> {
+        self.current = None;
+        let index = self.checkpoints.iter().rposition(|checkpoint| checkpoint.cycles <= cycles)?;
+        for checkpoint in self.checkpoints.drain(index + 1..) {
+            self.used -= checkpoint.state.len() + checkpoint.steps.iter().map(Step::size).sum::<usize>();
+        }
+
+        let checkpoint = self.checkpoints.back_mut()?;
+        self.used -= checkpoint.steps.drain(..).map(|step| step.size()).sum::<usize>();
+        Some(checkpoint.state.clone())
+    }
+}
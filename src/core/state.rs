@@ -0,0 +1,174 @@
+use std::fmt;
+
+/// Magic bytes at the start of every save state
+pub const MAGIC: [u8; 4] = *b"EMSS";
+/// Current save state format version, bumped whenever the layout of any saved component changes
+pub const VERSION: u16 = 1;
+
+/// Errors that can occur while loading a save state
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StateError {
+    /// The data doesn't start with `MAGIC`
+    BadMagic,
+    /// The state was saved by an incompatible version of the format
+    UnsupportedVersion(u16),
+    /// The checksum doesn't match the contents
+    ChecksumMismatch,
+    /// The data ended before all components were read
+    UnexpectedEof,
+    /// The state doesn't fit the machine it's loaded into
+    Mismatch(&'static str),
+}
+
+impl fmt::Display for StateError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StateError::BadMagic => write!(f, "not a save state"),
+            StateError::UnsupportedVersion(version) => write!(f, "unsupported save state version {}", version),
+            StateError::ChecksumMismatch => write!(f, "save state checksum mismatch"),
+            StateError::UnexpectedEof => write!(f, "save state is truncated"),
+            StateError::Mismatch(what) => write!(f, "save state doesn't match this machine: {}", what),
+        }
+    }
+}
+
+impl std::error::Error for StateError {}
+
+/// Serializes components of a save state (little endian)
+#[derive(Default)]
+pub struct StateWriter {
+    data: Vec<u8>,
+}
+
+impl StateWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn write_u8(&mut self, value: u8) {
+        self.data.push(value);
+    }
+
+    pub fn write_bool(&mut self, value: bool) {
+        self.write_u8(value as u8);
+    }
+
+    pub fn write_u16(&mut self, value: u16) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn write_u32(&mut self, value: u32) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn write_u64(&mut self, value: u64) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    /// Write a block of bytes, prefixed with its length
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        self.write_u32(bytes.len() as u32);
+        self.data.extend_from_slice(bytes);
+    }
+
+    /// Wrap the written components in a header and checksum, returning the complete save state
+    pub fn finish(self) -> Vec<u8> {
+        let mut state = Vec::with_capacity(self.data.len() + 14);
+        state.extend_from_slice(&MAGIC);
+        state.extend_from_slice(&VERSION.to_le_bytes());
+        state.extend_from_slice(&(self.data.len() as u32).to_le_bytes());
+        state.extend_from_slice(&self.data);
+
+        let checksum = crc32(&state);
+        state.extend_from_slice(&checksum.to_le_bytes());
+        state
+    }
+}
+
+/// Deserializes components of a save state, in the order they were written
+pub struct StateReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> StateReader<'a> {
+    /// Check the header and checksum of a complete save state, and start reading its components
+    pub fn new(state: &'a [u8]) -> Result<Self, StateError> {
+        if state.len() < 4 || state[..4] != MAGIC {
+            return Err(StateError::BadMagic);
+        }
+        if state.len() < 14 {
+            return Err(StateError::UnexpectedEof);
+        }
+
+        let version = u16::from_le_bytes([state[4], state[5]]);
+        if version != VERSION {
+            return Err(StateError::UnsupportedVersion(version));
+        }
+
+        let len = u32::from_le_bytes([state[6], state[7], state[8], state[9]]) as usize;
+        if state.len() != 10 + len + 4 {
+            return Err(StateError::UnexpectedEof);
+        }
+
+        let (contents, checksum) = state.split_at(10 + len);
+        if crc32(contents) != u32::from_le_bytes([checksum[0], checksum[1], checksum[2], checksum[3]]) {
+            return Err(StateError::ChecksumMismatch);
+        }
+
+        Ok(StateReader { data: &contents[10..], pos: 0 })
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], StateError> {
+        let bytes = self.data.get(self.pos..self.pos + len).ok_or(StateError::UnexpectedEof)?;
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, StateError> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub fn read_bool(&mut self) -> Result<bool, StateError> {
+        Ok(self.read_u8()? != 0)
+    }
+
+    pub fn read_u16(&mut self) -> Result<u16, StateError> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    pub fn read_u32(&mut self) -> Result<u32, StateError> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    pub fn read_u64(&mut self) -> Result<u64, StateError> {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(self.take(8)?);
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    /// Read a block of bytes written by `StateWriter::write_bytes` into `buffer`, which must have the same length
+    pub fn read_bytes_into(&mut self, buffer: &mut [u8]) -> Result<(), StateError> {
+        let len = self.read_u32()? as usize;
+        if len != buffer.len() {
+            return Err(StateError::Mismatch("memory size"));
+        }
+        buffer.copy_from_slice(self.take(len)?);
+        Ok(())
+    }
+}
+
+/// CRC-32 (IEEE 802.3) of `data`
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFF_u32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
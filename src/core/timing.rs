@@ -0,0 +1,75 @@
+use crate::core::{
+    cpu::{AddressingMode, CpuVariant, Flags},
+    memory::Memory,
+    opcodes::{self, Mnemonic},
+};
+
+/// Registers the timing of an instruction depends on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TimingState {
+    pub pc: u16,
+    pub x: u8,
+    pub y: u8,
+    pub sr: u8,
+}
+
+/// Predict how many cycles the instruction at `state.pc` takes, without executing it
+///
+/// This is a model of the datasheet timings, independent of `CPU::step`, so the two can be checked against each other:
+/// the base cycles from the opcode table, plus 1 for crossing a page on indexed reads, 1 for a taken branch
+/// (and 1 more if it lands on another page), and 1 for decimal ADC/SBC on the 65C02.
+pub fn instruction_cycles<M: Memory + ?Sized>(memory: &M, variant: CpuVariant, state: TimingState) -> u8 {
+    let op = &opcodes::table(variant)[memory.peek(state.pc) as usize];
+    let operand = memory.peek(state.pc.wrapping_add(1));
+    let operand_u16 = u16::from_le_bytes([operand, memory.peek(state.pc.wrapping_add(2))]);
+    let flag = |flag: Flags| state.sr & flag as u8 != 0;
+    let crosses = |base: u16, addr: u16| base & 0xFF00 != addr & 0xFF00;
+
+    let mut cycles = op.cycles;
+
+    // Indexed reads that cross a page
+    let crossed = match op.mode {
+        AddressingMode::ABX => crosses(operand_u16, operand_u16.wrapping_add(state.x as u16)),
+        AddressingMode::ABY => crosses(operand_u16, operand_u16.wrapping_add(state.y as u16)),
+        AddressingMode::IDY => {
+            let base = u16::from_le_bytes([memory.peek(operand as u16), memory.peek(operand.wrapping_add(1) as u16)]);
+            crosses(base, base.wrapping_add(state.y as u16))
+        },
+        _ => false,
+    };
+    if op.page_cross && crossed && op.mode != AddressingMode::REL {
+        cycles += 1;
+    }
+
+    // Branches
+    let (taken, next, offset) = match op.mnemonic {
+        Mnemonic::BCC => (!flag(Flags::C), 2, operand),
+        Mnemonic::BCS => (flag(Flags::C), 2, operand),
+        Mnemonic::BNE => (!flag(Flags::Z), 2, operand),
+        Mnemonic::BEQ => (flag(Flags::Z), 2, operand),
+        Mnemonic::BPL => (!flag(Flags::N), 2, operand),
+        Mnemonic::BMI => (flag(Flags::N), 2, operand),
+        Mnemonic::BVC => (!flag(Flags::V), 2, operand),
+        Mnemonic::BVS => (flag(Flags::V), 2, operand),
+        Mnemonic::BRA => (true, 2, operand),
+        Mnemonic::BBR | Mnemonic::BBS => {
+            let bit = memory.peek(operand as u16) & (1 << ((memory.peek(state.pc) >> 4) & 0x07)) != 0;
+            (bit == (op.mnemonic == Mnemonic::BBS), 3, (operand_u16 >> 8) as u8)
+        },
+        _ => (false, 0, 0),
+    };
+    if taken {
+        let next = state.pc.wrapping_add(next);
+        cycles += 1;
+        if op.page_cross && crosses(next, next.wrapping_add(offset as i8 as u16)) {
+            cycles += 1;
+        }
+    }
+
+    // The 65C02 takes an extra cycle to fix up N and Z in decimal mode
+    if variant.is_cmos() && flag(Flags::D) && matches!(op.mnemonic, Mnemonic::ADC | Mnemonic::SBC) && !op.illegal {
+        cycles += 1;
+    }
+
+    cycles
+}
@@ -0,0 +1,181 @@
+use std::{
+    collections::VecDeque,
+    fmt,
+    fs::File,
+    io::{self, BufWriter, Write},
+    ops::RangeInclusive,
+    path::Path,
+};
+
+use crate::disasm::Instruction;
+
+/// Snapshot of the CPU registers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Registers {
+    pub a: u8,
+    pub x: u8,
+    pub y: u8,
+    pub sp: u8,
+    pub pc: u16,
+    pub sr: u8,
+}
+
+impl fmt::Display for Registers {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "A:{:02X} X:{:02X} Y:{:02X} P:{:02X} SP:{:02X}", self.a, self.x, self.y, self.sr, self.sp)
+    }
+}
+
+/// A single executed instruction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceEntry {
+    pub instruction: Instruction,
+    /// Registers before the instruction executed
+    pub before: Registers,
+    /// Registers after the instruction executed
+    pub after: Registers,
+    /// Cycles the instruction took
+    pub cycles: u8,
+    /// Total cycles before the instruction executed
+    pub total_cycles: u64,
+}
+
+/// How trace entries are written
+#[derive(Debug, Clone, Copy, Default)]
+pub enum TraceFormat {
+    /// `C000  4C F5 C5  JMP $C5F5        A:00 X:00 Y:00 P:24 SP:FD CYC:7`, with registers and cycles from before the instruction
+    #[default]
+    Log,
+    /// Like `Log`, followed by the registers afterwards and the cycles taken: `-> A:00 X:00 Y:00 P:24 SP:FD (+3)`
+    Verbose,
+    Custom(fn(&TraceEntry) -> String),
+}
+
+impl TraceEntry {
+    pub fn format(&self, format: TraceFormat) -> String {
+        let bytes = self.instruction.bytes().iter().map(|byte| format!("{:02X}", byte)).collect::<Vec<_>>().join(" ");
+        let log = format!(
+            "{:04X}  {:<8}  {:<16} {} CYC:{}",
+            self.instruction.addr,
+            bytes,
+            self.instruction.to_string(),
+            self.before,
+            self.total_cycles,
+        );
+
+        match format {
+            TraceFormat::Log => log,
+            TraceFormat::Verbose => format!("{} -> {} (+{})", log, self.after, self.cycles),
+            TraceFormat::Custom(format) => format(self),
+        }
+    }
+}
+
+impl fmt::Display for TraceEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.format(TraceFormat::Log))
+    }
+}
+
+/// Records executed instructions to a ring buffer, and optionally to a writer (e.g. a file)
+pub struct Tracer {
+    entries: VecDeque<TraceEntry>,
+    capacity: usize,
+    ranges: Vec<RangeInclusive<u16>>,
+    format: TraceFormat,
+    writer: Option<Box<dyn Write>>,
+    error: Option<io::Error>,
+}
+
+impl Tracer {
+    /// Construct tracer keeping the last `capacity` instructions
+    pub fn new(capacity: usize) -> Self {
+        Tracer {
+            entries: VecDeque::with_capacity(capacity),
+            capacity,
+            ranges: Vec::new(),
+            format: TraceFormat::default(),
+            writer: None,
+            error: None,
+        }
+    }
+
+    /// Only trace instructions in `range` (can be given more than once; without any, everything is traced)
+    pub fn with_range(mut self, range: RangeInclusive<u16>) -> Self {
+        self.ranges.push(range);
+        self
+    }
+
+    /// Format entries written to the writer with `format`
+    pub fn with_format(mut self, format: TraceFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Also write every entry to `writer`, one line each
+    pub fn with_writer(mut self, writer: impl Write + 'static) -> Self {
+        self.writer = Some(Box::new(writer));
+        self
+    }
+
+    /// Also write every entry to the file at `path`, replacing it
+    pub fn with_file(self, path: &Path) -> io::Result<Self> {
+        Ok(self.with_writer(BufWriter::new(File::create(path)?)))
+    }
+
+    /// Whether an instruction at `pc` is traced
+    pub fn traces(&self, pc: u16) -> bool {
+        self.ranges.is_empty() || self.ranges.iter().any(|range| range.contains(&pc))
+    }
+
+    pub fn record(&mut self, entry: TraceEntry) {
+        if let Some(writer) = &mut self.writer {
+            // Stop writing after the first error, and keep it for `error`
+            if let Err(error) = writeln!(writer, "{}", entry.format(self.format)) {
+                self.error = Some(error);
+                self.writer = None;
+            }
+        }
+
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+
+    /// Return the buffered entries, oldest first
+    pub fn entries(&self) -> impl DoubleEndedIterator<Item = &TraceEntry> {
+        self.entries.iter()
+    }
+
+    /// Return the most recent entry
+    pub fn last(&self) -> Option<&TraceEntry> {
+        self.entries.back()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Flush the writer
+    pub fn flush(&mut self) -> io::Result<()> {
+        match &mut self.writer {
+            Some(writer) => writer.flush(),
+            None => Ok(()),
+        }
+    }
+
+    /// Return the error that stopped writing, if any
+    pub fn error(&self) -> Option<&io::Error> {
+        self.error.as_ref()
+    }
+}
+
+impl Drop for Tracer {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
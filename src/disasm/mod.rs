@@ -0,0 +1,231 @@
+use std::{
+    collections::HashMap,
+    fmt,
+};
+
+use crate::core::{
+    cpu::{AddressingMode, CpuVariant},
+    memory::Memory,
+    opcodes::{self, Mnemonic},
+};
+
+/// A single decoded instruction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Instruction {
+    /// Address of the opcode
+    pub addr: u16,
+    pub opcode: u8,
+    pub mnemonic: Mnemonic,
+    pub mode: AddressingMode,
+    /// Operand bytes as a little endian value (0 if there are none)
+    pub operand: u16,
+    /// Length in bytes, including the opcode
+    pub len: u8,
+}
+
+impl Instruction {
+    /// Decode the instruction at the start of `bytes`, located at `addr`
+    ///
+    /// Returns `None` if `bytes` is too short to hold the whole instruction.
+    pub fn decode(bytes: &[u8], addr: u16, variant: CpuVariant) -> Option<Self> {
+        let opcode = *bytes.first()?;
+        let op = &opcodes::table(variant)[opcode as usize];
+        let operand_bytes = bytes.get(1..op.len as usize)?;
+
+        let operand = operand_bytes
+            .iter()
+            .rev()
+            .fold(0, |operand, byte| (operand << 8) | *byte as u16);
+
+        Some(Instruction {
+            addr,
+            opcode,
+            mnemonic: op.mnemonic,
+            mode: op.mode,
+            operand,
+            len: op.len,
+        })
+    }
+
+    /// Decode the instruction at `addr` in `memory`, reading it without side effects
+    pub fn decode_at<M: Memory + ?Sized>(memory: &M, addr: u16, variant: CpuVariant) -> Self {
+        let bytes = [
+            memory.peek(addr),
+            memory.peek(addr.wrapping_add(1)),
+            memory.peek(addr.wrapping_add(2)),
+        ];
+        // Every instruction fits in 3 bytes
+        Self::decode(&bytes, addr, variant).unwrap()
+    }
+
+    /// Return the instruction's bytes
+    pub fn bytes(&self) -> Vec<u8> {
+        let operand = self.operand.to_le_bytes();
+        let mut bytes = vec![self.opcode];
+        bytes.extend_from_slice(&operand[..self.len as usize - 1]);
+        bytes
+    }
+
+    /// Return the address of the next instruction
+    pub fn next_addr(&self) -> u16 {
+        self.addr.wrapping_add(self.len as u16)
+    }
+
+    /// Return the target of a branch (REL, or the branch of BBR/BBS)
+    pub fn branch_target(&self) -> Option<u16> {
+        let offset = match self.mode {
+            AddressingMode::REL => self.operand as u8,
+            AddressingMode::ZPR => (self.operand >> 8) as u8,
+            _ => return None,
+        };
+        Some(self.next_addr().wrapping_add(offset as i8 as u16))
+    }
+
+    /// Return the instruction's mnemonic, including the bit number of RMB, SMB, BBR and BBS
+    pub fn name(&self) -> String {
+        match self.mnemonic {
+            Mnemonic::RMB | Mnemonic::SMB | Mnemonic::BBR | Mnemonic::BBS => {
+                format!("{:?}{}", self.mnemonic, (self.opcode >> 4) & 0x07)
+            },
+            mnemonic => format!("{:?}", mnemonic),
+        }
+    }
+
+    /// Format the instruction in standard (ca65) syntax, substituting labels from `symbols` for addresses
+    pub fn format(&self, symbols: Option<&SymbolTable>) -> String {
+        let zp = |addr: u16| label(symbols, addr).unwrap_or_else(|| format!("${:02X}", addr));
+        let abs = |addr: u16| label(symbols, addr).unwrap_or_else(|| format!("${:04X}", addr));
+
+        let operand = match self.mode {
+            AddressingMode::IMP => return self.name(),
+            AddressingMode::ACC => "A".to_string(),
+            AddressingMode::IMM => format!("#${:02X}", self.operand),
+            AddressingMode::ZP0 => zp(self.operand),
+            AddressingMode::ZPX => format!("{},X", zp(self.operand)),
+            AddressingMode::ZPY => format!("{},Y", zp(self.operand)),
+            AddressingMode::ABS => abs(self.operand),
+            AddressingMode::ABX => format!("{},X", abs(self.operand)),
+            AddressingMode::ABY => format!("{},Y", abs(self.operand)),
+            AddressingMode::IND => format!("({})", abs(self.operand)),
+            AddressingMode::IDX => format!("({},X)", zp(self.operand)),
+            AddressingMode::IDY => format!("({}),Y", zp(self.operand)),
+            AddressingMode::REL => abs(self.branch_target().unwrap()),
+            AddressingMode::ZPI => format!("({})", zp(self.operand)),
+            AddressingMode::IAX => format!("({},X)", abs(self.operand)),
+            AddressingMode::ZPR => format!("{},{}", zp(self.operand & 0x00FF), abs(self.branch_target().unwrap())),
+        };
+
+        format!("{} {}", self.name(), operand)
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.format(None))
+    }
+}
+
+// Look up the label of `addr`, if there is a symbol table
+fn label(symbols: Option<&SymbolTable>, addr: u16) -> Option<String> {
+    symbols.and_then(|symbols| symbols.get(addr)).map(str::to_string)
+}
+
+/// Disassemble `bytes`, located at `origin`
+///
+/// Stops at the last complete instruction.
+pub fn disassemble(bytes: &[u8], origin: u16, variant: CpuVariant) -> Vec<Instruction> {
+    let mut instructions = Vec::new();
+    let mut offset = 0;
+
+    while let Some(instruction) = Instruction::decode(&bytes[offset..], origin.wrapping_add(offset as u16), variant) {
+        offset += instruction.len as usize;
+        instructions.push(instruction);
+    }
+
+    instructions
+}
+
+/// Disassemble `count` instructions from `memory`, starting at `start`
+pub fn disassemble_memory<M: Memory + ?Sized>(memory: &M, start: u16, count: usize, variant: CpuVariant) -> Vec<Instruction> {
+    let mut instructions = Vec::with_capacity(count);
+    let mut addr = start;
+
+    for _ in 0..count {
+        let instruction = Instruction::decode_at(memory, addr, variant);
+        addr = instruction.next_addr();
+        instructions.push(instruction);
+    }
+
+    instructions
+}
+
+/// Format an instruction as a listing line: address, bytes, and instruction
+///
+/// E.g. `0600  A9 05     LDA #$05`, preceded by a `label:` line if `symbols` has one for the address.
+pub fn listing(instruction: &Instruction, symbols: Option<&SymbolTable>) -> String {
+    let bytes = instruction
+        .bytes()
+        .iter()
+        .map(|byte| format!("{:02X}", byte))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let line = format!("{:04X}  {:<8}  {}", instruction.addr, bytes, instruction.format(symbols));
+
+    match label(symbols, instruction.addr) {
+        Some(label) => format!("{}:\n{}", label, line),
+        None => line,
+    }
+}
+
+/// Labels for addresses, used to make disassembly readable
+#[derive(Debug, Clone, Default)]
+pub struct SymbolTable {
+    labels: HashMap<u16, String>,
+}
+
+impl SymbolTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse a label file
+    ///
+    /// Supports VICE label files as written by `ld65 -Ln` (`al 00C000 .label`) and assignments (`label = $C000`).
+    /// Other lines are ignored.
+    pub fn parse(text: &str) -> Self {
+        let mut symbols = Self::new();
+
+        for line in text.lines() {
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let (name, value) = match words.as_slice() {
+                ["al", value, name] => (name.trim_start_matches('.'), value.trim_start_matches("C:")),
+                [name, "=", value] => (*name, value.trim_start_matches('$')),
+                _ => continue,
+            };
+
+            if let Ok(addr) = u32::from_str_radix(value, 16) {
+                symbols.insert(addr as u16, name);
+            }
+        }
+
+        symbols
+    }
+
+    /// Add a label for `addr`, replacing any previous one
+    pub fn insert(&mut self, addr: u16, name: &str) {
+        self.labels.insert(addr, name.to_string());
+    }
+
+    /// Return the label of `addr`
+    pub fn get(&self, addr: u16) -> Option<&str> {
+        self.labels.get(&addr).map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.labels.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+}
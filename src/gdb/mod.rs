@@ -0,0 +1,363 @@
+use std::{
+    io::{self, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+};
+
+use crate::core::{
+    cpu::{CpuError, CPU},
+    debug::{Access, StopReason, WatchKind, Watchpoint},
+    memory::Memory,
+};
+
+/// Instructions executed between checks for an interrupt (Ctrl-C) from the debugger
+const INTERRUPT_POLL: u32 = 1024;
+
+/// Register file as described to the debugger, in `g` packet order
+///
+/// GDB has no built-in 6502 architecture, so the layout is sent as a target description.
+const TARGET_XML: &str = r#"<?xml version="1.0"?>
+<!DOCTYPE target SYSTEM "gdb-target.dtd">
+<target version="1.0">
+  <feature name="org.emulatorr.6502">
+    <reg name="a" bitsize="8" type="uint8" regnum="0"/>
+    <reg name="x" bitsize="8" type="uint8"/>
+    <reg name="y" bitsize="8" type="uint8"/>
+    <reg name="sp" bitsize="8" type="uint8"/>
+    <reg name="pc" bitsize="16" type="code_ptr"/>
+    <reg name="p" bitsize="8" type="uint8"/>
+  </feature>
+</target>
+"#;
+
+/// GDB remote serial protocol stub, serving one debugger connection at a time
+///
+/// Registers are A, X, Y, SP, PC (little endian) and P; memory is the CPU's 64K address space, read without side
+/// effects. Supports continue, step, breakpoints (`Z0`/`Z1`), watchpoints (`Z2`-`Z4`) and memory read/write.
+pub struct GdbStub {
+    listener: TcpListener,
+}
+
+impl GdbStub {
+    /// Listen for a debugger on `addr` (e.g. `127.0.0.1:6502`)
+    pub fn bind(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        Ok(GdbStub { listener: TcpListener::bind(addr)? })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Wait for a debugger, then serve it until it detaches, kills the target or disconnects
+    pub fn serve<M: Memory>(&self, cpu: &mut CPU<M>) -> io::Result<()> {
+        let (stream, _) = self.listener.accept()?;
+        stream.set_nodelay(true)?;
+        Session { stream, no_ack: false }.run(cpu)
+    }
+}
+
+struct Session {
+    stream: TcpStream,
+    no_ack: bool,
+}
+
+impl Session {
+    fn run<M: Memory>(&mut self, cpu: &mut CPU<M>) -> io::Result<()> {
+        loop {
+            let Some(packet) = self.receive()? else {
+                return Ok(());
+            };
+
+            let reply = match packet.as_bytes().first() {
+                Some(b'k') => return Ok(()),
+                Some(b'D') => {
+                    self.send("OK")?;
+                    return Ok(());
+                },
+                Some(b'c') => {
+                    jump(cpu, &packet[1..]);
+                    let reason = self.resume(cpu)?;
+                    stop_reply(reason)
+                },
+                Some(b's') => {
+                    jump(cpu, &packet[1..]);
+                    stop_reply(single_step(cpu))
+                },
+                _ => handle(cpu, &packet, &mut self.no_ack),
+            };
+            self.send(&reply)?;
+        }
+    }
+
+    /// Continue until something stops execution, or the debugger interrupts it
+    fn resume<M: Memory>(&mut self, cpu: &mut CPU<M>) -> io::Result<StopReason> {
+        self.stream.set_nonblocking(true)?;
+        let mut count = 0u32;
+        let mut interrupted = Ok(false);
+        let reason = cpu.run_until(|_| {
+            count = count.wrapping_add(1);
+            if count.is_multiple_of(INTERRUPT_POLL) {
+                interrupted = self.poll_interrupt();
+            }
+            !matches!(interrupted, Ok(false))
+        });
+        self.stream.set_nonblocking(false)?;
+        interrupted?;
+        Ok(reason)
+    }
+
+    // Whether the debugger sent an interrupt (0x03) while the target is running
+    fn poll_interrupt(&mut self) -> io::Result<bool> {
+        let mut byte = [0];
+        match self.stream.read(&mut byte) {
+            Ok(0) => Err(io::ErrorKind::UnexpectedEof.into()),
+            Ok(_) => Ok(byte[0] == 0x03),
+            Err(error) if error.kind() == io::ErrorKind::WouldBlock => Ok(false),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Read the next packet, acknowledging it, or `None` if the debugger disconnected
+    fn receive(&mut self) -> io::Result<Option<String>> {
+        loop {
+            // Skip acks and interrupts until the start of a packet
+            loop {
+                match self.read_byte()? {
+                    None => return Ok(None),
+                    Some(b'$') => break,
+                    Some(_) => {},
+                }
+            }
+
+            let mut data = Vec::new();
+            loop {
+                match self.read_byte()? {
+                    None => return Ok(None),
+                    Some(b'#') => break,
+                    Some(byte) => data.push(byte),
+                }
+            }
+            let (Some(high), Some(low)) = (self.read_byte()?, self.read_byte()?) else {
+                return Ok(None);
+            };
+
+            let valid = parse_hex(&String::from_utf8_lossy(&[high, low])) == Some(checksum(&data) as u32);
+            if !self.no_ack {
+                self.stream.write_all(if valid { b"+" } else { b"-" })?;
+            }
+            if valid {
+                return Ok(Some(String::from_utf8_lossy(&data).into_owned()));
+            }
+        }
+    }
+
+    fn read_byte(&mut self) -> io::Result<Option<u8>> {
+        let mut byte = [0];
+        match self.stream.read(&mut byte)? {
+            0 => Ok(None),
+            _ => Ok(Some(byte[0])),
+        }
+    }
+
+    /// Send `data` as a packet, resending until it is acknowledged
+    fn send(&mut self, data: &str) -> io::Result<()> {
+        let packet = format!("${}#{:02x}", data, checksum(data.as_bytes()));
+        loop {
+            self.stream.write_all(packet.as_bytes())?;
+            if self.no_ack {
+                return Ok(());
+            }
+            match self.read_byte()? {
+                Some(b'-') => {},
+                _ => return Ok(()),
+            }
+        }
+    }
+}
+
+/// Answer a packet that doesn't resume execution
+fn handle<M: Memory>(cpu: &mut CPU<M>, packet: &str, no_ack: &mut bool) -> String {
+    let Some(command) = packet.get(..1) else {
+        return String::new();
+    };
+    let args = &packet[1..];
+    let reply = match command {
+        "?" => Some("S05".to_string()),
+        "g" => Some(encode_registers(cpu)),
+        "G" => hex::decode(args).ok().filter(|bytes| bytes.len() == 7).map(|bytes| {
+            write_registers(cpu, &bytes);
+            "OK".to_string()
+        }),
+        "p" => parse_hex(args).and_then(|register| read_register(cpu, register)),
+        "P" => args.split_once('=').and_then(|(register, value)| {
+            let register = parse_hex(register)?;
+            write_register(cpu, register, &hex::decode(value).ok()?).then(|| "OK".to_string())
+        }),
+        "m" => parse_range(args).map(|(addr, len)| {
+            (0..len).map(|offset| format!("{:02x}", cpu.bus().peek(addr.wrapping_add(offset)))).collect()
+        }),
+        "M" => args.split_once(':').and_then(|(range, data)| {
+            let (addr, len) = parse_range(range)?;
+            let bytes = hex::decode(data).ok().filter(|bytes| bytes.len() == len as usize)?;
+            for (offset, byte) in (0..).zip(bytes) {
+                cpu.bus_mut().write(addr.wrapping_add(offset), byte);
+            }
+            Some("OK".to_string())
+        }),
+        "Z" | "z" => set_point(cpu, command == "Z", args),
+        "H" | "T" => Some("OK".to_string()),
+        "q" | "Q" => return query(packet, no_ack),
+        _ => return String::new(),
+    };
+    reply.unwrap_or_else(|| "E01".to_string())
+}
+
+/// Answer a general query; an empty reply means unsupported
+fn query(packet: &str, no_ack: &mut bool) -> String {
+    if packet.starts_with("qSupported") {
+        return "PacketSize=1000;qXfer:features:read+;QStartNoAckMode+;swbreak+;hwbreak+".to_string();
+    }
+    if let Some(args) = packet.strip_prefix("qXfer:features:read:target.xml:") {
+        let Some((offset, len)) = args.split_once(',').and_then(|(offset, len)| Some((parse_hex(offset)?, parse_hex(len)?)))
+        else {
+            return "E01".to_string();
+        };
+        let start = (offset as usize).min(TARGET_XML.len());
+        let end = (start + len as usize).min(TARGET_XML.len());
+        let prefix = if end == TARGET_XML.len() { 'l' } else { 'm' };
+        return format!("{}{}", prefix, &TARGET_XML[start..end]);
+    }
+
+    match packet {
+        "QStartNoAckMode" => {
+            *no_ack = true;
+            "OK".to_string()
+        },
+        "qAttached" => "1".to_string(),
+        "qC" => "QC1".to_string(),
+        "qfThreadInfo" => "m1".to_string(),
+        "qsThreadInfo" => "l".to_string(),
+        _ => String::new(),
+    }
+}
+
+/// Insert (`Z`) or remove (`z`) a breakpoint or watchpoint: `type,addr,kind`
+fn set_point<M: Memory>(cpu: &mut CPU<M>, insert: bool, args: &str) -> Option<String> {
+    let mut fields = args.split(',');
+    let kind = fields.next()?;
+    let addr = parse_hex(fields.next()?).and_then(|addr| u16::try_from(addr).ok())?;
+    let len = parse_hex(fields.next()?)?.max(1);
+    let range = addr..=addr.saturating_add((len - 1).min(0xFFFF) as u16);
+
+    let watch = match kind {
+        "0" | "1" => {
+            if insert {
+                cpu.add_breakpoint(addr);
+            } else {
+                cpu.remove_breakpoint(addr);
+            }
+            return Some("OK".to_string());
+        },
+        "2" => WatchKind::Write,
+        "3" => WatchKind::Read,
+        "4" => WatchKind::Access,
+        _ => return Some(String::new()),
+    };
+
+    if insert {
+        cpu.add_watchpoint(Watchpoint::new(range, watch));
+    } else {
+        cpu.remove_watchpoint(&range);
+    }
+    Some("OK".to_string())
+}
+
+/// Step one instruction, stepping over a breakpoint at PC
+fn single_step<M: Memory>(cpu: &mut CPU<M>) -> StopReason {
+    let mut result = cpu.step();
+    if let Err(CpuError::BreakpointHit { .. }) = result {
+        result = cpu.step();
+    }
+    match result {
+        Ok(_) => StopReason::Predicate,
+        Err(error) => error.into(),
+    }
+}
+
+/// Resume at the address argument of `c` or `s`, if there is one
+fn jump<M: Memory>(cpu: &mut CPU<M>, args: &str) {
+    if let Some(addr) = parse_hex(args) {
+        cpu.set_pc(addr as u16);
+    }
+}
+
+/// Stop reply packet for `reason`
+fn stop_reply(reason: StopReason) -> String {
+    match reason {
+        StopReason::Breakpoint { .. } => "T05swbreak:;".to_string(),
+        StopReason::Watchpoint { addr, access, .. } => {
+            let watch = match access {
+                Access::Read => "rwatch",
+                Access::Write => "watch",
+            };
+            format!("T05{}:{:04x};", watch, addr)
+        },
+        StopReason::Error(CpuError::IllegalOpcode { .. } | CpuError::Jammed { .. }) => "S04".to_string(),
+        // Finished a step, interrupted by the debugger, or WAI/STP
+        _ => "S05".to_string(),
+    }
+}
+
+fn encode_registers<M: Memory>(cpu: &CPU<M>) -> String {
+    let registers = cpu.registers();
+    let [pc_low, pc_high] = registers.pc.to_le_bytes();
+    hex::encode([registers.a, registers.x, registers.y, registers.sp, pc_low, pc_high, registers.sr])
+}
+
+fn write_registers<M: Memory>(cpu: &mut CPU<M>, bytes: &[u8]) {
+    cpu.set_a(bytes[0]);
+    cpu.set_x(bytes[1]);
+    cpu.set_y(bytes[2]);
+    cpu.set_sp(bytes[3]);
+    cpu.set_pc(u16::from_le_bytes([bytes[4], bytes[5]]));
+    cpu.set_sr(bytes[6]);
+}
+
+fn read_register<M: Memory>(cpu: &CPU<M>, register: u32) -> Option<String> {
+    let registers = cpu.registers();
+    Some(match register {
+        0 => hex::encode([registers.a]),
+        1 => hex::encode([registers.x]),
+        2 => hex::encode([registers.y]),
+        3 => hex::encode([registers.sp]),
+        4 => hex::encode(registers.pc.to_le_bytes()),
+        5 => hex::encode([registers.sr]),
+        _ => return None,
+    })
+}
+
+fn write_register<M: Memory>(cpu: &mut CPU<M>, register: u32, bytes: &[u8]) -> bool {
+    match (register, bytes) {
+        (0, [a]) => cpu.set_a(*a),
+        (1, [x]) => cpu.set_x(*x),
+        (2, [y]) => cpu.set_y(*y),
+        (3, [sp]) => cpu.set_sp(*sp),
+        (4, [low, high]) => cpu.set_pc(u16::from_le_bytes([*low, *high])),
+        (5, [sr]) => cpu.set_sr(*sr),
+        _ => return false,
+    }
+    true
+}
+
+/// Parse `addr,len`
+fn parse_range(args: &str) -> Option<(u16, u16)> {
+    let (addr, len) = args.split_once(',')?;
+    Some((u16::try_from(parse_hex(addr)?).ok()?, u16::try_from(parse_hex(len)?).ok()?))
+}
+
+fn parse_hex(text: &str) -> Option<u32> {
+    u32::from_str_radix(text, 16).ok()
+}
+
+fn checksum(data: &[u8]) -> u8 {
+    data.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte))
+}
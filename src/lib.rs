@@ -1,3 +1,8 @@
+pub mod asm;
 pub mod core;
+pub mod disasm;
+#[cfg(feature = "gdbstub")]
+pub mod gdb;
 pub mod io;
-
+pub mod nes;
+pub mod suites;
@@ -8,38 +8,166 @@ use crossterm::{
     execute,
 };
 
-mod core;
-mod io;
-mod nes;
-
-use core::{
-    cpu::{CPU, Flags},
-    bus::Bus,
+use emulatorr::{
+    core::{
+        cpu::Flags,
+        rewind::RewindConfig,
+    },
+    disasm,
+    nes::{
+        self,
+        input::{Button, ControllerState},
+        Nes,
+    },
+    suites::dormann::DormannSuite,
 };
 
+/// CPU cycles in one NES (NTSC) frame, the distance `B` rewinds
+const FRAME_CYCLES: u64 = 29_781;
+
+/// Keys for player 1's controller; terminals don't report key releases, so a press holds the button for one frame
+fn button_for(key: KeyCode) -> Option<Button> {
+    match key {
+        KeyCode::Up => Some(Button::Up),
+        KeyCode::Down => Some(Button::Down),
+        KeyCode::Left => Some(Button::Left),
+        KeyCode::Right => Some(Button::Right),
+        KeyCode::Char('z') => Some(Button::B),
+        KeyCode::Char('x') => Some(Button::A),
+        KeyCode::Char('c') => Some(Button::Select),
+        KeyCode::Char('v') => Some(Button::Start),
+        _ => None,
+    }
+}
+
+/// Describe the buttons held on a controller for the status panel
+fn describe_buttons(state: ControllerState) -> String {
+    let held = [
+        (Button::A, "A"),
+        (Button::B, "B"),
+        (Button::Select, "Select"),
+        (Button::Start, "Start"),
+        (Button::Up, "Up"),
+        (Button::Down, "Down"),
+        (Button::Left, "Left"),
+        (Button::Right, "Right"),
+    ]
+    .iter()
+    .filter(|(button, _)| state.is_pressed(*button))
+    .map(|(_, name)| *name)
+    .collect::<Vec<_>>();
+    format!("Holding {} for the next frame", held.join(", "))
+}
+
 enum Event<I> {
     Input(I),
     Tick,
 }
 
+/// Describe the result of an action for the status panel
+fn status_of<E: std::fmt::Display>(result: Result<(), E>, ok: &str) -> String {
+    match result {
+        Ok(()) => ok.to_string(),
+        Err(error) => error.to_string(),
+    }
+}
+
+/// Run one of Klaus Dormann's test binaries and report the result
+fn run_dormann(args: &[String]) -> Result<(), std::io::Error> {
+    let (suite, path) = match args {
+        [kind, path] if kind == "functional" => (DormannSuite::functional(), path),
+        [kind, path] if kind == "decimal" => (DormannSuite::decimal(), path),
+        _ => {
+            eprintln!("usage: emulatorr dormann <functional|decimal> <path>");
+            std::process::exit(2);
+        },
+    };
+
+    let outcome = suite.run_file(&std::path::PathBuf::from(path))?;
+    println!("{}", outcome);
+    if !outcome.passed() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Compare a run of nestest against its golden log
+fn run_nestest(args: &[String]) -> Result<(), std::io::Error> {
+    let [rom, log] = args else {
+        eprintln!("usage: emulatorr nestest <nestest.nes> <nestest.log>");
+        std::process::exit(2);
+    };
+
+    let outcome = nes::nestest::run(&std::path::PathBuf::from(rom), &std::path::PathBuf::from(log))?;
+    println!("{}", outcome);
+    if !outcome.passed() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Serve a raw program (loaded at 0x0600) to a GDB remote debugger
+#[cfg(feature = "gdbstub")]
+fn run_gdb(args: &[String]) -> Result<(), std::io::Error> {
+    use emulatorr::core::{bus::Bus, cpu::CPU};
+
+    let (path, port) = match args {
+        [path] => (path, 6502),
+        [path, port] => match port.parse::<u16>() {
+            Ok(port) => (path, port),
+            Err(_) => {
+                eprintln!("invalid port: {}", port);
+                std::process::exit(2);
+            },
+        },
+        _ => {
+            eprintln!("usage: emulatorr gdb <program.bin> [port]");
+            std::process::exit(2);
+        },
+    };
+
+    let mut cpu: CPU = CPU::new(Bus::new());
+    cpu.load_program(emulatorr::io::load_rom(&std::path::PathBuf::from(path))?);
+    cpu.power_on();
+
+    let stub = emulatorr::gdb::GdbStub::bind(("127.0.0.1", port))?;
+    println!("waiting for a debugger on {}", stub.local_addr()?);
+    stub.serve(&mut cpu)
+}
+
 #[allow(unused_variables)]
 fn main() -> Result<(), std::io::Error> {
-    // Init bus and CPU
-    let bus: Bus = Bus::new();
-    let mut cpu: CPU = CPU::new(bus);
-
     // Get CLI arguments
     let args: Vec<String> = std::env::args().collect();
 
-    nes::load_nes_rom(&mut cpu, &dirs::home_dir().unwrap().join(args[1].clone()))?;
-    cpu.reset();
+    // Run a test suite instead of the TUI
+    match args.get(1).map(String::as_str) {
+        Some("dormann") => return run_dormann(&args[2..]),
+        Some("nestest") => return run_nestest(&args[2..]),
+        #[cfg(feature = "gdbstub")]
+        Some("gdb") => return run_gdb(&args[2..]),
+        _ => {},
+    }
+
+    // Insert the cartridge
+    let rom_path = dirs::home_dir().unwrap().join(args[1].clone());
+    let mut nes = Nes::load(&rom_path)?;
+
+    // Quick-save slot, next to the ROM
+    let state_path = rom_path.with_extension("state");
 
     // cpu.write(0x00F1, 0x27);
     // let program = io::load_bytes(&dirs::home_dir().unwrap().join("stack.txt"))?;
     // cpu.load_program(program);
-    // cpu.reset();
 
-    cpu.reset();
+    nes.power_on();
+    nes.cpu_mut().enable_rewind(RewindConfig::default());
+
+    // Result of the last action (e.g. an error reported by the CPU)
+    let mut status = String::from("OK");
+
+    // Player 1's buttons, held until the next frame has run
+    let mut pad = ControllerState::default();
 
     // Set up terminal
     let (mut terminal, rx) = stdr::setup_terminal!();
@@ -47,8 +175,11 @@ fn main() -> Result<(), std::io::Error> {
     // Render loop
     loop {
         // Get values from CPU
+        let cpu = nes.cpu();
         let cpu_state = cpu.get_state();
+        let cycles = cpu.cycles();
         let mem = cpu.get_memory();
+        let instructions = disasm::disassemble_memory(cpu.bus(), cpu.get_pc(), 64, cpu.config().variant);
 
         // Draw terminal
         terminal.draw(|f| {
@@ -88,7 +219,7 @@ fn main() -> Result<(), std::io::Error> {
                 .constraints([
                     Constraint::Min(20),
                     Constraint::Min(3),
-                    Constraint::Length(6),
+                    Constraint::Length(10),
                 ])
                 .split(halves[1]);
 
@@ -102,7 +233,7 @@ fn main() -> Result<(), std::io::Error> {
             .block(
                 Block::default()
                     .borders(Borders::ALL)
-                    .title("Registers")
+                    .title(format!("Registers (cycle {})", cycles))
             )
             .widths(&[
                 Constraint::Percentage(13),
@@ -148,35 +279,32 @@ fn main() -> Result<(), std::io::Error> {
             .column_spacing(1);
             f.render_widget(flags, left_layout[1]);
 
-            // Memory (from program start)
-            let program: Vec<u8> = mem.iter().cloned().skip(0x0600).collect::<Vec<_>>();
-            let indices: Vec<u16> = (0..program.len() as u16).collect();
-
+            // Disassembly (from program counter)
             let program_list = Table::new(
-                indices
+                instructions
                     .iter()
-                    .map(|i| {
-                        if indices[*i as usize] + 0x0600 == cpu_state[4] {
-                            Row::new(vec![format!("0x{:04X}", 0x0600 + i), format!("0x{:02X}",program[*i as usize]).to_string()])
-                                .style(Style::default().bg(Color::White).fg(Color::Black))
-                        } else if program[*i as usize] == 0x00 {
-                            Row::new(vec![format!("0x{:04X}", 0x0600 + i), "----".to_string()])
+                    .map(|instruction| {
+                        let bytes = instruction.bytes().iter().map(|byte| format!("{:02X}", byte)).collect::<Vec<_>>().join(" ");
+                        let row = Row::new(vec![format!("0x{:04X}", instruction.addr), bytes, instruction.to_string()]);
+                        if instruction.addr == cpu_state[4] {
+                            row.style(Style::default().bg(Color::White).fg(Color::Black))
                         } else {
-                            Row::new(vec![format!("0x{:04X}", 0x0600 + i), format!("0x{:02X}",program[*i as usize]).to_string()])
+                            row
                         }
                     })
                 )
                 .header(
-                    Row::new(vec!["Address", "Value"])
+                    Row::new(vec!["Address", "Bytes", "Instruction"])
                 )
                 .block(
                     Block::default()
                         .borders(Borders::ALL)
-                        .title("Memory (from program start)")
+                        .title("Disassembly (from program counter)")
                 )
                 .widths(&[
-                    Constraint::Percentage(50),
-                    Constraint::Percentage(50),
+                    Constraint::Percentage(25),
+                    Constraint::Percentage(30),
+                    Constraint::Percentage(45),
                 ])
                 .column_spacing(1);
             f.render_widget(program_list, left_layout[2]);
@@ -213,8 +341,17 @@ fn main() -> Result<(), std::io::Error> {
                 .column_spacing(1);
             f.render_widget(stack_list, right_layout[0]);
 
+            // Status
+            let status_paragraph = Paragraph::new(status.as_str())
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .title("Status")
+                );
+            f.render_widget(status_paragraph, right_layout[1]);
+
             // Help
-            let help = Paragraph::new("<space>: advance to next cycle\n<enter>: start clock\nr: reset CPU\nq: quit application")
+            let help = Paragraph::new("<space>: advance to next cycle\n<enter>: run one frame\narrows/z/x/c/v: D-pad/B/A/Select/Start\nb: step back one instruction\nB: rewind one frame\nr: reset NES\ns/l: quick-save/quick-load\nq: quit application")
                 .block(
                     Block::default()
                         .borders(Borders::ALL)
@@ -230,16 +367,45 @@ fn main() -> Result<(), std::io::Error> {
                     break;
                 },
                 KeyCode::Char(' ') => {
-                    cpu.advance();
+                    nes.set_controller(0, pad);
+                    status = status_of(nes.step().map(|_| ()), "OK");
+                },
+                KeyCode::Char('b') => {
+                    status = if nes.cpu_mut().step_back() {
+                        String::from("Stepped back")
+                    } else if nes.cpu().rewind_history().is_none_or(|rewind| rewind.is_empty()) {
+                        String::from("No history to step back into")
+                    } else {
+                        String::from("Can't step back over a device write")
+                    };
+                },
+                KeyCode::Char('B') => {
+                    status = if nes.cpu_mut().rewind(FRAME_CYCLES) { String::from("Rewound") } else { String::from("No history to rewind into") };
                 },
                 KeyCode::Char('r') => {
-                    cpu.reset();
+                    nes.reset();
+                    status = String::from("OK");
                 },
                 KeyCode::Enter => {
-                    cpu.clock();
+                    nes.set_controller(0, pad);
+                    status = status_of(nes.run_frame(), "OK");
+                    pad.clear();
+                    nes.set_controller(0, pad);
+                },
+                KeyCode::Char('s') => {
+                    status = status_of(std::fs::write(&state_path, nes.cpu().save_state()), "State saved");
+                },
+                KeyCode::Char('l') => {
+                    status = match std::fs::read(&state_path) {
+                        Ok(state) => status_of(nes.cpu_mut().load_state(&state), "State loaded"),
+                        Err(error) => error.to_string(),
+                    };
                 },
-                _ => {
-                    
+                code => {
+                    if let Some(button) = button_for(code) {
+                        pad.press(button);
+                        status = describe_buttons(pad);
+                    }
                 },
             },
             Event::Tick => {
@@ -0,0 +1,195 @@
+use crate::core::state::{StateError, StateReader, StateWriter};
+
+/// Timer periods in CPU cycles (NTSC), indexed by the low 4 bits of the first register
+const RATES: [u16; 16] = [428, 380, 340, 320, 286, 254, 226, 214, 190, 160, 142, 128, 106, 84, 72, 54];
+
+/// Delta modulation channel: plays 1-bit delta-encoded samples fetched from CPU memory (0x4010-0x4013)
+///
+/// The channel can't read memory itself: whenever its sample buffer is empty it requests the next byte (see
+/// `Apu::dmc_request`), which the bus fetches with DMA.
+pub struct Dmc {
+    irq_enabled: bool,
+    irq: bool,
+    looping: bool,
+    period: u16,
+    timer: u16,
+    level: u8, // output level (0-127)
+
+    // Memory reader
+    sample_address: u16,
+    sample_length: u16,
+    address: u16,
+    remaining: u16,
+    buffer: Option<u8>,
+
+    // Output unit
+    shift: u8,
+    bits: u8,
+    silence: bool,
+}
+
+impl Default for Dmc {
+    fn default() -> Self {
+        Dmc {
+            irq_enabled: false,
+            irq: false,
+            looping: false,
+            period: RATES[0],
+            timer: 0,
+            level: 0,
+            sample_address: 0xC000,
+            sample_length: 1,
+            address: 0xC000,
+            remaining: 0,
+            buffer: None,
+            shift: 0,
+            bits: 8,
+            silence: true,
+        }
+    }
+}
+
+impl Dmc {
+    /// Write register `reg` (0-3)
+    pub fn write(&mut self, reg: u16, data: u8) {
+        match reg {
+            0 => {
+                self.irq_enabled = data & 0x80 != 0;
+                if !self.irq_enabled {
+                    self.irq = false;
+                }
+                self.looping = data & 0x40 != 0;
+                self.period = RATES[(data & 0x0F) as usize];
+            },
+            1 => self.level = data & 0x7F,
+            2 => self.sample_address = 0xC000 | (data as u16) << 6,
+            _ => self.sample_length = (data as u16) << 4 | 1,
+        }
+    }
+
+    /// Start the sample if enabled and it isn't playing, or stop it; either way clears the IRQ flag
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.irq = false;
+        if !enabled {
+            self.remaining = 0;
+        } else if self.remaining == 0 {
+            self.restart();
+        }
+    }
+
+    fn restart(&mut self) {
+        self.address = self.sample_address;
+        self.remaining = self.sample_length;
+    }
+
+    /// Whether sample bytes remain to be fetched
+    pub fn active(&self) -> bool {
+        self.remaining > 0
+    }
+
+    pub fn irq(&self) -> bool {
+        self.irq
+    }
+
+    /// Address of the next sample byte, if the buffer is empty and bytes remain
+    pub fn request(&self) -> Option<u16> {
+        (self.buffer.is_none() && self.remaining > 0).then_some(self.address)
+    }
+
+    /// Receive the byte fetched for `request`
+    pub fn fill(&mut self, data: u8) {
+        if self.remaining == 0 {
+            return;
+        }
+        self.buffer = Some(data);
+        // The address wraps around to 0x8000
+        self.address = self.address.checked_add(1).unwrap_or(0x8000);
+        self.remaining -= 1;
+        if self.remaining == 0 {
+            if self.looping {
+                self.restart();
+            } else if self.irq_enabled {
+                self.irq = true;
+            }
+        }
+    }
+
+    /// Clock the timer (every CPU cycle)
+    pub fn tick(&mut self) {
+        if self.timer > 0 {
+            self.timer -= 1;
+            return;
+        }
+        self.timer = self.period - 1;
+
+        if !self.silence {
+            if self.shift & 0x01 != 0 {
+                if self.level <= 125 {
+                    self.level += 2;
+                }
+            } else if self.level >= 2 {
+                self.level -= 2;
+            }
+        }
+        self.shift >>= 1;
+
+        self.bits -= 1;
+        if self.bits == 0 {
+            self.bits = 8;
+            match self.buffer.take() {
+                Some(data) => {
+                    self.shift = data;
+                    self.silence = false;
+                },
+                None => self.silence = true,
+            }
+        }
+    }
+
+    /// Current output level (0-127)
+    pub fn output(&self) -> u8 {
+        self.level
+    }
+
+    pub fn save_state(&self, writer: &mut StateWriter) {
+        writer.write_bool(self.irq_enabled);
+        writer.write_bool(self.irq);
+        writer.write_bool(self.looping);
+        writer.write_u16(self.period);
+        writer.write_u16(self.timer);
+        writer.write_u8(self.level);
+        writer.write_u16(self.sample_address);
+        writer.write_u16(self.sample_length);
+        writer.write_u16(self.address);
+        writer.write_u16(self.remaining);
+        writer.write_bool(self.buffer.is_some());
+        writer.write_u8(self.buffer.unwrap_or(0));
+        writer.write_u8(self.shift);
+        writer.write_u8(self.bits);
+        writer.write_bool(self.silence);
+    }
+
+    pub fn load_state(&mut self, reader: &mut StateReader) -> Result<(), StateError> {
+        self.irq_enabled = reader.read_bool()?;
+        self.irq = reader.read_bool()?;
+        self.looping = reader.read_bool()?;
+        let period = reader.read_u16()?;
+        if !RATES.contains(&period) {
+            return Err(StateError::Mismatch("DMC rate"));
+        }
+        self.period = period;
+        self.timer = reader.read_u16()?.min(period - 1);
+        self.level = reader.read_u8()? & 0x7F;
+        self.sample_address = reader.read_u16()?;
+        self.sample_length = reader.read_u16()?;
+        self.address = reader.read_u16()?;
+        self.remaining = reader.read_u16()?;
+        let buffered = reader.read_bool()?;
+        let buffer = reader.read_u8()?;
+        self.buffer = buffered.then_some(buffer);
+        self.shift = reader.read_u8()?;
+        self.bits = reader.read_u8()?.clamp(1, 8);
+        self.silence = reader.read_bool()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,422 @@
+use crate::core::state::{StateError, StateReader, StateWriter};
+
+pub mod dmc;
+pub mod noise;
+pub mod pulse;
+pub mod triangle;
+pub mod wav;
+
+pub use dmc::Dmc;
+pub use noise::Noise;
+pub use pulse::Pulse;
+pub use triangle::Triangle;
+pub use wav::WavWriter;
+
+/// NTSC CPU clock in Hz
+pub const CPU_CLOCK: u64 = 1_789_773;
+/// Default output sample rate in Hz
+pub const DEFAULT_SAMPLE_RATE: u32 = 44_100;
+
+/// Length counter values, indexed by the top 5 bits of the channels' fourth register
+const LENGTHS: [u8; 32] = [
+    10, 254, 20, 2, 40, 4, 80, 6, 160, 8, 60, 10, 14, 12, 26, 14, 12, 16, 24, 18, 48, 20, 96, 22, 192, 24, 72, 26, 16,
+    28, 32, 30,
+];
+
+/// CPU cycles at which the frame counter steps in 4-step mode: every step is a quarter frame (envelopes and linear
+/// counter), and the second and last are also half frames (length counters and sweeps)
+const FOUR_STEP: [u64; 4] = [7457, 14913, 22371, 29829];
+/// Same in 5-step mode, where the fourth step does nothing and the last is a half frame
+const FIVE_STEP: [u64; 5] = [7457, 14913, 22371, 29829, 37281];
+
+/// Counts down a channel's duration; the channel is silenced at 0
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct LengthCounter {
+    pub(crate) counter: u8,
+    pub(crate) halt: bool,
+    enabled: bool,
+}
+
+impl LengthCounter {
+    /// Load from the top 5 bits of `data`, if the channel is enabled
+    pub(crate) fn load(&mut self, data: u8) {
+        if self.enabled {
+            self.counter = LENGTHS[(data >> 3) as usize];
+        }
+    }
+
+    /// Enable or disable the channel (from $4015); disabling clears the counter
+    pub(crate) fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.counter = 0;
+        }
+    }
+
+    /// Clock on half frames
+    pub(crate) fn clock(&mut self) {
+        if !self.halt && self.counter > 0 {
+            self.counter -= 1;
+        }
+    }
+
+    pub(crate) fn active(&self) -> bool {
+        self.counter > 0
+    }
+
+    pub(crate) fn save_state(&self, writer: &mut StateWriter) {
+        writer.write_u8(self.counter);
+        writer.write_bool(self.halt);
+        writer.write_bool(self.enabled);
+    }
+
+    pub(crate) fn load_state(&mut self, reader: &mut StateReader) -> Result<(), StateError> {
+        self.counter = reader.read_u8()?;
+        self.halt = reader.read_bool()?;
+        self.enabled = reader.read_bool()?;
+        Ok(())
+    }
+}
+
+/// Volume envelope of the pulse and noise channels: a constant volume, or a sawtooth decaying from 15
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Envelope {
+    start: bool,
+    looping: bool,
+    constant: bool,
+    period: u8,
+    divider: u8,
+    decay: u8,
+}
+
+impl Envelope {
+    /// Write the low 6 bits of the channel's first register (loop flag, constant volume flag and volume/period)
+    pub(crate) fn write(&mut self, data: u8) {
+        self.looping = data & 0x20 != 0;
+        self.constant = data & 0x10 != 0;
+        self.period = data & 0x0F;
+    }
+
+    /// Restart the decay (when the channel's fourth register is written)
+    pub(crate) fn restart(&mut self) {
+        self.start = true;
+    }
+
+    /// Clock on quarter frames
+    pub(crate) fn clock(&mut self) {
+        if self.start {
+            self.start = false;
+            self.decay = 15;
+            self.divider = self.period;
+        } else if self.divider == 0 {
+            self.divider = self.period;
+            if self.decay > 0 {
+                self.decay -= 1;
+            } else if self.looping {
+                self.decay = 15;
+            }
+        } else {
+            self.divider -= 1;
+        }
+    }
+
+    pub(crate) fn volume(&self) -> u8 {
+        if self.constant {
+            self.period
+        } else {
+            self.decay
+        }
+    }
+
+    pub(crate) fn save_state(&self, writer: &mut StateWriter) {
+        for value in [self.start as u8, self.looping as u8, self.constant as u8, self.period, self.divider, self.decay] {
+            writer.write_u8(value);
+        }
+    }
+
+    pub(crate) fn load_state(&mut self, reader: &mut StateReader) -> Result<(), StateError> {
+        self.start = reader.read_bool()?;
+        self.looping = reader.read_bool()?;
+        self.constant = reader.read_bool()?;
+        self.period = reader.read_u8()? & 0x0F;
+        self.divider = reader.read_u8()? & 0x0F;
+        self.decay = reader.read_u8()? & 0x0F;
+        Ok(())
+    }
+}
+
+/// The 2A03's audio processing unit (registers 0x4000-0x4013, 0x4015 and 0x4017)
+///
+/// Clocked once per CPU cycle. The five channels are mixed with the non-linear formulas of the console's DACs, and
+/// the mix is averaged over each output sample period into `f32` samples between 0.0 and 1.0. DMC sample fetches are
+/// requested through `dmc_request`, to be served by the bus, which stalls the CPU for them.
+/// See https://www.nesdev.org/wiki/APU
+pub struct Apu {
+    pulse1: Pulse,
+    pulse2: Pulse,
+    triangle: Triangle,
+    noise: Noise,
+    dmc: Dmc,
+
+    five_step: bool,   // frame counter mode
+    irq_inhibit: bool, // frame IRQ disabled
+    frame_irq: bool,   // frame IRQ flag
+    frame_cycle: u64,  // CPU cycles since the frame counter was reset
+    odd_cycle: bool,   // pulse timers are clocked every other CPU cycle
+
+    sample_rate: u32,
+    sample_clock: u64, // accumulates `sample_rate` every cycle, producing a sample every `CPU_CLOCK`
+    sample_sum: f32,
+    sample_count: u32,
+    samples: Vec<f32>,
+}
+
+impl Default for Apu {
+    fn default() -> Self {
+        Self::new(DEFAULT_SAMPLE_RATE)
+    }
+}
+
+impl Apu {
+    /// APU producing `sample_rate` samples per second (of emulated time)
+    pub fn new(sample_rate: u32) -> Self {
+        Apu {
+            pulse1: Pulse::new(true),
+            pulse2: Pulse::new(false),
+            triangle: Triangle::default(),
+            noise: Noise::default(),
+            dmc: Dmc::default(),
+            five_step: false,
+            irq_inhibit: false,
+            frame_irq: false,
+            frame_cycle: 0,
+            odd_cycle: false,
+            sample_rate: sample_rate.max(1),
+            sample_clock: 0,
+            sample_sum: 0.0,
+            sample_count: 0,
+            samples: Vec::new(),
+        }
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        self.sample_rate = sample_rate.max(1);
+        self.sample_clock = 0;
+    }
+
+    /// Samples produced since the last drain
+    ///
+    /// The buffer grows until it's drained, so frontends should drain it every frame.
+    pub fn samples(&self) -> &[f32] {
+        &self.samples
+    }
+
+    /// Take the samples produced since the last drain
+    pub fn drain_samples(&mut self) -> Vec<f32> {
+        std::mem::take(&mut self.samples)
+    }
+
+    /// Whether the frame counter or the DMC is asserting the CPU's IRQ line
+    pub fn irq(&self) -> bool {
+        self.frame_irq || self.dmc.irq()
+    }
+
+    /// Address of the byte the DMC needs next, if its sample buffer is empty
+    ///
+    /// The bus should read it and hand it over with `dmc_fill`.
+    pub fn dmc_request(&self) -> Option<u16> {
+        self.dmc.request()
+    }
+
+    pub fn dmc_fill(&mut self, data: u8) {
+        self.dmc.fill(data);
+    }
+
+    /// Read 0x4015 (channel status), clearing the frame IRQ flag
+    pub fn read_status(&mut self) -> u8 {
+        let status = self.peek_status();
+        self.frame_irq = false;
+        status
+    }
+
+    /// Read 0x4015 without side effects
+    pub fn peek_status(&self) -> u8 {
+        (self.pulse1.active() as u8)
+            | (self.pulse2.active() as u8) << 1
+            | (self.triangle.active() as u8) << 2
+            | (self.noise.active() as u8) << 3
+            | (self.dmc.active() as u8) << 4
+            | (self.frame_irq as u8) << 6
+            | (self.dmc.irq() as u8) << 7
+    }
+
+    /// Write register `addr` (0x4000-0x4013, 0x4015 or 0x4017; others are ignored)
+    pub fn write(&mut self, addr: u16, data: u8) {
+        match addr {
+            0x4000..=0x4003 => self.pulse1.write(addr & 0x03, data),
+            0x4004..=0x4007 => self.pulse2.write(addr & 0x03, data),
+            0x4008..=0x400B => self.triangle.write(addr & 0x03, data),
+            0x400C..=0x400F => self.noise.write(addr & 0x03, data),
+            0x4010..=0x4013 => self.dmc.write(addr & 0x03, data),
+            0x4015 => {
+                self.pulse1.set_enabled(data & 0x01 != 0);
+                self.pulse2.set_enabled(data & 0x02 != 0);
+                self.triangle.set_enabled(data & 0x04 != 0);
+                self.noise.set_enabled(data & 0x08 != 0);
+                self.dmc.set_enabled(data & 0x10 != 0);
+            },
+            0x4017 => {
+                self.five_step = data & 0x80 != 0;
+                self.irq_inhibit = data & 0x40 != 0;
+                if self.irq_inhibit {
+                    self.frame_irq = false;
+                }
+                self.frame_cycle = 0;
+                // Entering 5-step mode clocks everything immediately
+                if self.five_step {
+                    self.quarter_frame();
+                    self.half_frame();
+                }
+            },
+            _ => {},
+        }
+    }
+
+    /// Silence every channel (as writing 0 to 0x4015 does) and restart the frame counter in its current mode, as the
+    /// reset button does
+    pub fn reset(&mut self) {
+        self.write(0x4015, 0x00);
+        self.frame_irq = false;
+        self.frame_cycle = 0;
+    }
+
+    /// Advance one CPU cycle
+    pub fn tick(&mut self) {
+        self.frame_cycle += 1;
+        self.clock_frame_counter();
+
+        self.triangle.tick();
+        self.noise.tick();
+        self.dmc.tick();
+        if self.odd_cycle {
+            self.pulse1.tick();
+            self.pulse2.tick();
+        }
+        self.odd_cycle = !self.odd_cycle;
+
+        self.sample_sum += self.mix();
+        self.sample_count += 1;
+        self.sample_clock += self.sample_rate as u64;
+        if self.sample_clock >= CPU_CLOCK {
+            self.sample_clock -= CPU_CLOCK;
+            self.samples.push(self.sample_sum / self.sample_count as f32);
+            self.sample_sum = 0.0;
+            self.sample_count = 0;
+        }
+    }
+
+    fn clock_frame_counter(&mut self) {
+        let cycle = self.frame_cycle;
+        if self.five_step {
+            match FIVE_STEP.iter().position(|&step| step == cycle) {
+                Some(0 | 2) => self.quarter_frame(),
+                Some(1) => {
+                    self.quarter_frame();
+                    self.half_frame();
+                },
+                Some(4) => {
+                    self.quarter_frame();
+                    self.half_frame();
+                    self.frame_cycle = 0;
+                },
+                _ => {},
+            }
+        } else {
+            match FOUR_STEP.iter().position(|&step| step == cycle) {
+                Some(0 | 2) => self.quarter_frame(),
+                Some(1) => {
+                    self.quarter_frame();
+                    self.half_frame();
+                },
+                Some(3) => {
+                    self.quarter_frame();
+                    self.half_frame();
+                    if !self.irq_inhibit {
+                        self.frame_irq = true;
+                    }
+                    self.frame_cycle = 0;
+                },
+                _ => {},
+            }
+        }
+    }
+
+    fn quarter_frame(&mut self) {
+        self.pulse1.quarter_frame();
+        self.pulse2.quarter_frame();
+        self.triangle.quarter_frame();
+        self.noise.quarter_frame();
+    }
+
+    fn half_frame(&mut self) {
+        self.pulse1.half_frame();
+        self.pulse2.half_frame();
+        self.triangle.half_frame();
+        self.noise.half_frame();
+    }
+
+    /// Current output level of the mixer, between 0.0 and 1.0
+    ///
+    /// See https://www.nesdev.org/wiki/APU_Mixer
+    pub fn mix(&self) -> f32 {
+        let pulse = (self.pulse1.output() + self.pulse2.output()) as f32;
+        let pulse_out = if pulse == 0.0 { 0.0 } else { 95.88 / (8128.0 / pulse + 100.0) };
+
+        let triangle = self.triangle.output() as f32 / 8227.0;
+        let noise = self.noise.output() as f32 / 12241.0;
+        let dmc = self.dmc.output() as f32 / 22638.0;
+        let tnd = triangle + noise + dmc;
+        let tnd_out = if tnd == 0.0 { 0.0 } else { 159.79 / (1.0 / tnd + 100.0) };
+
+        pulse_out + tnd_out
+    }
+
+    pub fn save_state(&self, writer: &mut StateWriter) {
+        self.pulse1.save_state(writer);
+        self.pulse2.save_state(writer);
+        self.triangle.save_state(writer);
+        self.noise.save_state(writer);
+        self.dmc.save_state(writer);
+        writer.write_bool(self.five_step);
+        writer.write_bool(self.irq_inhibit);
+        writer.write_bool(self.frame_irq);
+        writer.write_u64(self.frame_cycle);
+        writer.write_bool(self.odd_cycle);
+    }
+
+    /// Restore the state written by `save_state`; buffered samples are dropped
+    pub fn load_state(&mut self, reader: &mut StateReader) -> Result<(), StateError> {
+        self.pulse1.load_state(reader)?;
+        self.pulse2.load_state(reader)?;
+        self.triangle.load_state(reader)?;
+        self.noise.load_state(reader)?;
+        self.dmc.load_state(reader)?;
+        self.five_step = reader.read_bool()?;
+        self.irq_inhibit = reader.read_bool()?;
+        self.frame_irq = reader.read_bool()?;
+        self.frame_cycle = reader.read_u64()?;
+        let last = if self.five_step { FIVE_STEP[4] } else { FOUR_STEP[3] };
+        if self.frame_cycle >= last {
+            self.frame_cycle = 0;
+        }
+        self.odd_cycle = reader.read_bool()?;
+        self.samples.clear();
+        self.sample_sum = 0.0;
+        self.sample_count = 0;
+        Ok(())
+    }
+}
@@ -0,0 +1,113 @@
+use crate::{
+    core::state::{StateError, StateReader, StateWriter},
+    nes::apu::{Envelope, LengthCounter},
+};
+
+/// Timer periods in CPU cycles (NTSC), indexed by the low 4 bits of the third register
+const PERIODS: [u16; 16] = [4, 8, 16, 32, 64, 96, 128, 160, 202, 254, 380, 508, 762, 1016, 2034, 4068];
+
+/// Noise channel: a 15-bit linear feedback shift register, with envelope and length counter (0x400C-0x400F)
+pub struct Noise {
+    // Short mode feeds back bit 6 instead of bit 1, for a metallic 93-step sequence
+    short: bool,
+    shift: u16,
+    period: u16,
+    timer: u16,
+    envelope: Envelope,
+    length: LengthCounter,
+}
+
+impl Default for Noise {
+    fn default() -> Self {
+        // The shift register is 1 at power on
+        Noise {
+            short: false,
+            shift: 1,
+            period: PERIODS[0],
+            timer: 0,
+            envelope: Envelope::default(),
+            length: LengthCounter::default(),
+        }
+    }
+}
+
+impl Noise {
+    /// Write register `reg` (0-3)
+    pub fn write(&mut self, reg: u16, data: u8) {
+        match reg {
+            0 => {
+                self.length.halt = data & 0x20 != 0;
+                self.envelope.write(data);
+            },
+            1 => {},
+            2 => {
+                self.short = data & 0x80 != 0;
+                self.period = PERIODS[(data & 0x0F) as usize];
+            },
+            _ => {
+                self.length.load(data);
+                self.envelope.restart();
+            },
+        }
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.length.set_enabled(enabled);
+    }
+
+    /// Whether the length counter is non-zero
+    pub fn active(&self) -> bool {
+        self.length.active()
+    }
+
+    /// Clock the timer (every CPU cycle)
+    pub fn tick(&mut self) {
+        if self.timer == 0 {
+            self.timer = self.period - 1;
+            let tap = if self.short { 6 } else { 1 };
+            let feedback = (self.shift ^ (self.shift >> tap)) & 0x01;
+            self.shift = (self.shift >> 1) | (feedback << 14);
+        } else {
+            self.timer -= 1;
+        }
+    }
+
+    pub fn quarter_frame(&mut self) {
+        self.envelope.clock();
+    }
+
+    pub fn half_frame(&mut self) {
+        self.length.clock();
+    }
+
+    /// Current output level (0-15)
+    pub fn output(&self) -> u8 {
+        if !self.length.active() || self.shift & 0x01 != 0 {
+            0
+        } else {
+            self.envelope.volume()
+        }
+    }
+
+    pub fn save_state(&self, writer: &mut StateWriter) {
+        writer.write_bool(self.short);
+        writer.write_u16(self.shift);
+        writer.write_u16(self.period);
+        writer.write_u16(self.timer);
+        self.envelope.save_state(writer);
+        self.length.save_state(writer);
+    }
+
+    pub fn load_state(&mut self, reader: &mut StateReader) -> Result<(), StateError> {
+        self.short = reader.read_bool()?;
+        self.shift = reader.read_u16()? & 0x7FFF;
+        let period = reader.read_u16()?;
+        if !PERIODS.contains(&period) {
+            return Err(StateError::Mismatch("noise period"));
+        }
+        self.period = period;
+        self.timer = reader.read_u16()?.min(period - 1);
+        self.envelope.load_state(reader)?;
+        self.length.load_state(reader)
+    }
+}
@@ -0,0 +1,171 @@
+use crate::{
+    core::state::{StateError, StateReader, StateWriter},
+    nes::apu::{Envelope, LengthCounter},
+};
+
+/// Duty cycle waveforms, indexed by the top 2 bits of the first register
+const DUTIES: [[u8; 8]; 4] = [
+    [0, 1, 0, 0, 0, 0, 0, 0],
+    [0, 1, 1, 0, 0, 0, 0, 0],
+    [0, 1, 1, 1, 1, 0, 0, 0],
+    [1, 0, 0, 1, 1, 1, 1, 1],
+];
+
+/// Pulse (square wave) channel, with envelope, sweep unit and length counter (0x4000-0x4003 and 0x4004-0x4007)
+pub struct Pulse {
+    // Pulse 1 negates sweeps in ones' complement, pulse 2 in two's complement
+    first: bool,
+    duty: u8,
+    step: u8,
+    period: u16,
+    timer: u16,
+    envelope: Envelope,
+    length: LengthCounter,
+
+    sweep_enabled: bool,
+    sweep_period: u8,
+    sweep_negate: bool,
+    sweep_shift: u8,
+    sweep_divider: u8,
+    sweep_reload: bool,
+}
+
+impl Pulse {
+    /// `first` selects pulse 1's sweep behaviour
+    pub fn new(first: bool) -> Self {
+        Pulse {
+            first,
+            duty: 0,
+            step: 0,
+            period: 0,
+            timer: 0,
+            envelope: Envelope::default(),
+            length: LengthCounter::default(),
+            sweep_enabled: false,
+            sweep_period: 0,
+            sweep_negate: false,
+            sweep_shift: 0,
+            sweep_divider: 0,
+            sweep_reload: false,
+        }
+    }
+
+    /// Write register `reg` (0-3)
+    pub fn write(&mut self, reg: u16, data: u8) {
+        match reg {
+            0 => {
+                self.duty = data >> 6;
+                self.length.halt = data & 0x20 != 0;
+                self.envelope.write(data);
+            },
+            1 => {
+                self.sweep_enabled = data & 0x80 != 0;
+                self.sweep_period = (data >> 4) & 0x07;
+                self.sweep_negate = data & 0x08 != 0;
+                self.sweep_shift = data & 0x07;
+                self.sweep_reload = true;
+            },
+            2 => self.period = (self.period & 0x0700) | data as u16,
+            _ => {
+                self.period = (self.period & 0x00FF) | ((data as u16 & 0x07) << 8);
+                self.length.load(data);
+                self.envelope.restart();
+                self.step = 0;
+            },
+        }
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.length.set_enabled(enabled);
+    }
+
+    /// Whether the length counter is non-zero
+    pub fn active(&self) -> bool {
+        self.length.active()
+    }
+
+    /// Clock the timer (every other CPU cycle)
+    pub fn tick(&mut self) {
+        if self.timer == 0 {
+            self.timer = self.period;
+            self.step = (self.step + 1) % 8;
+        } else {
+            self.timer -= 1;
+        }
+    }
+
+    pub fn quarter_frame(&mut self) {
+        self.envelope.clock();
+    }
+
+    pub fn half_frame(&mut self) {
+        self.length.clock();
+
+        if self.sweep_divider == 0 && self.sweep_enabled && self.sweep_shift > 0 && !self.muted() {
+            self.period = self.sweep_target();
+        }
+        if self.sweep_divider == 0 || self.sweep_reload {
+            self.sweep_divider = self.sweep_period;
+            self.sweep_reload = false;
+        } else {
+            self.sweep_divider -= 1;
+        }
+    }
+
+    // Period the sweep unit is moving towards (computed continuously, even when the sweep is disabled)
+    fn sweep_target(&self) -> u16 {
+        let change = self.period >> self.sweep_shift;
+        if !self.sweep_negate {
+            self.period + change
+        } else if self.first {
+            self.period.saturating_sub(change + 1)
+        } else {
+            self.period.saturating_sub(change)
+        }
+    }
+
+    // Periods under 8, or sweeping beyond 0x7FF, silence the channel
+    fn muted(&self) -> bool {
+        self.period < 8 || self.sweep_target() > 0x7FF
+    }
+
+    /// Current output level (0-15)
+    pub fn output(&self) -> u8 {
+        if !self.length.active() || self.muted() || DUTIES[self.duty as usize][self.step as usize] == 0 {
+            0
+        } else {
+            self.envelope.volume()
+        }
+    }
+
+    pub fn save_state(&self, writer: &mut StateWriter) {
+        writer.write_u8(self.duty);
+        writer.write_u8(self.step);
+        writer.write_u16(self.period);
+        writer.write_u16(self.timer);
+        self.envelope.save_state(writer);
+        self.length.save_state(writer);
+        writer.write_bool(self.sweep_enabled);
+        writer.write_u8(self.sweep_period);
+        writer.write_bool(self.sweep_negate);
+        writer.write_u8(self.sweep_shift);
+        writer.write_u8(self.sweep_divider);
+        writer.write_bool(self.sweep_reload);
+    }
+
+    pub fn load_state(&mut self, reader: &mut StateReader) -> Result<(), StateError> {
+        self.duty = reader.read_u8()? & 0x03;
+        self.step = reader.read_u8()? & 0x07;
+        self.period = reader.read_u16()? & 0x07FF;
+        self.timer = reader.read_u16()? & 0x07FF;
+        self.envelope.load_state(reader)?;
+        self.length.load_state(reader)?;
+        self.sweep_enabled = reader.read_bool()?;
+        self.sweep_period = reader.read_u8()? & 0x07;
+        self.sweep_negate = reader.read_bool()?;
+        self.sweep_shift = reader.read_u8()? & 0x07;
+        self.sweep_divider = reader.read_u8()? & 0x07;
+        self.sweep_reload = reader.read_bool()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,109 @@
+use crate::{
+    core::state::{StateError, StateReader, StateWriter},
+    nes::apu::LengthCounter,
+};
+
+/// Triangle channel, with linear counter and length counter (0x4008-0x400B)
+#[derive(Default)]
+pub struct Triangle {
+    step: u8,
+    period: u16,
+    timer: u16,
+    length: LengthCounter,
+
+    // Linear counter: a finer duration, reloaded on every quarter frame while `control` is set
+    control: bool,
+    linear_period: u8,
+    linear_counter: u8,
+    linear_reload: bool,
+}
+
+impl Triangle {
+    /// Write register `reg` (0-3)
+    pub fn write(&mut self, reg: u16, data: u8) {
+        match reg {
+            0 => {
+                self.control = data & 0x80 != 0;
+                self.length.halt = self.control;
+                self.linear_period = data & 0x7F;
+            },
+            1 => {},
+            2 => self.period = (self.period & 0x0700) | data as u16,
+            _ => {
+                self.period = (self.period & 0x00FF) | ((data as u16 & 0x07) << 8);
+                self.length.load(data);
+                self.linear_reload = true;
+            },
+        }
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.length.set_enabled(enabled);
+    }
+
+    /// Whether the length counter is non-zero
+    pub fn active(&self) -> bool {
+        self.length.active()
+    }
+
+    /// Clock the timer (every CPU cycle); the sequencer only advances while both counters are non-zero
+    pub fn tick(&mut self) {
+        if self.timer == 0 {
+            self.timer = self.period;
+            if self.length.active() && self.linear_counter > 0 {
+                self.step = (self.step + 1) % 32;
+            }
+        } else {
+            self.timer -= 1;
+        }
+    }
+
+    pub fn quarter_frame(&mut self) {
+        if self.linear_reload {
+            self.linear_counter = self.linear_period;
+        } else if self.linear_counter > 0 {
+            self.linear_counter -= 1;
+        }
+        if !self.control {
+            self.linear_reload = false;
+        }
+    }
+
+    pub fn half_frame(&mut self) {
+        self.length.clock();
+    }
+
+    /// Current output level (0-15): 15 down to 0, then back up to 15
+    ///
+    /// The channel holds its level when silenced rather than dropping to 0, as on the console.
+    pub fn output(&self) -> u8 {
+        if self.step < 16 {
+            15 - self.step
+        } else {
+            self.step - 16
+        }
+    }
+
+    pub fn save_state(&self, writer: &mut StateWriter) {
+        writer.write_u8(self.step);
+        writer.write_u16(self.period);
+        writer.write_u16(self.timer);
+        self.length.save_state(writer);
+        writer.write_bool(self.control);
+        writer.write_u8(self.linear_period);
+        writer.write_u8(self.linear_counter);
+        writer.write_bool(self.linear_reload);
+    }
+
+    pub fn load_state(&mut self, reader: &mut StateReader) -> Result<(), StateError> {
+        self.step = reader.read_u8()? & 0x1F;
+        self.period = reader.read_u16()? & 0x07FF;
+        self.timer = reader.read_u16()? & 0x07FF;
+        self.length.load_state(reader)?;
+        self.control = reader.read_bool()?;
+        self.linear_period = reader.read_u8()? & 0x7F;
+        self.linear_counter = reader.read_u8()? & 0x7F;
+        self.linear_reload = reader.read_bool()?;
+        Ok(())
+    }
+}
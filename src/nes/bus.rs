@@ -0,0 +1,195 @@
+use crate::{
+    core::{
+        memory::Memory,
+        state::{StateError, StateReader, StateWriter},
+    },
+    nes::{
+        apu::Apu,
+        input::{Controller, Device},
+        mapper::Mapper,
+        ppu::Ppu,
+    },
+};
+
+/// CPU cycles taken by OAM DMA, plus one when it starts on an odd cycle
+pub const OAM_DMA_CYCLES: u64 = 513;
+/// CPU cycles taken by each DMC sample fetch
+pub const DMC_DMA_CYCLES: u64 = 4;
+
+/// The NES CPU's address space: 2KB internal RAM (mirrored up to 0x1FFF), the PPU registers (mirrored up to 0x3FFF),
+/// the APU registers, OAM DMA at 0x4014, the controller ports at 0x4016-0x4017 and the cartridge's mapper
+///
+/// Devices in the controller ports aren't part of save states; frontends set their state every frame anyway.
+pub struct NesBus {
+    ram: [u8; 0x0800],
+    ppu: Ppu,
+    apu: Apu,
+    ports: [Device; 2],
+    mapper: Box<dyn Mapper>,
+    // OAM DMA started since the last `take_stall`
+    oam_dma: bool,
+    // CPU cycles stolen by DMC DMA since the last `take_stall`
+    dmc_stall: u64,
+}
+
+impl NesBus {
+    /// Bus with a standard controller in both ports
+    pub fn new(mapper: Box<dyn Mapper>) -> Self {
+        NesBus {
+            ram: [0; 0x0800],
+            ppu: Ppu::new(),
+            apu: Apu::default(),
+            ports: [Device::Controller(Controller::new()), Device::Controller(Controller::new())],
+            mapper,
+            oam_dma: false,
+            dmc_stall: 0,
+        }
+    }
+
+    pub fn ppu(&self) -> &Ppu {
+        &self.ppu
+    }
+
+    pub fn ppu_mut(&mut self) -> &mut Ppu {
+        &mut self.ppu
+    }
+
+    /// Device in controller port `port` (0 for 0x4016, 1 for 0x4017)
+    pub fn port(&self, port: usize) -> &Device {
+        &self.ports[port & 0x01]
+    }
+
+    pub fn port_mut(&mut self, port: usize) -> &mut Device {
+        &mut self.ports[port & 0x01]
+    }
+
+    /// Plug `device` into controller port `port`, returning the device that was there
+    pub fn connect(&mut self, port: usize, device: Device) -> Device {
+        std::mem::replace(&mut self.ports[port & 0x01], device)
+    }
+
+    pub fn apu(&self) -> &Apu {
+        &self.apu
+    }
+
+    pub fn apu_mut(&mut self) -> &mut Apu {
+        &mut self.apu
+    }
+
+    /// Reset the PPU, APU and mapper registers (RAM and the controller ports are untouched)
+    pub fn reset(&mut self) {
+        self.ppu.reset();
+        self.apu.reset();
+        self.mapper.reset();
+        self.oam_dma = false;
+        self.dmc_stall = 0;
+    }
+
+    /// Advance the APU and PPU by `cycles` CPU cycles (3 PPU dots each), serving DMC sample fetches
+    pub fn tick(&mut self, cycles: u64) {
+        for _ in 0..cycles {
+            self.apu.tick();
+            if let Some(addr) = self.apu.dmc_request() {
+                let data = self.read(addr);
+                self.apu.dmc_fill(data);
+                self.dmc_stall += DMC_DMA_CYCLES;
+            }
+            for _ in 0..3 {
+                self.ppu.tick(self.mapper.as_mut());
+            }
+        }
+    }
+
+    /// Return the CPU cycles stolen by DMA since the last call, for a CPU that has run `cycles` cycles
+    pub fn take_stall(&mut self, cycles: u64) -> u64 {
+        let mut stall = std::mem::take(&mut self.dmc_stall);
+        if std::mem::take(&mut self.oam_dma) {
+            stall += OAM_DMA_CYCLES + cycles % 2;
+        }
+        stall
+    }
+
+    // Copy page `page` of the CPU's address space to OAM
+    fn oam_dma(&mut self, page: u8) {
+        for addr in (page as u16) << 8..=(page as u16) << 8 | 0xFF {
+            let data = self.read(addr);
+            self.ppu.write_oam(data);
+        }
+        self.oam_dma = true;
+    }
+
+    pub fn mapper(&self) -> &dyn Mapper {
+        self.mapper.as_ref()
+    }
+
+    pub fn mapper_mut(&mut self) -> &mut dyn Mapper {
+        self.mapper.as_mut()
+    }
+}
+
+impl Memory for NesBus {
+    fn read(&mut self, addr: u16) -> u8 {
+        match addr {
+            0x0000..=0x1FFF => self.ram[addr as usize & 0x07FF],
+            0x2000..=0x3FFF => self.ppu.read_register(addr, self.mapper.as_mut()),
+            0x4015 => self.apu.read_status(),
+            // The upper bits are open bus, left over from the address
+            0x4016 => 0x40 | self.ports[0].read(&self.ppu),
+            0x4017 => 0x40 | self.ports[1].read(&self.ppu),
+            0x4000..=0x401F => 0,
+            _ => self.mapper.cpu_read(addr),
+        }
+    }
+
+    fn write(&mut self, addr: u16, data: u8) {
+        match addr {
+            0x0000..=0x1FFF => self.ram[addr as usize & 0x07FF] = data,
+            0x2000..=0x3FFF => self.ppu.write_register(addr, data, self.mapper.as_mut()),
+            0x4014 => self.oam_dma(data),
+            0x4016 => {
+                for port in &mut self.ports {
+                    port.write(data);
+                }
+            },
+            0x4000..=0x401F => self.apu.write(addr, data),
+            _ => self.mapper.cpu_write(addr, data),
+        }
+    }
+
+    fn peek(&self, addr: u16) -> u8 {
+        match addr {
+            0x0000..=0x1FFF => self.ram[addr as usize & 0x07FF],
+            0x2000..=0x3FFF => self.ppu.peek_register(addr, self.mapper.as_ref()),
+            0x4015 => self.apu.peek_status(),
+            0x4016 => 0x40 | self.ports[0].peek(&self.ppu),
+            0x4017 => 0x40 | self.ports[1].peek(&self.ppu),
+            0x4000..=0x401F => 0,
+            _ => self.mapper.cpu_peek(addr),
+        }
+    }
+
+    // Only the internal RAM: everything else is a device or belongs to the cartridge
+    fn poke(&mut self, addr: u16, data: u8) -> bool {
+        match addr {
+            0x0000..=0x1FFF => {
+                self.ram[addr as usize & 0x07FF] = data;
+                true
+            },
+            _ => false,
+        }
+    }
+
+    fn save_state(&self, writer: &mut StateWriter) {
+        writer.write_bytes(&self.ram);
+        self.ppu.save_state(writer);
+        self.apu.save_state(writer);
+        self.mapper.save_state(writer);
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader) -> Result<(), StateError> {
+        reader.read_bytes_into(&mut self.ram)?;
+        self.ppu.load_state(reader)?;
+        self.apu.load_state(reader)?;
+        self.mapper.load_state(reader)
+    }
+}
@@ -0,0 +1,234 @@
+use std::{fmt, io, path::Path};
+
+const HEADER_SIZE: usize = 16;
+const TRAINER_SIZE: usize = 512;
+const MAGIC: [u8; 4] = [0x4E, 0x45, 0x53, 0x1A];
+
+/// Errors that can occur while parsing a ROM file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RomError {
+    /// The file couldn't be read
+    Io(io::ErrorKind),
+    /// The file doesn't start with `NES<EOF>`
+    BadMagic,
+    /// The file is shorter than the header says it is
+    Truncated { expected: usize, actual: usize },
+    /// The cartridge uses a mapper that isn't emulated
+    UnsupportedMapper(u16),
+}
+
+impl fmt::Display for RomError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RomError::Io(kind) => write!(f, "couldn't read ROM: {}", kind),
+            RomError::BadMagic => write!(f, "not an iNES or NES 2.0 ROM"),
+            RomError::Truncated { expected, actual } => {
+                write!(f, "ROM is truncated: expected {} bytes, found {}", expected, actual)
+            },
+            RomError::UnsupportedMapper(mapper) => write!(f, "unsupported mapper {}", mapper),
+        }
+    }
+}
+
+impl std::error::Error for RomError {}
+
+impl From<io::Error> for RomError {
+    fn from(error: io::Error) -> Self {
+        RomError::Io(error.kind())
+    }
+}
+
+impl From<RomError> for io::Error {
+    fn from(error: RomError) -> Self {
+        match error {
+            RomError::Io(kind) => kind.into(),
+            error => io::Error::new(io::ErrorKind::InvalidData, error),
+        }
+    }
+}
+
+#[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RomFormat {
+    iNES,
+    NES2,
+}
+
+/// Nametable arrangement wired on the cartridge (mappers may override it)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mirroring {
+    Horizontal,
+    Vertical,
+    /// The cartridge provides 2KB of extra VRAM for four unique nametables
+    FourScreen,
+    /// All nametables show the first page of VRAM (selected by mappers like AxROM and MMC1)
+    SingleScreenLower,
+    /// All nametables show the second page of VRAM
+    SingleScreenUpper,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConsoleType {
+    Nes,
+    VsSystem,
+    Playchoice10,
+    /// NES 2.0 extended console type (e.g. 3 for VT01 famiclones)
+    Extended(u8),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TvSystem {
+    Ntsc,
+    Pal,
+    /// Runs on both NTSC and PAL consoles
+    MultiRegion,
+    Dendy,
+}
+
+/// A cartridge, parsed from an iNES or NES 2.0 ROM file
+///
+/// See https://www.nesdev.org/wiki/INES and https://www.nesdev.org/wiki/NES_2.0
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cartridge {
+    pub format: RomFormat,
+    pub mapper: u16,
+    /// Always 0 for iNES
+    pub submapper: u8,
+    pub prg_rom: Vec<u8>,
+    /// Empty if the cartridge has CHR RAM instead
+    pub chr_rom: Vec<u8>,
+    /// 512 bytes loaded at 0x7000
+    pub trainer: Option<Vec<u8>>,
+    /// Sizes in bytes; iNES only knows PRG RAM, and assumes 8KB CHR RAM when there is no CHR ROM
+    pub prg_ram_size: usize,
+    pub prg_nvram_size: usize,
+    pub chr_ram_size: usize,
+    pub chr_nvram_size: usize,
+    pub mirroring: Mirroring,
+    /// Whether the cartridge has battery-backed memory
+    pub battery: bool,
+    pub console: ConsoleType,
+    pub tv_system: TvSystem,
+}
+
+impl Cartridge {
+    /// Read and parse the ROM file at `path`
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, RomError> {
+        Self::from_bytes(&std::fs::read(path)?)
+    }
+
+    /// Parse an iNES or NES 2.0 ROM file
+    pub fn from_bytes(rom: &[u8]) -> Result<Self, RomError> {
+        if rom.len() < HEADER_SIZE || rom[0..4] != MAGIC {
+            return Err(RomError::BadMagic);
+        }
+        let header = &rom[0..HEADER_SIZE];
+        let format = if header[7] & 0x0C == 0x08 { RomFormat::NES2 } else { RomFormat::iNES };
+
+        let mirroring = match (header[6] & 0x08 != 0, header[6] & 0x01 != 0) {
+            (true, _) => Mirroring::FourScreen,
+            (false, true) => Mirroring::Vertical,
+            (false, false) => Mirroring::Horizontal,
+        };
+        let battery = header[6] & 0x02 != 0;
+        let has_trainer = header[6] & 0x04 != 0;
+        let console = match header[7] & 0x03 {
+            0 => ConsoleType::Nes,
+            1 => ConsoleType::VsSystem,
+            2 => ConsoleType::Playchoice10,
+            _ if format == RomFormat::NES2 => ConsoleType::Extended(header[13] & 0x0F),
+            _ => ConsoleType::Nes,
+        };
+
+        let mut cartridge = Cartridge {
+            format,
+            mapper: 0,
+            submapper: 0,
+            prg_rom: Vec::new(),
+            chr_rom: Vec::new(),
+            trainer: None,
+            prg_ram_size: 0,
+            prg_nvram_size: 0,
+            chr_ram_size: 0,
+            chr_nvram_size: 0,
+            mirroring,
+            battery,
+            console,
+            tv_system: TvSystem::Ntsc,
+        };
+
+        let (prg_rom_size, chr_rom_size) = match format {
+            RomFormat::NES2 => {
+                cartridge.mapper = (header[6] >> 4) as u16 | (header[7] & 0xF0) as u16 | ((header[8] & 0x0F) as u16) << 8;
+                cartridge.submapper = header[8] >> 4;
+                cartridge.prg_ram_size = shift_size(header[10] & 0x0F);
+                cartridge.prg_nvram_size = shift_size(header[10] >> 4);
+                cartridge.chr_ram_size = shift_size(header[11] & 0x0F);
+                cartridge.chr_nvram_size = shift_size(header[11] >> 4);
+                cartridge.tv_system = match header[12] & 0x03 {
+                    0 => TvSystem::Ntsc,
+                    1 => TvSystem::Pal,
+                    2 => TvSystem::MultiRegion,
+                    _ => TvSystem::Dendy,
+                };
+                (rom_size(header[4], header[9] & 0x0F, 16 * 1024), rom_size(header[5], header[9] >> 4, 8 * 1024))
+            },
+            RomFormat::iNES => {
+                // Old dumps have garbage (e.g. "DiskDude!") in bytes 7-15, in which case the upper nibble is unusable
+                let upper = if header[12..16].iter().all(|&byte| byte == 0) { header[7] & 0xF0 } else { 0 };
+                cartridge.mapper = ((header[6] >> 4) | upper) as u16;
+
+                // 0 means 8KB, for compatibility
+                let prg_ram_size = header[8].max(1) as usize * 8 * 1024;
+                if battery {
+                    cartridge.prg_nvram_size = prg_ram_size;
+                } else {
+                    cartridge.prg_ram_size = prg_ram_size;
+                }
+                if header[5] == 0 {
+                    cartridge.chr_ram_size = 8 * 1024;
+                }
+                if header[9] & 0x01 != 0 {
+                    cartridge.tv_system = TvSystem::Pal;
+                }
+                (header[4] as usize * 16 * 1024, header[5] as usize * 8 * 1024)
+            },
+        };
+
+        let trainer_size = if has_trainer { TRAINER_SIZE } else { 0 };
+        let expected = (HEADER_SIZE + trainer_size).saturating_add(prg_rom_size).saturating_add(chr_rom_size);
+        if rom.len() < expected {
+            return Err(RomError::Truncated { expected, actual: rom.len() });
+        }
+
+        let prg_start = HEADER_SIZE + trainer_size;
+        let chr_start = prg_start + prg_rom_size;
+        if has_trainer {
+            cartridge.trainer = Some(rom[HEADER_SIZE..prg_start].to_vec());
+        }
+        cartridge.prg_rom = rom[prg_start..chr_start].to_vec();
+        cartridge.chr_rom = rom[chr_start..chr_start + chr_rom_size].to_vec();
+
+        Ok(cartridge)
+    }
+}
+
+/// NES 2.0 ROM size from its LSB and MSB nibble, in units of `unit` bytes or in exponent-multiplier notation
+fn rom_size(lsb: u8, msb: u8, unit: usize) -> usize {
+    if msb == 0x0F {
+        // EEEEEEMM: 2^E * (MM * 2 + 1) bytes
+        let exponent = (lsb >> 2) as u32;
+        let multiplier = (lsb & 0x03) as usize * 2 + 1;
+        1usize.checked_shl(exponent).map_or(usize::MAX, |size| size.saturating_mul(multiplier))
+    } else {
+        ((msb as usize) << 8 | lsb as usize) * unit
+    }
+}
+
+/// NES 2.0 RAM size from its shift count: 64 << shift bytes, or none for 0
+fn shift_size(shift: u8) -> usize {
+    match shift {
+        0 => 0,
+        shift => 64 << shift,
+    }
+}
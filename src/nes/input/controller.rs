@@ -0,0 +1,61 @@
+use crate::nes::input::ControllerState;
+
+/// Standard controller: an 8-bit shift register latched from the buttons while the strobe is high
+///
+/// Buttons are read out A, B, Select, Start, Up, Down, Left, Right; every read after that returns 1.
+#[derive(Debug, Clone, Default)]
+pub struct Controller {
+    state: ControllerState,
+    strobe: bool,
+    shift: u8,
+    // Bits left in the shift register before it returns 1s
+    remaining: u8,
+}
+
+impl Controller {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn state(&self) -> ControllerState {
+        self.state
+    }
+
+    /// Set the buttons held from now on (latched by the next strobe)
+    pub fn set_state(&mut self, state: ControllerState) {
+        self.state = state;
+        if self.strobe {
+            self.latch();
+        }
+    }
+
+    pub fn strobe(&mut self, high: bool) {
+        self.strobe = high;
+        if high {
+            self.latch();
+        }
+    }
+
+    fn latch(&mut self) {
+        self.shift = self.state.bits();
+        self.remaining = 8;
+    }
+
+    pub fn read(&mut self) -> u8 {
+        let bit = self.peek();
+        // While the strobe is high, the register keeps reloading, so A is read over and over
+        if !self.strobe && self.remaining > 0 {
+            self.shift >>= 1;
+            self.remaining -= 1;
+        }
+        bit
+    }
+
+    pub fn peek(&self) -> u8 {
+        if self.remaining == 0 {
+            1
+        } else {
+            self.shift & 0x01
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use crate::nes::input::ControllerState;
+
+/// One port of a Four Score four-player adapter
+///
+/// Each port reads out two controllers (1 and 3 on 0x4016, 2 and 4 on 0x4017) followed by a signature identifying
+/// the port, 24 bits in all; every read after that returns 1.
+#[derive(Debug, Clone)]
+pub struct FourScore {
+    states: [ControllerState; 2],
+    signature: u8,
+    strobe: bool,
+    shift: u32,
+    // Bits left in the shift register before it returns 1s
+    remaining: u8,
+}
+
+impl FourScore {
+    /// Adapter half for `port` (0 for 0x4016, 1 for 0x4017)
+    pub fn new(port: usize) -> Self {
+        // Read LSB first: 0,0,0,1,0,0,0,0 on the first port and 0,0,1,0,0,0,0,0 on the second
+        let signature = if port == 0 { 0x08 } else { 0x04 };
+        FourScore { states: [ControllerState::default(); 2], signature, strobe: false, shift: 0, remaining: 0 }
+    }
+
+    /// Buttons of the first (players 1 or 2) or second (players 3 or 4) controller plugged into this port
+    pub fn state(&self, slot: usize) -> ControllerState {
+        self.states[slot & 0x01]
+    }
+
+    /// Set the buttons held on controller `slot` (0 or 1) from now on
+    pub fn set_state(&mut self, slot: usize, state: ControllerState) {
+        self.states[slot & 0x01] = state;
+        if self.strobe {
+            self.latch();
+        }
+    }
+
+    pub fn strobe(&mut self, high: bool) {
+        self.strobe = high;
+        if high {
+            self.latch();
+        }
+    }
+
+    fn latch(&mut self) {
+        self.shift =
+            self.states[0].bits() as u32 | (self.states[1].bits() as u32) << 8 | (self.signature as u32) << 16;
+        self.remaining = 24;
+    }
+
+    pub fn read(&mut self) -> u8 {
+        let bit = self.peek();
+        if !self.strobe && self.remaining > 0 {
+            self.shift >>= 1;
+            self.remaining -= 1;
+        }
+        bit
+    }
+
+    pub fn peek(&self) -> u8 {
+        if self.remaining == 0 {
+            1
+        } else {
+            (self.shift & 0x01) as u8
+        }
+    }
+}
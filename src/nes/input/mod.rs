@@ -0,0 +1,110 @@
+use crate::nes::ppu::Ppu;
+
+pub mod controller;
+pub mod four_score;
+pub mod zapper;
+
+pub use controller::Controller;
+pub use four_score::FourScore;
+pub use zapper::{Zapper, ZapperState};
+
+/// Buttons of a standard controller, as bits in the order they're read out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Button {
+    A = 0b0000_0001,
+    B = 0b0000_0010,
+    Select = 0b0000_0100,
+    Start = 0b0000_1000,
+    Up = 0b0001_0000,
+    Down = 0b0010_0000,
+    Left = 0b0100_0000,
+    Right = 0b1000_0000,
+}
+
+/// Buttons held on a standard controller, set by the frontend (usually once per frame)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ControllerState {
+    buttons: u8,
+}
+
+impl ControllerState {
+    /// State from a byte of `Button` bits
+    pub fn from_bits(buttons: u8) -> Self {
+        ControllerState { buttons }
+    }
+
+    /// Byte of `Button` bits, A in bit 0
+    pub fn bits(&self) -> u8 {
+        self.buttons
+    }
+
+    pub fn set(&mut self, button: Button, pressed: bool) {
+        if pressed {
+            self.buttons |= button as u8;
+        } else {
+            self.buttons &= !(button as u8);
+        }
+    }
+
+    pub fn press(&mut self, button: Button) {
+        self.set(button, true);
+    }
+
+    pub fn release(&mut self, button: Button) {
+        self.set(button, false);
+    }
+
+    pub fn is_pressed(&self, button: Button) -> bool {
+        self.buttons & button as u8 != 0
+    }
+
+    /// Release all buttons
+    pub fn clear(&mut self) {
+        self.buttons = 0;
+    }
+}
+
+/// What's plugged into a controller port (0x4016 or 0x4017)
+///
+/// Writes to 0x4016 reach the devices on both ports; bit 0 is the strobe that latches controller state.
+/// See https://www.nesdev.org/wiki/Input_devices
+pub enum Device {
+    Disconnected,
+    Controller(Controller),
+    Zapper(Zapper),
+    /// One half of a four-player adapter; connect one to each port (see `FourScore::new`)
+    FourScore(FourScore),
+}
+
+impl Device {
+    /// Read the port's data lines (bits 0-4), shifting out the next bit
+    ///
+    /// The Zapper senses light from the PPU's picture.
+    pub fn read(&mut self, ppu: &Ppu) -> u8 {
+        match self {
+            Device::Disconnected => 0,
+            Device::Controller(controller) => controller.read(),
+            Device::Zapper(zapper) => zapper.read(ppu),
+            Device::FourScore(four_score) => four_score.read(),
+        }
+    }
+
+    /// Read the port's data lines without side effects
+    pub fn peek(&self, ppu: &Ppu) -> u8 {
+        match self {
+            Device::Disconnected => 0,
+            Device::Controller(controller) => controller.peek(),
+            Device::Zapper(zapper) => zapper.read(ppu),
+            Device::FourScore(four_score) => four_score.peek(),
+        }
+    }
+
+    /// Write 0x4016
+    pub fn write(&mut self, data: u8) {
+        match self {
+            Device::Controller(controller) => controller.strobe(data & 0x01 != 0),
+            Device::FourScore(four_score) => four_score.strobe(data & 0x01 != 0),
+            Device::Disconnected | Device::Zapper(_) => {},
+        }
+    }
+}
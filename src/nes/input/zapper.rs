@@ -0,0 +1,60 @@
+use crate::nes::ppu::{Ppu, HEIGHT, WIDTH};
+
+/// Scanlines the Zapper's photodiode keeps sensing light after the beam passed the aimed pixel
+const SENSE_LINES: u16 = 24;
+/// Minimum sum of a pixel's RGB components that counts as light
+const BRIGHTNESS: u16 = 3 * 192;
+
+/// Where the Zapper is pointed and whether its trigger is pulled, set by the frontend
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ZapperState {
+    /// Pixel coordinates on the screen, or `None` when pointed away from it
+    pub aim: Option<(u16, u16)>,
+    pub trigger: bool,
+}
+
+/// Zapper light gun: reports the trigger in bit 4, and light in bit 3 (0 when sensed)
+///
+/// Light is sensed when the aimed pixel is bright and the PPU drew it within the last `SENSE_LINES` scanlines.
+#[derive(Debug, Clone, Default)]
+pub struct Zapper {
+    state: ZapperState,
+}
+
+impl Zapper {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn state(&self) -> ZapperState {
+        self.state
+    }
+
+    pub fn set_state(&mut self, state: ZapperState) {
+        self.state = state;
+    }
+
+    /// Whether the photodiode currently senses light from `ppu`'s picture
+    pub fn senses_light(&self, ppu: &Ppu) -> bool {
+        let Some((x, y)) = self.state.aim else {
+            return false;
+        };
+        if x as usize >= WIDTH || y as usize >= HEIGHT {
+            return false;
+        }
+        let scanline = ppu.scanline();
+        if scanline <= y || scanline > y + SENSE_LINES {
+            return false;
+        }
+
+        let offset = (y as usize * WIDTH + x as usize) * 3;
+        let brightness: u16 = ppu.picture()[offset..offset + 3].iter().map(|&c| c as u16).sum();
+        brightness >= BRIGHTNESS
+    }
+
+    pub fn read(&self, ppu: &Ppu) -> u8 {
+        let light = if self.senses_light(ppu) { 0 } else { 0x08 };
+        let trigger = if self.state.trigger { 0x10 } else { 0 };
+        light | trigger
+    }
+}
@@ -0,0 +1,62 @@
+use crate::{
+    core::state::{StateError, StateReader, StateWriter},
+    nes::{cartridge::{Cartridge, Mirroring}, mapper::{Board, Mapper}},
+};
+
+/// Mapper 7: switchable 32KB PRG bank, single-screen mirroring selected by bit 4, 8KB CHR RAM
+pub struct Axrom {
+    board: Board,
+    // Bits 0-2: PRG bank, bit 4: nametable page
+    register: u8,
+}
+
+impl Axrom {
+    pub fn new(cartridge: Cartridge) -> Self {
+        Axrom { board: Board::new(cartridge), register: 0 }
+    }
+}
+
+impl Mapper for Axrom {
+    fn cpu_peek(&self, addr: u16) -> u8 {
+        match addr {
+            0x8000..=0xFFFF => self.board.prg(0x8000, (self.register & 0x07) as usize, addr),
+            _ => 0,
+        }
+    }
+
+    fn cpu_write(&mut self, addr: u16, data: u8) {
+        if let 0x8000..=0xFFFF = addr {
+            self.register = data;
+        }
+    }
+
+    fn ppu_peek(&self, addr: u16) -> u8 {
+        self.board.chr(0x2000, 0, addr)
+    }
+
+    fn ppu_write(&mut self, addr: u16, data: u8) {
+        self.board.write_chr(0x2000, 0, addr, data);
+    }
+
+    fn mirroring(&self) -> Mirroring {
+        if self.register & 0x10 == 0 {
+            Mirroring::SingleScreenLower
+        } else {
+            Mirroring::SingleScreenUpper
+        }
+    }
+
+    fn reset(&mut self) {
+        self.register = 0;
+    }
+
+    fn save_state(&self, writer: &mut StateWriter) {
+        writer.write_u8(self.register);
+        self.board.save_state(writer);
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader) -> Result<(), StateError> {
+        self.register = reader.read_u8()?;
+        self.board.load_state(reader)
+    }
+}
@@ -0,0 +1,60 @@
+use crate::{
+    core::state::{StateError, StateReader, StateWriter},
+    nes::{cartridge::{Cartridge, Mirroring}, mapper::{Board, Mapper}},
+};
+
+/// Mapper 3: PRG like NROM, switchable 8KB CHR ROM bank
+pub struct Cnrom {
+    board: Board,
+    bank: u8,
+}
+
+impl Cnrom {
+    pub fn new(cartridge: Cartridge) -> Self {
+        Cnrom { board: Board::new(cartridge), bank: 0 }
+    }
+}
+
+impl Mapper for Cnrom {
+    fn cpu_peek(&self, addr: u16) -> u8 {
+        match addr {
+            0x6000..=0x7FFF => self.board.prg_ram(addr),
+            0x8000..=0xFFFF => self.board.prg(0x8000, 0, addr),
+            _ => 0,
+        }
+    }
+
+    fn cpu_write(&mut self, addr: u16, data: u8) {
+        match addr {
+            0x6000..=0x7FFF => self.board.write_prg_ram(addr, data),
+            0x8000..=0xFFFF => self.bank = data,
+            _ => {},
+        }
+    }
+
+    fn ppu_peek(&self, addr: u16) -> u8 {
+        self.board.chr(0x2000, self.bank as usize, addr)
+    }
+
+    fn ppu_write(&mut self, addr: u16, data: u8) {
+        self.board.write_chr(0x2000, self.bank as usize, addr, data);
+    }
+
+    fn mirroring(&self) -> Mirroring {
+        self.board.mirroring
+    }
+
+    fn reset(&mut self) {
+        self.bank = 0;
+    }
+
+    fn save_state(&self, writer: &mut StateWriter) {
+        writer.write_u8(self.bank);
+        self.board.save_state(writer);
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader) -> Result<(), StateError> {
+        self.bank = reader.read_u8()?;
+        self.board.load_state(reader)
+    }
+}
@@ -0,0 +1,138 @@
+use crate::{
+    core::state::{StateError, StateReader, StateWriter},
+    nes::{cartridge::{Cartridge, Mirroring}, mapper::{Board, Mapper}},
+};
+
+/// Mapper 1 (MMC1): registers written one bit at a time through a 5-bit shift register
+///
+/// PRG is switched in 32KB or 16KB banks (with either half fixed), CHR in 8KB or 4KB banks. On 512KB boards (SUROM)
+/// bit 4 of the CHR registers selects the 256KB PRG half.
+pub struct Mmc1 {
+    board: Board,
+    shift: u8,
+    // Writes shifted in so far
+    count: u8,
+    // Bits 0-1: mirroring, bits 2-3: PRG mode, bit 4: CHR mode
+    control: u8,
+    chr0: u8,
+    chr1: u8,
+    // Bits 0-3: PRG bank, bit 4: PRG RAM disable
+    prg: u8,
+}
+
+impl Mmc1 {
+    pub fn new(cartridge: Cartridge) -> Self {
+        Mmc1 { board: Board::new(cartridge), shift: 0, count: 0, control: 0x0C, chr0: 0, chr1: 0, prg: 0 }
+    }
+
+    // Select the 256KB half of a 512KB PRG ROM
+    fn prg_outer(&self) -> usize {
+        if self.board.prg_banks(0x4000) > 16 {
+            (self.chr0 & 0x10) as usize
+        } else {
+            0
+        }
+    }
+
+    fn prg_bank(&self, addr: u16) -> usize {
+        let bank = (self.prg & 0x0F) as usize;
+        let last = (self.board.prg_banks(0x4000) - 1).min(15);
+        let upper = addr >= 0xC000;
+
+        self.prg_outer() + match (self.control >> 2) & 0x03 {
+            // 32KB, ignoring the low bit
+            0 | 1 => (bank & !1) + upper as usize,
+            2 => if upper { bank } else { 0 },
+            _ => if upper { last } else { bank },
+        }
+    }
+
+    fn chr_bank(&self, addr: u16) -> (usize, usize) {
+        match (self.control & 0x10 != 0, addr >= 0x1000) {
+            (false, _) => (0x2000, (self.chr0 >> 1) as usize),
+            (true, false) => (0x1000, self.chr0 as usize),
+            (true, true) => (0x1000, self.chr1 as usize),
+        }
+    }
+}
+
+impl Mapper for Mmc1 {
+    fn cpu_peek(&self, addr: u16) -> u8 {
+        match addr {
+            0x6000..=0x7FFF if self.prg & 0x10 == 0 => self.board.prg_ram(addr),
+            0x8000..=0xFFFF => self.board.prg(0x4000, self.prg_bank(addr), addr),
+            _ => 0,
+        }
+    }
+
+    fn cpu_write(&mut self, addr: u16, data: u8) {
+        match addr {
+            0x6000..=0x7FFF if self.prg & 0x10 == 0 => self.board.write_prg_ram(addr, data),
+            0x8000..=0xFFFF if data & 0x80 != 0 => {
+                self.shift = 0;
+                self.count = 0;
+                self.control |= 0x0C;
+            },
+            0x8000..=0xFFFF => {
+                self.shift |= (data & 0x01) << self.count;
+                self.count += 1;
+                if self.count == 5 {
+                    match addr {
+                        0x8000..=0x9FFF => self.control = self.shift,
+                        0xA000..=0xBFFF => self.chr0 = self.shift,
+                        0xC000..=0xDFFF => self.chr1 = self.shift,
+                        _ => self.prg = self.shift,
+                    }
+                    self.shift = 0;
+                    self.count = 0;
+                }
+            },
+            _ => {},
+        }
+    }
+
+    fn ppu_peek(&self, addr: u16) -> u8 {
+        let (size, bank) = self.chr_bank(addr);
+        self.board.chr(size, bank, addr)
+    }
+
+    fn ppu_write(&mut self, addr: u16, data: u8) {
+        let (size, bank) = self.chr_bank(addr);
+        self.board.write_chr(size, bank, addr, data);
+    }
+
+    fn mirroring(&self) -> Mirroring {
+        match self.control & 0x03 {
+            0 => Mirroring::SingleScreenLower,
+            1 => Mirroring::SingleScreenUpper,
+            2 => Mirroring::Vertical,
+            _ => Mirroring::Horizontal,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.shift = 0;
+        self.count = 0;
+        self.control = 0x0C;
+        self.chr0 = 0;
+        self.chr1 = 0;
+        self.prg = 0;
+    }
+
+    fn save_state(&self, writer: &mut StateWriter) {
+        for register in [self.shift, self.count, self.control, self.chr0, self.chr1, self.prg] {
+            writer.write_u8(register);
+        }
+        self.board.save_state(writer);
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader) -> Result<(), StateError> {
+        self.shift = reader.read_u8()?;
+        self.count = reader.read_u8()?.min(4);
+        self.control = reader.read_u8()?;
+        self.chr0 = reader.read_u8()?;
+        self.chr1 = reader.read_u8()?;
+        self.prg = reader.read_u8()?;
+        self.board.load_state(reader)
+    }
+}
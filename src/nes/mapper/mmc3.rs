@@ -0,0 +1,177 @@
+use crate::{
+    core::state::{StateError, StateReader, StateWriter},
+    nes::{cartridge::{Cartridge, Mirroring}, mapper::{Board, Mapper}},
+};
+
+/// Mapper 4 (MMC3): 8KB PRG banks, 2KB and 1KB CHR banks, and a scanline counter that raises IRQs
+pub struct Mmc3 {
+    board: Board,
+    // Bits 0-2: register written by the next bank data write, bit 6: PRG mode, bit 7: CHR A12 inversion
+    select: u8,
+    // R0-R7
+    registers: [u8; 8],
+    mirroring: Mirroring,
+    // Bit 7: PRG RAM enable, bit 6: PRG RAM write protect
+    prg_ram_protect: u8,
+    irq_latch: u8,
+    irq_counter: u8,
+    irq_reload: bool,
+    irq_enabled: bool,
+    irq_pending: bool,
+}
+
+impl Mmc3 {
+    pub fn new(cartridge: Cartridge) -> Self {
+        let mirroring = cartridge.mirroring;
+        Mmc3 {
+            board: Board::new(cartridge),
+            select: 0,
+            registers: [0, 2, 4, 5, 6, 7, 0, 1],
+            mirroring,
+            prg_ram_protect: 0x80,
+            irq_latch: 0,
+            irq_counter: 0,
+            irq_reload: false,
+            irq_enabled: false,
+            irq_pending: false,
+        }
+    }
+
+    fn prg_bank(&self, addr: u16) -> usize {
+        let second_last = self.board.prg_banks(0x2000).saturating_sub(2);
+        let swapped = self.select & 0x40 != 0;
+        match (addr >> 13) & 0x03 {
+            0 if swapped => second_last,
+            0 => self.registers[6] as usize,
+            1 => self.registers[7] as usize,
+            2 if swapped => self.registers[6] as usize,
+            2 => second_last,
+            _ => second_last + 1,
+        }
+    }
+
+    // CHR bank of 1KB containing `addr`
+    fn chr_bank(&self, addr: u16) -> usize {
+        // Inversion swaps the 2KB half with the 1KB half
+        let addr = if self.select & 0x80 != 0 { addr ^ 0x1000 } else { addr };
+        let slot = (addr >> 10) as usize & 0x07;
+        match slot {
+            0..=3 => (self.registers[slot / 2] & 0xFE) as usize + slot % 2,
+            _ => self.registers[slot - 2] as usize,
+        }
+    }
+}
+
+impl Mapper for Mmc3 {
+    fn cpu_peek(&self, addr: u16) -> u8 {
+        match addr {
+            0x6000..=0x7FFF if self.prg_ram_protect & 0x80 != 0 => self.board.prg_ram(addr),
+            0x8000..=0xFFFF => self.board.prg(0x2000, self.prg_bank(addr), addr),
+            _ => 0,
+        }
+    }
+
+    fn cpu_write(&mut self, addr: u16, data: u8) {
+        let even = addr & 0x01 == 0;
+        match addr {
+            0x6000..=0x7FFF if self.prg_ram_protect & 0xC0 == 0x80 => self.board.write_prg_ram(addr, data),
+            0x8000..=0x9FFF if even => self.select = data,
+            0x8000..=0x9FFF => self.registers[(self.select & 0x07) as usize] = data,
+            0xA000..=0xBFFF if even => {
+                self.mirroring = match (self.mirroring, data & 0x01) {
+                    (Mirroring::FourScreen, _) => Mirroring::FourScreen,
+                    (_, 0) => Mirroring::Vertical,
+                    _ => Mirroring::Horizontal,
+                };
+            },
+            0xA000..=0xBFFF => self.prg_ram_protect = data,
+            0xC000..=0xDFFF if even => self.irq_latch = data,
+            0xC000..=0xDFFF => {
+                self.irq_counter = 0;
+                self.irq_reload = true;
+            },
+            0xE000..=0xFFFF if even => {
+                self.irq_enabled = false;
+                self.irq_pending = false;
+            },
+            0xE000..=0xFFFF => self.irq_enabled = true,
+            _ => {},
+        }
+    }
+
+    fn ppu_peek(&self, addr: u16) -> u8 {
+        self.board.chr(0x0400, self.chr_bank(addr), addr)
+    }
+
+    fn ppu_write(&mut self, addr: u16, data: u8) {
+        self.board.write_chr(0x0400, self.chr_bank(addr), addr, data);
+    }
+
+    fn mirroring(&self) -> Mirroring {
+        self.mirroring
+    }
+
+    fn irq(&self) -> bool {
+        self.irq_pending
+    }
+
+    fn scanline(&mut self) {
+        if self.irq_counter == 0 || self.irq_reload {
+            self.irq_counter = self.irq_latch;
+            self.irq_reload = false;
+        } else {
+            self.irq_counter -= 1;
+        }
+
+        if self.irq_counter == 0 && self.irq_enabled {
+            self.irq_pending = true;
+        }
+    }
+
+    fn reset(&mut self) {
+        self.select = 0;
+        self.registers = [0, 2, 4, 5, 6, 7, 0, 1];
+        self.mirroring = self.board.mirroring;
+        self.prg_ram_protect = 0x80;
+        self.irq_latch = 0;
+        self.irq_counter = 0;
+        self.irq_reload = false;
+        self.irq_enabled = false;
+        self.irq_pending = false;
+    }
+
+    fn save_state(&self, writer: &mut StateWriter) {
+        writer.write_u8(self.select);
+        for register in self.registers {
+            writer.write_u8(register);
+        }
+        writer.write_u8(self.mirroring as u8);
+        writer.write_u8(self.prg_ram_protect);
+        writer.write_u8(self.irq_latch);
+        writer.write_u8(self.irq_counter);
+        writer.write_bool(self.irq_reload);
+        writer.write_bool(self.irq_enabled);
+        writer.write_bool(self.irq_pending);
+        self.board.save_state(writer);
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader) -> Result<(), StateError> {
+        self.select = reader.read_u8()?;
+        for register in &mut self.registers {
+            *register = reader.read_u8()?;
+        }
+        self.mirroring = match reader.read_u8()? {
+            0 => Mirroring::Horizontal,
+            1 => Mirroring::Vertical,
+            2 => Mirroring::FourScreen,
+            _ => return Err(StateError::Mismatch("MMC3 mirroring")),
+        };
+        self.prg_ram_protect = reader.read_u8()?;
+        self.irq_latch = reader.read_u8()?;
+        self.irq_counter = reader.read_u8()?;
+        self.irq_reload = reader.read_bool()?;
+        self.irq_enabled = reader.read_bool()?;
+        self.irq_pending = reader.read_bool()?;
+        self.board.load_state(reader)
+    }
+}
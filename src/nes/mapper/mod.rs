@@ -0,0 +1,164 @@
+use crate::{
+    core::state::{StateError, StateReader, StateWriter},
+    nes::cartridge::{Cartridge, Mirroring, RomError},
+};
+
+pub mod axrom;
+pub mod cnrom;
+pub mod mmc1;
+pub mod mmc3;
+pub mod nrom;
+pub mod uxrom;
+
+pub use axrom::Axrom;
+pub use cnrom::Cnrom;
+pub use mmc1::Mmc1;
+pub use mmc3::Mmc3;
+pub use nrom::Nrom;
+pub use uxrom::Uxrom;
+
+/// Cartridge hardware: maps PRG into the CPU's address space (0x4020-0xFFFF) and CHR into the PPU's (0x0000-0x1FFF)
+///
+/// See https://www.nesdev.org/wiki/Mapper
+pub trait Mapper {
+    /// Read from the CPU's address space
+    fn cpu_read(&mut self, addr: u16) -> u8 {
+        self.cpu_peek(addr)
+    }
+
+    /// Read from the CPU's address space without side effects
+    fn cpu_peek(&self, addr: u16) -> u8;
+
+    /// Write to the CPU's address space (PRG RAM or mapper registers)
+    fn cpu_write(&mut self, addr: u16, data: u8);
+
+    /// Read from pattern memory
+    fn ppu_read(&mut self, addr: u16) -> u8 {
+        self.ppu_peek(addr)
+    }
+
+    /// Read from pattern memory without side effects
+    fn ppu_peek(&self, addr: u16) -> u8;
+
+    /// Write to pattern memory (ignored for CHR ROM)
+    fn ppu_write(&mut self, addr: u16, data: u8);
+
+    /// Current nametable arrangement
+    fn mirroring(&self) -> Mirroring;
+
+    /// Whether the mapper is asserting the CPU's IRQ line
+    fn irq(&self) -> bool {
+        false
+    }
+
+    /// Called by the PPU once per rendered scanline (at dot 260, while rendering is enabled)
+    fn scanline(&mut self) {}
+
+    /// Put the registers back in their power-up state, keeping the contents of cartridge RAM
+    fn reset(&mut self) {}
+
+    /// Save bank registers and cartridge RAM
+    fn save_state(&self, writer: &mut StateWriter);
+
+    /// Restore the state written by `save_state`
+    fn load_state(&mut self, reader: &mut StateReader) -> Result<(), StateError>;
+}
+
+/// Construct the mapper `cartridge` asks for
+pub fn from_cartridge(cartridge: Cartridge) -> Result<Box<dyn Mapper>, RomError> {
+    Ok(match cartridge.mapper {
+        0 => Box::new(Nrom::new(cartridge)),
+        1 => Box::new(Mmc1::new(cartridge)),
+        2 => Box::new(Uxrom::new(cartridge)),
+        3 => Box::new(Cnrom::new(cartridge)),
+        4 => Box::new(Mmc3::new(cartridge)),
+        7 => Box::new(Axrom::new(cartridge)),
+        mapper => return Err(RomError::UnsupportedMapper(mapper)),
+    })
+}
+
+/// Memory on the cartridge board, shared by all mappers: PRG ROM, PRG RAM at 0x6000 and CHR ROM or RAM
+///
+/// Banks are numbered in units of the bank size passed in, and wrap around the size of the memory.
+pub(crate) struct Board {
+    prg_rom: Vec<u8>,
+    prg_ram: Vec<u8>,
+    chr: Vec<u8>,
+    chr_ram: bool,
+    pub(crate) mirroring: Mirroring,
+}
+
+impl Board {
+    pub(crate) fn new(cartridge: Cartridge) -> Self {
+        let chr_ram = cartridge.chr_rom.is_empty();
+        let chr = if chr_ram {
+            vec![0; (cartridge.chr_ram_size + cartridge.chr_nvram_size).max(0x2000)]
+        } else {
+            cartridge.chr_rom
+        };
+
+        Board {
+            prg_rom: cartridge.prg_rom,
+            prg_ram: vec![0; cartridge.prg_ram_size + cartridge.prg_nvram_size],
+            chr,
+            chr_ram,
+            mirroring: cartridge.mirroring,
+        }
+    }
+
+    /// Number of `bank_size` banks of PRG ROM
+    pub(crate) fn prg_banks(&self, bank_size: usize) -> usize {
+        (self.prg_rom.len() / bank_size).max(1)
+    }
+
+    /// Read `addr` within PRG ROM bank `bank` of `bank_size` bytes
+    pub(crate) fn prg(&self, bank_size: usize, bank: usize, addr: u16) -> u8 {
+        match self.prg_rom.len() {
+            0 => 0,
+            len => self.prg_rom[(bank * bank_size + addr as usize % bank_size) % len],
+        }
+    }
+
+    /// Read `addr` within CHR bank `bank` of `bank_size` bytes
+    pub(crate) fn chr(&self, bank_size: usize, bank: usize, addr: u16) -> u8 {
+        self.chr[(bank * bank_size + addr as usize % bank_size) % self.chr.len()]
+    }
+
+    /// Write `addr` within CHR bank `bank` of `bank_size` bytes, if it is RAM
+    pub(crate) fn write_chr(&mut self, bank_size: usize, bank: usize, addr: u16, data: u8) {
+        if self.chr_ram {
+            let len = self.chr.len();
+            self.chr[(bank * bank_size + addr as usize % bank_size) % len] = data;
+        }
+    }
+
+    /// Read PRG RAM, mapped at 0x6000-0x7FFF
+    pub(crate) fn prg_ram(&self, addr: u16) -> u8 {
+        match self.prg_ram.len() {
+            0 => 0,
+            len => self.prg_ram[(addr as usize - 0x6000) % len],
+        }
+    }
+
+    pub(crate) fn write_prg_ram(&mut self, addr: u16, data: u8) {
+        let len = self.prg_ram.len();
+        if len > 0 {
+            self.prg_ram[(addr as usize - 0x6000) % len] = data;
+        }
+    }
+
+    pub(crate) fn save_state(&self, writer: &mut StateWriter) {
+        writer.write_bytes(&self.prg_ram);
+        if self.chr_ram {
+            writer.write_bytes(&self.chr);
+        }
+    }
+
+    pub(crate) fn load_state(&mut self, reader: &mut StateReader) -> Result<(), StateError> {
+        reader.read_bytes_into(&mut self.prg_ram)?;
+        if self.chr_ram {
+            reader.read_bytes_into(&mut self.chr)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,51 @@
+use crate::{
+    core::state::{StateError, StateReader, StateWriter},
+    nes::{cartridge::{Cartridge, Mirroring}, mapper::{Board, Mapper}},
+};
+
+/// Mapper 0: 16KB or 32KB PRG ROM (16KB is mirrored into 0xC000-0xFFFF), 8KB CHR, no bank switching
+pub struct Nrom {
+    board: Board,
+}
+
+impl Nrom {
+    pub fn new(cartridge: Cartridge) -> Self {
+        Nrom { board: Board::new(cartridge) }
+    }
+}
+
+impl Mapper for Nrom {
+    fn cpu_peek(&self, addr: u16) -> u8 {
+        match addr {
+            0x6000..=0x7FFF => self.board.prg_ram(addr),
+            0x8000..=0xFFFF => self.board.prg(0x8000, 0, addr),
+            _ => 0,
+        }
+    }
+
+    fn cpu_write(&mut self, addr: u16, data: u8) {
+        if let 0x6000..=0x7FFF = addr {
+            self.board.write_prg_ram(addr, data);
+        }
+    }
+
+    fn ppu_peek(&self, addr: u16) -> u8 {
+        self.board.chr(0x2000, 0, addr)
+    }
+
+    fn ppu_write(&mut self, addr: u16, data: u8) {
+        self.board.write_chr(0x2000, 0, addr, data);
+    }
+
+    fn mirroring(&self) -> Mirroring {
+        self.board.mirroring
+    }
+
+    fn save_state(&self, writer: &mut StateWriter) {
+        self.board.save_state(writer);
+    }
+
+    fn load_state(&mut self, reader: &mut StateReader) -> Result<(), StateError> {
+        self.board.load_state(reader)
+    }
+}
@@ -1,75 +1,152 @@
-use std::path::PathBuf;
-use crate::{core::cpu::CPU, io};
+use std::path::{Path, PathBuf};
+use crate::core::cpu::{CpuError, CpuVariant, StepOutcome, CPU};
 
-const HEADER_SIZE: usize = 16;
+pub mod apu;
+pub mod bus;
+pub mod cartridge;
+pub mod input;
+pub mod mapper;
+pub mod nestest;
+pub mod ppu;
 
-#[allow(non_camel_case_types)]
-enum ROMFormat {
-    NES2,
-    iNES,
-    INVALID,
+use bus::NesBus;
+use cartridge::{Cartridge, RomError};
+use input::{ControllerState, Device, ZapperState};
+
+/// IRQ source (see `CPU::set_irq_source`) driven by the cartridge's mapper
+pub const MAPPER_IRQ: u8 = 1;
+/// IRQ source driven by the APU's frame counter and DMC
+pub const APU_IRQ: u8 = 2;
+
+/// A NES: a 2A03 CPU connected to a `NesBus` with the PPU and the cartridge's mapper
+///
+/// The PPU catches up with the CPU after every instruction, so register accesses see it as it was when the
+/// instruction started.
+pub struct Nes {
+    cpu: CPU<NesBus>,
 }
 
-pub fn load_nes_rom(cpu: &mut CPU, path: &PathBuf) -> Result<(), std::io::Error> {
-    // Load ROM from file
-    // TODO: get file as CLI argument
-    let rom: Vec<u8> = io::load_rom(path)?;
-    let header: &[u8] = &rom[0..HEADER_SIZE];
-    let mut format: ROMFormat = ROMFormat::INVALID;
-
-    // Check ROM format
-    if header[0..4] == [0x4E, 0x45, 0x53, 0x1A] {
-        if header[7] & 0x0C == 0x08 {
-            println!("NES2.0");
-            format = ROMFormat::NES2;
-        } else {
-            println!("iNES");
-            format = ROMFormat::iNES;
+impl Nes {
+    /// Insert `cartridge`, failing if its mapper isn't emulated
+    pub fn new(cartridge: Cartridge) -> Result<Self, RomError> {
+        let mapper = mapper::from_cartridge(cartridge)?;
+        Ok(Nes { cpu: CPU::with_variant(NesBus::new(mapper), CpuVariant::Ricoh2A03) })
+    }
+
+    /// Load the ROM file at `path`
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, RomError> {
+        Self::new(Cartridge::load(path)?)
+    }
+
+    pub fn power_on(&mut self) {
+        self.cpu.power_on();
+    }
+
+    /// Press the reset button: reset the PPU, APU and mapper registers, then the CPU from the reset vector
+    pub fn reset(&mut self) {
+        self.cpu.bus_mut().reset();
+        self.cpu.set_irq_source(MAPPER_IRQ, false);
+        self.cpu.set_irq_source(APU_IRQ, false);
+        self.cpu.reset();
+    }
+
+    /// Execute one instruction (or interrupt), run the PPU and APU for as long, then update the NMI and IRQ lines
+    pub fn step(&mut self) -> Result<StepOutcome, CpuError> {
+        let start = self.cpu.cycles();
+        let outcome = self.cpu.step();
+
+        let cycles = self.cpu.cycles();
+        let stall = self.cpu.bus_mut().take_stall(cycles);
+        self.cpu.stall(stall);
+
+        let elapsed = self.cpu.cycles() - start;
+        let bus = self.cpu.bus_mut();
+        bus.tick(elapsed);
+        if bus.ppu_mut().take_nmi() {
+            self.cpu.trigger_nmi();
         }
+        let (mapper_irq, apu_irq) = (self.cpu.bus().mapper().irq(), self.cpu.bus().apu().irq());
+        self.cpu.set_irq_source(MAPPER_IRQ, mapper_irq);
+        self.cpu.set_irq_source(APU_IRQ, apu_irq);
+        outcome
     }
 
-    match format {
-        ROMFormat::NES2 => {},
-        ROMFormat::iNES => {
-            // Check if trainer area is present
-            let mut trainer_size: usize = 0;
-            if header[6] & 0b10 == 0b10 {
-                trainer_size = 512;
+    /// Run until the PPU finishes a frame (enters vblank)
+    pub fn run_frame(&mut self) -> Result<(), CpuError> {
+        loop {
+            self.step()?;
+            if self.cpu.bus_mut().ppu_mut().take_frame() {
+                return Ok(());
             }
+        }
+    }
+
+    /// The last frame completed by the PPU, 256x240 pixels of RGB (see `Ppu::framebuffer`)
+    pub fn framebuffer(&self) -> &[u8] {
+        self.cpu.bus().ppu().framebuffer()
+    }
 
-            // Get PRG and CHR rom sizes
-            let prg_rom_size: usize = (header[4] as usize + (header[9] as usize & 0b1111 << 7)) * 16384;
-            let chr_rom_size: usize = (header[5] as usize + (header[9] as usize & 0b1111_0000 << 7)) * 8192;
+    /// Plug `device` into controller port `port` (0 or 1), returning the device that was there
+    pub fn connect(&mut self, port: usize, device: Device) -> Device {
+        self.cpu.bus_mut().connect(port, device)
+    }
 
-            let trainer: Vec<_> = rom.iter()
-                .skip(HEADER_SIZE)
-                .take(trainer_size)
-                .collect();
+    /// Set the buttons held by `player` (0-3), returning whether a controller for them is connected
+    ///
+    /// Players 1 and 2 use standard controllers or a four-player adapter; players 3 and 4 need the adapter.
+    pub fn set_controller(&mut self, player: usize, state: ControllerState) -> bool {
+        match (self.cpu.bus_mut().port_mut(player % 2), player / 2) {
+            (Device::Controller(controller), 0) => controller.set_state(state),
+            (Device::FourScore(four_score), slot @ 0..=1) => four_score.set_state(slot, state),
+            _ => return false,
+        }
+        true
+    }
 
-            for i in 0..trainer_size {
-                cpu.write(0x7000 + i as u16, *trainer[i]);
+    /// Point and fire the Zapper, in whichever port it's connected, returning whether there is one
+    pub fn set_zapper(&mut self, state: ZapperState) -> bool {
+        for port in 0..2 {
+            if let Device::Zapper(zapper) = self.cpu.bus_mut().port_mut(port) {
+                zapper.set_state(state);
+                return true;
             }
+        }
+        false
+    }
 
-            let prg_rom: Vec<_> = rom.iter()
-                .skip(HEADER_SIZE + trainer_size)
-                .take(prg_rom_size)
-                .collect();
+    /// Take the audio samples produced since the last call (see `Apu::set_sample_rate`)
+    pub fn drain_samples(&mut self) -> Vec<f32> {
+        self.cpu.bus_mut().apu_mut().drain_samples()
+    }
 
-            for i in 0..prg_rom_size {
-                cpu.write(0x8000 + i as u16, *prg_rom[i]);
-            }
+    pub fn cpu(&self) -> &CPU<NesBus> {
+        &self.cpu
+    }
 
-            let chr_rom: Vec<_> = rom.iter()
-                .skip(HEADER_SIZE + trainer_size + chr_rom_size)
-                .take(chr_rom_size)
-                .collect();
+    pub fn cpu_mut(&mut self) -> &mut CPU<NesBus> {
+        &mut self.cpu
+    }
+}
 
-            for i in 0..chr_rom_size {
-                cpu.write(i as u16, *chr_rom[i]);
-            }
-        },
-        _ => {}
+/// Load the NROM cartridge at `path` into CPU memory
+///
+/// The trainer goes to 0x7000 and PRG ROM to 0x8000, with 16KB PRG ROM mirrored into 0xC000-0xFFFF (NROM-128).
+/// CHR ROM belongs to the PPU's address space, so it isn't loaded into CPU memory.
+pub fn load_nes_rom(cpu: &mut CPU, path: &PathBuf) -> Result<Cartridge, RomError> {
+    let cartridge = Cartridge::load(path)?;
+    if cartridge.mapper != 0 || cartridge.prg_rom.len() > 0x8000 {
+        return Err(RomError::UnsupportedMapper(cartridge.mapper));
+    }
+
+    if let Some(trainer) = &cartridge.trainer {
+        for (addr, byte) in (0x7000..).zip(trainer) {
+            cpu.write(addr, *byte);
+        }
+    }
+
+    for (addr, byte) in (0x8000..=0xFFFF).zip(cartridge.prg_rom.iter().cycle()) {
+        cpu.write(addr, *byte);
     }
 
-    Ok(())
+    Ok(cartridge)
 }
@@ -0,0 +1,198 @@
+use std::{
+    fmt,
+    io,
+    path::PathBuf,
+};
+
+use crate::{
+    core::{
+        cpu::{AddressingMode, CPU, CpuError},
+        memory::Memory,
+        opcodes::{self, Mnemonic},
+    },
+    disasm::Instruction,
+    nes,
+};
+
+/// Where nestest starts in automation mode (running every test without a PPU)
+pub const AUTOMATION_START: u16 = 0xC000;
+
+/// Lines of our trace shown before a divergence
+const CONTEXT: usize = 5;
+
+/// Format the instruction at PC and the registers in Nintendulator's log format, as used by `nestest.log`
+///
+/// The PPU column is left out, as there is no PPU to report on. Operand annotations (`= 00`, `@ 0300`) are read
+/// without side effects, from memory as it is before the instruction executes.
+pub fn trace_line<M: Memory>(cpu: &CPU<M>) -> String {
+    let memory = cpu.bus();
+    let instruction = Instruction::decode_at(memory, cpu.get_pc(), cpu.config().variant);
+    let illegal = opcodes::table(cpu.config().variant)[instruction.opcode as usize].illegal;
+
+    let bytes = instruction.bytes().iter().map(|byte| format!("{:02X}", byte)).collect::<Vec<_>>().join(" ");
+    let name = match instruction.mnemonic {
+        Mnemonic::ISC => "ISB".to_string(),
+        _ => instruction.name(),
+    };
+
+    format!(
+        "{:04X}  {:<8} {}{:<32}A:{:02X} X:{:02X} Y:{:02X} P:{:02X} SP:{:02X} CYC:{}",
+        instruction.addr,
+        bytes,
+        if illegal { '*' } else { ' ' },
+        format!("{} {}", name, operand(memory, &instruction, cpu.get_x(), cpu.get_y())).trim_end(),
+        cpu.get_a(),
+        cpu.get_x(),
+        cpu.get_y(),
+        cpu.get_sr(),
+        cpu.get_sp(),
+        cpu.cycles(),
+    )
+}
+
+/// Format an operand the way Nintendulator does, with the effective address and the value found there
+fn operand<M: Memory>(memory: &M, instruction: &Instruction, x: u8, y: u8) -> String {
+    let operand = instruction.operand;
+    let zp = operand as u8;
+    let peek_u16_zp = |addr: u8| u16::from_le_bytes([memory.peek(addr as u16), memory.peek(addr.wrapping_add(1) as u16)]);
+
+    match instruction.mode {
+        AddressingMode::IMP => String::new(),
+        AddressingMode::ACC => "A".to_string(),
+        AddressingMode::IMM => format!("#${:02X}", operand),
+        AddressingMode::ZP0 => format!("${:02X} = {:02X}", zp, memory.peek(zp as u16)),
+        AddressingMode::ZPX => {
+            let addr = zp.wrapping_add(x);
+            format!("${:02X},X @ {:02X} = {:02X}", zp, addr, memory.peek(addr as u16))
+        },
+        AddressingMode::ZPY => {
+            let addr = zp.wrapping_add(y);
+            format!("${:02X},Y @ {:02X} = {:02X}", zp, addr, memory.peek(addr as u16))
+        },
+        AddressingMode::ABS => match instruction.mnemonic {
+            Mnemonic::JMP | Mnemonic::JSR => format!("${:04X}", operand),
+            _ => format!("${:04X} = {:02X}", operand, memory.peek(operand)),
+        },
+        AddressingMode::ABX => {
+            let addr = operand.wrapping_add(x as u16);
+            format!("${:04X},X @ {:04X} = {:02X}", operand, addr, memory.peek(addr))
+        },
+        AddressingMode::ABY => {
+            let addr = operand.wrapping_add(y as u16);
+            format!("${:04X},Y @ {:04X} = {:02X}", operand, addr, memory.peek(addr))
+        },
+        AddressingMode::IND => {
+            // The NMOS page wrap bug: the high byte comes from the start of the same page
+            let hi = (operand & 0xFF00) | (operand.wrapping_add(1) & 0x00FF);
+            let target = u16::from_le_bytes([memory.peek(operand), memory.peek(hi)]);
+            format!("(${:04X}) = {:04X}", operand, target)
+        },
+        AddressingMode::IDX => {
+            let pointer = zp.wrapping_add(x);
+            let addr = peek_u16_zp(pointer);
+            format!("(${:02X},X) @ {:02X} = {:04X} = {:02X}", zp, pointer, addr, memory.peek(addr))
+        },
+        AddressingMode::IDY => {
+            let base = peek_u16_zp(zp);
+            let addr = base.wrapping_add(y as u16);
+            format!("(${:02X}),Y = {:04X} @ {:04X} = {:02X}", zp, base, addr, memory.peek(addr))
+        },
+        // Not on the 2A03
+        _ => instruction.format(None).split_once(' ').map_or(String::new(), |(_, operand)| operand.to_string()),
+    }
+}
+
+/// Remove the PPU column from a Nintendulator log line, so it can be compared to `trace_line`
+pub fn without_ppu(line: &str) -> String {
+    let line = line.trim_end();
+    match (line.find(" PPU:"), line.find(" CYC:")) {
+        (Some(ppu), Some(cyc)) if ppu < cyc => format!("{}{}", &line[..ppu], &line[cyc..]),
+        _ => line.to_string(),
+    }
+}
+
+/// First line where the trace differs from the golden log
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    /// Line number in the log (1-based)
+    pub line: usize,
+    pub expected: String,
+    pub actual: String,
+    /// Lines leading up to the divergence (which matched)
+    pub context: Vec<String>,
+}
+
+/// Result of comparing a run against a golden log
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NestestOutcome {
+    /// Every line of the log matched
+    Matched { lines: usize },
+    Diverged(Divergence),
+    /// The CPU reported an error after `line`
+    Error { line: usize, error: CpuError },
+}
+
+impl NestestOutcome {
+    pub fn passed(&self) -> bool {
+        matches!(self, NestestOutcome::Matched { .. })
+    }
+}
+
+impl fmt::Display for NestestOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NestestOutcome::Matched { lines } => write!(f, "all {} lines match", lines),
+            NestestOutcome::Diverged(divergence) => {
+                writeln!(f, "diverged at line {}:", divergence.line)?;
+                for line in &divergence.context {
+                    writeln!(f, "           {}", line)?;
+                }
+                writeln!(f, "expected:  {}", divergence.expected)?;
+                write!(f, "actual:    {}", divergence.actual)
+            },
+            NestestOutcome::Error { line, error } => write!(f, "error after line {}: {}", line, error),
+        }
+    }
+}
+
+/// Load `nestest.nes`, power on and jump to the automation entry point
+pub fn load(path: &PathBuf) -> io::Result<CPU> {
+    let mut cpu: CPU = CPU::new(crate::core::bus::Bus::new());
+    nes::load_nes_rom(&mut cpu, path)?;
+    cpu.power_on();
+    cpu.set_pc(AUTOMATION_START);
+    Ok(cpu)
+}
+
+/// Run `cpu` one instruction per line of `log`, stopping at the first line that differs
+pub fn compare<M: Memory>(cpu: &mut CPU<M>, log: &str) -> NestestOutcome {
+    let mut context = Vec::new();
+    let mut lines = 0;
+
+    for (i, expected) in log.lines().filter(|line| !line.trim().is_empty()).enumerate() {
+        let expected = without_ppu(expected);
+        let actual = trace_line(cpu);
+        if actual != expected {
+            return NestestOutcome::Diverged(Divergence { line: i + 1, expected, actual, context });
+        }
+
+        if let Err(error) = cpu.step() {
+            return NestestOutcome::Error { line: i + 1, error };
+        }
+
+        if context.len() == CONTEXT {
+            context.remove(0);
+        }
+        context.push(actual);
+        lines = i + 1;
+    }
+
+    NestestOutcome::Matched { lines }
+}
+
+/// Run `nestest.nes` at `rom` against the golden log at `log`
+pub fn run(rom: &PathBuf, log: &PathBuf) -> io::Result<NestestOutcome> {
+    let mut cpu = load(rom)?;
+    let log = std::fs::read_to_string(log)?;
+    Ok(compare(&mut cpu, &log))
+}
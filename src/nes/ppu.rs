@@ -0,0 +1,628 @@
+use crate::{
+    core::state::{StateError, StateReader, StateWriter},
+    nes::{cartridge::Mirroring, mapper::Mapper},
+};
+
+pub const WIDTH: usize = 256;
+pub const HEIGHT: usize = 240;
+
+/// Dots per scanline, and scanlines per frame (NTSC)
+const DOTS: u16 = 341;
+const SCANLINES: u16 = 262;
+const PRE_RENDER: u16 = 261;
+const VBLANK: u16 = 241;
+
+/// 2C02 palette as RGB
+#[rustfmt::skip]
+const PALETTE: [[u8; 3]; 64] = [
+    [84, 84, 84], [0, 30, 116], [8, 16, 144], [48, 0, 136], [68, 0, 100], [92, 0, 48], [84, 4, 0], [60, 24, 0],
+    [32, 42, 0], [8, 58, 0], [0, 64, 0], [0, 60, 0], [0, 50, 60], [0, 0, 0], [0, 0, 0], [0, 0, 0],
+    [152, 150, 152], [8, 76, 196], [48, 50, 236], [92, 30, 228], [136, 20, 176], [160, 20, 100], [152, 34, 32], [120, 60, 0],
+    [84, 90, 0], [40, 114, 0], [8, 124, 0], [0, 118, 40], [0, 102, 120], [0, 0, 0], [0, 0, 0], [0, 0, 0],
+    [236, 238, 236], [76, 154, 236], [120, 124, 236], [176, 98, 236], [228, 84, 236], [236, 88, 180], [236, 106, 100], [212, 136, 32],
+    [160, 170, 0], [116, 196, 0], [76, 208, 32], [56, 204, 108], [56, 180, 204], [60, 60, 60], [0, 0, 0], [0, 0, 0],
+    [236, 238, 236], [168, 204, 236], [188, 188, 236], [212, 178, 236], [236, 174, 236], [236, 174, 212], [236, 180, 176], [228, 196, 144],
+    [204, 210, 120], [180, 222, 120], [168, 226, 144], [152, 226, 180], [160, 214, 228], [160, 162, 160], [0, 0, 0], [0, 0, 0],
+];
+
+// PPUCTRL
+const CTRL_INCREMENT: u8 = 0x04;
+const CTRL_SPRITE_TABLE: u8 = 0x08;
+const CTRL_BACKGROUND_TABLE: u8 = 0x10;
+const CTRL_SPRITE_16: u8 = 0x20;
+const CTRL_NMI: u8 = 0x80;
+
+// PPUMASK
+const MASK_GRAYSCALE: u8 = 0x01;
+const MASK_BACKGROUND_LEFT: u8 = 0x02;
+const MASK_SPRITES_LEFT: u8 = 0x04;
+const MASK_BACKGROUND: u8 = 0x08;
+const MASK_SPRITES: u8 = 0x10;
+
+// PPUSTATUS
+const STATUS_OVERFLOW: u8 = 0x20;
+const STATUS_SPRITE_ZERO: u8 = 0x40;
+const STATUS_VBLANK: u8 = 0x80;
+
+/// A sprite fetched for the current scanline
+#[derive(Debug, Clone, Copy, Default)]
+struct Sprite {
+    x: u8,
+    attributes: u8,
+    // Pattern bits, already flipped horizontally, leftmost pixel in bit 7
+    low: u8,
+    high: u8,
+    zero: bool,
+}
+
+/// The 2C02 picture processing unit
+///
+/// Rendering follows the loopy scroll model (`v`, `t`, `x`, `w`) one dot at a time: the background is fetched into
+/// shift registers every 8 dots, and sprites are evaluated and fetched for the next scanline at dot 257. Sprite
+/// overflow is set whenever more than 8 sprites share a scanline, without the hardware's evaluation bug.
+/// See https://www.nesdev.org/wiki/PPU
+pub struct Ppu {
+    ctrl: u8,
+    mask: u8,
+    status: u8,
+    oam_addr: u8,
+    oam: [u8; 256],
+
+    v: u16,  // current VRAM address
+    t: u16,  // temporary VRAM address (top left of the screen)
+    x: u8,   // fine X scroll
+    w: bool, // first or second write toggle of PPUSCROLL and PPUADDR
+
+    read_buffer: u8, // PPUDATA read buffer
+    latch: u8,       // last value written to any register, read back from write-only ones
+
+    vram: [u8; 0x1000], // nametables (2KB on the console, 4KB with four-screen cartridges)
+    palette: [u8; 32],
+
+    scanline: u16,
+    dot: u16,
+    frame: u64,
+    odd_frame: bool,
+    nmi_output: bool, // whether the NMI line is asserted
+    nmi_edge: bool,   // rising edge of the NMI line not yet taken by the CPU
+
+    // Background fetches and shift registers
+    next_tile: u8,
+    next_attribute: u8,
+    next_low: u8,
+    next_high: u8,
+    pattern_low: u16,
+    pattern_high: u16,
+    attribute_low: u16,
+    attribute_high: u16,
+
+    sprites: Vec<Sprite>,
+
+    picture: Vec<u8>,     // pixels as they're drawn
+    framebuffer: Vec<u8>, // copy of `picture` taken when the last frame was completed
+    frame_complete: bool,
+}
+
+impl Default for Ppu {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Ppu {
+    pub fn new() -> Self {
+        Ppu {
+            ctrl: 0,
+            mask: 0,
+            status: 0,
+            oam_addr: 0,
+            oam: [0; 256],
+            v: 0,
+            t: 0,
+            x: 0,
+            w: false,
+            read_buffer: 0,
+            latch: 0,
+            vram: [0; 0x1000],
+            palette: [0; 32],
+            scanline: 0,
+            dot: 0,
+            frame: 0,
+            odd_frame: false,
+            nmi_output: false,
+            nmi_edge: false,
+            next_tile: 0,
+            next_attribute: 0,
+            next_low: 0,
+            next_high: 0,
+            pattern_low: 0,
+            pattern_high: 0,
+            attribute_low: 0,
+            attribute_high: 0,
+            sprites: Vec::with_capacity(8),
+            picture: vec![0; WIDTH * HEIGHT * 3],
+            framebuffer: vec![0; WIDTH * HEIGHT * 3],
+            frame_complete: false,
+        }
+    }
+
+    /// The last completed frame, 256x240 pixels of RGB
+    ///
+    /// It's only updated when the next frame is completed (at the start of vblank), so it never mixes two frames.
+    pub fn framebuffer(&self) -> &[u8] {
+        &self.framebuffer
+    }
+
+    /// The picture as it's being drawn, in the same format as `framebuffer`
+    ///
+    /// Pixels above the current scanline are from the frame in progress, the others from the previous one.
+    pub fn picture(&self) -> &[u8] {
+        &self.picture
+    }
+
+    /// Clear PPUCTRL, PPUMASK, the scroll and address latches and the read buffer, as the reset button does
+    ///
+    /// Nametables, palette, OAM and the beam position are kept.
+    pub fn reset(&mut self) {
+        self.ctrl = 0;
+        self.mask = 0;
+        self.t = 0;
+        self.x = 0;
+        self.w = false;
+        self.read_buffer = 0;
+        self.odd_frame = false;
+        self.nmi_output = false;
+        self.nmi_edge = false;
+    }
+
+    /// Return whether a frame was completed since the last call
+    pub fn take_frame(&mut self) -> bool {
+        std::mem::take(&mut self.frame_complete)
+    }
+
+    /// Return whether the NMI line was asserted since the last call
+    pub fn take_nmi(&mut self) -> bool {
+        std::mem::take(&mut self.nmi_edge)
+    }
+
+    pub fn scanline(&self) -> u16 {
+        self.scanline
+    }
+
+    pub fn dot(&self) -> u16 {
+        self.dot
+    }
+
+    /// Number of frames started since power on
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    pub fn oam(&self) -> &[u8; 256] {
+        &self.oam
+    }
+
+    /// Write a byte to OAM at OAMADDR, as OAMDMA does
+    pub fn write_oam(&mut self, data: u8) {
+        self.oam[self.oam_addr as usize] = data;
+        self.oam_addr = self.oam_addr.wrapping_add(1);
+    }
+
+    /// Read register `0x2000 + (addr & 7)` from the CPU
+    pub fn read_register(&mut self, addr: u16, mapper: &mut dyn Mapper) -> u8 {
+        let data = match addr & 0x07 {
+            0x02 => {
+                let data = (self.status & 0xE0) | (self.latch & 0x1F);
+                self.status &= !STATUS_VBLANK;
+                self.w = false;
+                self.update_nmi();
+                data
+            },
+            0x04 => self.oam[self.oam_addr as usize],
+            0x07 => {
+                let addr = self.v & 0x3FFF;
+                let data = if addr >= 0x3F00 {
+                    // Palette reads aren't buffered, but the buffer is filled from the nametable underneath
+                    self.read_buffer = self.read(addr - 0x1000, mapper);
+                    (self.read(addr, mapper) & 0x3F) | (self.latch & 0xC0)
+                } else {
+                    let data = self.read(addr, mapper);
+                    std::mem::replace(&mut self.read_buffer, data)
+                };
+                self.increment_v();
+                data
+            },
+            _ => self.latch,
+        };
+        self.latch = data;
+        data
+    }
+
+    /// Read register `0x2000 + (addr & 7)` without side effects
+    pub fn peek_register(&self, addr: u16, mapper: &dyn Mapper) -> u8 {
+        match addr & 0x07 {
+            0x02 => (self.status & 0xE0) | (self.latch & 0x1F),
+            0x04 => self.oam[self.oam_addr as usize],
+            0x07 if self.v & 0x3FFF >= 0x3F00 => self.peek(self.v & 0x3FFF, mapper),
+            0x07 => self.read_buffer,
+            _ => self.latch,
+        }
+    }
+
+    /// Write register `0x2000 + (addr & 7)` from the CPU
+    pub fn write_register(&mut self, addr: u16, data: u8, mapper: &mut dyn Mapper) {
+        self.latch = data;
+        match addr & 0x07 {
+            0x00 => {
+                self.ctrl = data;
+                self.t = (self.t & 0xF3FF) | ((data as u16 & 0x03) << 10);
+                self.update_nmi();
+            },
+            0x01 => self.mask = data,
+            0x03 => self.oam_addr = data,
+            0x04 => self.write_oam(data),
+            0x05 => {
+                if self.w {
+                    self.t = (self.t & 0x8C1F) | ((data as u16 & 0x07) << 12) | ((data as u16 & 0xF8) << 2);
+                } else {
+                    self.t = (self.t & 0xFFE0) | (data as u16 >> 3);
+                    self.x = data & 0x07;
+                }
+                self.w = !self.w;
+            },
+            0x06 => {
+                if self.w {
+                    self.t = (self.t & 0xFF00) | data as u16;
+                    self.v = self.t;
+                } else {
+                    self.t = (self.t & 0x00FF) | ((data as u16 & 0x3F) << 8);
+                }
+                self.w = !self.w;
+            },
+            0x07 => {
+                self.write(self.v & 0x3FFF, data, mapper);
+                self.increment_v();
+            },
+            _ => {},
+        }
+    }
+
+    /// Advance one dot
+    pub fn tick(&mut self, mapper: &mut dyn Mapper) {
+        let rendering = self.mask & (MASK_BACKGROUND | MASK_SPRITES) != 0;
+        let visible = self.scanline < HEIGHT as u16;
+
+        if visible || self.scanline == PRE_RENDER {
+            if self.scanline == PRE_RENDER && self.dot == 1 {
+                self.status &= !(STATUS_VBLANK | STATUS_SPRITE_ZERO | STATUS_OVERFLOW);
+                self.sprites.clear();
+                self.update_nmi();
+            }
+
+            if rendering {
+                self.fetch_background(mapper);
+            }
+
+            if visible && (1..=256).contains(&self.dot) {
+                self.render_pixel(mapper);
+            }
+
+            if rendering {
+                if self.dot == 257 {
+                    self.v = (self.v & !0x041F) | (self.t & 0x041F);
+                    if visible {
+                        self.evaluate_sprites(mapper);
+                    } else {
+                        self.sprites.clear();
+                    }
+                }
+                if self.scanline == PRE_RENDER && (280..=304).contains(&self.dot) {
+                    self.v = (self.v & !0x7BE0) | (self.t & 0x7BE0);
+                }
+                if self.dot == 260 {
+                    mapper.scanline();
+                }
+            }
+        }
+
+        if self.scanline == VBLANK && self.dot == 1 {
+            self.status |= STATUS_VBLANK;
+            self.framebuffer.copy_from_slice(&self.picture);
+            self.frame_complete = true;
+            self.update_nmi();
+        }
+
+        // The pre-render line is one dot shorter on odd frames while rendering
+        let last_dot = if self.scanline == PRE_RENDER && self.odd_frame && rendering { DOTS - 2 } else { DOTS - 1 };
+        if self.dot >= last_dot {
+            self.dot = 0;
+            self.scanline += 1;
+            if self.scanline == SCANLINES {
+                self.scanline = 0;
+                self.frame += 1;
+                self.odd_frame = !self.odd_frame;
+            }
+        } else {
+            self.dot += 1;
+        }
+    }
+
+    // Update the NMI line, latching a rising edge for the CPU
+    fn update_nmi(&mut self) {
+        let output = self.status & STATUS_VBLANK != 0 && self.ctrl & CTRL_NMI != 0;
+        if output && !self.nmi_output {
+            self.nmi_edge = true;
+        }
+        self.nmi_output = output;
+    }
+
+    fn increment_v(&mut self) {
+        let step = if self.ctrl & CTRL_INCREMENT != 0 { 32 } else { 1 };
+        self.v = self.v.wrapping_add(step) & 0x7FFF;
+    }
+
+    fn increment_x(&mut self) {
+        if self.v & 0x001F == 31 {
+            self.v &= !0x001F;
+            self.v ^= 0x0400;
+        } else {
+            self.v += 1;
+        }
+    }
+
+    fn increment_y(&mut self) {
+        if self.v & 0x7000 != 0x7000 {
+            self.v += 0x1000;
+            return;
+        }
+
+        self.v &= !0x7000;
+        let mut y = (self.v & 0x03E0) >> 5;
+        if y == 29 {
+            y = 0;
+            self.v ^= 0x0800;
+        } else if y == 31 {
+            y = 0;
+        } else {
+            y += 1;
+        }
+        self.v = (self.v & !0x03E0) | (y << 5);
+    }
+
+    // Fetch background tiles into the shift registers (dots 1-256 and 321-336)
+    fn fetch_background(&mut self, mapper: &mut dyn Mapper) {
+        let dot = self.dot;
+        let shifting = (2..=257).contains(&dot) || (322..=337).contains(&dot);
+        if !shifting && dot != 1 && dot != 321 {
+            return;
+        }
+
+        if shifting {
+            self.pattern_low <<= 1;
+            self.pattern_high <<= 1;
+            self.attribute_low <<= 1;
+            self.attribute_high <<= 1;
+        }
+        if dot == 257 {
+            self.load_shifters();
+            return;
+        }
+
+        match (dot - 1) % 8 {
+            0 => {
+                self.load_shifters();
+                self.next_tile = self.read(0x2000 | (self.v & 0x0FFF), mapper);
+            },
+            2 => {
+                let addr = 0x23C0 | (self.v & 0x0C00) | ((self.v >> 4) & 0x38) | ((self.v >> 2) & 0x07);
+                let mut attribute = self.read(addr, mapper);
+                if self.v & 0x0040 != 0 {
+                    attribute >>= 4;
+                }
+                if self.v & 0x0002 != 0 {
+                    attribute >>= 2;
+                }
+                self.next_attribute = attribute & 0x03;
+            },
+            4 => self.next_low = self.read(self.background_pattern(), mapper),
+            6 => self.next_high = self.read(self.background_pattern() + 8, mapper),
+            7 => {
+                self.increment_x();
+                if dot == 256 {
+                    self.increment_y();
+                }
+            },
+            _ => {},
+        }
+    }
+
+    fn background_pattern(&self) -> u16 {
+        let table = if self.ctrl & CTRL_BACKGROUND_TABLE != 0 { 0x1000 } else { 0 };
+        table + self.next_tile as u16 * 16 + ((self.v >> 12) & 0x07)
+    }
+
+    fn load_shifters(&mut self) {
+        self.pattern_low = (self.pattern_low & 0xFF00) | self.next_low as u16;
+        self.pattern_high = (self.pattern_high & 0xFF00) | self.next_high as u16;
+        self.attribute_low = (self.attribute_low & 0xFF00) | if self.next_attribute & 0x01 != 0 { 0xFF } else { 0 };
+        self.attribute_high = (self.attribute_high & 0xFF00) | if self.next_attribute & 0x02 != 0 { 0xFF } else { 0 };
+    }
+
+    // Find the sprites on the next scanline and fetch their patterns
+    fn evaluate_sprites(&mut self, mapper: &mut dyn Mapper) {
+        let height = if self.ctrl & CTRL_SPRITE_16 != 0 { 16 } else { 8 };
+        self.sprites.clear();
+
+        for index in 0..64 {
+            let [y, tile, attributes, x] = [0, 1, 2, 3].map(|byte| self.oam[index * 4 + byte]);
+            let row = self.scanline.wrapping_sub(y as u16);
+            if row >= height {
+                continue;
+            }
+            if self.sprites.len() == 8 {
+                self.status |= STATUS_OVERFLOW;
+                break;
+            }
+
+            let row = if attributes & 0x80 != 0 { height - 1 - row } else { row };
+            let addr = if height == 16 {
+                let table = (tile as u16 & 0x01) * 0x1000;
+                let tile = (tile & 0xFE) as u16 + row / 8;
+                table + tile * 16 + row % 8
+            } else {
+                let table = if self.ctrl & CTRL_SPRITE_TABLE != 0 { 0x1000 } else { 0 };
+                table + tile as u16 * 16 + row
+            };
+
+            let (mut low, mut high) = (self.read(addr, mapper), self.read(addr + 8, mapper));
+            if attributes & 0x40 != 0 {
+                low = low.reverse_bits();
+                high = high.reverse_bits();
+            }
+            self.sprites.push(Sprite { x, attributes, low, high, zero: index == 0 });
+        }
+    }
+
+    fn render_pixel(&mut self, mapper: &mut dyn Mapper) {
+        let x = self.dot - 1;
+
+        let mut background = 0;
+        let mut background_palette = 0;
+        if self.mask & MASK_BACKGROUND != 0 && (x >= 8 || self.mask & MASK_BACKGROUND_LEFT != 0) {
+            let bit = 0x8000 >> self.x;
+            background = ((self.pattern_low & bit != 0) as u8) | ((self.pattern_high & bit != 0) as u8) << 1;
+            background_palette = ((self.attribute_low & bit != 0) as u8) | ((self.attribute_high & bit != 0) as u8) << 1;
+        }
+
+        let mut sprite = None;
+        if self.mask & MASK_SPRITES != 0 && (x >= 8 || self.mask & MASK_SPRITES_LEFT != 0) {
+            sprite = self.sprites.iter().find_map(|sprite| {
+                let offset = x.checked_sub(sprite.x as u16).filter(|&offset| offset < 8)?;
+                let bit = 0x80 >> offset;
+                let color = ((sprite.low & bit != 0) as u8) | ((sprite.high & bit != 0) as u8) << 1;
+                (color != 0).then_some((color, sprite.attributes, sprite.zero))
+            });
+        }
+
+        let palette_index = match sprite {
+            Some((color, attributes, zero)) if background != 0 => {
+                if zero && x != 255 {
+                    self.status |= STATUS_SPRITE_ZERO;
+                }
+                if attributes & 0x20 == 0 {
+                    0x10 | (attributes & 0x03) << 2 | color
+                } else {
+                    background_palette << 2 | background
+                }
+            },
+            Some((color, attributes, _)) => 0x10 | (attributes & 0x03) << 2 | color,
+            None if background != 0 => background_palette << 2 | background,
+            None => 0,
+        };
+
+        let mut color = self.read(0x3F00 | palette_index as u16, mapper) & 0x3F;
+        if self.mask & MASK_GRAYSCALE != 0 {
+            color &= 0x30;
+        }
+
+        let offset = (self.scanline as usize * WIDTH + x as usize) * 3;
+        self.picture[offset..offset + 3].copy_from_slice(&PALETTE[color as usize]);
+    }
+
+    /// Read the PPU's address space: pattern tables from the mapper, nametables and palette
+    pub fn read(&mut self, addr: u16, mapper: &mut dyn Mapper) -> u8 {
+        match addr & 0x3FFF {
+            0x0000..=0x1FFF => mapper.ppu_read(addr),
+            _ => self.peek(addr, mapper),
+        }
+    }
+
+    /// Read the PPU's address space without side effects
+    pub fn peek(&self, addr: u16, mapper: &dyn Mapper) -> u8 {
+        match addr & 0x3FFF {
+            addr @ 0x0000..=0x1FFF => mapper.ppu_peek(addr),
+            addr @ 0x2000..=0x3EFF => self.vram[nametable_index(addr, mapper.mirroring())],
+            addr => self.palette[palette_index(addr)],
+        }
+    }
+
+    pub fn write(&mut self, addr: u16, data: u8, mapper: &mut dyn Mapper) {
+        match addr & 0x3FFF {
+            addr @ 0x0000..=0x1FFF => mapper.ppu_write(addr, data),
+            addr @ 0x2000..=0x3EFF => self.vram[nametable_index(addr, mapper.mirroring())] = data,
+            addr => self.palette[palette_index(addr)] = data & 0x3F,
+        }
+    }
+
+    pub fn save_state(&self, writer: &mut StateWriter) {
+        for register in [self.ctrl, self.mask, self.status, self.oam_addr, self.x, self.read_buffer, self.latch] {
+            writer.write_u8(register);
+        }
+        writer.write_u16(self.v);
+        writer.write_u16(self.t);
+        writer.write_bool(self.w);
+        writer.write_u16(self.scanline);
+        writer.write_u16(self.dot);
+        writer.write_u64(self.frame);
+        writer.write_bool(self.odd_frame);
+        writer.write_bool(self.nmi_output);
+        writer.write_bytes(&self.oam);
+        writer.write_bytes(&self.vram);
+        writer.write_bytes(&self.palette);
+    }
+
+    /// Restore the state written by `save_state`
+    ///
+    /// Rendering resumes from the next scanline's fetches, so the rest of the current scanline may be drawn wrong.
+    pub fn load_state(&mut self, reader: &mut StateReader) -> Result<(), StateError> {
+        for register in [
+            &mut self.ctrl,
+            &mut self.mask,
+            &mut self.status,
+            &mut self.oam_addr,
+            &mut self.x,
+            &mut self.read_buffer,
+            &mut self.latch,
+        ] {
+            *register = reader.read_u8()?;
+        }
+        self.v = reader.read_u16()?;
+        self.t = reader.read_u16()?;
+        self.w = reader.read_bool()?;
+        self.scanline = reader.read_u16()? % SCANLINES;
+        self.dot = reader.read_u16()? % DOTS;
+        self.frame = reader.read_u64()?;
+        self.odd_frame = reader.read_bool()?;
+        self.nmi_output = reader.read_bool()?;
+        self.nmi_edge = false;
+        reader.read_bytes_into(&mut self.oam)?;
+        reader.read_bytes_into(&mut self.vram)?;
+        reader.read_bytes_into(&mut self.palette)?;
+        self.sprites.clear();
+        Ok(())
+    }
+}
+
+/// Index into VRAM of nametable address `addr` (0x2000-0x3EFF, mirrored above 0x3000)
+fn nametable_index(addr: u16, mirroring: Mirroring) -> usize {
+    let addr = (addr - 0x2000) as usize & 0x0FFF;
+    let (table, offset) = (addr / 0x0400, addr % 0x0400);
+    let page = match mirroring {
+        Mirroring::Vertical => table & 0x01,
+        Mirroring::Horizontal => table >> 1,
+        Mirroring::SingleScreenLower => 0,
+        Mirroring::SingleScreenUpper => 1,
+        Mirroring::FourScreen => table,
+    };
+    page * 0x0400 + offset
+}
+
+/// Index into palette RAM of `addr` (0x3F00-0x3FFF); the backdrop entries of sprite palettes mirror the background's
+fn palette_index(addr: u16) -> usize {
+    let index = addr as usize & 0x1F;
+    if index & 0x13 == 0x10 {
+        index & !0x10
+    } else {
+        index
+    }
+}
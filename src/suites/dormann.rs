@@ -0,0 +1,132 @@
+use std::{
+    fmt,
+    io,
+    path::PathBuf,
+};
+
+use crate::core::{
+    bus::Bus,
+    cpu::{CPU, CpuConfig, CpuError, StepOutcome},
+    memory::Memory,
+};
+
+/// Klaus Dormann's 6502 test suite (https://github.com/Klaus2m5/6502_65C02_functional_tests)
+///
+/// The suite signals its result by trapping the PC in a jump or branch to itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DormannSuite {
+    /// Address the binary is loaded at
+    pub origin: u16,
+    /// Address execution starts at
+    pub start: u16,
+    /// Address of the success trap, or `None` if success is read from `status` instead (0 means passed)
+    pub success: Option<u16>,
+    /// Address of the byte holding the current test number (or error flag)
+    pub status: u16,
+    /// Cycles after which the run is abandoned
+    pub max_cycles: u64,
+    pub config: CpuConfig,
+}
+
+/// Result of running a `DormannSuite`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DormannOutcome {
+    Passed { cycles: u64 },
+    /// Trapped outside the success trap; `test` is the status byte at that point
+    Failed { pc: u16, test: u8, cycles: u64 },
+    /// The CPU reported an error
+    Error(CpuError),
+    /// No trap within `max_cycles`
+    Timeout { pc: u16 },
+}
+
+impl DormannOutcome {
+    pub fn passed(&self) -> bool {
+        matches!(self, DormannOutcome::Passed { .. })
+    }
+}
+
+impl fmt::Display for DormannOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DormannOutcome::Passed { cycles } => write!(f, "passed after {} cycles", cycles),
+            DormannOutcome::Failed { pc, test, cycles } => {
+                write!(f, "failed test 0x{:02X} (trapped at 0x{:04X} after {} cycles)", test, pc, cycles)
+            },
+            DormannOutcome::Error(error) => write!(f, "error: {}", error),
+            DormannOutcome::Timeout { pc } => write!(f, "no trap, PC at 0x{:04X}", pc),
+        }
+    }
+}
+
+impl DormannSuite {
+    /// `6502_functional_test.bin` as shipped in `bin_files` (a full 64KB image, success trap at 0x3469)
+    pub fn functional() -> Self {
+        DormannSuite {
+            origin: 0x0000,
+            start: 0x0400,
+            success: Some(0x3469),
+            status: 0x0200,
+            max_cycles: 200_000_000,
+            config: CpuConfig::default(),
+        }
+    }
+
+    /// `6502_decimal_test.bin`, assembled at its default addresses (code at 0x0200, `ERROR` at 0x000B)
+    pub fn decimal() -> Self {
+        DormannSuite {
+            origin: 0x0000,
+            start: 0x0200,
+            success: None,
+            status: 0x000B,
+            max_cycles: 200_000_000,
+            config: CpuConfig::default(),
+        }
+    }
+
+    /// Run the suite from the binary at `path`
+    pub fn run_file(&self, path: &PathBuf) -> io::Result<DormannOutcome> {
+        let image = crate::io::load_rom(path)?;
+        Ok(self.run(&image))
+    }
+
+    /// Run the suite from `image`
+    pub fn run(&self, image: &[u8]) -> DormannOutcome {
+        let mut cpu: CPU = CPU::with_config(Bus::new(), self.config);
+        for (i, byte) in image.iter().enumerate() {
+            cpu.write(self.origin.wrapping_add(i as u16), *byte);
+        }
+        cpu.power_on();
+        cpu.set_pc(self.start);
+
+        loop {
+            let pc = cpu.get_pc();
+            if cpu.cycles() > self.max_cycles {
+                return DormannOutcome::Timeout { pc };
+            }
+
+            match cpu.step() {
+                Ok(StepOutcome::Executed { .. }) if cpu.get_pc() != pc => {},
+                Ok(StepOutcome::Interrupt { .. }) => {},
+                // Trapped in a self-loop, or stopped by STP
+                Ok(_) => return self.outcome(&cpu, pc),
+                Err(error) => return DormannOutcome::Error(error),
+            }
+        }
+    }
+
+    fn outcome(&self, cpu: &CPU, pc: u16) -> DormannOutcome {
+        let test = cpu.bus().peek(self.status);
+        let cycles = cpu.cycles();
+        let passed = match self.success {
+            Some(success) => pc == success,
+            None => test == 0,
+        };
+
+        if passed {
+            DormannOutcome::Passed { cycles }
+        } else {
+            DormannOutcome::Failed { pc, test, cycles }
+        }
+    }
+}
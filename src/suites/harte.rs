@@ -0,0 +1,284 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    io,
+    path::Path,
+};
+
+use crate::{
+    core::{
+        cpu::{CPU, CpuConfig, CpuVariant},
+        memory::Memory,
+        timing::{self, TimingState},
+    },
+    suites::json::{Json, JsonError},
+};
+
+/// Register and memory state before or after a test case
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct HarteState {
+    pub pc: u16,
+    pub s: u8,
+    pub a: u8,
+    pub x: u8,
+    pub y: u8,
+    pub p: u8,
+    pub ram: Vec<(u16, u8)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BusAccess {
+    Read,
+    Write,
+}
+
+/// One cycle of bus activity
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BusCycle {
+    pub addr: u16,
+    pub value: u8,
+    pub access: BusAccess,
+}
+
+impl fmt::Display for BusCycle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let access = match self.access {
+            BusAccess::Read => "read",
+            BusAccess::Write => "write",
+        };
+        write!(f, "{} 0x{:02X} at 0x{:04X}", access, self.value, self.addr)
+    }
+}
+
+/// A single test case of the SingleStepTests/ProcessorTests suite (https://github.com/SingleStepTests/ProcessorTests)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HarteCase {
+    pub name: String,
+    pub initial: HarteState,
+    pub after: HarteState,
+    /// Bus activity of every cycle
+    pub cycles: Vec<BusCycle>,
+}
+
+/// A field of a test case that didn't match
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    pub field: String,
+    pub expected: String,
+    pub actual: String,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: expected {}, got {}", self.field, self.expected, self.actual)
+    }
+}
+
+/// Results of running a file of test cases
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct HarteReport {
+    pub cases: usize,
+    /// Name and mismatches of every failing case
+    pub failures: Vec<(String, Vec<Mismatch>)>,
+}
+
+impl HarteReport {
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+impl fmt::Display for HarteReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{} cases passed", self.cases - self.failures.len(), self.cases)?;
+        for (name, mismatches) in &self.failures {
+            write!(f, "\n{}:", name)?;
+            for mismatch in mismatches {
+                write!(f, "\n    {}", mismatch)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Parse a file of test cases (a JSON array)
+pub fn parse_cases(text: &str) -> Result<Vec<HarteCase>, JsonError> {
+    let invalid = |message: &str| JsonError { offset: 0, message: message.to_string() };
+
+    Json::parse(text)?
+        .as_array()
+        .ok_or_else(|| invalid("expected an array of test cases"))?
+        .iter()
+        .map(|case| parse_case(case).ok_or_else(|| invalid("malformed test case")))
+        .collect()
+}
+
+fn parse_case(case: &Json) -> Option<HarteCase> {
+    let cycles = case.get("cycles")?.as_array()?.iter().map(|cycle| {
+        match cycle.as_array()? {
+            [addr, value, access] => Some(BusCycle {
+                addr: addr.as_u64()? as u16,
+                value: value.as_u64()? as u8,
+                access: match access.as_str()? {
+                    "read" => BusAccess::Read,
+                    "write" => BusAccess::Write,
+                    _ => return None,
+                },
+            }),
+            _ => None,
+        }
+    }).collect::<Option<_>>()?;
+
+    Some(HarteCase {
+        name: case.get("name")?.as_str()?.to_string(),
+        initial: parse_state(case.get("initial")?)?,
+        after: parse_state(case.get("final")?)?,
+        cycles,
+    })
+}
+
+fn parse_state(state: &Json) -> Option<HarteState> {
+    let field = |name| state.get(name).and_then(Json::as_u64);
+    let ram = state.get("ram")?.as_array()?.iter().map(|entry| {
+        match entry.as_array()? {
+            [addr, value] => Some((addr.as_u64()? as u16, value.as_u64()? as u8)),
+            _ => None,
+        }
+    }).collect::<Option<_>>()?;
+
+    Some(HarteState {
+        pc: field("pc")? as u16,
+        s: field("s")? as u8,
+        a: field("a")? as u8,
+        x: field("x")? as u8,
+        y: field("y")? as u8,
+        p: field("p")? as u8,
+        ram,
+    })
+}
+
+/// Sparse memory that logs every access, so test cases don't need a full 64KB `Bus` each
+#[derive(Default)]
+struct LoggedRam {
+    bytes: HashMap<u16, u8>,
+    log: Vec<BusCycle>,
+}
+
+impl Memory for LoggedRam {
+    fn read(&mut self, addr: u16) -> u8 {
+        let value = self.peek(addr);
+        self.log.push(BusCycle { addr, value, access: BusAccess::Read });
+        value
+    }
+
+    fn write(&mut self, addr: u16, value: u8) {
+        self.bytes.insert(addr, value);
+        self.log.push(BusCycle { addr, value, access: BusAccess::Write });
+    }
+
+    fn peek(&self, addr: u16) -> u8 {
+        self.bytes.get(&addr).copied().unwrap_or(0x00)
+    }
+}
+
+/// Runs test cases against `CPU`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct HarteRunner {
+    pub variant: CpuVariant,
+    /// Compare the bus log cycle by cycle
+    ///
+    /// `CPU` skips the dummy reads and writes of the real chip, so by default its accesses only have to appear
+    /// somewhere in the expected log.
+    pub strict_bus: bool,
+}
+
+impl HarteRunner {
+    pub fn new(variant: CpuVariant) -> Self {
+        HarteRunner { variant, strict_bus: false }
+    }
+
+    /// Run a single test case, returning every field that doesn't match
+    pub fn run(&self, case: &HarteCase) -> Vec<Mismatch> {
+        let initial = &case.initial;
+        let mut ram = LoggedRam::default();
+        ram.bytes.extend(initial.ram.iter().copied());
+
+        let model = timing::instruction_cycles(&ram, self.variant, TimingState {
+            pc: initial.pc,
+            x: initial.x,
+            y: initial.y,
+            sr: initial.p,
+        });
+
+        let config = CpuConfig { variant: self.variant, ..Default::default() };
+        let mut cpu = CPU::with_config(ram, config);
+        cpu.set_pc(initial.pc);
+        cpu.set_sp(initial.s);
+        cpu.set_a(initial.a);
+        cpu.set_x(initial.x);
+        cpu.set_y(initial.y);
+        cpu.set_sr(initial.p);
+
+        let mut mismatches = Vec::new();
+        let mut check = |field: &str, expected: String, actual: String| {
+            if expected != actual {
+                mismatches.push(Mismatch { field: field.to_string(), expected, actual });
+            }
+        };
+
+        if let Err(error) = cpu.step() {
+            check("error", "none".to_string(), error.to_string());
+        }
+
+        let after = &case.after;
+        check("pc", format!("0x{:04X}", after.pc), format!("0x{:04X}", cpu.get_pc()));
+        check("s", format!("0x{:02X}", after.s), format!("0x{:02X}", cpu.get_sp()));
+        check("a", format!("0x{:02X}", after.a), format!("0x{:02X}", cpu.get_a()));
+        check("x", format!("0x{:02X}", after.x), format!("0x{:02X}", cpu.get_x()));
+        check("y", format!("0x{:02X}", after.y), format!("0x{:02X}", cpu.get_y()));
+        // B and U aren't real flags, so only compare the others
+        check("p", format!("0x{:02X}", after.p | 0x30), format!("0x{:02X}", cpu.get_sr() | 0x30));
+        for (addr, value) in &after.ram {
+            check(&format!("ram[0x{:04X}]", addr), format!("0x{:02X}", value), format!("0x{:02X}", cpu.bus().peek(*addr)));
+        }
+
+        check("cycles", case.cycles.len().to_string(), cpu.cycles().to_string());
+        check("model cycles", case.cycles.len().to_string(), model.to_string());
+
+        let log = &cpu.bus().log;
+        if self.strict_bus {
+            for i in 0..case.cycles.len().max(log.len()) {
+                let show = |cycle: Option<&BusCycle>| cycle.map_or("nothing".to_string(), BusCycle::to_string);
+                check(&format!("bus[{}]", i), show(case.cycles.get(i)), show(log.get(i)));
+            }
+        } else {
+            let mut expected = case.cycles.clone();
+            for access in log {
+                match expected.iter().position(|cycle| cycle == access) {
+                    Some(i) => { expected.remove(i); },
+                    None => check("unexpected bus access", "none".to_string(), access.to_string()),
+                }
+            }
+        }
+
+        mismatches
+    }
+
+    /// Run every test case in `cases`
+    pub fn run_all(&self, cases: &[HarteCase]) -> HarteReport {
+        let failures = cases
+            .iter()
+            .map(|case| (case.name.clone(), self.run(case)))
+            .filter(|(_, mismatches)| !mismatches.is_empty())
+            .collect();
+
+        HarteReport { cases: cases.len(), failures }
+    }
+
+    /// Run every test case in the file at `path` (e.g. `6502/v1/a9.json`)
+    pub fn run_file(&self, path: &Path) -> io::Result<HarteReport> {
+        let text = std::fs::read_to_string(path)?;
+        let cases = parse_cases(&text).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        Ok(self.run_all(&cases))
+    }
+}
@@ -0,0 +1,3 @@
+pub mod dormann;
+pub mod harte;
+pub mod json;
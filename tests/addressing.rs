@@ -113,19 +113,40 @@ fn idy() {
     let pc: u16 = 0x0301;
     let y: u8 = 0x02;
     let zp_addr: u8 = 0x30;
-    let base: u8 = 0x91;
-    let ptr: u8 = base + y;
-    let addr: u8 = 0x00EF;
+    let lo: u8 = 0x91;
+    let hi: u8 = 0x04;
+    let addr: u16 = ((hi as u16) << 8 | (lo as u16)) + y as u16;
     let mut cpu: CPU = CPU::custom(0, 0, y, 0, pc, 0, 0, Bus::new());
     cpu.write(pc, zp_addr);
-    cpu.write(zp_addr as u16, base);
-    cpu.write(ptr as u16, addr);
-    assert_eq!(cpu.get_address(AddressingMode::IDY), addr as u16);
+    cpu.write(zp_addr as u16, lo);
+    cpu.write(zp_addr as u16 + 1, hi);
+    assert_eq!(cpu.get_address(AddressingMode::IDY), addr);
+    assert_eq!(cpu.get_pc(), pc + 1);
+}
+
+#[test]
+fn idx_zero_page_wrap() {
+    let pc: u16 = 0x0301;
+    let x: u8 = 0x01;
+    let mut cpu: CPU = CPU::custom(0, x, 0, 0, pc, 0, 0, Bus::new());
+    cpu.write(pc, 0xFE);
+    cpu.write(0x00FF, 0x34);
+    cpu.write(0x0000, 0x12);
+    assert_eq!(cpu.get_address(AddressingMode::IDX), 0x1234);
+    assert_eq!(cpu.get_pc(), pc + 1);
 }
 
 #[test]
 fn rel() {
-    // TODO: how to test relative addressing?
+    let pc: u16 = 0x0301;
+    let mut cpu: CPU = CPU::custom(0, 0, 0, 0, pc, 0, 0, Bus::new());
+    cpu.write(pc, 0x10);
+    assert_eq!(cpu.get_address(AddressingMode::REL), pc + 1 + 0x10);
+
+    // Negative offsets are relative to the next instruction too
+    let mut cpu: CPU = CPU::custom(0, 0, 0, 0, pc, 0, 0, Bus::new());
+    cpu.write(pc, 0xFE);
+    assert_eq!(cpu.get_address(AddressingMode::REL), pc - 1);
 }
 
 #[test]
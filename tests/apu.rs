@@ -0,0 +1,258 @@
+mod common;
+
+use std::io::Cursor;
+
+use common::{ines_cartridge, nes_bus};
+use emulatorr::{
+    asm,
+    core::memory::Memory,
+    nes::{
+        apu::{Apu, WavWriter, CPU_CLOCK},
+        bus::DMC_DMA_CYCLES,
+        cartridge::Cartridge,
+        Nes,
+    },
+};
+
+/// NROM-128 cartridge with `program` at 0x8000 (reset vector), 0xFF everywhere else and the IRQ vector at 0x8100
+fn cartridge(program: &[u8]) -> Cartridge {
+    let mut prg = vec![0xFF; 0x4000];
+    prg[..program.len()].copy_from_slice(program);
+    prg[0x3FFA..].copy_from_slice(&[0x00, 0x80, 0x00, 0x80, 0x00, 0x81]);
+    ines_cartridge(&prg, &[0; 0x2000], 0)
+}
+
+fn run(apu: &mut Apu, cycles: u64) {
+    for _ in 0..cycles {
+        apu.tick();
+    }
+}
+
+#[test]
+fn length_counter_and_status() {
+    let mut apu = Apu::default();
+    // Length counters only load while their channel is enabled
+    apu.write(0x4003, 0x18);
+    assert_eq!(apu.peek_status() & 0x01, 0);
+
+    apu.write(0x4015, 0x0F);
+    for addr in [0x4003, 0x4007, 0x400B, 0x400F] {
+        // Length index 3: 2 half frames
+        apu.write(addr, 0x18);
+    }
+    assert_eq!(apu.peek_status() & 0x0F, 0x0F);
+
+    run(&mut apu, 14913);
+    assert_eq!(apu.peek_status() & 0x0F, 0x0F);
+    run(&mut apu, 14916);
+    assert_eq!(apu.peek_status() & 0x0F, 0x00);
+
+    // Disabling a channel clears its counter
+    apu.write(0x4003, 0x18);
+    apu.write(0x4015, 0x00);
+    assert_eq!(apu.peek_status() & 0x01, 0);
+}
+
+#[test]
+fn halted_length_counter() {
+    let mut apu = Apu::default();
+    apu.write(0x4015, 0x01);
+    apu.write(0x4000, 0x20);
+    apu.write(0x4003, 0x18);
+    run(&mut apu, 100_000);
+    assert_eq!(apu.peek_status() & 0x01, 0x01);
+}
+
+#[test]
+fn frame_irq() {
+    let mut apu = Apu::default();
+    run(&mut apu, 29828);
+    assert!(!apu.irq());
+    run(&mut apu, 1);
+    assert!(apu.irq());
+    assert_eq!(apu.read_status() & 0x40, 0x40);
+    assert!(!apu.irq());
+
+    // 5-step mode never raises it
+    apu.write(0x4017, 0x80);
+    run(&mut apu, 100_000);
+    assert!(!apu.irq());
+
+    // Neither does 4-step mode with the inhibit flag, which also clears it
+    apu.write(0x4017, 0x00);
+    run(&mut apu, 29829);
+    assert!(apu.irq());
+    apu.write(0x4017, 0x40);
+    assert!(!apu.irq());
+    run(&mut apu, 100_000);
+    assert!(!apu.irq());
+}
+
+#[test]
+fn pulse_frequency_and_samples() {
+    let mut apu = Apu::new(44_100);
+    // Pulse 1: 50% duty, halted length counter, constant volume 15, period 253 (about 440 Hz)
+    apu.write(0x4015, 0x01);
+    apu.write(0x4000, 0xBF);
+    apu.write(0x4002, 253);
+    apu.write(0x4003, 0x00);
+
+    run(&mut apu, CPU_CLOCK);
+    let samples = apu.drain_samples();
+    assert_eq!(samples.len(), 44_100);
+    assert!(apu.samples().is_empty());
+    assert!(samples.iter().all(|&sample| (0.0..=1.0).contains(&sample)));
+
+    let min = samples.iter().cloned().fold(1.0, f32::min);
+    let max = samples.iter().cloned().fold(0.0, f32::max);
+    let middle = (min + max) / 2.0;
+    let rising = samples.windows(2).filter(|pair| pair[0] < middle && pair[1] >= middle).count();
+    assert!((439..=441).contains(&rising), "{} cycles", rising);
+}
+
+#[test]
+fn pulse_silenced_by_short_period_and_sweep() {
+    let mut apu = Apu::default();
+    // The triangle holds its level when silent
+    let silence = apu.mix();
+    apu.write(0x4015, 0x01);
+    apu.write(0x4000, 0xBF);
+    apu.write(0x4002, 7);
+    apu.write(0x4003, 0x00);
+    run(&mut apu, 1000);
+    assert_eq!(apu.mix(), silence);
+
+    // The sweep target of 0x7FF + (0x7FF >> 1) overflows
+    apu.write(0x4001, 0x81);
+    apu.write(0x4002, 0xFF);
+    apu.write(0x4003, 0x07);
+    run(&mut apu, 1000);
+    assert_eq!(apu.mix(), silence);
+
+    // Without overflow, the sweep lowers the pitch every half frame
+    apu.write(0x4002, 0x00);
+    apu.write(0x4003, 0x01);
+    run(&mut apu, 1000);
+    assert!(apu.samples().iter().any(|&sample| sample > silence));
+}
+
+#[test]
+fn triangle_and_noise_play() {
+    let mut apu = Apu::default();
+    apu.write(0x4015, 0x0C);
+    // Triangle with a long linear counter
+    apu.write(0x4008, 0xFF);
+    apu.write(0x400A, 100);
+    apu.write(0x400B, 0x00);
+    // Noise at constant volume 8
+    apu.write(0x400C, 0x38);
+    apu.write(0x400E, 0x03);
+    apu.write(0x400F, 0x00);
+
+    run(&mut apu, 20_000);
+    let samples = apu.drain_samples();
+    let min = samples.iter().cloned().fold(1.0, f32::min);
+    let max = samples.iter().cloned().fold(0.0, f32::max);
+    assert!(max - min > 0.05, "{}..{}", min, max);
+}
+
+#[test]
+fn non_linear_mixer() {
+    let mut apu = Apu::default();
+    // At power on, only the triangle outputs (level 15)
+    let triangle = 159.79 / (1.0 / (15.0 / 8227.0) + 100.0);
+    assert!((apu.mix() - triangle).abs() < 0.0001, "{}", apu.mix());
+
+    // The triangle, noise and DMC share a DAC, so their levels don't add up linearly
+    apu.write(0x4011, 0x7F);
+    let expected = 159.79 / (1.0 / (15.0 / 8227.0 + 127.0 / 22638.0) + 100.0);
+    assert!((apu.mix() - expected).abs() < 0.0001, "{}", apu.mix());
+    let dmc = 159.79 / (1.0 / (127.0 / 22638.0) + 100.0);
+    assert!(apu.mix() < triangle + dmc);
+}
+
+#[test]
+fn dmc_fetches_with_dma() {
+    let mut bus = nes_bus(cartridge(&[]));
+    // IRQ enabled, fastest rate, one byte sample at 0xC000 (all bits set)
+    bus.write(0x4010, 0x8F);
+    bus.write(0x4011, 0x40);
+    bus.write(0x4012, 0x00);
+    bus.write(0x4013, 0x00);
+    bus.write(0x4015, 0x10);
+    assert_eq!(bus.peek(0x4015) & 0x10, 0x10);
+
+    bus.tick(1);
+    assert_eq!(bus.take_stall(0), DMC_DMA_CYCLES);
+    assert_eq!(bus.peek(0x4015) & 0x90, 0x80);
+    assert!(bus.apu().irq());
+
+    // The sample raises the level by 2 per bit
+    let before = bus.apu().mix();
+    bus.tick(54 * 10);
+    assert!(bus.apu().mix() > before);
+    assert_eq!(bus.take_stall(0), 0);
+
+    // Writing 0x4015 acknowledges the IRQ
+    bus.write(0x4015, 0x00);
+    assert!(!bus.apu().irq());
+}
+
+#[test]
+fn dmc_loops() {
+    let mut bus = nes_bus(cartridge(&[]));
+    bus.write(0x4010, 0x4F);
+    bus.write(0x4013, 0x01);
+    bus.write(0x4015, 0x10);
+    bus.tick(54 * 8 * 40);
+    assert_eq!(bus.peek(0x4015) & 0x90, 0x10);
+    // One byte up front, then one every 8 bits
+    assert_eq!(bus.take_stall(0), DMC_DMA_CYCLES * 41);
+}
+
+#[test]
+fn frame_irq_reaches_cpu() {
+    // Enable IRQs and spin; the handler acknowledges the frame IRQ and stores the status at 0x0010
+    let program = asm::assemble(
+        "
+        .org $8000
+        CLI
+    loop:
+        JMP loop
+        .org $8100
+        LDA $4015
+        STA $10
+        RTI
+        ",
+    )
+    .unwrap()
+    .bytes;
+    let mut nes = Nes::new(cartridge(&program)).unwrap();
+    nes.power_on();
+    while nes.cpu().bus().peek(0x0010) == 0 && nes.cpu().cycles() < 40_000 {
+        nes.step().unwrap();
+    }
+    assert_eq!(nes.cpu().bus().peek(0x0010) & 0x40, 0x40);
+    assert!(nes.cpu().cycles() > 29_829);
+    assert!(!nes.cpu().bus().apu().irq());
+    assert!(!nes.drain_samples().is_empty());
+}
+
+#[test]
+fn wav_writer() {
+    let mut wav = WavWriter::new(Cursor::new(Vec::new()), 48_000).unwrap();
+    wav.write_samples(&[0.0, 1.0, -1.0, 2.0]).unwrap();
+    wav.write_samples(&[0.5]).unwrap();
+    let bytes = wav.finish().unwrap().into_inner();
+
+    assert_eq!(bytes.len(), 44 + 10);
+    assert_eq!(&bytes[0..4], b"RIFF");
+    assert_eq!(u32::from_le_bytes(bytes[4..8].try_into().unwrap()), 36 + 10);
+    assert_eq!(&bytes[8..16], b"WAVEfmt ");
+    assert_eq!(u32::from_le_bytes(bytes[24..28].try_into().unwrap()), 48_000);
+    assert_eq!(&bytes[36..40], b"data");
+    assert_eq!(u32::from_le_bytes(bytes[40..44].try_into().unwrap()), 10);
+
+    let samples: Vec<i16> = bytes[44..].chunks(2).map(|pair| i16::from_le_bytes([pair[0], pair[1]])).collect();
+    assert_eq!(samples, [0, 32767, -32767, 32767, 16383]);
+}
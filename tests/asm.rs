@@ -0,0 +1,183 @@
+use emulatorr::{
+    asm::{assemble, assemble_for, AsmError, AsmErrorKind, DEFAULT_ORIGIN},
+    core::cpu::CpuVariant,
+    disasm,
+};
+
+fn bytes(source: &str) -> Vec<u8> {
+    assemble(source).unwrap().bytes
+}
+
+fn error(source: &str) -> AsmError {
+    assemble(source).unwrap_err()
+}
+
+#[test]
+fn addressing_modes() {
+    assert_eq!(bytes("ASL"), vec![0x0A]);
+    assert_eq!(bytes("asl a"), vec![0x0A]);
+    assert_eq!(bytes("LDA #$05"), vec![0xA9, 0x05]);
+    assert_eq!(bytes("LDA $10"), vec![0xA5, 0x10]);
+    assert_eq!(bytes("LDA $10,X"), vec![0xB5, 0x10]);
+    assert_eq!(bytes("LDX $10,Y"), vec![0xB6, 0x10]);
+    assert_eq!(bytes("LDA $1234"), vec![0xAD, 0x34, 0x12]);
+    assert_eq!(bytes("LDA $1234,X"), vec![0xBD, 0x34, 0x12]);
+    assert_eq!(bytes("LDA $1234,Y"), vec![0xB9, 0x34, 0x12]);
+    assert_eq!(bytes("JMP ($1234)"), vec![0x6C, 0x34, 0x12]);
+    assert_eq!(bytes("LDA ($10,X)"), vec![0xA1, 0x10]);
+    assert_eq!(bytes("LDA ($10),Y"), vec![0xB1, 0x10]);
+    assert_eq!(bytes("BRK"), vec![0x00]);
+
+    // LDA has no zero page,Y mode, so it falls back to absolute,Y
+    assert_eq!(bytes("LDA $10,Y"), vec![0xB9, 0x10, 0x00]);
+}
+
+#[test]
+fn labels_and_branches() {
+    let program = assemble("
+        start:
+            LDX #3
+        @loop:
+            DEX
+            BNE @loop
+            BEQ end
+            NOP
+        end:
+            JMP start
+    ").unwrap();
+
+    assert_eq!(program.origin, DEFAULT_ORIGIN);
+    assert_eq!(program.bytes, vec![0xA2, 0x03, 0xCA, 0xD0, 0xFD, 0xF0, 0x01, 0xEA, 0x4C, 0x00, 0x06]);
+    assert_eq!(program.symbol("start"), Some(0x0600));
+    assert_eq!(program.symbol("start@loop"), Some(0x0602));
+    assert_eq!(program.symbol("end"), Some(0x0608));
+}
+
+#[test]
+fn local_labels_are_scoped() {
+    let program = assemble("
+        first:
+        @loop: BNE @loop
+        second:
+        @loop: BNE @loop
+    ").unwrap();
+
+    assert_eq!(program.symbol("first@loop"), Some(0x0600));
+    assert_eq!(program.symbol("second@loop"), Some(0x0602));
+}
+
+#[test]
+fn forward_references_are_absolute() {
+    // The address of `data` isn't known on the first pass, so it's assembled as absolute even though it fits in zero page
+    let program = assemble("
+        .org $0000
+            LDA data
+            LDA data2
+        data = $10
+        data2:
+            .byte 0
+    ").unwrap();
+
+    assert_eq!(program.bytes, vec![0xAD, 0x10, 0x00, 0xAD, 0x06, 0x00, 0x00]);
+
+    // Known zero page addresses are shortened
+    assert_eq!(bytes("ptr = $FE\nLDA ptr"), vec![0xA5, 0xFE]);
+}
+
+#[test]
+fn directives() {
+    let program = assemble("
+        .org $8000
+        .byte 1, $02, %11, 'A', \"hi\"
+        .word $1234, table
+        .res 2
+        .res 3, $EA
+        table:
+        .org $8010
+        .byte -1
+    ").unwrap();
+
+    assert_eq!(program.origin, 0x8000);
+    assert_eq!(program.bytes, vec![
+        0x01, 0x02, 0x03, 0x41, 0x68, 0x69,
+        0x34, 0x12, 0x0F, 0x80,
+        0x00, 0x00,
+        0xEA, 0xEA, 0xEA,
+        0x00,
+        0xFF,
+    ]);
+}
+
+#[test]
+fn expressions() {
+    assert_eq!(bytes("LDA #2+3*4"), vec![0xA9, 14]);
+    assert_eq!(bytes("LDA #(2+3)*4"), vec![0xA9, 20]);
+    assert_eq!(bytes("LDA #$F0 | $0F & $3C"), vec![0xA9, 0xFC]);
+    assert_eq!(bytes("LDA #<$1234\nLDX #>$1234"), vec![0xA9, 0x34, 0xA2, 0x12]);
+    assert_eq!(bytes("LDA #-1"), vec![0xA9, 0xFF]);
+    assert_eq!(bytes("JMP *"), vec![0x4C, 0x00, 0x06]);
+    assert_eq!(bytes("vector = $FFFC\nLDA vector+1"), vec![0xAD, 0xFD, 0xFF]);
+    assert_eq!(bytes("LDA #';' ; comment"), vec![0xA9, b';']);
+}
+
+#[test]
+fn cmos_instructions() {
+    let program = assemble_for("
+            STZ $10
+            LDA ($10)
+            JMP ($1234,X)
+            RMB3 $10
+        loop:
+            BBS7 $10,loop
+            BRA loop
+    ", CpuVariant::Wdc65C02).unwrap();
+
+    assert_eq!(program.bytes, vec![
+        0x64, 0x10,
+        0xB2, 0x10,
+        0x7C, 0x34, 0x12,
+        0x37, 0x10,
+        0xFF, 0x10, 0xFD,
+        0x80, 0xFB,
+    ]);
+
+    // Not available on the NMOS 6502
+    assert_eq!(error("STZ $10").kind, AsmErrorKind::UnknownInstruction("STZ".to_string()));
+    assert_eq!(error("LDA ($10)").kind, AsmErrorKind::InvalidAddressingMode("LDA".to_string()));
+}
+
+#[test]
+fn errors_have_line_numbers() {
+    assert_eq!(error("NOP\nFOO #1"), AsmError { line: 2, kind: AsmErrorKind::UnknownInstruction("FOO".to_string()) });
+    assert_eq!(error("NOP\nNOP\nJMP nowhere").line, 3);
+    assert_eq!(error("JMP nowhere").kind, AsmErrorKind::UndefinedSymbol("nowhere".to_string()));
+    assert_eq!(error("a:\na:").kind, AsmErrorKind::DuplicateSymbol("a".to_string()));
+    assert_eq!(error(".foo").kind, AsmErrorKind::UnknownDirective(".foo".to_string()));
+    assert_eq!(error("LDA #$100").kind, AsmErrorKind::ValueOutOfRange(0x100));
+    assert_eq!(error("STX $1234,X").kind, AsmErrorKind::InvalidAddressingMode("STX".to_string()));
+    assert_eq!(error(".org $0700\n.byte 0\n.org $0600").kind, AsmErrorKind::OrgBackwards(0x0600));
+    assert_eq!(error("start:\n.res 200\nBNE start").kind, AsmErrorKind::BranchOutOfRange(-202));
+    assert!(matches!(error("LDA #(1").kind, AsmErrorKind::Syntax(_)));
+
+    assert_eq!(error("NOP\nFOO").to_string(), "line 2: unknown instruction FOO");
+}
+
+#[test]
+fn round_trips_through_disassembler() {
+    let program = assemble("
+        loop:
+            LDA ($20),Y
+            STA $0300,X
+            INX
+            BNE loop
+            RTS
+    ").unwrap();
+
+    let symbols = program.symbol_table();
+    let text: Vec<String> = disasm::disassemble(&program.bytes, program.origin, CpuVariant::Nmos6502)
+        .iter()
+        .map(|instruction| instruction.format(Some(&symbols)))
+        .collect();
+
+    assert_eq!(text, vec!["LDA ($20),Y", "STA $0300,X", "INX", "BNE loop", "RTS"]);
+}
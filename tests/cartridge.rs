@@ -0,0 +1,106 @@
+use emulatorr::nes::cartridge::{Cartridge, ConsoleType, Mirroring, RomError, RomFormat, TvSystem};
+
+/// ROM file with `header` bytes 4-15, followed by `len` bytes of data
+fn rom(header: [u8; 12], len: usize) -> Vec<u8> {
+    let mut rom = vec![0x4E, 0x45, 0x53, 0x1A];
+    rom.extend_from_slice(&header);
+    rom.extend((0..len).map(|i| i as u8));
+    rom
+}
+
+#[test]
+fn parses_ines() {
+    // 2x16KB PRG, 1x8KB CHR, mapper 0x41, vertical mirroring, battery, PAL
+    let cartridge = Cartridge::from_bytes(&rom([2, 1, 0x13, 0x40, 0, 1, 0, 0, 0, 0, 0, 0], 0xA000)).unwrap();
+    assert_eq!(cartridge.format, RomFormat::iNES);
+    assert_eq!(cartridge.mapper, 0x41);
+    assert_eq!(cartridge.submapper, 0);
+    assert_eq!(cartridge.prg_rom.len(), 0x8000);
+    assert_eq!(cartridge.chr_rom.len(), 0x2000);
+    assert_eq!(cartridge.chr_rom[0], 0x00);
+    assert_eq!(cartridge.mirroring, Mirroring::Vertical);
+    assert!(cartridge.battery);
+    assert_eq!(cartridge.prg_nvram_size, 0x2000);
+    assert_eq!(cartridge.prg_ram_size, 0);
+    assert_eq!(cartridge.chr_ram_size, 0);
+    assert_eq!(cartridge.tv_system, TvSystem::Pal);
+    assert_eq!(cartridge.console, ConsoleType::Nes);
+    assert_eq!(cartridge.trainer, None);
+}
+
+#[test]
+fn ines_ignores_byte_9_for_sizes() {
+    // Byte 9 only holds the TV system in iNES; it must not add to the PRG ROM size
+    let cartridge = Cartridge::from_bytes(&rom([1, 0, 0, 0, 0, 0x0E, 0, 0, 0, 0, 0, 0], 0x4000)).unwrap();
+    assert_eq!(cartridge.prg_rom.len(), 0x4000);
+    assert_eq!(cartridge.chr_ram_size, 0x2000);
+    assert_eq!(cartridge.prg_ram_size, 0x2000);
+}
+
+#[test]
+fn ines_ignores_garbage_in_upper_nibble() {
+    let mut data = rom([1, 0, 0x10, 0x00, 0, 0, 0, 0, 0, 0, 0, 0], 0x4000);
+    data[7..16].copy_from_slice(b"DiskDude!");
+    let cartridge = Cartridge::from_bytes(&data).unwrap();
+    assert_eq!(cartridge.mapper, 1);
+}
+
+#[test]
+fn trainer_comes_before_prg() {
+    let cartridge = Cartridge::from_bytes(&rom([1, 0, 0x04, 0, 0, 0, 0, 0, 0, 0, 0, 0], 512 + 0x4000)).unwrap();
+    assert_eq!(cartridge.trainer.as_ref().map(Vec::len), Some(512));
+    assert_eq!(cartridge.prg_rom[0], 0x00);
+    assert_eq!(cartridge.prg_rom.len(), 0x4000);
+}
+
+#[test]
+fn parses_nes2() {
+    // Mapper 0x104 submapper 3, PRG MSB 1 (0x102 x 16KB), 0 CHR ROM, 8KB PRG RAM, 8KB PRG NVRAM, 8KB CHR RAM,
+    // four-screen, multi-region, Vs. System
+    let header = [0x02, 0x00, 0x48, 0x09, 0x31, 0x01, 0x77, 0x07, 0x02, 0, 0, 0];
+    let cartridge = Cartridge::from_bytes(&rom(header, 0x102 * 0x4000)).unwrap();
+    assert_eq!(cartridge.format, RomFormat::NES2);
+    assert_eq!(cartridge.mapper, 0x104);
+    assert_eq!(cartridge.submapper, 3);
+    assert_eq!(cartridge.prg_rom.len(), 0x102 * 0x4000);
+    assert!(cartridge.chr_rom.is_empty());
+    assert_eq!(cartridge.prg_ram_size, 0x2000);
+    assert_eq!(cartridge.prg_nvram_size, 0x2000);
+    assert_eq!(cartridge.chr_ram_size, 0x2000);
+    assert_eq!(cartridge.chr_nvram_size, 0);
+    assert_eq!(cartridge.mirroring, Mirroring::FourScreen);
+    assert_eq!(cartridge.console, ConsoleType::VsSystem);
+    assert_eq!(cartridge.tv_system, TvSystem::MultiRegion);
+}
+
+#[test]
+fn nes2_exponent_multiplier_sizes() {
+    // PRG: 2^10 * 3 bytes, CHR: 2^9 * 1 bytes
+    let header = [(10 << 2) | 1, 9 << 2, 0x00, 0x08, 0, 0xFF, 0, 0, 3, 0x01, 0, 0];
+    let cartridge = Cartridge::from_bytes(&rom(header, 3072 + 512)).unwrap();
+    assert_eq!(cartridge.prg_rom.len(), 3072);
+    assert_eq!(cartridge.chr_rom.len(), 512);
+    assert_eq!(cartridge.tv_system, TvSystem::Dendy);
+    assert_eq!(cartridge.console, ConsoleType::Nes);
+
+    // Extended console type from byte 13
+    let header = [1, 0, 0x00, 0x0B, 0, 0, 0, 0, 0, 0x03, 0, 0];
+    let cartridge = Cartridge::from_bytes(&rom(header, 0x4000)).unwrap();
+    assert_eq!(cartridge.console, ConsoleType::Extended(3));
+}
+
+#[test]
+fn rejects_bad_roms() {
+    assert_eq!(Cartridge::from_bytes(b"NES"), Err(RomError::BadMagic));
+    assert_eq!(Cartridge::from_bytes(&[0; 16]), Err(RomError::BadMagic));
+    assert_eq!(
+        Cartridge::from_bytes(&rom([2, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0], 0x100)),
+        Err(RomError::Truncated { expected: 16 + 0xA000, actual: 16 + 0x100 }),
+    );
+
+    // Exponent-multiplier sizes can't overflow
+    let header = [0xFF, 0, 0, 0x08, 0, 0x0F, 0, 0, 0, 0, 0, 0];
+    assert!(matches!(Cartridge::from_bytes(&rom(header, 0)), Err(RomError::Truncated { .. })));
+
+    assert!(matches!(Cartridge::load("does/not/exist.nes"), Err(RomError::Io(_))));
+}
@@ -0,0 +1,199 @@
+// Shared helpers for the integration tests (not every test uses all of them)
+#![allow(dead_code)]
+
+use emulatorr::{
+    asm,
+    core::{
+        cpu::{CPU, CpuConfig, Flags, StepOutcome},
+        bus::Bus,
+        memory::Memory,
+        rewind::RewindConfig,
+        trace::Tracer,
+    },
+    nes::{bus::NesBus, cartridge::Cartridge, mapper},
+};
+
+/// Cycles a program may take before it's assumed to be stuck
+pub const DEFAULT_BUDGET: u64 = 100_000;
+
+/// iNES cartridge holding `prg` (a multiple of 16KB) and `chr` (a multiple of 8KB, or empty for CHR RAM)
+///
+/// `flags6` is header byte 6: bit 0 selects vertical mirroring, and the high nibble another mapper than NROM.
+pub fn ines_cartridge(prg: &[u8], chr: &[u8], flags6: u8) -> Cartridge {
+    let mut rom = vec![0x4E, 0x45, 0x53, 0x1A, (prg.len() / 0x4000) as u8, (chr.len() / 0x2000) as u8, flags6];
+    rom.resize(16, 0);
+    rom.extend_from_slice(prg);
+    rom.extend_from_slice(chr);
+    Cartridge::from_bytes(&rom).unwrap()
+}
+
+/// `NesBus` with `cartridge` inserted
+pub fn nes_bus(cartridge: Cartridge) -> NesBus {
+    NesBus::new(mapper::from_cartridge(cartridge).unwrap())
+}
+
+/// CPU with a program loaded at 0x0600, run until BRK, with chainable assertions
+///
+/// ```ignore
+/// TestMachine::from_asm("LDA #$05\nBRK")
+///     .run()
+///     .a(0x05)
+///     .flag(Flags::Z, false);
+/// ```
+pub struct TestMachine {
+    pub cpu: CPU,
+    budget: u64,
+}
+
+impl TestMachine {
+    pub fn new() -> Self {
+        Self::with_config(CpuConfig::default())
+    }
+
+    pub fn with_config(config: CpuConfig) -> Self {
+        TestMachine {
+            cpu: CPU::with_config(Bus::new(), config),
+            budget: DEFAULT_BUDGET,
+        }
+    }
+
+    /// Assemble `source` for the NMOS 6502 and load it
+    pub fn from_asm(source: &str) -> Self {
+        Self::new().asm(source)
+    }
+
+    /// Assemble `source` for the configured variant and load it
+    ///
+    /// The program must start at the default origin (0x0600), where `CPU::load_program` puts it.
+    pub fn asm(mut self, source: &str) -> Self {
+        let program = asm::assemble_for(source, self.cpu.config().variant)
+            .unwrap_or_else(|error| panic!("{}", error));
+        assert_eq!(program.origin, asm::DEFAULT_ORIGIN, "program must start at the default origin");
+        self.cpu.load_program(program.bytes);
+        self
+    }
+
+    /// Load an already assembled program
+    pub fn program(mut self, program: Vec<u8>) -> Self {
+        self.cpu.load_program(program);
+        self
+    }
+
+    /// Write `bytes` to memory starting at `addr`
+    pub fn poke(mut self, addr: u16, bytes: &[u8]) -> Self {
+        for (i, byte) in bytes.iter().enumerate() {
+            self.cpu.write(addr.wrapping_add(i as u16), *byte);
+        }
+        self
+    }
+
+    /// Power on now, for tests that step the CPU themselves instead of calling `run`
+    pub fn powered_on(mut self) -> Self {
+        self.cpu.power_on();
+        self
+    }
+
+    /// Attach `tracer` to the CPU
+    pub fn tracer(mut self, tracer: Tracer) -> Self {
+        self.cpu.set_tracer(tracer);
+        self
+    }
+
+    /// Start recording rewind history
+    pub fn rewind(mut self, config: RewindConfig) -> Self {
+        self.cpu.enable_rewind(config);
+        self
+    }
+
+    /// Limit the number of cycles `run` may take
+    pub fn budget(mut self, cycles: u64) -> Self {
+        self.budget = cycles;
+        self
+    }
+
+    /// Power on and run until the PC reaches a BRK (which isn't executed), WAI or STP
+    ///
+    /// Panics if the CPU reports an error or the cycle budget runs out.
+    pub fn run(mut self) -> Self {
+        self.cpu.power_on();
+        loop {
+            if self.cpu.bus().peek(self.cpu.get_pc()) == 0x00 {
+                return self;
+            }
+            if self.cpu.cycles() > self.budget {
+                panic!("no BRK within {} cycles, PC at 0x{:04X}", self.budget, self.cpu.get_pc());
+            }
+
+            match self.cpu.step() {
+                Ok(StepOutcome::Waiting | StepOutcome::Stopped) => return self,
+                Ok(_) => {},
+                Err(error) => panic!("{}", error),
+            }
+        }
+    }
+
+    #[track_caller]
+    pub fn a(&self, value: u8) -> &Self {
+        assert_eq!(self.cpu.get_a(), value, "A");
+        self
+    }
+
+    #[track_caller]
+    pub fn x(&self, value: u8) -> &Self {
+        assert_eq!(self.cpu.get_x(), value, "X");
+        self
+    }
+
+    #[track_caller]
+    pub fn y(&self, value: u8) -> &Self {
+        assert_eq!(self.cpu.get_y(), value, "Y");
+        self
+    }
+
+    #[track_caller]
+    pub fn sp(&self, value: u8) -> &Self {
+        assert_eq!(self.cpu.get_sp(), value, "SP");
+        self
+    }
+
+    #[track_caller]
+    pub fn pc(&self, value: u16) -> &Self {
+        assert_eq!(self.cpu.get_pc(), value, "PC");
+        self
+    }
+
+    #[track_caller]
+    pub fn flag(&self, flag: Flags, set: bool) -> &Self {
+        let name = format!("{:?}", flag);
+        assert_eq!(self.cpu.get_sr() & flag as u8 != 0, set, "flag {}", name);
+        self
+    }
+
+    /// Assert the flags in `set` are set and those in `clear` are clear
+    #[track_caller]
+    pub fn flags(&self, set: &[Flags], clear: &[Flags]) -> &Self {
+        for flag in set {
+            self.flag(*flag, true);
+        }
+        for flag in clear {
+            self.flag(*flag, false);
+        }
+        self
+    }
+
+    #[track_caller]
+    pub fn mem(&self, addr: u16, value: u8) -> &Self {
+        assert_eq!(self.cpu.bus().peek(addr), value, "memory at 0x{:04X}", addr);
+        self
+    }
+
+    /// Assert memory starting at `addr` holds `bytes`
+    #[track_caller]
+    pub fn mem_range(&self, addr: u16, bytes: &[u8]) -> &Self {
+        let actual: Vec<u8> = (0..bytes.len())
+            .map(|i| self.cpu.bus().peek(addr.wrapping_add(i as u16)))
+            .collect();
+        assert_eq!(actual, bytes, "memory at 0x{:04X}", addr);
+        self
+    }
+}
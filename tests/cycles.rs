@@ -0,0 +1,123 @@
+use emulatorr::core::{
+    cpu::{
+        CPU,
+        CpuConfig,
+        CpuVariant,
+        Flags,
+    },
+    bus::Bus,
+    opcodes,
+    timing::{self, TimingState},
+};
+
+/// Construct CPU at `pc` with registers X and Y set, and `program` written at `pc`
+fn cpu_with(pc: u16, x: u8, y: u8, sr: u8, program: &[u8]) -> CPU {
+    let mut cpu: CPU = CPU::custom(0, x, y, 0xFF, pc, sr, 0, Bus::new());
+    for (i, byte) in program.iter().enumerate() {
+        cpu.write(pc + i as u16, *byte);
+    }
+    cpu
+}
+
+#[test]
+fn base_cycles() {
+    // LDA #$05
+    let mut cpu = cpu_with(0x0600, 0, 0, 0, &[0xA9, 0x05]);
+    assert_eq!(cpu.step().unwrap().cycles(), 2);
+    assert_eq!(cpu.cycles(), 2);
+
+    // ASL $1234,X
+    let mut cpu = cpu_with(0x0600, 0, 0, 0, &[0x1E, 0x34, 0x12]);
+    assert_eq!(cpu.step().unwrap().cycles(), 7);
+}
+
+#[test]
+fn page_cross_penalty() {
+    // LDA $2000,X without crossing a page
+    let mut cpu = cpu_with(0x0600, 0x10, 0, 0, &[0xBD, 0x00, 0x20]);
+    assert_eq!(cpu.step().unwrap().cycles(), 4);
+
+    // LDA $20F0,X crosses into page 0x21
+    let mut cpu = cpu_with(0x0600, 0x20, 0, 0, &[0xBD, 0xF0, 0x20]);
+    assert_eq!(cpu.step().unwrap().cycles(), 5);
+
+    // LDA ($10),Y crosses into page 0x21
+    let mut cpu = cpu_with(0x0600, 0, 0x20, 0, &[0xB1, 0x10]);
+    cpu.write(0x0010, 0xF0);
+    cpu.write(0x0011, 0x20);
+    assert_eq!(cpu.step().unwrap().cycles(), 6);
+}
+
+#[test]
+fn stores_ignore_page_cross() {
+    // STA $20F0,X always takes 5 cycles
+    let mut cpu = cpu_with(0x0600, 0x20, 0, 0, &[0x9D, 0xF0, 0x20]);
+    assert_eq!(cpu.step().unwrap().cycles(), 5);
+
+    let mut cpu = cpu_with(0x0600, 0x01, 0, 0, &[0x9D, 0x00, 0x20]);
+    assert_eq!(cpu.step().unwrap().cycles(), 5);
+}
+
+#[test]
+fn branch_penalties() {
+    // BNE not taken
+    let mut cpu = cpu_with(0x0600, 0, 0, Flags::Z as u8, &[0xD0, 0x10]);
+    assert_eq!(cpu.step().unwrap().cycles(), 2);
+    assert_eq!(cpu.get_pc(), 0x0602);
+
+    // BNE taken, same page
+    let mut cpu = cpu_with(0x0600, 0, 0, 0, &[0xD0, 0x10]);
+    assert_eq!(cpu.step().unwrap().cycles(), 3);
+    assert_eq!(cpu.get_pc(), 0x0612);
+
+    // BNE taken, into the next page
+    let mut cpu = cpu_with(0x06F0, 0, 0, 0, &[0xD0, 0x20]);
+    assert_eq!(cpu.step().unwrap().cycles(), 4);
+    assert_eq!(cpu.get_pc(), 0x0712);
+
+    // BNE taken, backwards into the previous page
+    let mut cpu = cpu_with(0x0600, 0, 0, 0, &[0xD0, 0xF0]);
+    assert_eq!(cpu.step().unwrap().cycles(), 4);
+    assert_eq!(cpu.get_pc(), 0x05F2);
+}
+
+#[test]
+fn cycles_accumulate() {
+    // LDA #$01, LDX #$02, STA $10, INX
+    let mut cpu = cpu_with(0x0600, 0, 0, 0, &[0xA9, 0x01, 0xA2, 0x02, 0x85, 0x10, 0xE8]);
+    let mut total: u64 = 0;
+    for _ in 0..4 {
+        total += cpu.step().unwrap().cycles() as u64;
+    }
+    assert_eq!(total, 2 + 2 + 3 + 2);
+    assert_eq!(cpu.cycles(), total);
+}
+
+#[test]
+fn timing_model_matches_cpu() {
+    let variants = [CpuVariant::Nmos6502, CpuVariant::Cmos65C02, CpuVariant::Rockwell65C02, CpuVariant::Wdc65C02];
+
+    for variant in variants {
+        for (opcode, op) in opcodes::table(variant).iter().enumerate() {
+            if op.illegal {
+                continue;
+            }
+            // Cover page crosses, taken and untaken branches, set and clear bits, and decimal mode
+            for (operand, index, sr) in [(0x00, 0x00, 0x00), (0xF0, 0x20, 0xFF), (0x80, 0xFF, 0x08), (0x7F, 0x01, 0xC3)] {
+                let pc = 0x06F0;
+                let config = CpuConfig { variant, ..Default::default() };
+                let mut cpu: CPU = CPU::with_config(Bus::filled(operand), config);
+                cpu.write(pc, opcode as u8);
+                cpu.set_pc(pc);
+                cpu.set_x(index);
+                cpu.set_y(index);
+                cpu.set_sr(sr);
+
+                let state = TimingState { pc, x: index, y: index, sr };
+                let model = timing::instruction_cycles(cpu.bus(), variant, state);
+                let cycles = cpu.step().unwrap().cycles();
+                assert_eq!(model, cycles, "{:?} opcode 0x{:02X}, operand 0x{:02X}, index 0x{:02X}, sr 0x{:02X}", variant, opcode, operand, index, sr);
+            }
+        }
+    }
+}
@@ -0,0 +1,111 @@
+mod common;
+
+use common::TestMachine;
+use emulatorr::core::{
+    bus::Bus,
+    cpu::{CpuVariant, CPU},
+    debug::{Access, Breakpoint, Condition, StopReason, WatchKind, Watchpoint},
+    trace::Registers,
+};
+
+fn registers(a: u8, x: u8, sr: u8) -> Registers {
+    Registers { a, x, y: 0, sp: 0xFD, pc: 0x0600, sr }
+}
+
+#[test]
+fn parses_conditions() {
+    let condition: Condition = "A == $FF && X > 3".parse().unwrap();
+    assert!(condition.evaluate(&registers(0xFF, 4, 0)));
+    assert!(!condition.evaluate(&registers(0xFF, 3, 0)));
+    assert!(!condition.evaluate(&registers(0xFE, 4, 0)));
+
+    // || binds weaker than &&, parentheses override it
+    let condition: Condition = "a == 1 || a == 2 && x == 0".parse().unwrap();
+    assert!(condition.evaluate(&registers(1, 5, 0)));
+    let condition: Condition = "(a == 1 || a == 2) && x == 0".parse().unwrap();
+    assert!(!condition.evaluate(&registers(1, 5, 0)));
+
+    // Flags are 0 or 1
+    let condition: Condition = "C == 1 && N != 1 && P >= %1".parse().unwrap();
+    assert!(condition.evaluate(&registers(0, 0, 0x01)));
+    assert!(!condition.evaluate(&registers(0, 0, 0x81)));
+
+    assert!("A ==".parse::<Condition>().is_err());
+    assert!("A = 1".parse::<Condition>().is_err());
+    assert!("Q == 1".parse::<Condition>().is_err());
+    assert!("(A == 1".parse::<Condition>().is_err());
+    assert!("A == $1FFFF".parse::<Condition>().is_err());
+}
+
+#[test]
+fn conditional_breakpoint_with_hit_count() {
+    let mut cpu = TestMachine::from_asm("
+            LDX #$00
+        loop:
+            INX
+            CPX #$10
+            BNE loop
+            BRK
+    ").powered_on().cpu;
+    let condition = "X >= 4".parse().unwrap();
+    cpu.set_breakpoint(Breakpoint::new(0x0603).when(condition).after_hits(2));
+
+    // X is 4 when CPX is reached for the first time with the condition holding
+    assert_eq!(cpu.run_until(|_| false), StopReason::Breakpoint { pc: 0x0603 });
+    assert_eq!(cpu.get_x(), 0x05);
+    assert_eq!(cpu.breakpoint(0x0603).unwrap().hits, 2);
+
+    // Later hits keep stopping
+    assert_eq!(cpu.run_until(|_| false), StopReason::Breakpoint { pc: 0x0603 });
+    assert_eq!(cpu.get_x(), 0x06);
+}
+
+#[test]
+fn watchpoints_stop_after_the_access() {
+    let mut cpu = TestMachine::from_asm("LDA #$2A\nSTA $0210\nLDX $0300\nINX\nBRK").powered_on().cpu;
+    cpu.add_watchpoint(Watchpoint::new(0x0200..=0x02FF, WatchKind::Write));
+    cpu.add_watchpoint(Watchpoint::new(0x0300..=0x0300, WatchKind::Read));
+
+    let stop = cpu.run_until(|_| false);
+    assert_eq!(stop, StopReason::Watchpoint { pc: 0x0602, addr: 0x0210, access: Access::Write, value: 0x2A });
+    assert_eq!(cpu.get_pc(), 0x0605);
+
+    let stop = cpu.run_until(|_| false);
+    assert_eq!(stop, StopReason::Watchpoint { pc: 0x0605, addr: 0x0300, access: Access::Read, value: 0x00 });
+    assert_eq!(cpu.watchpoints()[0].hits, 1);
+    assert_eq!(cpu.watchpoints()[1].hits, 1);
+
+    assert!(cpu.remove_watchpoint(&(0x0300..=0x0300)));
+    assert!(!cpu.remove_watchpoint(&(0x0300..=0x0300)));
+}
+
+#[test]
+fn access_watchpoint_sees_stack() {
+    let mut cpu = TestMachine::from_asm("LDA #$01\nPHA\nPLA\nBRK").powered_on().cpu;
+    cpu.add_watchpoint(Watchpoint::new(0x0100..=0x01FF, WatchKind::Access));
+
+    assert!(matches!(cpu.run_until(|_| false), StopReason::Watchpoint { access: Access::Write, .. }));
+    assert!(matches!(cpu.run_until(|_| false), StopReason::Watchpoint { access: Access::Read, .. }));
+}
+
+#[test]
+fn run_until_predicate() {
+    let mut cpu = TestMachine::from_asm("LDX #$00\nloop: INX\nJMP loop").powered_on().cpu;
+    assert_eq!(cpu.run_until(|cpu| cpu.get_x() == 0x20), StopReason::Predicate);
+    assert_eq!(cpu.get_x(), 0x20);
+}
+
+#[test]
+fn run_for_cycles() {
+    let mut cpu = TestMachine::from_asm("loop: JMP loop").powered_on().cpu;
+    let start = cpu.cycles();
+    assert_eq!(cpu.run_for(30), StopReason::CycleLimit);
+    // JMP takes 3 cycles
+    assert_eq!(cpu.cycles() - start, 30);
+
+    // STP stops the clock
+    let mut cpu: CPU = CPU::with_variant(Bus::new(), CpuVariant::Wdc65C02);
+    cpu.load_program(vec![0xDB]);
+    cpu.power_on();
+    assert_eq!(cpu.run_for(100), StopReason::Stopped);
+}
@@ -0,0 +1,90 @@
+use emulatorr::core::{
+    cpu::{
+        CPU,
+        CpuConfig,
+        Flags,
+    },
+    bus::Bus,
+};
+
+/// Run `SED, CLC/SEC, LDA #a, ADC/SBC #b, BRK` and return the CPU
+fn bcd(opcode: u8, a: u8, b: u8, carry: bool, config: CpuConfig) -> CPU {
+    let mut cpu: CPU = CPU::with_config(Bus::new(), config);
+    let carry_op = if carry { 0x38 } else { 0x18 };
+    cpu.quick_start(vec![0xF8, carry_op, 0xA9, a, opcode, b, 0x00]);
+    cpu
+}
+
+fn adc(a: u8, b: u8, carry: bool) -> CPU {
+    bcd(0x69, a, b, carry, CpuConfig::default())
+}
+
+fn sbc(a: u8, b: u8, carry: bool) -> CPU {
+    bcd(0xE9, a, b, carry, CpuConfig::default())
+}
+
+#[test]
+fn adc_decimal() {
+    let mut cpu = adc(0x12, 0x34, false);
+    assert_eq!(cpu.get_a(), 0x46);
+    assert!(!cpu.get_flag(Flags::C));
+
+    let mut cpu = adc(0x58, 0x46, true);
+    assert_eq!(cpu.get_a(), 0x05);
+    assert!(cpu.get_flag(Flags::C));
+
+    let mut cpu = adc(0x09, 0x01, false);
+    assert_eq!(cpu.get_a(), 0x10);
+    assert!(!cpu.get_flag(Flags::C));
+}
+
+#[test]
+fn adc_decimal_nmos_flags() {
+    // 99 + 01 = 00 with carry, but Z comes from the binary sum (0x9A) and N from the unadjusted sum (0xA0)
+    let mut cpu = adc(0x99, 0x01, false);
+    assert_eq!(cpu.get_a(), 0x00);
+    assert!(cpu.get_flag(Flags::C));
+    assert!(!cpu.get_flag(Flags::Z));
+    assert!(cpu.get_flag(Flags::N));
+
+    // 79 + 00 + 1 = 80: V set because the unadjusted signed sum overflows
+    let mut cpu = adc(0x79, 0x00, true);
+    assert_eq!(cpu.get_a(), 0x80);
+    assert!(cpu.get_flag(Flags::V));
+    assert!(cpu.get_flag(Flags::N));
+}
+
+#[test]
+fn sbc_decimal() {
+    let mut cpu = sbc(0x46, 0x12, true);
+    assert_eq!(cpu.get_a(), 0x34);
+    assert!(cpu.get_flag(Flags::C));
+
+    let mut cpu = sbc(0x40, 0x13, true);
+    assert_eq!(cpu.get_a(), 0x27);
+    assert!(cpu.get_flag(Flags::C));
+
+    let mut cpu = sbc(0x32, 0x02, false);
+    assert_eq!(cpu.get_a(), 0x29);
+    assert!(cpu.get_flag(Flags::C));
+
+    // 00 - 01 borrows and wraps to 99
+    let mut cpu = sbc(0x00, 0x01, true);
+    assert_eq!(cpu.get_a(), 0x99);
+    assert!(!cpu.get_flag(Flags::C));
+    assert!(!cpu.get_flag(Flags::Z));
+    assert!(cpu.get_flag(Flags::N));
+}
+
+#[test]
+fn decimal_mode_disabled() {
+    let config = CpuConfig { decimal_mode: false, ..Default::default() };
+
+    // D is still set, but arithmetic stays binary
+    let mut cpu = bcd(0x69, 0x09, 0x01, false, config);
+    assert_eq!(cpu.get_a(), 0x0A);
+    assert!(cpu.get_flag(Flags::D));
+
+    let cpu = bcd(0xE9, 0x10, 0x01, true, config);
+    assert_eq!(cpu.get_a(), 0x0F);
+}
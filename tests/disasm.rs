@@ -0,0 +1,113 @@
+use emulatorr::{
+    core::{
+        cpu::{AddressingMode, CpuVariant},
+        opcodes::Mnemonic,
+        bus::Bus,
+        memory::Memory,
+    },
+    disasm::{self, Instruction, SymbolTable},
+};
+
+const NMOS: CpuVariant = CpuVariant::Nmos6502;
+
+/// Disassemble `bytes` at 0x0600 and format each instruction
+fn formatted(bytes: &[u8], variant: CpuVariant) -> Vec<String> {
+    disasm::disassemble(bytes, 0x0600, variant).iter().map(|i| i.to_string()).collect()
+}
+
+#[test]
+fn decode_fields() {
+    let instruction = Instruction::decode(&[0xBD, 0x34, 0x12], 0x0600, NMOS).unwrap();
+    assert_eq!(instruction.addr, 0x0600);
+    assert_eq!(instruction.opcode, 0xBD);
+    assert_eq!(instruction.mnemonic, Mnemonic::LDA);
+    assert_eq!(instruction.mode, AddressingMode::ABX);
+    assert_eq!(instruction.operand, 0x1234);
+    assert_eq!(instruction.len, 3);
+    assert_eq!(instruction.bytes(), vec![0xBD, 0x34, 0x12]);
+
+    // Truncated
+    assert!(Instruction::decode(&[0xBD, 0x34], 0x0600, NMOS).is_none());
+}
+
+#[test]
+fn addressing_mode_syntax() {
+    let program = [
+        0xEA,             // NOP
+        0x0A,             // ASL A
+        0xA9, 0x05,       // LDA #$05
+        0xA5, 0x10,       // LDA $10
+        0xB5, 0x10,       // LDA $10,X
+        0xB6, 0x10,       // LDX $10,Y
+        0xAD, 0x34, 0x12, // LDA $1234
+        0xBD, 0x34, 0x12, // LDA $1234,X
+        0xB9, 0x34, 0x12, // LDA $1234,Y
+        0x6C, 0x34, 0x12, // JMP ($1234)
+        0xA1, 0x10,       // LDA ($10,X)
+        0xB1, 0x10,       // LDA ($10),Y
+    ];
+    assert_eq!(formatted(&program, NMOS), vec![
+        "NOP", "ASL A", "LDA #$05", "LDA $10", "LDA $10,X", "LDX $10,Y", "LDA $1234",
+        "LDA $1234,X", "LDA $1234,Y", "JMP ($1234)", "LDA ($10,X)", "LDA ($10),Y",
+    ]);
+}
+
+#[test]
+fn branch_targets_are_resolved() {
+    // BNE -2 (to itself), BEQ +4
+    assert_eq!(formatted(&[0xD0, 0xFE, 0xF0, 0x04], NMOS), vec!["BNE $0600", "BEQ $0608"]);
+}
+
+#[test]
+fn cmos_syntax() {
+    // LDA ($10), JMP ($1234,X), BBS3 $10,+0, RMB7 $20
+    let program = [0xB2, 0x10, 0x7C, 0x34, 0x12, 0xBF, 0x10, 0x00, 0x77, 0x20];
+    assert_eq!(formatted(&program, CpuVariant::Rockwell65C02), vec![
+        "LDA ($10)", "JMP ($1234,X)", "BBS3 $10,$0608", "RMB7 $20",
+    ]);
+}
+
+#[test]
+fn incomplete_trailing_instruction() {
+    assert_eq!(formatted(&[0xE8, 0xAD, 0x00], NMOS), vec!["INX"]);
+}
+
+#[test]
+fn symbols() {
+    let symbols = SymbolTable::parse("al 000610 .loop\nal C:00F0 .ptr\ncounter = $0200\n; comment\n");
+    assert_eq!(symbols.len(), 3);
+    assert_eq!(symbols.get(0x0610), Some("loop"));
+    assert_eq!(symbols.get(0x00F0), Some("ptr"));
+    assert_eq!(symbols.get(0x0200), Some("counter"));
+
+    // JSR loop, LDA (ptr),Y, INC counter, LDA #$10 (immediates aren't addresses)
+    let program = [0x20, 0x10, 0x06, 0xB1, 0xF0, 0xEE, 0x00, 0x02, 0xA9, 0x10];
+    let lines: Vec<String> = disasm::disassemble(&program, 0x0600, NMOS)
+        .iter()
+        .map(|i| i.format(Some(&symbols)))
+        .collect();
+    assert_eq!(lines, vec!["JSR loop", "LDA (ptr),Y", "INC counter", "LDA #$10"]);
+}
+
+#[test]
+fn listing() {
+    let mut symbols = SymbolTable::new();
+    symbols.insert(0x0600, "start");
+
+    let instruction = Instruction::decode(&[0xA9, 0x05], 0x0600, NMOS).unwrap();
+    assert_eq!(disasm::listing(&instruction, None), "0600  A9 05     LDA #$05");
+    assert_eq!(disasm::listing(&instruction, Some(&symbols)), "start:\n0600  A9 05     LDA #$05");
+}
+
+#[test]
+fn live_memory() {
+    let mut bus = Bus::new();
+    // LDX #$00, INX, BNE -3
+    for (i, byte) in [0xA2, 0x00, 0xE8, 0xD0, 0xFD].iter().enumerate() {
+        bus.write(0x0600 + i as u16, *byte);
+    }
+
+    let instructions = disasm::disassemble_memory(&bus, 0x0600, 3, NMOS);
+    let lines: Vec<String> = instructions.iter().map(|i| i.to_string()).collect();
+    assert_eq!(lines, vec!["LDX #$00", "INX", "BNE $0602"]);
+}
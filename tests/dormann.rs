@@ -0,0 +1,93 @@
+use std::path::PathBuf;
+
+use emulatorr::{
+    asm::assemble,
+    suites::dormann::{DormannOutcome, DormannSuite},
+};
+
+/// Directory holding the suite's binaries (`DORMANN_DIR`, or `tests/roms`)
+fn binary(name: &str) -> Option<PathBuf> {
+    let dir = std::env::var("DORMANN_DIR").unwrap_or_else(|_| "tests/roms".to_string());
+    let path = PathBuf::from(dir).join(name);
+    if path.exists() {
+        Some(path)
+    } else {
+        eprintln!("skipping, {} not found", path.display());
+        None
+    }
+}
+
+#[test]
+fn functional_test() {
+    let Some(path) = binary("6502_functional_test.bin") else { return };
+    let outcome = DormannSuite::functional().run_file(&path).unwrap();
+    assert!(outcome.passed(), "{}", outcome);
+}
+
+#[test]
+fn decimal_test() {
+    let Some(path) = binary("6502_decimal_test.bin") else { return };
+    let outcome = DormannSuite::decimal().run_file(&path).unwrap();
+    assert!(outcome.passed(), "{}", outcome);
+}
+
+/// A suite that starts at 0x0600 with its status byte at 0x0200
+fn suite(success: Option<u16>) -> DormannSuite {
+    DormannSuite {
+        origin: 0x0600,
+        start: 0x0600,
+        success,
+        status: 0x0200,
+        max_cycles: 10_000,
+        ..DormannSuite::functional()
+    }
+}
+
+#[test]
+fn trap_at_success() {
+    let program = assemble("
+            LDA #$F0
+            STA $0200
+        done:
+            JMP done
+    ").unwrap();
+
+    let outcome = suite(program.symbol("done")).run(&program.bytes);
+    assert!(matches!(outcome, DormannOutcome::Passed { .. }));
+}
+
+#[test]
+fn trap_elsewhere_reports_test_number() {
+    let program = assemble("
+            LDA #$2A
+            STA $0200
+            CMP #$00
+        fail:
+            BNE fail
+        done:
+            JMP done
+    ").unwrap();
+
+    match suite(program.symbol("done")).run(&program.bytes) {
+        DormannOutcome::Failed { pc, test, .. } => {
+            assert_eq!(pc, program.symbol("fail").unwrap());
+            assert_eq!(test, 0x2A);
+        },
+        outcome => panic!("unexpected outcome: {}", outcome),
+    }
+}
+
+#[test]
+fn status_decides_without_success_trap() {
+    let passing = assemble("LDA #0\nSTA $0200\nloop: JMP loop").unwrap();
+    assert!(suite(None).run(&passing.bytes).passed());
+
+    let failing = assemble("LDA #1\nSTA $0200\nloop: JMP loop").unwrap();
+    assert!(matches!(suite(None).run(&failing.bytes), DormannOutcome::Failed { test: 1, .. }));
+}
+
+#[test]
+fn timeout_without_trap() {
+    let program = assemble("loop: INX\nJMP loop").unwrap();
+    assert!(matches!(suite(None).run(&program.bytes), DormannOutcome::Timeout { .. }));
+}
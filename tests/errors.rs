@@ -0,0 +1,102 @@
+use emulatorr::core::{
+    cpu::{
+        CPU,
+        CpuConfig,
+        CpuError,
+        PowerOnState,
+        StepOutcome,
+        AddressingMode,
+    },
+    bus::Bus,
+};
+
+/// Power on CPU at 0x0600 with `program` written there, and SP at `sp`
+fn cpu_with(config: CpuConfig, sp: u8, program: &[u8]) -> CPU {
+    // Reset drops SP by 3
+    let power_on = PowerOnState::Fixed { a: 0, x: 0, y: 0, sp: sp.wrapping_add(3), sr: 0 };
+    let mut cpu: CPU = CPU::with_config(Bus::new(), CpuConfig { power_on, ..config });
+    cpu.load_program(program.to_vec());
+    cpu.power_on();
+    cpu
+}
+
+#[test]
+fn step_reports_cycles() {
+    let mut cpu = cpu_with(CpuConfig::default(), 0xFD, &[0xA9, 0x01]);
+    assert_eq!(cpu.step(), Ok(StepOutcome::Executed { cycles: 2 }));
+}
+
+#[test]
+fn stack_wraps_by_default() {
+    // PHA with SP at 0x00
+    let mut cpu = cpu_with(CpuConfig::default(), 0x00, &[0x48]);
+    assert!(cpu.step().is_ok());
+    assert_eq!(cpu.get_sp(), 0xFF);
+}
+
+#[test]
+fn stack_wrap_reported_when_disallowed() {
+    let config = CpuConfig { allow_stack_wrap: false, ..CpuConfig::default() };
+
+    // PHA with SP at 0x00: the push still happens
+    let mut cpu = cpu_with(config, 0x00, &[0x48]);
+    assert_eq!(cpu.step(), Err(CpuError::StackWrap { pc: 0x0600, sp: 0x00 }));
+    assert_eq!(cpu.get_sp(), 0xFF);
+
+    // PLA with SP at 0xFF
+    let mut cpu = cpu_with(config, 0xFF, &[0x68]);
+    assert_eq!(cpu.step(), Err(CpuError::StackWrap { pc: 0x0600, sp: 0xFF }));
+    assert_eq!(cpu.get_sp(), 0x00);
+}
+
+#[test]
+fn breakpoints() {
+    // LDA #$01, LDX #$02, LDY #$03
+    let mut cpu = cpu_with(CpuConfig::default(), 0xFD, &[0xA9, 0x01, 0xA2, 0x02, 0xA0, 0x03]);
+    cpu.add_breakpoint(0x0602);
+
+    assert!(cpu.step().is_ok());
+    assert_eq!(cpu.step(), Err(CpuError::BreakpointHit { pc: 0x0602 }));
+    assert_eq!(cpu.get_x(), 0x00);
+
+    // Stepping again resumes past the breakpoint
+    assert!(cpu.step().is_ok());
+    assert_eq!(cpu.get_x(), 0x02);
+
+    assert!(cpu.remove_breakpoint(0x0602));
+    assert!(!cpu.remove_breakpoint(0x0602));
+}
+
+#[test]
+fn clock_stops_at_breakpoint() {
+    let mut cpu = cpu_with(CpuConfig::default(), 0xFD, &[0xA9, 0x01, 0xA2, 0x02, 0x00]);
+    cpu.add_breakpoint(0x0602);
+    assert_eq!(cpu.clock(), Err(CpuError::BreakpointHit { pc: 0x0602 }));
+    assert_eq!(cpu.clock(), Ok(()));
+    assert_eq!(cpu.get_x(), 0x02);
+}
+
+#[test]
+fn implied_addressing_consumes_nothing() {
+    let mut cpu: CPU = CPU::custom(0, 0, 0, 0xFF, 0x0600, 0, 0, Bus::new());
+    assert_eq!(cpu.get_address(AddressingMode::IMP), 0x0600);
+    assert_eq!(cpu.get_address(AddressingMode::ACC), 0x0600);
+    assert_eq!(cpu.get_pc(), 0x0600);
+}
+
+#[test]
+fn pc_wraps_around() {
+    // LDA #$01 at 0xFFFF, with its operand at 0x0000
+    let mut cpu: CPU = CPU::custom(0, 0, 0, 0xFF, 0xFFFF, 0, 0, Bus::new());
+    cpu.write(0xFFFF, 0xA9);
+    cpu.write(0x0000, 0x01);
+    assert!(cpu.step().is_ok());
+    assert_eq!(cpu.get_a(), 0x01);
+    assert_eq!(cpu.get_pc(), 0x0001);
+}
+
+#[test]
+fn display() {
+    let error = CpuError::IllegalOpcode { pc: 0x0600, opcode: 0x02 };
+    assert_eq!(error.to_string(), "illegal opcode 0x02 at 0x0600");
+}
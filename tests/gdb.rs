@@ -0,0 +1,145 @@
+#![cfg(feature = "gdbstub")]
+
+use std::{
+    io::{Read, Write},
+    net::TcpStream,
+    thread,
+};
+
+use emulatorr::{
+    asm::assemble,
+    core::{bus::Bus, cpu::CPU, trace::Registers},
+    gdb::GdbStub,
+};
+
+/// Minimal debugger side of the remote serial protocol
+struct Client {
+    stream: TcpStream,
+}
+
+impl Client {
+    fn send_raw(&mut self, packet: &str) {
+        let checksum = packet.bytes().fold(0u8, |sum, byte| sum.wrapping_add(byte));
+        write!(self.stream, "${}#{:02x}", packet, checksum).unwrap();
+    }
+
+    /// Send `packet` and return the reply, acknowledging both ways
+    fn request(&mut self, packet: &str) -> String {
+        self.send_raw(packet);
+        assert_eq!(self.byte(), b'+');
+        self.reply()
+    }
+
+    fn reply(&mut self) -> String {
+        while self.byte() != b'$' {}
+        let mut data = Vec::new();
+        loop {
+            match self.byte() {
+                b'#' => break,
+                byte => data.push(byte),
+            }
+        }
+        self.byte();
+        self.byte();
+        self.stream.write_all(b"+").unwrap();
+        String::from_utf8(data).unwrap()
+    }
+
+    fn byte(&mut self) -> u8 {
+        let mut byte = [0];
+        self.stream.read_exact(&mut byte).unwrap();
+        byte[0]
+    }
+}
+
+/// Serve a CPU running `source` on a background thread, and connect to it
+///
+/// The thread returns the registers and memory once the session ends.
+fn connect(source: &'static str) -> (Client, thread::JoinHandle<(Registers, Vec<u8>)>) {
+    let stub = GdbStub::bind("127.0.0.1:0").unwrap();
+    let addr = stub.local_addr().unwrap();
+    let server = thread::spawn(move || {
+        let mut cpu: CPU = CPU::new(Bus::new());
+        cpu.load_program(assemble(source).unwrap().bytes);
+        cpu.power_on();
+        stub.serve(&mut cpu).unwrap();
+        (cpu.registers(), cpu.get_memory().to_vec())
+    });
+    (Client { stream: TcpStream::connect(addr).unwrap() }, server)
+}
+
+#[test]
+fn registers_and_memory() {
+    let (mut client, server) = connect("LDA #$2A\nLDX #$07\nBRK");
+
+    assert!(client.request("qSupported:swbreak+").contains("qXfer:features:read+"));
+    assert!(client.request("qXfer:features:read:target.xml:0,1000").starts_with("l<?xml"));
+    assert_eq!(client.request("?"), "S05");
+
+    // A, X, Y, SP, PC (little endian), P after reset
+    assert_eq!(client.request("g"), "000000fd000624");
+    assert_eq!(client.request("m600,4"), "a92aa207");
+    assert_eq!(client.request("M10,2:beef"), "OK");
+    assert_eq!(client.request("m10,2"), "beef");
+
+    assert_eq!(client.request("P2=55"), "OK");
+    assert_eq!(client.request("p2"), "55");
+    assert_eq!(client.request("p9"), "E01");
+    assert_eq!(client.request("vMustReplyEmpty"), "");
+
+    assert_eq!(client.request("s"), "S05");
+    assert_eq!(client.request("p0"), "2a");
+    assert_eq!(client.request("p4"), "0206");
+
+    assert_eq!(client.request("D"), "OK");
+    let (registers, memory) = server.join().unwrap();
+    assert_eq!(registers.y, 0x55);
+    assert_eq!(memory[0x10], 0xBE);
+}
+
+#[test]
+fn breakpoints_and_watchpoints() {
+    let (mut client, server) = connect("
+            LDX #$00
+        loop:
+            INX
+            STX $0300
+            JMP loop
+    ");
+
+    assert_eq!(client.request("Z0,603,1"), "OK");
+    assert_eq!(client.request("c"), "T05swbreak:;");
+    assert_eq!(client.request("p1"), "01");
+
+    // Continuing from the breakpoint runs a whole loop
+    assert_eq!(client.request("c"), "T05swbreak:;");
+    assert_eq!(client.request("p1"), "02");
+    assert_eq!(client.request("z0,603,1"), "OK");
+
+    assert_eq!(client.request("Z2,300,1"), "OK");
+    assert_eq!(client.request("c"), "T05watch:0300;");
+    assert_eq!(client.request("m300,1"), "02");
+    assert_eq!(client.request("z2,300,1"), "OK");
+
+    // Interrupt a free-running loop
+    client.send_raw("c");
+    assert_eq!(client.byte(), b'+');
+    client.stream.write_all(&[0x03]).unwrap();
+    assert_eq!(client.reply(), "S05");
+
+    client.send_raw("k");
+    assert_eq!(client.byte(), b'+');
+    server.join().unwrap();
+}
+
+#[test]
+fn no_ack_mode() {
+    let (mut client, server) = connect("BRK");
+
+    assert_eq!(client.request("QStartNoAckMode"), "OK");
+    client.send_raw("m600,1");
+    assert_eq!(client.reply(), "00");
+
+    drop(client);
+    server.join().unwrap();
+}
@@ -0,0 +1,125 @@
+use std::path::PathBuf;
+
+use emulatorr::{
+    core::{
+        cpu::CpuVariant,
+        opcodes,
+    },
+    suites::harte::{parse_cases, BusAccess, HarteRunner},
+};
+
+// LDA #$42 at 0x0200
+const LDA_IMM: &str = r#"[{
+    "name": "a9 42 00",
+    "initial": { "pc": 512, "s": 253, "a": 0, "x": 0, "y": 0, "p": 36, "ram": [[512, 169], [513, 66]] },
+    "final": { "pc": 514, "s": 253, "a": 66, "x": 0, "y": 0, "p": 36, "ram": [[512, 169], [513, 66]] },
+    "cycles": [[512, 169, "read"], [513, 66, "read"]]
+}]"#;
+
+// BNE +$10 at 0x02FD, taken into the next page
+const BNE_TAKEN: &str = r#"[{
+    "name": "d0 10 00",
+    "initial": { "pc": 765, "s": 253, "a": 0, "x": 0, "y": 0, "p": 32, "ram": [[765, 208], [766, 16]] },
+    "final": { "pc": 783, "s": 253, "a": 0, "x": 0, "y": 0, "p": 32, "ram": [[765, 208], [766, 16]] },
+    "cycles": [[765, 208, "read"], [766, 16, "read"], [767, 0, "read"], [527, 0, "read"]]
+}]"#;
+
+#[test]
+fn parses_cases() {
+    let cases = parse_cases(LDA_IMM).unwrap();
+    assert_eq!(cases.len(), 1);
+
+    let case = &cases[0];
+    assert_eq!(case.name, "a9 42 00");
+    assert_eq!(case.initial.pc, 0x0200);
+    assert_eq!(case.initial.ram, vec![(0x0200, 0xA9), (0x0201, 0x42)]);
+    assert_eq!(case.after.a, 0x42);
+    assert_eq!(case.cycles.len(), 2);
+    assert_eq!(case.cycles[1].addr, 0x0201);
+    assert_eq!(case.cycles[1].access, BusAccess::Read);
+
+    assert!(parse_cases("{}").is_err());
+    assert!(parse_cases("[{\"name\": \"a9\"}]").is_err());
+    assert!(parse_cases("[1, 2").is_err());
+}
+
+#[test]
+fn passing_case() {
+    let runner = HarteRunner::new(CpuVariant::Nmos6502);
+    let report = runner.run_all(&parse_cases(LDA_IMM).unwrap());
+    assert!(report.passed(), "{}", report);
+    assert_eq!(report.to_string(), "1/1 cases passed");
+}
+
+#[test]
+fn dummy_accesses() {
+    let cases = parse_cases(BNE_TAKEN).unwrap();
+
+    // The CPU's own accesses are all in the log, and the totals match
+    let runner = HarteRunner::new(CpuVariant::Nmos6502);
+    assert_eq!(runner.run(&cases[0]), vec![]);
+
+    // ...but it doesn't do the dummy reads of the last 2 cycles
+    let strict = HarteRunner { strict_bus: true, ..runner };
+    let fields: Vec<String> = strict.run(&cases[0]).into_iter().map(|mismatch| mismatch.field).collect();
+    assert_eq!(fields, vec!["bus[2]", "bus[3]"]);
+}
+
+#[test]
+fn reports_mismatches() {
+    let source = LDA_IMM
+        .replace("\"a\": 66", "\"a\": 67")
+        .replace("[[512, 169, \"read\"]", "[[511, 0, \"read\"], [512, 169, \"read\"]");
+    let report = HarteRunner::new(CpuVariant::Nmos6502).run_all(&parse_cases(&source).unwrap());
+
+    assert!(!report.passed());
+    let (name, mismatches) = &report.failures[0];
+    assert_eq!(name, "a9 42 00");
+    let fields: Vec<&str> = mismatches.iter().map(|mismatch| mismatch.field.as_str()).collect();
+    assert_eq!(fields, vec!["a", "cycles", "model cycles"]);
+    assert_eq!(mismatches[0].to_string(), "a: expected 0x43, got 0x42");
+}
+
+/// Directory holding the suite's files for `suite` (`HARTE_DIR`, or `tests/roms/harte`), e.g. `6502/v1`
+fn suite_dir(suite: &str) -> Option<PathBuf> {
+    let dir = std::env::var("HARTE_DIR").unwrap_or_else(|_| "tests/roms/harte".to_string());
+    let path = PathBuf::from(dir).join(suite);
+    if path.is_dir() {
+        Some(path)
+    } else {
+        eprintln!("skipping, {} not found", path.display());
+        None
+    }
+}
+
+fn run_suite(suite: &str, variant: CpuVariant) {
+    let Some(dir) = suite_dir(suite) else { return };
+    let runner = HarteRunner::new(variant);
+    let mut failed = Vec::new();
+
+    // Undocumented opcodes are only checked on request, as several of them are unstable on real hardware
+    let illegal = std::env::var("HARTE_ILLEGAL").is_ok();
+    for (opcode, op) in opcodes::table(variant).iter().enumerate() {
+        let path = dir.join(format!("{:02x}.json", opcode));
+        if (op.illegal && !illegal) || !path.exists() {
+            continue;
+        }
+        let report = runner.run_file(&path).unwrap();
+        if !report.passed() {
+            eprintln!("opcode 0x{:02X}: {}", opcode, report);
+            failed.push(opcode);
+        }
+    }
+
+    assert!(failed.is_empty(), "failing opcodes: {:02X?}", failed);
+}
+
+#[test]
+fn nmos_suite() {
+    run_suite("6502/v1", CpuVariant::Nmos6502);
+}
+
+#[test]
+fn wdc_suite() {
+    run_suite("wdc65c02/v1", CpuVariant::Wdc65C02);
+}
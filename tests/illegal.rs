@@ -0,0 +1,149 @@
+use emulatorr::core::{
+    cpu::{
+        CPU,
+        CpuConfig,
+        CpuError,
+        Flags,
+    },
+    bus::Bus,
+};
+
+#[test]
+fn lax_zp0() {
+    let mut cpu: CPU = CPU::new(Bus::new());
+    cpu.write(0x0010, 0x8F);
+    cpu.quick_start(vec![0xA7, 0x10, 0x00]);
+    assert_eq!(cpu.get_a(), 0x8F);
+    assert_eq!(cpu.get_x(), 0x8F);
+    assert!(cpu.get_flag(Flags::N));
+}
+
+#[test]
+fn sax_zp0() {
+    let mut cpu: CPU = CPU::new(Bus::new());
+    // LDA 0xF0, LDX 0x3C, SAX 0x10
+    cpu.quick_start(vec![0xA9, 0xF0, 0xA2, 0x3C, 0x87, 0x10, 0x00]);
+    assert_eq!(cpu.read(0x0010), 0x30);
+}
+
+#[test]
+fn dcp_zp0() {
+    let mut cpu: CPU = CPU::new(Bus::new());
+    cpu.write(0x0010, 0x43);
+    // LDA 0x42, DCP 0x10
+    cpu.quick_start(vec![0xA9, 0x42, 0xC7, 0x10, 0x00]);
+    assert_eq!(cpu.read(0x0010), 0x42);
+    assert!(cpu.get_flag(Flags::Z));
+    assert!(cpu.get_flag(Flags::C));
+}
+
+#[test]
+fn isc_zp0() {
+    let mut cpu: CPU = CPU::new(Bus::new());
+    cpu.write(0x0010, 0x04);
+    // SEC, LDA 0x10, ISC 0x10
+    cpu.quick_start(vec![0x38, 0xA9, 0x10, 0xE7, 0x10, 0x00]);
+    assert_eq!(cpu.read(0x0010), 0x05);
+    assert_eq!(cpu.get_a(), 0x0B);
+    assert!(cpu.get_flag(Flags::C));
+}
+
+#[test]
+fn slo_rla_sre_rra() {
+    let mut cpu: CPU = CPU::new(Bus::new());
+
+    // LDA 0x01, SLO 0x10 (0x81 -> 0x02, C set), A = 0x03
+    cpu.write(0x0010, 0x81);
+    cpu.quick_start(vec![0xA9, 0x01, 0x07, 0x10, 0x00]);
+    assert_eq!(cpu.read(0x0010), 0x02);
+    assert_eq!(cpu.get_a(), 0x03);
+    assert!(cpu.get_flag(Flags::C));
+
+    // SEC, LDA 0xFF, RLA 0x10 (0x40 -> 0x81), A = 0x81
+    cpu.write(0x0010, 0x40);
+    cpu.quick_start(vec![0x38, 0xA9, 0xFF, 0x27, 0x10, 0x00]);
+    assert_eq!(cpu.read(0x0010), 0x81);
+    assert_eq!(cpu.get_a(), 0x81);
+    assert!(!cpu.get_flag(Flags::C));
+
+    // LDA 0x0F, SRE 0x10 (0x03 -> 0x01, C set), A = 0x0E
+    cpu.write(0x0010, 0x03);
+    cpu.quick_start(vec![0xA9, 0x0F, 0x47, 0x10, 0x00]);
+    assert_eq!(cpu.read(0x0010), 0x01);
+    assert_eq!(cpu.get_a(), 0x0E);
+    assert!(cpu.get_flag(Flags::C));
+
+    // LDA 0x10, RRA 0x10 (0x03 -> 0x01, C set), A = 0x10 + 0x01 + 1
+    cpu.write(0x0010, 0x03);
+    cpu.quick_start(vec![0xA9, 0x10, 0x67, 0x10, 0x00]);
+    assert_eq!(cpu.read(0x0010), 0x01);
+    assert_eq!(cpu.get_a(), 0x12);
+    assert!(!cpu.get_flag(Flags::C));
+}
+
+#[test]
+fn immediate_combinations() {
+    let mut cpu: CPU = CPU::new(Bus::new());
+
+    // LDA 0xF0, ANC 0x80
+    cpu.quick_start(vec![0xA9, 0xF0, 0x0B, 0x80, 0x00]);
+    assert_eq!(cpu.get_a(), 0x80);
+    assert!(cpu.get_flag(Flags::C));
+
+    // LDA 0xFF, ALR 0x03
+    cpu.quick_start(vec![0xA9, 0xFF, 0x4B, 0x03, 0x00]);
+    assert_eq!(cpu.get_a(), 0x01);
+    assert!(cpu.get_flag(Flags::C));
+
+    // SEC, LDA 0xFF, ARR 0xC0: (0xC0 >> 1) | 0x80 = 0xE0, C = bit 6, V = bit 6 ^ bit 5
+    cpu.quick_start(vec![0x38, 0xA9, 0xFF, 0x6B, 0xC0, 0x00]);
+    assert_eq!(cpu.get_a(), 0xE0);
+    assert!(cpu.get_flag(Flags::C));
+    assert!(!cpu.get_flag(Flags::V));
+
+    // LDA 0x0F, LDX 0xFC, SBX 0x02: X = (0x0F & 0xFC) - 0x02
+    cpu.quick_start(vec![0xA9, 0x0F, 0xA2, 0xFC, 0xCB, 0x02, 0x00]);
+    assert_eq!(cpu.get_x(), 0x0A);
+    assert!(cpu.get_flag(Flags::C));
+}
+
+#[test]
+fn multi_byte_nops() {
+    let mut cpu: CPU = CPU::custom(0, 0, 0, 0xFF, 0x0600, 0, 0, Bus::new());
+    // NOP #imm, NOP zp, NOP zp,X, NOP abs, NOP abs,X
+    let program = [0x80, 0x01, 0x04, 0x02, 0x14, 0x03, 0x0C, 0x00, 0x30, 0x1C, 0x00, 0x30];
+    for (i, byte) in program.iter().enumerate() {
+        cpu.write(0x0600 + i as u16, *byte);
+    }
+
+    let cycles: Vec<u8> = (0..5).map(|_| cpu.step().unwrap().cycles()).collect();
+    assert_eq!(cycles, vec![2, 3, 4, 4, 4]);
+    assert_eq!(cpu.get_pc(), 0x0600 + program.len() as u16);
+}
+
+#[test]
+fn jam_halts_until_reset() {
+    let mut cpu: CPU = CPU::new(Bus::new());
+    cpu.quick_start(vec![0xA9, 0x01, 0x02, 0xA9, 0x02, 0x00]);
+    assert!(cpu.is_jammed());
+    assert_eq!(cpu.get_a(), 0x01);
+    assert_eq!(cpu.get_pc(), 0x0602);
+
+    assert_eq!(cpu.step(), Err(CpuError::Jammed { pc: 0x0602 }));
+    assert_eq!(cpu.get_pc(), 0x0602);
+
+    cpu.reset();
+    assert!(!cpu.is_jammed());
+}
+
+#[test]
+fn strict_mode_traps() {
+    let mut cpu: CPU = CPU::with_config(Bus::new(), CpuConfig { strict: true, ..Default::default() });
+    cpu.load_program(vec![0xA9, 0x01, 0xA7, 0x10, 0x00]);
+    cpu.power_on();
+
+    // LAX is trapped before it has any effect
+    assert_eq!(cpu.clock(), Err(CpuError::IllegalOpcode { pc: 0x0602, opcode: 0xA7 }));
+    assert_eq!(cpu.get_pc(), 0x0602);
+    assert_eq!(cpu.get_x(), 0x00);
+}
@@ -0,0 +1,145 @@
+mod common;
+
+use common::{ines_cartridge, nes_bus};
+use emulatorr::{
+    core::memory::Memory,
+    nes::{
+        bus::NesBus,
+        cartridge::Cartridge,
+        input::{Button, ControllerState, Device, FourScore, Zapper, ZapperState},
+        Nes,
+    },
+};
+
+fn cartridge() -> Cartridge {
+    ines_cartridge(&[0xEA; 0x4000], &[0; 0x2000], 0)
+}
+
+fn bus() -> NesBus {
+    nes_bus(cartridge())
+}
+
+/// Strobe the controllers, then read `count` bits from `addr`
+fn read_bits(bus: &mut NesBus, addr: u16, count: usize) -> Vec<u8> {
+    bus.write(0x4016, 1);
+    bus.write(0x4016, 0);
+    (0..count).map(|_| bus.read(addr) & 0x01).collect()
+}
+
+#[test]
+fn controller_state() {
+    let mut state = ControllerState::default();
+    state.press(Button::A);
+    state.press(Button::Start);
+    state.set(Button::Left, true);
+    assert_eq!(state.bits(), 0b0100_1001);
+    assert!(state.is_pressed(Button::Start));
+    state.release(Button::Start);
+    assert!(!state.is_pressed(Button::Start));
+    assert_eq!(ControllerState::from_bits(0b0100_0001), state);
+    state.clear();
+    assert_eq!(state.bits(), 0);
+}
+
+#[test]
+fn standard_controller_shifts_buttons_out() {
+    let mut nes = Nes::new(cartridge()).unwrap();
+    let mut state = ControllerState::default();
+    state.press(Button::A);
+    state.press(Button::Select);
+    state.press(Button::Right);
+    assert!(nes.set_controller(0, state));
+    assert!(nes.set_controller(1, ControllerState::from_bits(0x02)));
+    // Players 3 and 4 need a four-player adapter
+    assert!(!nes.set_controller(2, state));
+
+    let bus = nes.cpu_mut().bus_mut();
+    assert_eq!(read_bits(bus, 0x4016, 10), [1, 0, 1, 0, 0, 0, 0, 1, 1, 1]);
+    assert_eq!(read_bits(bus, 0x4017, 8), [0, 1, 0, 0, 0, 0, 0, 0]);
+    // Open bus in the upper bits
+    assert_eq!(bus.read(0x4016) & 0xE0, 0x40);
+    // Peeking doesn't shift
+    bus.write(0x4016, 1);
+    bus.write(0x4016, 0);
+    assert_eq!(bus.peek(0x4016), 0x41);
+    assert_eq!(bus.peek(0x4016), 0x41);
+}
+
+#[test]
+fn strobe_high_keeps_reading_a() {
+    let mut bus = bus();
+    if let Device::Controller(controller) = bus.port_mut(0) {
+        controller.set_state(ControllerState::from_bits(0xFE));
+    }
+    bus.write(0x4016, 1);
+    assert!((0..4).all(|_| bus.read(0x4016) & 0x01 == 0));
+
+    // State changes are latched while the strobe is high
+    if let Device::Controller(controller) = bus.port_mut(0) {
+        controller.set_state(ControllerState::from_bits(0x01));
+    }
+    assert_eq!(bus.read(0x4016) & 0x01, 1);
+}
+
+#[test]
+fn four_score() {
+    let mut nes = Nes::new(cartridge()).unwrap();
+    nes.connect(0, Device::FourScore(FourScore::new(0)));
+    nes.connect(1, Device::FourScore(FourScore::new(1)));
+    for player in 0..4 {
+        assert!(nes.set_controller(player, ControllerState::from_bits(1 << player)));
+    }
+
+    let bus = nes.cpu_mut().bus_mut();
+    let port1 = read_bits(bus, 0x4016, 25);
+    assert_eq!(port1[0..8], [1, 0, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(port1[8..16], [0, 0, 1, 0, 0, 0, 0, 0]);
+    assert_eq!(port1[16..25], [0, 0, 0, 1, 0, 0, 0, 0, 1]);
+
+    let port2 = read_bits(bus, 0x4017, 24);
+    assert_eq!(port2[0..8], [0, 1, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(port2[8..16], [0, 0, 0, 1, 0, 0, 0, 0]);
+    assert_eq!(port2[16..24], [0, 0, 1, 0, 0, 0, 0, 0]);
+}
+
+#[test]
+fn disconnected_port() {
+    let mut bus = bus();
+    let previous = bus.connect(1, Device::Disconnected);
+    assert!(matches!(previous, Device::Controller(_)));
+    assert_eq!(read_bits(&mut bus, 0x4017, 8), [0; 8]);
+    assert_eq!(bus.read(0x4017), 0x40);
+}
+
+#[test]
+fn zapper_trigger_and_light() {
+    let mut nes = Nes::new(cartridge()).unwrap();
+    assert!(!nes.set_zapper(ZapperState::default()));
+    nes.connect(1, Device::Zapper(Zapper::new()));
+    assert!(nes.set_zapper(ZapperState { aim: None, trigger: true }));
+
+    let bus = nes.cpu_mut().bus_mut();
+    // Trigger pulled, no light
+    assert_eq!(bus.read(0x4017) & 0x18, 0x18);
+
+    // White backdrop, drawn even with rendering disabled
+    bus.write(0x2006, 0x3F);
+    bus.write(0x2006, 0x00);
+    bus.write(0x2007, 0x30);
+    while bus.ppu().scanline() != 100 {
+        bus.tick(1);
+    }
+
+    let aim = |bus: &mut NesBus, x, y| {
+        if let Device::Zapper(zapper) = bus.port_mut(1) {
+            zapper.set_state(ZapperState { aim: Some((x, y)), trigger: false });
+        }
+        bus.read(0x4017) & 0x18
+    };
+    // Light is sensed just behind the beam
+    assert_eq!(aim(bus, 50, 90), 0x00);
+    // Not where the beam passed long ago or hasn't reached yet
+    assert_eq!(aim(bus, 50, 20), 0x08);
+    assert_eq!(aim(bus, 50, 150), 0x08);
+    assert_eq!(aim(bus, 300, 90), 0x08);
+}
@@ -1,256 +1,195 @@
-use emulatorr::core::{
-    cpu::{
-        CPU,
-        Flags,
-    },
-    bus::Bus,
-};
+mod common;
+
+use common::TestMachine;
+use emulatorr::core::cpu::Flags;
 
 // TODO: Test all instructions
 #[test]
 fn lda_imm_flags() {
-    let bus: Bus = Bus::new();
-    let mut cpu: CPU = CPU::new(bus);
-    cpu.quick_start(vec![0xA9, 0x05, 0x00]);
-
-    assert_eq!(cpu.get_a(), 0x05);
-    assert!(!cpu.get_flag(Flags::Z));
-    assert!(!cpu.get_flag(Flags::N));
+    TestMachine::from_asm("LDA #$05\nBRK")
+        .run()
+        .a(0x05)
+        .flags(&[], &[Flags::Z, Flags::N]);
 }
 
 #[test]
 fn lda_imm_zero_flag() {
-    let bus: Bus = Bus::new();
-    let mut cpu: CPU = CPU::new(bus);
-    cpu.quick_start(vec![0xA9, 0x00, 0x00]);
-
-    assert!(cpu.get_flag(Flags::Z));
+    TestMachine::from_asm("LDA #$00\nBRK")
+        .run()
+        .flag(Flags::Z, true);
 }
 
 #[test]
 fn lda_zp0() {
-    let bus: Bus = Bus::new();
-    let mut cpu: CPU = CPU::new(bus);
-    // write 0x55 to address 0x10
-    cpu.write(0x10, 0x55);
-    // LDA from address 0x10
-    cpu.quick_start(vec![0xA5, 0x10, 0x00]);
-
-    assert_eq!(cpu.get_a(), 0x55);
+    TestMachine::from_asm("LDA $10\nBRK")
+        .poke(0x10, &[0x55])
+        .run()
+        .a(0x55);
 }
 
 #[test]
 fn adc_imm() {
-    let bus: Bus = Bus::new();
-    let mut cpu: CPU = CPU::new(bus);
-
     // No carry -> no carry
-    {
-        cpu.quick_start(vec![0xA9, 0x10, 0x69, 0x02, 0x00]);
-        assert_eq!(cpu.get_a(), 0x12);
-        assert!(!cpu.get_flag(Flags::C));
-        assert!(!cpu.get_flag(Flags::V));
-    }
+    TestMachine::from_asm("LDA #$10\nADC #$02\nBRK")
+        .run()
+        .a(0x12)
+        .flags(&[], &[Flags::C, Flags::V]);
 
     // Carry -> no carry
-    {
-        // TODO: how to verify carry?
-        cpu.set_flag(Flags::C, true);
-        cpu.quick_start(vec![0xA9, 0x10, 0x69, 0x02, 0x00]);
-        assert_eq!(cpu.get_a(), 0x12);
-        assert!(!cpu.get_flag(Flags::C));
-        assert!(!cpu.get_flag(Flags::V));
-    }
-
-    // No carry -> carry
-    {
-        // LDA 0xFE, ADC 0x03. Should wrap around to 0x01.
-        cpu.quick_start(vec![0xA9, 0xFE, 0x69, 0x03, 0x00]);
-        assert_eq!(cpu.get_a(), 0x01);
-        assert!(cpu.get_flag(Flags::C));
-        assert!(!cpu.get_flag(Flags::V));
-    }
-
-    // No carry -> carry
-    {
-        // LDA 0xFE, ADC 0x12. Should wrap around to 0x10.
-        cpu.quick_start(vec![0xA9, 0xFE, 0x69, 0x12, 0x00]);
-        assert_eq!(cpu.get_a(), 0x10);
-        assert!(cpu.get_flag(Flags::C));
-        assert!(!cpu.get_flag(Flags::V));
-    }
-
-    // No overflow -> overflow
-    {
-        // LDA 0x7F (127), ADC 0x01 (1). Should wrap around to -128, so overflow.
-        cpu.quick_start(vec![0xA9, 0x7F, 0x69, 0x01, 0x00]);
-        assert_eq!(cpu.get_a(), 0x80);
-        assert!(cpu.get_flag(Flags::V));
-        assert!(!cpu.get_flag(Flags::C));
-    }
+    TestMachine::from_asm("SEC\nLDA #$10\nADC #$02\nBRK")
+        .run()
+        .a(0x13)
+        .flags(&[], &[Flags::C, Flags::V]);
+
+    // No carry -> carry, wraps around to 0x01
+    TestMachine::from_asm("LDA #$FE\nADC #$03\nBRK")
+        .run()
+        .a(0x01)
+        .flags(&[Flags::C], &[Flags::V]);
+
+    // No carry -> carry, wraps around to 0x10
+    TestMachine::from_asm("LDA #$FE\nADC #$12\nBRK")
+        .run()
+        .a(0x10)
+        .flags(&[Flags::C], &[Flags::V]);
+
+    // No overflow -> overflow: 127 + 1 wraps around to -128
+    TestMachine::from_asm("LDA #$7F\nADC #$01\nBRK")
+        .run()
+        .a(0x80)
+        .flags(&[Flags::V], &[Flags::C]);
 }
 
 #[test]
 fn sbc_imm() {
-    let mut cpu: CPU = CPU::new(Bus::new());
-
-    // No carry -> carry
-    {
-        // LDA 0x
-    }
-
-    // No overflow -> overflow
-    {
-        // LDA 0x7F (127), SBC (0xFF) (-1). Should wrap to -128, so overflow.
-        cpu.quick_start(vec![0xA9, 0x7F, 0xE9, 0xFF, 0x00]);
-        assert_eq!(cpu.get_a(), 0x80);
-        assert!(cpu.get_flag(Flags::V));
-        assert!(cpu.get_flag(Flags::C));
-    }
+    // No borrow: 0x10 - 0x02 with carry set
+    TestMachine::from_asm("SEC\nLDA #$10\nSBC #$02\nBRK")
+        .run()
+        .a(0x0E)
+        .flags(&[Flags::C], &[Flags::V]);
+
+    // No overflow -> overflow: 127 - (-1) wraps to -128
+    // 0x7F < 0xFF unsigned, so a borrow occurs and carry is cleared.
+    TestMachine::from_asm("SEC\nLDA #$7F\nSBC #$FF\nBRK")
+        .run()
+        .a(0x80)
+        .flags(&[Flags::V], &[Flags::C]);
+
+    // Borrow when carry is clear: computes 0x10 - 0x02 - 1
+    TestMachine::from_asm("CLC\nLDA #$10\nSBC #$02\nBRK")
+        .run()
+        .a(0x0D)
+        .flags(&[Flags::C], &[Flags::V]);
 }
 
 #[test]
 fn and_imm() {
-    let bus: Bus = Bus::new();
-    let mut cpu: CPU = CPU::new(bus);
-    // LDA(IMM) with 0x6b, AND(IMM) with 0x2c
-    cpu.quick_start(vec![0xA9, 0x6b, 0x29, 0x2c, 0x00]);
-
-    assert_eq!(cpu.get_a(), 0x28);
+    TestMachine::from_asm("LDA #$6B\nAND #$2C\nBRK")
+        .run()
+        .a(0x28);
 }
 
 #[test]
 fn and_imm_zero() {
-    let mut cpu: CPU = CPU::new(Bus::new());
-    // LDA 0x6B, AND 0x14, BRK
-    cpu.quick_start(vec![0xA9, 0x6B, 0x29, 0x14, 0x00]);
-    assert!(cpu.get_flag(Flags::Z));
-    assert_eq!(cpu.get_a(), 0x00);
+    TestMachine::from_asm("LDA #$6B\nAND #$14\nBRK")
+        .run()
+        .a(0x00)
+        .flag(Flags::Z, true);
 }
 
 // write number to memory, lda immediate, ldx immediate, then adc with zpx
 #[test]
 fn adc_zpx() {
-    let bus: Bus = Bus::new();
-    let mut cpu: CPU = CPU::new(bus);
-    cpu.write(0x00F1, 0x27);
-    cpu.quick_start(vec![0xA9, 0x03, 0xA2, 0x10, 0x75, 0xE1, 0x00]);
-
-    assert_eq!(cpu.get_a(), 0x2A);
+    TestMachine::from_asm("LDA #$03\nLDX #$10\nADC $E1,X\nBRK")
+        .poke(0x00F1, &[0x27])
+        .run()
+        .a(0x2A);
 }
 
 #[test]
 fn asl_acc() {
-    let bus: Bus = Bus::new();
-    let mut cpu: CPU = CPU::new(bus);
-
-    {
-        cpu.quick_start(vec![0xA9, 0b0010_1000, 0x0A, 0x00]);
-        assert_eq!(cpu.get_a(), 0b0101_0000);
-        assert!(!cpu.get_flag(Flags::C));
-        assert!(!cpu.get_flag(Flags::N));
-        assert!(!cpu.get_flag(Flags::Z));
-    }
-
-    {
-        cpu.quick_start(vec![0xA9, 0b1010_0000, 0x0A, 0x00]);
-        assert_eq!(cpu.get_a(), 0b0100_0000);
-        assert!(cpu.get_flag(Flags::C));
-    }
-
-    {
-        cpu.quick_start(vec![0xA9, 0b1000_0000, 0x0A, 0x00]);
-        assert_eq!(cpu.get_a(), 0x00);
-        assert!(cpu.get_flag(Flags::C));
-        assert!(cpu.get_flag(Flags::Z));
-        assert!(!cpu.get_flag(Flags::N));
-    }
-
-    {
-        cpu.quick_start(vec![0xA9, 0b0100_0000, 0x0A, 0x00]);
-        assert_eq!(cpu.get_a(), 0b1000_0000);
-        assert!(cpu.get_flag(Flags::N));
-        assert!(!cpu.get_flag(Flags::Z));
-        assert!(!cpu.get_flag(Flags::C));
-    }
+    TestMachine::from_asm("LDA #%00101000\nASL A\nBRK")
+        .run()
+        .a(0b0101_0000)
+        .flags(&[], &[Flags::C, Flags::N, Flags::Z]);
+
+    TestMachine::from_asm("LDA #%10100000\nASL A\nBRK")
+        .run()
+        .a(0b0100_0000)
+        .flag(Flags::C, true);
+
+    TestMachine::from_asm("LDA #%10000000\nASL A\nBRK")
+        .run()
+        .a(0x00)
+        .flags(&[Flags::C, Flags::Z], &[Flags::N]);
+
+    TestMachine::from_asm("LDA #%01000000\nASL A\nBRK")
+        .run()
+        .a(0b1000_0000)
+        .flags(&[Flags::N], &[Flags::Z, Flags::C]);
 }
 
 #[test]
 fn asl_zp0() {
-    let mut cpu: CPU = CPU::new(Bus::new());
-    
-    {
-        cpu.write(0x0012, 0b0010_1000);
-        cpu.quick_start(vec![0x06, 0x12, 0x00]);
-        assert_eq!(cpu.read(0x0012), 0b0101_0000);
-        assert!(!cpu.get_flag(Flags::C));
-    }
+    TestMachine::from_asm("ASL $12\nBRK")
+        .poke(0x0012, &[0b0010_1000])
+        .run()
+        .mem(0x0012, 0b0101_0000)
+        .flag(Flags::C, false);
+
+    TestMachine::from_asm("ASL $12\nBRK")
+        .poke(0x0012, &[0b1000_0000])
+        .run()
+        .mem(0x0012, 0x00)
+        .flags(&[Flags::C, Flags::Z], &[]);
+}
 
-    {
-        cpu.write(0x0012, 0b1000_0000);
-        cpu.quick_start(vec![0x06, 0x12, 0x00]);
-        assert_eq!(cpu.read(0x0012), 0x00);
-        assert!(cpu.get_flag(Flags::C));
-        assert!(cpu.get_flag(Flags::Z));
-    }
+#[test]
+fn asl_abs() {
+    TestMachine::from_asm("LDA #%00101000\nSTA $3B01\nASL $3B01\nBRK")
+        .run()
+        .mem(0x3B01, 0b0101_0000);
 }
 
 #[test]
 fn lsr_acc() {
-    let mut cpu: CPU = CPU::new(Bus::new());
-
-    {
-        cpu.quick_start(vec![0xA9, 0b0010_1000, 0x4A, 0x00]);
-        assert_eq!(cpu.get_a(), 0b0001_0100);
-    }
-
-    {
-        cpu.quick_start(vec![0xA9, 0b0010_1001, 0x4A, 0x00]);
-        assert_eq!(cpu.get_a(), 0b0001_0100);
-        assert!(cpu.get_flag(Flags::C));
-    }
-
-    {
-        cpu.quick_start(vec![0xA9, 0b0010_1000, 0x4A, 0x00]);
-        assert_eq!(cpu.get_a(), 0b0001_0100);
-    }
+    TestMachine::from_asm("LDA #%00101000\nLSR A\nBRK")
+        .run()
+        .a(0b0001_0100)
+        .flag(Flags::C, false);
+
+    TestMachine::from_asm("LDA #%00101001\nLSR A\nBRK")
+        .run()
+        .a(0b0001_0100)
+        .flag(Flags::C, true);
 }
 
 #[test]
 fn lsr_zp0() {
-    let mut cpu: CPU = CPU::new(Bus::new());
-    
-    {
-        cpu.write(0x0012, 0b0010_1000);
-        cpu.quick_start(vec![0x46, 0x12, 0x00]);
-        assert_eq!(cpu.read(0x0012), 0b0001_0100);
-        assert!(!cpu.get_flag(Flags::C));
-    }
-
-    {
-        cpu.write(0x0012, 0b0000_0001);
-        cpu.quick_start(vec![0x46, 0x12, 0x00]);
-        assert_eq!(cpu.read(0x0012), 0x00);
-        assert!(cpu.get_flag(Flags::C));
-        assert!(cpu.get_flag(Flags::Z));
-    }
+    TestMachine::from_asm("LSR $12\nBRK")
+        .poke(0x0012, &[0b0010_1000])
+        .run()
+        .mem(0x0012, 0b0001_0100)
+        .flag(Flags::C, false);
+
+    TestMachine::from_asm("LSR $12\nBRK")
+        .poke(0x0012, &[0b0000_0001])
+        .run()
+        .mem(0x0012, 0x00)
+        .flags(&[Flags::C, Flags::Z], &[]);
 }
 
 #[test]
 fn rol_acc() {
-    let mut cpu: CPU = CPU::new(Bus::new());
-
-    {
-        cpu.quick_start(vec![0xA9, 0b1000_0010, 0x2A, 0x00]);
-        assert_eq!(cpu.get_a(), 0b0000_0100);
-        assert!(cpu.get_flag(Flags::C));
-    }
-
-    {
-        cpu.quick_start(vec![0xA9, 0b0010_0010, 0x38, 0x2A, 0x00]);
-        assert_eq!(cpu.get_a(), 0b0100_0101);
-        assert!(!cpu.get_flag(Flags::C));
-    }
+    TestMachine::from_asm("LDA #%10000010\nROL A\nBRK")
+        .run()
+        .a(0b0000_0100)
+        .flag(Flags::C, true);
+
+    TestMachine::from_asm("LDA #%00100010\nSEC\nROL A\nBRK")
+        .run()
+        .a(0b0100_0101)
+        .flag(Flags::C, false);
 }
 
 #[test]
@@ -260,19 +199,17 @@ fn rol_zp0() {
 
 #[test]
 fn ror_acc() {
-    let mut cpu: CPU = CPU::new(Bus::new());
-
-    {   // No carry -> carry
-        cpu.quick_start(vec![0xA9, 0b0100_0001, 0x6A, 0x00]);
-        assert_eq!(cpu.get_a(), 0b0010_0000);
-        assert!(cpu.get_flag(Flags::C));
-    }
+    // No carry -> carry
+    TestMachine::from_asm("LDA #%01000001\nROR A\nBRK")
+        .run()
+        .a(0b0010_0000)
+        .flag(Flags::C, true);
 
-    {   // Carry -> no carry
-        cpu.quick_start(vec![0xA9, 0b0100_0100, 0x38, 0x6A, 0x00]);
-        assert_eq!(cpu.get_a(), 0b1010_0010);
-        assert!(!cpu.get_flag(Flags::C));
-    }
+    // Carry -> no carry
+    TestMachine::from_asm("LDA #%01000100\nSEC\nROR A\nBRK")
+        .run()
+        .a(0b1010_0010)
+        .flag(Flags::C, false);
 }
 
 #[test]
@@ -282,21 +219,37 @@ fn ror_zp0() {
 
 #[test]
 fn beq_rel_pos() {
-    let mut cpu: CPU = CPU::new(Bus::new());
-    // LDA 0xA9, AND 0xC0, BEQ -> LDA 0xFF, BRK if no zero flag (A would remain 0xA9)
-    cpu.quick_start(vec![0xA9, 0x2A, 0x29, 0xC0, 0xF0, 0x03, 0x00, 0x00, 0xA9, 0xFF, 0x00]);
-    assert!(!cpu.get_flag(Flags::Z));
-    assert_eq!(cpu.get_a(), 0xFF);
+    // A would remain 0x2A if the branch isn't taken
+    TestMachine::from_asm("
+            LDA #$2A
+            AND #$C0
+            BEQ taken
+            BRK
+            BRK
+        taken:
+            LDA #$FF
+            BRK
+    ")
+        .run()
+        .a(0xFF)
+        .flag(Flags::Z, false);
 }
 
 #[test]
 fn beq_rel_neg() {
-    let mut cpu: CPU = CPU::new(Bus::new());
-    cpu.write(0x05D5, 0xA9);
-    cpu.write(0x05D6, 0xFF);
-    // LDA 0xA9, AND 0xC0, BEQ -> LDA 0xFF, LDA 0xAF if no zero flag
-    cpu.quick_start(vec![0xA9, 0x2A, 0x29, 0xC0, 0xF0, 0xD0, 0xA9, 0xAF]);
-    assert_eq!(cpu.get_a(), 0xFF);
+    // Branch back to LDA #$FF below the program, which runs into the BRK at 0x05D7
+    TestMachine::from_asm("LDA #$2A\nAND #$C0\nBEQ $05D5\nLDA #$AF\nBRK")
+        .poke(0x05D5, &[0xA9, 0xFF])
+        .run()
+        .a(0xFF)
+        .pc(0x05D7);
+}
+
+#[test]
+fn bne_not_taken() {
+    TestMachine::from_asm("LDA #$00\nBNE skip\nLDA #$07\nskip: BRK")
+        .run()
+        .a(0x07);
 }
 
 // TODO: test what happens if underflow occurs
@@ -307,114 +260,144 @@ fn beq_rel_neg() {
 
 #[test]
 fn clc_imp() {
-    let mut cpu: CPU = CPU::new(Bus::new());
-    assert!(!cpu.get_flag(Flags::C));
-    cpu.set_flag(Flags::C, true);
-    assert!(cpu.get_flag(Flags::C));
-    cpu.quick_start(vec![0x18, 0x00]);
-    assert!(!cpu.get_flag(Flags::C));
+    TestMachine::from_asm("SEC\nCLC\nBRK")
+        .run()
+        .flag(Flags::C, false);
 }
 
 #[test]
 fn cld_imp() {
-    let mut cpu: CPU = CPU::new(Bus::new());
-    assert!(!cpu.get_flag(Flags::D));
-    cpu.set_flag(Flags::D, true);
-    assert!(cpu.get_flag(Flags::D));
-    cpu.quick_start(vec![0xD8, 0x00]);
-    assert!(!cpu.get_flag(Flags::D));
+    TestMachine::from_asm("SED\nCLD\nBRK")
+        .run()
+        .flag(Flags::D, false);
 }
 
 #[test]
 fn cli_imp() {
-    let mut cpu: CPU = CPU::new(Bus::new());
-    assert!(!cpu.get_flag(Flags::I));
-    cpu.set_flag(Flags::I, true);
-    assert!(cpu.get_flag(Flags::I));
-    cpu.quick_start(vec![0x58, 0x00]);
-    assert!(!cpu.get_flag(Flags::I));
+    // Reset leaves I set
+    TestMachine::from_asm("CLI\nBRK")
+        .run()
+        .flag(Flags::I, false);
 }
 
 #[test]
 fn clv_imp() {
-    let mut cpu: CPU = CPU::new(Bus::new());
-    assert!(!cpu.get_flag(Flags::V));
-    cpu.set_flag(Flags::V, true);
-    assert!(cpu.get_flag(Flags::V));
-    cpu.quick_start(vec![0xB8, 0x00]);
-    assert!(!cpu.get_flag(Flags::V));
+    // 127 + 1 overflows
+    TestMachine::from_asm("LDA #$7F\nADC #$01\nCLV\nBRK")
+        .run()
+        .flag(Flags::V, false);
 }
 
-// TODO: test ASL(ABS)
-// #[test]
-// fn test_asl_abs() {
-//     let bus: Bus = Bus::new();
-//     let mut cpu: CPU = CPU::new(bus);
-
-//     // TODO: LDA(IMM): 0b0010_1000, STA(ABS) to addr: 0x3B01, ASL(ABS) to addr: 0x3B01
-//     {
-//         cpu.quick_start(vec![0xA9, 0b0010_1000, 0x8D, 0x01, 0x3B, 0x0E, 0x01, 0x3B, 0x00]);
-//         assert_eq!(cpu.read(0x3B), 0b0101_0000);
-//     }
-// }
-
 // TODO: test ASL with different mode(s)
 
 #[test]
 fn jmp_abs() {
-    let bus: Bus = Bus::new();
-    let mut cpu: CPU = CPU::new(bus);
-    cpu.write(0x3000, 0xA9);
-    cpu.write(0x3001, 0x04);
-    cpu.write(0x3002, 0x00);
-
-    // LDA 0x02, JMP to 0x3000. Then LDA 0x04 and BRK.
-    cpu.quick_start(vec![0xA9, 0x02, 0x4C, 0x00, 0x30]);
-    assert_eq!(cpu.get_a(), 0x04);
+    TestMachine::from_asm("
+            LDA #$02
+            JMP target
+        .org $3000
+        target:
+            LDA #$04
+            BRK
+    ")
+        .run()
+        .a(0x04)
+        .pc(0x3002);
 }
 
 #[test]
 fn jmp_ind() {
-    let bus: Bus = Bus::new();
-    let mut cpu: CPU = CPU::new(bus);
-    cpu.write(0x1234, 0x30);
-    cpu.write(0x1235, 0x24);
-    cpu.write(0x2430, 0xA9);
-    cpu.write(0x2431, 0x04);
-    cpu.write(0x2432, 0x00);
-
-    // LDA 0x02, JMP to pointer specified by 0x1234 (so to 0x2430). Then LDA 0x04 and BRK.
-    cpu.quick_start(vec![0xA9, 0x02, 0x6C, 0x34, 0x12]);
-    assert_eq!(cpu.get_a(), 0x04);
+    // JMP to the pointer at 0x1234 (so to 0x2430), then LDA #$04 and BRK
+    TestMachine::from_asm("LDA #$02\nJMP ($1234)")
+        .poke(0x1234, &[0x30, 0x24])
+        .poke(0x2430, &[0xA9, 0x04, 0x00])
+        .run()
+        .a(0x04);
 }
 
 #[test]
 fn sec_imp() {
-    let mut cpu: CPU = CPU::new(Bus::new());
-    assert!(!cpu.get_flag(Flags::C));
-    cpu.quick_start(vec![0x38, 0x00]);
-    assert!(cpu.get_flag(Flags::C));
+    TestMachine::from_asm("SEC\nBRK")
+        .run()
+        .flag(Flags::C, true);
 }
 
 #[test]
 fn sed_imp() {
-    let mut cpu: CPU = CPU::new(Bus::new());
-    assert!(!cpu.get_flag(Flags::D));
-    cpu.quick_start(vec![0xF8, 0x00]);
-    assert!(cpu.get_flag(Flags::D));
+    TestMachine::from_asm("SED\nBRK")
+        .run()
+        .flag(Flags::D, true);
 }
 
 #[test]
 fn sei_imp() {
-    let mut cpu: CPU = CPU::new(Bus::new());
-    assert!(!cpu.get_flag(Flags::I));
-    cpu.quick_start(vec![0x78, 0x00]);
-    assert!(cpu.get_flag(Flags::I));
+    TestMachine::from_asm("CLI\nSEI\nBRK")
+        .run()
+        .flag(Flags::I, true);
+}
+
+#[test]
+fn cmp_imm() {
+    // Equal: Z and C set, accumulator unchanged
+    TestMachine::from_asm("LDA #$42\nCMP #$42\nBRK")
+        .run()
+        .a(0x42)
+        .flags(&[Flags::Z, Flags::C], &[]);
+
+    // Less than: C clear, N from the difference
+    TestMachine::from_asm("LDA #$10\nCMP #$20\nBRK")
+        .run()
+        .a(0x10)
+        .flags(&[Flags::N], &[Flags::C]);
+}
+
+#[test]
+fn jsr_rts() {
+    TestMachine::from_asm("
+            JSR sub
+            LDA #$01
+            BRK
+        .org $3000
+        sub:
+            LDX #$07
+            RTS
+    ")
+        .run()
+        .x(0x07)
+        .a(0x01)
+        .sp(0xFD)
+        // Return address (last byte of JSR) was pushed high byte first
+        .mem_range(0x01FC, &[0x02, 0x06]);
+}
+
+#[test]
+fn pha_pla() {
+    TestMachine::from_asm("LDA #$33\nPHA\nLDA #$00\nPLA\nBRK")
+        .run()
+        .a(0x33)
+        .sp(0xFD)
+        .mem(0x01FD, 0x33);
+}
+
+#[test]
+fn inx_wraps() {
+    TestMachine::from_asm("LDX #$FF\nINX\nBRK")
+        .run()
+        .x(0x00)
+        .flag(Flags::Z, true);
 }
 
 #[test]
 fn sta_zp0() {
-    let mut cpu: CPU = CPU::new(Bus::new());
-    cpu.quick_start(vec![0xA9, 0xFF, 0x85, 0xAB]);
-    assert_eq!(cpu.read(0xAB), 0xFF);
-}
\ No newline at end of file
+    TestMachine::from_asm("LDA #$FF\nSTA $AB\nBRK")
+        .run()
+        .mem(0x00AB, 0xFF);
+}
+
+#[test]
+#[should_panic(expected = "no BRK within")]
+fn budget_catches_endless_loops() {
+    TestMachine::from_asm("loop: JMP loop")
+        .budget(1_000)
+        .run();
+}
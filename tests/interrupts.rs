@@ -0,0 +1,192 @@
+use emulatorr::core::{
+    cpu::{
+        CPU,
+        CpuVariant,
+        Flags,
+        StepOutcome,
+    },
+    bus::Bus,
+};
+
+const IRQ_HANDLER: u16 = 0x0800;
+const NMI_HANDLER: u16 = 0x0900;
+
+/// CPU with `program` at 0x0600, PC on its first instruction and handlers set up (both are `INX, RTI`)
+fn cpu_with(program: Vec<u8>, sr: u8) -> CPU {
+    let mut cpu: CPU = CPU::custom(0, 0, 0, 0xFF, 0x0600, sr, 0, Bus::new());
+    cpu.load_program(program);
+    cpu.write_u16(0xFFFE, IRQ_HANDLER);
+    cpu.write_u16(0xFFFA, NMI_HANDLER);
+    cpu.write(IRQ_HANDLER, 0xE8);
+    cpu.write(IRQ_HANDLER + 1, 0x40);
+    cpu.write(NMI_HANDLER, 0xE8);
+    cpu.write(NMI_HANDLER + 1, 0x40);
+    cpu
+}
+
+#[test]
+fn irq_sequence() {
+    let mut cpu = cpu_with(vec![0xEA, 0xEA], Flags::C as u8);
+    cpu.set_irq_line(true);
+
+    assert_eq!(cpu.step().unwrap().cycles(), 7);
+    assert_eq!(cpu.get_pc(), IRQ_HANDLER);
+    assert!(cpu.get_flag(Flags::I));
+    assert_eq!(cpu.get_sp(), 0xFC);
+
+    // Return address, then SR with B clear and U set
+    let memory = cpu.get_memory();
+    assert_eq!(memory[0x01FF], 0x06);
+    assert_eq!(memory[0x01FE], 0x00);
+    assert_eq!(memory[0x01FD], Flags::C as u8 | Flags::U as u8);
+    assert_eq!(cpu.cycles(), 7);
+}
+
+#[test]
+fn irq_masked_by_interrupt_disable() {
+    let mut cpu = cpu_with(vec![0xEA, 0xEA], Flags::I as u8);
+    cpu.set_irq_line(true);
+
+    cpu.step().unwrap();
+    cpu.step().unwrap();
+    assert_eq!(cpu.get_pc(), 0x0602);
+    assert_eq!(cpu.get_x(), 0x00);
+}
+
+#[test]
+fn irq_is_level_triggered() {
+    let mut cpu = cpu_with(vec![0xEA], 0);
+    cpu.set_irq_line(true);
+
+    // IRQ, INX, RTI, and the still asserted line interrupts again before the NOP
+    cpu.step().unwrap();
+    cpu.step().unwrap();
+    cpu.step().unwrap();
+    assert_eq!(cpu.get_pc(), 0x0600);
+    assert_eq!(cpu.step().unwrap().cycles(), 7);
+    assert_eq!(cpu.get_pc(), IRQ_HANDLER);
+
+    // Once released, RTI returns to the program
+    cpu.set_irq_line(false);
+    cpu.step().unwrap();
+    cpu.step().unwrap();
+    cpu.step().unwrap();
+    assert_eq!(cpu.get_pc(), 0x0601);
+    assert_eq!(cpu.get_x(), 0x02);
+}
+
+#[test]
+fn irq_sources_are_ored() {
+    let mut cpu = cpu_with(vec![], 0);
+
+    cpu.set_irq_source(1, true);
+    cpu.set_irq_source(2, true);
+    assert!(cpu.irq_line());
+
+    cpu.set_irq_source(1, false);
+    assert!(cpu.irq_line());
+
+    cpu.set_irq_source(2, false);
+    assert!(!cpu.irq_line());
+}
+
+#[test]
+fn nmi_is_edge_triggered() {
+    let mut cpu = cpu_with(vec![0xEA, 0xEA, 0xEA], Flags::I as u8);
+    cpu.trigger_nmi();
+
+    // Serviced once, regardless of the interrupt disable flag
+    assert_eq!(cpu.step().unwrap().cycles(), 7);
+    assert_eq!(cpu.get_pc(), NMI_HANDLER);
+    cpu.step().unwrap();
+    cpu.step().unwrap();
+    assert_eq!(cpu.get_pc(), 0x0600);
+    cpu.step().unwrap();
+    assert_eq!(cpu.get_pc(), 0x0601);
+    assert_eq!(cpu.get_x(), 0x01);
+}
+
+#[test]
+fn nmi_has_priority_over_irq() {
+    let mut cpu = cpu_with(vec![0xEA], 0);
+    cpu.set_irq_line(true);
+    cpu.trigger_nmi();
+
+    cpu.step().unwrap();
+    assert_eq!(cpu.get_pc(), NMI_HANDLER);
+}
+
+#[test]
+fn cli_takes_effect_after_next_instruction() {
+    // CLI, INY, INY
+    let mut cpu = cpu_with(vec![0x58, 0xC8, 0xC8], Flags::I as u8);
+    cpu.set_irq_line(true);
+
+    cpu.step().unwrap();
+    assert!(!cpu.get_flag(Flags::I));
+
+    // The instruction after CLI still runs before the IRQ
+    cpu.step().unwrap();
+    assert_eq!(cpu.get_y(), 0x01);
+    cpu.step().unwrap();
+    assert_eq!(cpu.get_pc(), IRQ_HANDLER);
+}
+
+#[test]
+fn irq_during_sei_is_still_serviced() {
+    // SEI, INY
+    let mut cpu = cpu_with(vec![0x78, 0xC8], 0);
+
+    cpu.step().unwrap();
+    cpu.set_irq_line(true);
+    cpu.step().unwrap();
+    assert_eq!(cpu.get_pc(), IRQ_HANDLER);
+    assert_eq!(cpu.get_y(), 0x00);
+}
+
+#[test]
+fn rti_restores_interrupt_disable_immediately() {
+    let mut cpu = cpu_with(vec![0xEA, 0xEA], 0);
+    cpu.set_irq_line(true);
+
+    // IRQ, then release the line inside the handler
+    cpu.step().unwrap();
+    cpu.set_irq_line(false);
+    cpu.step().unwrap();
+    cpu.step().unwrap();
+    assert_eq!(cpu.get_pc(), 0x0600);
+
+    // Unlike CLI, RTI unmasks straight away
+    cpu.set_irq_line(true);
+    cpu.step().unwrap();
+    assert_eq!(cpu.get_pc(), IRQ_HANDLER);
+}
+
+#[test]
+fn brk_pushes_break_flag() {
+    let mut cpu = cpu_with(vec![0x00], 0);
+    cpu.step().unwrap();
+    assert_eq!(cpu.get_pc(), IRQ_HANDLER);
+    assert_eq!(cpu.get_memory()[0x01FD], Flags::B as u8 | Flags::U as u8);
+    assert_eq!(cpu.get_memory()[0x01FE], 0x02);
+}
+
+#[test]
+fn wai_resumes_on_interrupt() {
+    let mut cpu: CPU = CPU::with_variant(Bus::new(), CpuVariant::Wdc65C02);
+    // SEI, WAI, INX
+    cpu.load_program(vec![0x78, 0xCB, 0xE8]);
+    cpu.reset();
+
+    cpu.step().unwrap();
+    cpu.step().unwrap();
+    assert!(cpu.is_waiting());
+    assert_eq!(cpu.step(), Ok(StepOutcome::Waiting));
+
+    // A masked IRQ wakes the CPU up without being serviced
+    cpu.set_irq_line(true);
+    cpu.step().unwrap();
+    assert!(!cpu.is_waiting());
+    assert_eq!(cpu.get_x(), 0x01);
+    assert_eq!(cpu.get_pc(), 0x0603);
+}
@@ -0,0 +1,259 @@
+mod common;
+
+use common::{ines_cartridge, nes_bus};
+use emulatorr::{
+    core::{cpu::CPU, memory::Memory},
+    nes::{
+        cartridge::{Cartridge, Mirroring, RomError},
+        mapper::{self, Mapper},
+        Nes,
+    },
+};
+
+/// Cartridge with `prg` 16KB PRG banks and `chr` 8KB CHR banks
+///
+/// Every byte of PRG ROM holds the number of its 8KB bank, and every byte of CHR ROM the number of its 1KB bank.
+fn cartridge(mapper: u8, prg: u8, chr: u8) -> Cartridge {
+    let prg: Vec<u8> = (0..prg as usize * 0x4000).map(|i| (i / 0x2000) as u8).collect();
+    let chr: Vec<u8> = (0..chr as usize * 0x2000).map(|i| (i / 0x0400) as u8).collect();
+    ines_cartridge(&prg, &chr, mapper << 4)
+}
+
+fn mapper(number: u8, prg: u8, chr: u8) -> Box<dyn Mapper> {
+    mapper::from_cartridge(cartridge(number, prg, chr)).unwrap()
+}
+
+/// Write `value` to an MMC1 register one bit at a time
+fn mmc1_write(mapper: &mut dyn Mapper, addr: u16, value: u8) {
+    for bit in 0..5 {
+        mapper.cpu_write(addr, (value >> bit) & 0x01);
+    }
+}
+
+#[test]
+fn nrom() {
+    let mut nrom = mapper(0, 1, 1);
+    // 16KB is mirrored
+    assert_eq!(nrom.cpu_peek(0x8000), 0);
+    assert_eq!(nrom.cpu_peek(0xE000), 1);
+    assert_eq!(nrom.ppu_peek(0x1C00), 7);
+    assert_eq!(nrom.mirroring(), Mirroring::Horizontal);
+
+    nrom.cpu_write(0x6123, 0x42);
+    assert_eq!(nrom.cpu_peek(0x6123), 0x42);
+
+    // CHR ROM can't be written
+    nrom.ppu_write(0x0000, 0xFF);
+    assert_eq!(nrom.ppu_peek(0x0000), 0);
+}
+
+#[test]
+fn uxrom() {
+    let mut uxrom = mapper(2, 8, 0);
+    assert_eq!(uxrom.cpu_peek(0x8000), 0);
+    assert_eq!(uxrom.cpu_peek(0xC000), 14);
+
+    uxrom.cpu_write(0x8000, 3);
+    assert_eq!(uxrom.cpu_peek(0x8000), 6);
+    assert_eq!(uxrom.cpu_peek(0xA000), 7);
+    assert_eq!(uxrom.cpu_peek(0xFFFF), 15);
+
+    // CHR RAM
+    uxrom.ppu_write(0x1234, 0x55);
+    assert_eq!(uxrom.ppu_peek(0x1234), 0x55);
+}
+
+#[test]
+fn cnrom() {
+    let mut cnrom = mapper(3, 2, 4);
+    cnrom.cpu_write(0x8000, 2);
+    assert_eq!(cnrom.ppu_peek(0x0000), 16);
+    assert_eq!(cnrom.ppu_peek(0x1FFF), 23);
+    assert_eq!(cnrom.cpu_peek(0xC000), 2);
+}
+
+#[test]
+fn axrom() {
+    let mut axrom = mapper(7, 8, 0);
+    assert_eq!(axrom.mirroring(), Mirroring::SingleScreenLower);
+
+    axrom.cpu_write(0x8000, 0x12);
+    assert_eq!(axrom.cpu_peek(0x8000), 8);
+    assert_eq!(axrom.cpu_peek(0xE000), 11);
+    assert_eq!(axrom.mirroring(), Mirroring::SingleScreenUpper);
+}
+
+#[test]
+fn mmc1() {
+    let mut mmc1 = mapper(1, 8, 2);
+
+    // Powers on with the last bank fixed at 0xC000
+    assert_eq!(mmc1.cpu_peek(0xC000), 14);
+    mmc1_write(mmc1.as_mut(), 0xE000, 2);
+    assert_eq!(mmc1.cpu_peek(0x8000), 4);
+    assert_eq!(mmc1.cpu_peek(0xC000), 14);
+
+    // Fixed first bank, vertical mirroring, 4KB CHR banks
+    mmc1_write(mmc1.as_mut(), 0x8000, 0b1_10_10);
+    assert_eq!(mmc1.mirroring(), Mirroring::Vertical);
+    assert_eq!(mmc1.cpu_peek(0x8000), 0);
+    assert_eq!(mmc1.cpu_peek(0xC000), 4);
+    mmc1_write(mmc1.as_mut(), 0xA000, 1);
+    mmc1_write(mmc1.as_mut(), 0xC000, 3);
+    assert_eq!(mmc1.ppu_peek(0x0000), 4);
+    assert_eq!(mmc1.ppu_peek(0x1000), 12);
+
+    // 32KB mode ignores the low bit of the bank
+    mmc1_write(mmc1.as_mut(), 0x8000, 0b0_00_11);
+    mmc1_write(mmc1.as_mut(), 0xE000, 3);
+    assert_eq!(mmc1.cpu_peek(0x8000), 4);
+    assert_eq!(mmc1.cpu_peek(0xC000), 6);
+    assert_eq!(mmc1.mirroring(), Mirroring::Horizontal);
+
+    // Bit 7 resets the shift register and fixes the last bank
+    mmc1.cpu_write(0x8000, 0x01);
+    mmc1.cpu_write(0x8000, 0x80);
+    assert_eq!(mmc1.cpu_peek(0xC000), 14);
+    mmc1_write(mmc1.as_mut(), 0xE000, 1);
+    assert_eq!(mmc1.cpu_peek(0x8000), 2);
+
+    // PRG RAM, which bit 4 of the PRG register disables
+    mmc1.cpu_write(0x6000, 0x42);
+    assert_eq!(mmc1.cpu_peek(0x6000), 0x42);
+    mmc1_write(mmc1.as_mut(), 0xE000, 0x11);
+    assert_eq!(mmc1.cpu_peek(0x6000), 0);
+}
+
+#[test]
+fn mmc3_banks() {
+    let mut mmc3 = mapper(4, 8, 8);
+
+    // R6 = 3, R7 = 5
+    mmc3.cpu_write(0x8000, 6);
+    mmc3.cpu_write(0x8001, 3);
+    mmc3.cpu_write(0x8000, 7);
+    mmc3.cpu_write(0x8001, 5);
+    assert_eq!([0x8000, 0xA000, 0xC000, 0xE000].map(|addr| mmc3.cpu_peek(addr)), [3, 5, 14, 15]);
+
+    // PRG mode 1 swaps 0x8000 and 0xC000
+    mmc3.cpu_write(0x8000, 0x40);
+    assert_eq!([0x8000, 0xA000, 0xC000, 0xE000].map(|addr| mmc3.cpu_peek(addr)), [14, 5, 3, 15]);
+
+    // R0 = 9 (2KB, low bit ignored), R2 = 20 (1KB)
+    mmc3.cpu_write(0x8000, 0);
+    mmc3.cpu_write(0x8001, 9);
+    mmc3.cpu_write(0x8000, 2);
+    mmc3.cpu_write(0x8001, 20);
+    assert_eq!([0x0000, 0x0400, 0x1000].map(|addr| mmc3.ppu_peek(addr)), [8, 9, 20]);
+
+    // CHR inversion swaps the halves
+    mmc3.cpu_write(0x8000, 0x80);
+    assert_eq!([0x1000, 0x1400, 0x0000].map(|addr| mmc3.ppu_peek(addr)), [8, 9, 20]);
+
+    mmc3.cpu_write(0xA000, 1);
+    assert_eq!(mmc3.mirroring(), Mirroring::Horizontal);
+    mmc3.cpu_write(0xA000, 0);
+    assert_eq!(mmc3.mirroring(), Mirroring::Vertical);
+}
+
+#[test]
+fn mmc3_scanline_irq() {
+    let mut mmc3 = mapper(4, 2, 1);
+    mmc3.cpu_write(0xC000, 2);
+    mmc3.cpu_write(0xC001, 0);
+    mmc3.cpu_write(0xE001, 0);
+
+    // Reload to 2, then 1, then 0 which raises the IRQ
+    mmc3.scanline();
+    mmc3.scanline();
+    assert!(!mmc3.irq());
+    mmc3.scanline();
+    assert!(mmc3.irq());
+
+    // Acknowledged (and disabled) by writing 0xE000
+    mmc3.cpu_write(0xE000, 0);
+    assert!(!mmc3.irq());
+    mmc3.scanline();
+    mmc3.scanline();
+    mmc3.scanline();
+    assert!(!mmc3.irq());
+}
+
+#[test]
+fn unsupported_mapper() {
+    assert_eq!(mapper::from_cartridge(cartridge(5, 1, 1)).err(), Some(RomError::UnsupportedMapper(5)));
+}
+
+#[test]
+fn bus_mirrors_ram_and_saves_mapper() {
+    let mut bus = nes_bus(cartridge(2, 4, 0));
+    bus.write(0x0801, 0x12);
+    assert_eq!(bus.read(0x1801), 0x12);
+    bus.write(0x8000, 2);
+    assert_eq!(bus.peek(0x8000), 4);
+
+    // Save states include RAM and the mapper's bank registers
+    let mut cpu = CPU::new(bus);
+    let state = cpu.save_state();
+    cpu.write(0x0801, 0x00);
+    cpu.write(0x8000, 0);
+    cpu.load_state(&state).unwrap();
+    assert_eq!(cpu.get_memory()[0x0001], 0x12);
+    assert_eq!(cpu.get_memory()[0x8000], 4);
+}
+
+#[test]
+fn nes_wires_mapper_irq() {
+    // Reset vector 0x8000: CLI, then loop; the IRQ handler at 0x8010 loads A
+    let mut cartridge = cartridge(4, 2, 1);
+    let program = [0x58, 0x4C, 0x01, 0x80];
+    let handler = [0xA9, 0x2A, 0x8D, 0x00, 0xE0, 0x40];
+    cartridge.prg_rom[..program.len()].copy_from_slice(&program);
+    cartridge.prg_rom[0x10..0x10 + handler.len()].copy_from_slice(&handler);
+    cartridge.prg_rom[0x7FFC..].copy_from_slice(&[0x00, 0x80, 0x10, 0x80]);
+
+    let mut nes = Nes::new(cartridge).unwrap();
+    nes.power_on();
+    nes.step().unwrap();
+    assert_eq!(nes.cpu().get_pc(), 0x8001);
+
+    let mapper = nes.cpu_mut().bus_mut().mapper_mut();
+    mapper.cpu_write(0xC000, 0);
+    mapper.cpu_write(0xE001, 0);
+    mapper.scanline();
+    assert!(nes.cpu().bus().mapper().irq());
+
+    for _ in 0..4 {
+        nes.step().unwrap();
+    }
+    assert_eq!(nes.cpu().get_a(), 0x2A);
+    assert!(!nes.cpu().bus().mapper().irq());
+}
+
+#[test]
+fn nes_reset() {
+    let mut nes = Nes::new(cartridge(1, 8, 2)).unwrap();
+    nes.power_on();
+    assert_eq!(nes.cpu().get_pc(), 0x0F0F);
+
+    let bus = nes.cpu_mut().bus_mut();
+    // 32KB mode with banks 8-11, NMI on vblank, pulse 1 playing
+    mmc1_write(bus.mapper_mut(), 0x8000, 0b0_00_11);
+    mmc1_write(bus.mapper_mut(), 0xE000, 4);
+    assert_eq!(bus.peek(0xFFFC), 11);
+    bus.write(0x0010, 0x42);
+    bus.write(0x2000, 0x80);
+    bus.write(0x4015, 0x01);
+    bus.write(0x4003, 0x18);
+
+    // The reset vector comes from the power-up banks again, and only RAM is kept
+    nes.reset();
+    assert_eq!(nes.cpu().get_pc(), 0x0F0F);
+    let bus = nes.cpu_mut().bus_mut();
+    assert_eq!(bus.peek(0x0010), 0x42);
+    assert_eq!(bus.peek(0x4015) & 0x01, 0);
+    while !bus.ppu_mut().take_frame() {
+        bus.tick(1);
+    }
+    assert!(!bus.ppu_mut().take_nmi());
+}
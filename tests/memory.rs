@@ -0,0 +1,136 @@
+use emulatorr::core::{
+    cpu::CPU,
+    memory::{Memory, MemoryMap, Ram, Rom},
+    bus::Bus,
+};
+
+/// Device that counts reads, to check side effects
+struct Counter {
+    reads: u8,
+}
+
+impl Memory for Counter {
+    fn read(&mut self, _addr: u16) -> u8 {
+        self.reads += 1;
+        self.reads
+    }
+
+    fn write(&mut self, _addr: u16, _data: u8) {
+        self.reads = 0;
+    }
+
+    fn peek(&self, _addr: u16) -> u8 {
+        self.reads
+    }
+}
+
+#[test]
+fn ram_and_rom() {
+    let mut ram = Ram::new(0x0100);
+    ram.write(0x0110, 0x42);
+    assert_eq!(ram.read(0x0010), 0x42);
+
+    let mut rom = Rom::new(vec![0x01, 0x02]);
+    rom.write(0x0000, 0xFF);
+    assert_eq!(rom.read(0x0000), 0x01);
+    assert_eq!(rom.read_u16(0x0000), 0x0201);
+}
+
+#[test]
+fn empty_devices() {
+    let mut ram = Ram::new(0);
+    ram.write(0x1234, 0x42);
+    assert_eq!(ram.read(0x1234), 0x00);
+
+    let mut rom = Rom::new(Vec::new());
+    assert_eq!(rom.read(0xFFFF), 0x00);
+}
+
+#[test]
+fn mirrored_region() {
+    let mut map = MemoryMap::new();
+    map.map(0x0000, 0x1FFF, Ram::new(0x0800)).mirror(0x07FF);
+
+    map.write(0x0001, 0x42);
+    assert_eq!(map.read(0x0801), 0x42);
+    assert_eq!(map.read(0x1801), 0x42);
+}
+
+#[test]
+fn read_only_region() {
+    let mut map = MemoryMap::new();
+    map.map(0x8000, 0xFFFF, Ram::new(0x8000)).read_only();
+
+    map.write(0x8000, 0x42);
+    assert_eq!(map.read(0x8000), 0x00);
+}
+
+#[test]
+fn devices_see_relative_addresses() {
+    let mut map = MemoryMap::new();
+    map.map(0x8000, 0x8001, Rom::new(vec![0x11, 0x22]));
+    assert_eq!(map.read(0x8001), 0x22);
+}
+
+#[test]
+fn unmapped_reads_return_open_bus() {
+    let mut map = MemoryMap::new();
+    map.map(0x0000, 0x00FF, Rom::new(vec![0x5A]));
+
+    assert_eq!(map.read(0x0000), 0x5A);
+    assert_eq!(map.read(0x4000), 0x5A);
+    map.write(0x4000, 0x01);
+    assert_eq!(map.peek(0x4000), 0x5A);
+}
+
+#[test]
+fn earlier_mappings_take_precedence() {
+    let mut map = MemoryMap::new();
+    map.map(0x2000, 0x2000, Rom::new(vec![0x01]));
+    map.map(0x0000, 0xFFFF, Rom::new(vec![0x02]));
+    assert_eq!(map.read(0x2000), 0x01);
+    assert_eq!(map.read(0x2001), 0x02);
+}
+
+#[test]
+fn peek_has_no_side_effects() {
+    let mut map = MemoryMap::new();
+    map.map(0x4000, 0x4000, Counter { reads: 0 });
+
+    assert_eq!(map.read(0x4000), 1);
+    assert_eq!(map.peek(0x4000), 1);
+    assert_eq!(map.read(0x4000), 2);
+}
+
+#[test]
+fn cpu_on_memory_map() {
+    // RAM at 0x0000, program ROM at 0x8000 with the reset vector at its end
+    let mut rom = vec![0xEA; 0x8000];
+    // LDA $4000, STA $0801 (mirror of $0001), BRK
+    rom[..6].copy_from_slice(&[0xAD, 0x00, 0x40, 0x8D, 0x01, 0x08]);
+    rom[6] = 0x00;
+    rom[0x7FFC] = 0x00;
+    rom[0x7FFD] = 0x80;
+
+    let mut map = MemoryMap::new();
+    map.map(0x0000, 0x1FFF, Ram::new(0x0800)).mirror(0x07FF);
+    map.map(0x4000, 0x4000, Counter { reads: 0 });
+    map.map(0x8000, 0xFFFF, Rom::new(rom)).read_only();
+
+    let mut cpu = CPU::new(map);
+    cpu.power_on();
+    cpu.clock().unwrap();
+
+    assert_eq!(cpu.get_a(), 0x01);
+    assert_eq!(cpu.bus().peek(0x0001), 0x01);
+    assert_eq!(cpu.get_memory()[0x1001], 0x01);
+}
+
+#[test]
+fn cpu_on_boxed_memory() {
+    let mut cpu: CPU<Box<dyn Memory>> = CPU::new(Box::new(Bus::new()));
+    cpu.load_program(vec![0xA9, 0x07, 0x00]);
+    cpu.power_on();
+    cpu.clock().unwrap();
+    assert_eq!(cpu.get_a(), 0x07);
+}
@@ -0,0 +1,106 @@
+use std::path::PathBuf;
+
+use emulatorr::{
+    asm::assemble,
+    core::{
+        bus::Bus,
+        cpu::CPU,
+    },
+    nes::nestest::{self, NestestOutcome, AUTOMATION_START},
+};
+
+/// Power on with `source` assembled at 0xC000, like nestest in automation mode
+fn cpu_with(source: &str) -> CPU {
+    let program = assemble(&format!(".org $C000\n{}", source)).unwrap();
+    let mut cpu: CPU = CPU::new(Bus::new());
+    for (i, byte) in program.bytes.iter().enumerate() {
+        cpu.write(program.origin + i as u16, *byte);
+    }
+    cpu.power_on();
+    cpu.set_pc(AUTOMATION_START);
+    cpu
+}
+
+#[test]
+fn first_lines_of_nestest() {
+    let mut cpu = cpu_with("JMP $C5F5\n.org $C5F5\nLDX #$00\nSTX $00");
+    assert_eq!(nestest::trace_line(&cpu), "C000  4C F5 C5  JMP $C5F5                       A:00 X:00 Y:00 P:24 SP:FD CYC:7");
+    cpu.step().unwrap();
+    assert_eq!(nestest::trace_line(&cpu), "C5F5  A2 00     LDX #$00                        A:00 X:00 Y:00 P:24 SP:FD CYC:10");
+    cpu.step().unwrap();
+    assert_eq!(nestest::trace_line(&cpu), "C5F7  86 00     STX $00 = 00                    A:00 X:00 Y:00 P:26 SP:FD CYC:12");
+}
+
+#[test]
+fn operand_annotations() {
+    let line = |source: &str| {
+        let mut cpu = cpu_with(source);
+        // Run the setup, then format the last instruction
+        for _ in 0..source.lines().count() - 1 {
+            cpu.step().unwrap();
+        }
+        let line = nestest::trace_line(&cpu);
+        line[16..48].trim_end().to_string()
+    };
+
+    assert_eq!(line("LSR A"), "LSR A");
+    assert_eq!(line("LDX #$02\nLDA $10,X"), "LDA $10,X @ 12 = 00");
+    assert_eq!(line("LDY #$01\nLDX $FF,Y"), "LDX $FF,Y @ 00 = 00");
+    assert_eq!(line("LDA #$5A\nSTA $0633\nLDX #$01\nLDA $0632,X"), "LDA $0632,X @ 0633 = 5A");
+    assert_eq!(line("LDA #$00\nSTA $80\nLDA #$02\nSTA $81\nLDA ($80,X)"), "LDA ($80,X) @ 80 = 0200 = 00");
+    assert_eq!(line("LDA #$02\nSTA $8A\nLDY #$04\nLDA ($89),Y"), "LDA ($89),Y = 0200 @ 0204 = 00");
+    assert_eq!(line("LDA #$7E\nSTA $0200\nLDA #$DB\nSTA $0201\nJMP ($0200)"), "JMP ($0200) = DB7E");
+    assert_eq!(line("JSR $C72D"), "JSR $C72D");
+    assert_eq!(line("BCS $C010"), "BCS $C010");
+
+    // Undocumented opcodes are marked with a star, and ISC is called ISB
+    let mut cpu = cpu_with(".byte $04, $A9, $E7, $10");
+    assert!(nestest::trace_line(&cpu).starts_with("C000  04 A9    *NOP $A9 = 00  "));
+    cpu.step().unwrap();
+    assert!(nestest::trace_line(&cpu).starts_with("C002  E7 10    *ISB $10 = 00  "));
+}
+
+#[test]
+fn ppu_column_is_ignored() {
+    let line = "C000  4C F5 C5  JMP $C5F5                       A:00 X:00 Y:00 P:24 SP:FD PPU:  0, 21 CYC:7";
+    assert_eq!(nestest::without_ppu(line), "C000  4C F5 C5  JMP $C5F5                       A:00 X:00 Y:00 P:24 SP:FD CYC:7");
+}
+
+#[test]
+fn stops_at_first_divergence() {
+    let source = "LDX #$05\nloop: DEX\nBNE loop\nLDA #$01\nBRK";
+
+    // Record a golden log from the emulator itself
+    let mut cpu = cpu_with(source);
+    let mut log = Vec::new();
+    for _ in 0..14 {
+        log.push(nestest::trace_line(&cpu));
+        cpu.step().unwrap();
+    }
+    assert_eq!(nestest::compare(&mut cpu_with(source), &log.join("\n")), NestestOutcome::Matched { lines: 14 });
+
+    // Change a register in line 8
+    log[7] = log[7].replace("X:02", "X:03");
+    match nestest::compare(&mut cpu_with(source), &log.join("\n")) {
+        NestestOutcome::Diverged(divergence) => {
+            assert_eq!(divergence.line, 8);
+            assert!(divergence.actual.contains("X:02"));
+            assert!(divergence.expected.contains("X:03"));
+            assert_eq!(divergence.context, log[2..7].to_vec());
+        },
+        outcome => panic!("unexpected outcome: {}", outcome),
+    }
+}
+
+#[test]
+fn nestest_rom() {
+    let dir = PathBuf::from(std::env::var("NESTEST_DIR").unwrap_or_else(|_| "tests/roms".to_string()));
+    let (rom, log) = (dir.join("nestest.nes"), dir.join("nestest.log"));
+    if !rom.exists() || !log.exists() {
+        eprintln!("skipping, nestest.nes and nestest.log not found in {}", dir.display());
+        return;
+    }
+
+    let outcome = nestest::run(&rom, &log).unwrap();
+    assert!(outcome.passed(), "{}", outcome);
+}
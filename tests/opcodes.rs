@@ -0,0 +1,78 @@
+use emulatorr::core::{
+    cpu::{
+        CPU,
+        CpuVariant,
+        AddressingMode,
+    },
+    opcodes::{self, Mnemonic, OPCODES},
+    bus::Bus,
+};
+
+#[test]
+fn decode_fixed_entries() {
+    let ora = opcodes::decode(0x01);
+    assert_eq!(ora.mnemonic, Mnemonic::ORA);
+    assert_eq!(ora.mode, AddressingMode::IDX);
+
+    let iny = opcodes::decode(0xC8);
+    assert_eq!(iny.mnemonic, Mnemonic::INY);
+    assert_eq!(iny.mode, AddressingMode::IMP);
+
+    let lda = opcodes::decode(0xB1);
+    assert_eq!(lda.mnemonic, Mnemonic::LDA);
+    assert_eq!(lda.mode, AddressingMode::IDY);
+    assert!(lda.page_cross);
+}
+
+const VARIANTS: [CpuVariant; 5] = [
+    CpuVariant::Nmos6502,
+    CpuVariant::Ricoh2A03,
+    CpuVariant::Cmos65C02,
+    CpuVariant::Rockwell65C02,
+    CpuVariant::Wdc65C02,
+];
+
+#[test]
+fn lengths_match_addressing_modes() {
+    for variant in VARIANTS {
+        for (opcode, op) in opcodes::table(variant).iter().enumerate() {
+            let expected = match op.mode {
+                AddressingMode::IMP | AddressingMode::ACC => 1,
+                AddressingMode::ABS | AddressingMode::ABX | AddressingMode::ABY | AddressingMode::IND => 3,
+                AddressingMode::IAX | AddressingMode::ZPR => 3,
+                _ => 2,
+            };
+            assert_eq!(op.len, expected, "{:?} opcode 0x{:02X}", variant, opcode);
+        }
+    }
+}
+
+#[test]
+fn documented_opcode_count() {
+    let count = OPCODES.iter().filter(|op| !op.illegal).count();
+    assert_eq!(count, 151);
+}
+
+#[test]
+fn cmos_opcode_counts() {
+    let count = |variant| opcodes::table(variant).iter().filter(|op| !op.illegal).count();
+    assert_eq!(count(CpuVariant::Cmos65C02), 178);
+    assert_eq!(count(CpuVariant::Rockwell65C02), 210);
+    assert_eq!(count(CpuVariant::Wdc65C02), 212);
+}
+
+#[test]
+fn operands_consumed_per_length() {
+    // Every opcode that reads memory should leave PC right after its operand
+    // (ZPR only consumes the zero page address, the instruction itself reads the branch offset)
+    for variant in VARIANTS {
+        for (opcode, op) in opcodes::table(variant).iter().enumerate() {
+            if op.mode == AddressingMode::IMP || op.mode == AddressingMode::ACC || op.mode == AddressingMode::ZPR {
+                continue;
+            }
+            let mut cpu: CPU = CPU::custom(0, 0, 0, 0, 0x0300, 0, opcode as u8, Bus::new());
+            cpu.get_address(op.mode);
+            assert_eq!(cpu.get_pc(), 0x0300 + op.len as u16 - 1, "{:?} opcode 0x{:02X}", variant, opcode);
+        }
+    }
+}
@@ -0,0 +1,328 @@
+mod common;
+
+use common::{ines_cartridge, nes_bus};
+use emulatorr::{
+    asm,
+    core::memory::Memory,
+    nes::{
+        bus::{NesBus, OAM_DMA_CYCLES},
+        cartridge::Cartridge,
+        mapper::{self, Mapper},
+        ppu::{Ppu, HEIGHT, WIDTH},
+        Nes,
+    },
+};
+
+/// NROM-128 cartridge with `program` at 0x8000, NMI vector 0x8100 and reset vector 0x8000
+///
+/// CHR tile 0 is blank, tile 1 solid in color 1, and tile 2 has only its leftmost column set.
+fn cartridge(program: &[u8], vertical: bool) -> Cartridge {
+    let mut prg = vec![0xEA; 0x4000];
+    prg[..program.len()].copy_from_slice(program);
+    prg[0x3FFA..].copy_from_slice(&[0x00, 0x81, 0x00, 0x80, 0x00, 0x80]);
+    let mut chr = vec![0; 0x2000];
+    chr[0x10..0x18].fill(0xFF);
+    chr[0x20..0x28].fill(0x80);
+    ines_cartridge(&prg, &chr, vertical as u8)
+}
+
+fn assemble(source: &str) -> Vec<u8> {
+    asm::assemble(source).unwrap_or_else(|error| panic!("{}", error)).bytes
+}
+
+fn bus(vertical: bool) -> NesBus {
+    nes_bus(cartridge(&[], vertical))
+}
+
+/// Point PPUADDR at `addr` and write `data` through PPUDATA
+fn write_vram(bus: &mut NesBus, addr: u16, data: &[u8]) {
+    bus.read(0x2002);
+    bus.write(0x2006, (addr >> 8) as u8);
+    bus.write(0x2006, addr as u8);
+    for &byte in data {
+        bus.write(0x2007, byte);
+    }
+}
+
+fn read_vram(bus: &mut NesBus, addr: u16) -> u8 {
+    bus.read(0x2002);
+    bus.write(0x2006, (addr >> 8) as u8);
+    bus.write(0x2006, addr as u8);
+    // The first read only fills the buffer
+    bus.read(0x2007);
+    bus.read(0x2007)
+}
+
+/// Run the PPU until the start of vblank
+fn run_frame(ppu: &mut Ppu, mapper: &mut dyn Mapper) {
+    while !ppu.take_frame() {
+        ppu.tick(mapper);
+    }
+}
+
+/// RGB of the pixel at (`x`, `y`)
+fn pixel(framebuffer: &[u8], x: usize, y: usize) -> [u8; 3] {
+    let offset = (y * WIDTH + x) * 3;
+    [framebuffer[offset], framebuffer[offset + 1], framebuffer[offset + 2]]
+}
+
+#[test]
+fn ppudata_reads_are_buffered() {
+    let mut bus = bus(false);
+    write_vram(&mut bus, 0x2000, &[0x11, 0x22, 0x33]);
+
+    bus.read(0x2002);
+    bus.write(0x2006, 0x20);
+    bus.write(0x2006, 0x00);
+    assert_eq!(bus.read(0x2007), 0x00);
+    assert_eq!(bus.read(0x2007), 0x11);
+    assert_eq!(bus.read(0x2007), 0x22);
+    // Registers are mirrored every 8 bytes
+    assert_eq!(bus.read(0x3FFF), 0x33);
+
+    // Pattern tables come from the cartridge
+    assert_eq!(read_vram(&mut bus, 0x0010), 0xFF);
+}
+
+#[test]
+fn ppudata_increments_by_32() {
+    let mut bus = bus(false);
+    bus.write(0x2000, 0x04);
+    write_vram(&mut bus, 0x2000, &[0x01, 0x02]);
+    bus.write(0x2000, 0x00);
+    assert_eq!(read_vram(&mut bus, 0x2000), 0x01);
+    assert_eq!(read_vram(&mut bus, 0x2020), 0x02);
+}
+
+#[test]
+fn palette_reads_are_immediate_and_mirrored() {
+    let mut bus = bus(false);
+    write_vram(&mut bus, 0x3F00, &[0x21]);
+    write_vram(&mut bus, 0x3F14, &[0x05]);
+
+    bus.read(0x2002);
+    bus.write(0x2006, 0x3F);
+    bus.write(0x2006, 0x10);
+    assert_eq!(bus.read(0x2007), 0x21);
+    // The buffer gets the nametable byte underneath
+    write_vram(&mut bus, 0x2F11, &[0x99]);
+    bus.write(0x2006, 0x3F);
+    bus.write(0x2006, 0x11);
+    bus.read(0x2007);
+    bus.write(0x2006, 0x20);
+    bus.write(0x2006, 0x00);
+    assert_eq!(bus.read(0x2007), 0x99);
+
+    let ppu = bus.ppu();
+    assert_eq!(ppu.peek(0x3F04, bus.mapper()), 0x05);
+    assert_eq!(ppu.peek(0x3F20, bus.mapper()), 0x21);
+}
+
+#[test]
+fn nametable_mirroring() {
+    let mut horizontal = bus(false);
+    write_vram(&mut horizontal, 0x2000, &[0xAA]);
+    assert_eq!(read_vram(&mut horizontal, 0x2400), 0xAA);
+    assert_eq!(read_vram(&mut horizontal, 0x2800), 0x00);
+    assert_eq!(read_vram(&mut horizontal, 0x3000), 0xAA);
+
+    let mut vertical = bus(true);
+    write_vram(&mut vertical, 0x2000, &[0xAA]);
+    assert_eq!(read_vram(&mut vertical, 0x2400), 0x00);
+    assert_eq!(read_vram(&mut vertical, 0x2800), 0xAA);
+}
+
+#[test]
+fn status_read_clears_vblank_and_write_toggle() {
+    let mut bus = bus(false);
+    // Pre-render line to vblank: 241 scanlines of 341 dots, plus one dot
+    bus.tick(241 * 341 / 3 + 2);
+    assert_eq!(bus.ppu().scanline(), 241);
+    assert_eq!(bus.peek(0x2002) & 0x80, 0x80);
+    assert_eq!(bus.read(0x2002) & 0x80, 0x80);
+    assert_eq!(bus.read(0x2002) & 0x80, 0x00);
+
+    // Reading PPUSTATUS between the two PPUADDR writes restarts the address
+    bus.write(0x2006, 0x12);
+    bus.read(0x2002);
+    bus.write(0x2006, 0x20);
+    bus.write(0x2006, 0x05);
+    bus.write(0x2007, 0x77);
+    assert_eq!(read_vram(&mut bus, 0x2005), 0x77);
+}
+
+#[test]
+fn vblank_nmi() {
+    // Enable NMI and spin; the NMI handler counts frames at 0x0010
+    let program = assemble(
+        "
+        .org $8000
+        LDA #$80
+        STA $2000
+    loop:
+        JMP loop
+        .org $8100
+        INC $10
+        RTI
+        ",
+    );
+    let mut nes = Nes::new(cartridge(&program, false)).unwrap();
+    nes.power_on();
+
+    for frame in 1..=3 {
+        nes.run_frame().unwrap();
+        // The NMI is taken after the instruction that crossed into vblank
+        nes.step().unwrap();
+        nes.step().unwrap();
+        assert_eq!(nes.cpu().bus().peek(0x0010), frame);
+    }
+}
+
+#[test]
+fn renders_background() {
+    let mut bus = bus(false);
+    write_vram(&mut bus, 0x3F00, &[0x21, 0x16]);
+    // Solid tiles in the top left 2x2 tile square
+    write_vram(&mut bus, 0x2000, &[1, 1]);
+    write_vram(&mut bus, 0x2020, &[1, 1]);
+    bus.write(0x2005, 0);
+    bus.write(0x2005, 0);
+    bus.write(0x2001, 0x0A);
+
+    // The first frame starts at the first visible line, so the second one has all the fetches
+    for _ in 0..2 {
+        while !bus.ppu_mut().take_frame() {
+            bus.tick(1);
+        }
+    }
+    let framebuffer = bus.ppu().framebuffer();
+    assert_eq!(framebuffer.len(), WIDTH * HEIGHT * 3);
+    assert_eq!(pixel(framebuffer, 0, 0), [152, 34, 32]);
+    assert_eq!(pixel(framebuffer, 15, 15), [152, 34, 32]);
+    assert_eq!(pixel(framebuffer, 16, 0), [76, 154, 236]);
+    assert_eq!(pixel(framebuffer, 100, 200), [76, 154, 236]);
+}
+
+#[test]
+fn fine_x_scroll() {
+    let mut mapper = mapper::from_cartridge(cartridge(&[], false)).unwrap();
+    let mut ppu = Ppu::new();
+    ppu.write(0x3F00, 0x0F, mapper.as_mut());
+    ppu.write(0x3F01, 0x30, mapper.as_mut());
+    for addr in 0x2000..0x23C0 {
+        ppu.write(addr, 2, mapper.as_mut());
+    }
+    ppu.write_register(0x2005, 3, mapper.as_mut());
+    ppu.write_register(0x2005, 0, mapper.as_mut());
+    ppu.write_register(0x2001, 0x0A, mapper.as_mut());
+
+    run_frame(&mut ppu, mapper.as_mut());
+    run_frame(&mut ppu, mapper.as_mut());
+    for x in 0..WIDTH {
+        let lit = pixel(ppu.framebuffer(), x, 100) == [236, 238, 236];
+        assert_eq!(lit, x % 8 == 5, "pixel {}", x);
+    }
+}
+
+#[test]
+fn framebuffer_holds_last_complete_frame() {
+    let mut mapper = mapper::from_cartridge(cartridge(&[], false)).unwrap();
+    let mut ppu = Ppu::new();
+    // The backdrop is drawn even with rendering disabled
+    ppu.write(0x3F00, 0x16, mapper.as_mut());
+    run_frame(&mut ppu, mapper.as_mut());
+    run_frame(&mut ppu, mapper.as_mut());
+
+    // Halfway through the next frame, only the picture being drawn has changed
+    ppu.write(0x3F00, 0x30, mapper.as_mut());
+    while ppu.scanline() != 120 {
+        ppu.tick(mapper.as_mut());
+    }
+    assert_eq!(pixel(ppu.framebuffer(), 0, 50), [152, 34, 32]);
+    assert_eq!(pixel(ppu.framebuffer(), 0, 200), [152, 34, 32]);
+    assert_eq!(pixel(ppu.picture(), 0, 50), [236, 238, 236]);
+    assert_eq!(pixel(ppu.picture(), 0, 200), [152, 34, 32]);
+
+    run_frame(&mut ppu, mapper.as_mut());
+    assert_eq!(pixel(ppu.framebuffer(), 0, 200), [236, 238, 236]);
+}
+
+#[test]
+fn sprite_zero_hit_and_priority() {
+    let mut mapper = mapper::from_cartridge(cartridge(&[], false)).unwrap();
+    let mut ppu = Ppu::new();
+    ppu.write(0x3F01, 0x16, mapper.as_mut());
+    ppu.write(0x3F11, 0x2A, mapper.as_mut());
+    ppu.write(0x2000 + 4 * 32 + 4, 1, mapper.as_mut());
+    ppu.write(0x2000 + 4 * 32 + 5, 1, mapper.as_mut());
+    // Solid background at (32..48, 32..40), with sprite 0 in front of it at (32, 32) and sprite 1 behind it at (44, 32)
+    // (OAM holds the Y coordinate minus one)
+    for byte in [31, 1, 0x00, 32, 31, 1, 0x20, 44] {
+        ppu.write_oam(byte);
+    }
+    ppu.write_register(0x2001, 0x1E, mapper.as_mut());
+
+    run_frame(&mut ppu, mapper.as_mut());
+    run_frame(&mut ppu, mapper.as_mut());
+    assert_eq!(ppu.peek_register(0x2002, mapper.as_ref()) & 0x40, 0x40);
+
+    let framebuffer = ppu.framebuffer();
+    assert_eq!(pixel(framebuffer, 32, 32), [76, 208, 32]);
+    assert_eq!(pixel(framebuffer, 39, 39), [76, 208, 32]);
+    assert_eq!(pixel(framebuffer, 40, 32), [152, 34, 32]);
+    assert_eq!(pixel(framebuffer, 44, 32), [152, 34, 32]);
+    // Past the background, the sprite behind it shows
+    assert_eq!(pixel(framebuffer, 48, 32), [76, 208, 32]);
+    assert_eq!(pixel(framebuffer, 32, 40), [0x54, 0x54, 0x54]);
+}
+
+#[test]
+fn sprite_overflow() {
+    let mut mapper = mapper::from_cartridge(cartridge(&[], false)).unwrap();
+    let mut ppu = Ppu::new();
+    for sprite in 0..9 {
+        for byte in [50, 1, 0, sprite * 10] {
+            ppu.write_oam(byte);
+        }
+    }
+    ppu.write_register(0x2001, 0x10, mapper.as_mut());
+
+    run_frame(&mut ppu, mapper.as_mut());
+    assert_eq!(ppu.read_register(0x2002, mapper.as_mut()) & 0x20, 0x20);
+    // Cleared at the pre-render line
+    while ppu.scanline() != 0 {
+        ppu.tick(mapper.as_mut());
+    }
+    assert_eq!(ppu.read_register(0x2002, mapper.as_mut()) & 0x20, 0);
+}
+
+#[test]
+fn oam_dma() {
+    let program = assemble(
+        "
+        .org $8000
+        LDX #0
+    fill:
+        TXA
+        STA $0200,X
+        INX
+        BNE fill
+        LDA #$02
+        STA $4014
+        BRK
+        ",
+    );
+    let mut nes = Nes::new(cartridge(&program, false)).unwrap();
+    nes.power_on();
+    while nes.cpu().get_pc() != 0x800B {
+        nes.step().unwrap();
+    }
+    let before = nes.cpu().cycles();
+    nes.step().unwrap();
+
+    let oam = nes.cpu().bus().ppu().oam();
+    assert!(oam.iter().enumerate().all(|(i, &byte)| byte == i as u8));
+    // STA abs takes 4 cycles, and DMA one more when it starts on an odd cycle
+    let stall = nes.cpu().cycles() - before - 4;
+    assert_eq!(stall, OAM_DMA_CYCLES + (before + 4) % 2);
+}
@@ -0,0 +1,82 @@
+use emulatorr::core::{
+    cpu::{
+        CPU,
+        CpuConfig,
+        CpuVariant,
+        Flags,
+        PowerOnState,
+    },
+    bus::Bus,
+};
+
+#[test]
+fn reset_reads_vector_immediately() {
+    let mut cpu: CPU = CPU::new(Bus::new());
+    cpu.write_u16(0xFFFC, 0x1234);
+    cpu.reset();
+    assert_eq!(cpu.get_pc(), 0x1234);
+    assert_eq!(cpu.cycles(), 7);
+}
+
+#[test]
+fn reset_decrements_sp_without_writing() {
+    let mut cpu: CPU = CPU::custom(0x11, 0x22, 0x33, 0x01, 0x0600, 0x00, 0, Bus::filled(0xAA));
+    cpu.reset();
+    assert_eq!(cpu.get_sp(), 0xFE);
+    assert!(cpu.get_flag(Flags::I));
+
+    // Nothing is pushed, and A, X and Y are kept
+    assert!(cpu.get_memory()[0x0100..0x0200].iter().all(|byte| *byte == 0xAA));
+    assert_eq!((cpu.get_a(), cpu.get_x(), cpu.get_y()), (0x11, 0x22, 0x33));
+}
+
+#[test]
+fn pc_at_reset_vector_address_is_not_special() {
+    // JMP $FFFC, where the reset vector's low byte (0x00) is a BRK
+    let mut cpu: CPU = CPU::new(Bus::new());
+    cpu.load_program(vec![0x4C, 0xFC, 0xFF]);
+    cpu.reset();
+    cpu.advance().unwrap();
+    assert_eq!(cpu.get_pc(), 0xFFFC);
+
+    // The next step fetches the opcode at 0xFFFC instead of reloading the vector
+    cpu.advance().unwrap();
+    assert_eq!(cpu.get_pc(), 0xFFFD);
+    assert_eq!(cpu.get_opcode(), 0x00);
+}
+
+#[test]
+fn first_advance_executes_an_instruction() {
+    let mut cpu: CPU = CPU::new(Bus::new());
+    cpu.load_program(vec![0xA9, 0x01, 0x00]);
+    cpu.reset();
+    cpu.advance().unwrap();
+    assert_eq!(cpu.get_a(), 0x01);
+}
+
+#[test]
+fn power_on_state() {
+    let mut cpu: CPU = CPU::new(Bus::new());
+    cpu.power_on();
+    assert_eq!(cpu.get_sp(), 0xFD);
+    assert_eq!(cpu.get_sr(), Flags::I as u8 | Flags::U as u8);
+
+    let config = CpuConfig {
+        power_on: PowerOnState::Fixed { a: 0x01, x: 0x02, y: 0x03, sp: 0x10, sr: Flags::D as u8 },
+        ..CpuConfig::default()
+    };
+    let mut cpu: CPU = CPU::with_config(Bus::new(), config);
+    cpu.power_on();
+    assert_eq!((cpu.get_a(), cpu.get_x(), cpu.get_y()), (0x01, 0x02, 0x03));
+    assert_eq!(cpu.get_sp(), 0x0D);
+    assert!(cpu.get_flag(Flags::D));
+    assert_eq!(cpu.cycles(), 7);
+}
+
+#[test]
+fn cmos_reset_clears_decimal() {
+    let mut cpu: CPU = CPU::with_variant(Bus::new(), CpuVariant::Cmos65C02);
+    cpu.set_flag(Flags::D, true);
+    cpu.reset();
+    assert!(!cpu.get_flag(Flags::D));
+}
@@ -0,0 +1,171 @@
+mod common;
+
+use std::{cell::Cell, rc::Rc};
+
+use common::TestMachine;
+use emulatorr::{
+    asm::assemble,
+    core::{
+        cpu::CPU,
+        memory::{Memory, MemoryMap, Ram, Rom},
+        rewind::RewindConfig,
+    },
+};
+
+/// Memory-mapped register that counts the writes it receives
+struct Register {
+    writes: Rc<Cell<u32>>,
+}
+
+impl Memory for Register {
+    fn read(&mut self, _addr: u16) -> u8 {
+        0
+    }
+
+    fn write(&mut self, _addr: u16, _data: u8) {
+        self.writes.set(self.writes.get() + 1);
+    }
+
+    fn peek(&self, _addr: u16) -> u8 {
+        0
+    }
+}
+
+#[test]
+fn step_back_undoes_registers_and_memory() {
+    let mut cpu = TestMachine::from_asm("LDA #$2A\nSTA $10\nPHA\nINX\nBRK").powered_on().rewind(RewindConfig::default()).cpu;
+    let start = cpu.registers();
+    for _ in 0..4 {
+        cpu.step().unwrap();
+    }
+    assert_eq!(cpu.rewind_history().unwrap().len(), 4);
+
+    assert!(cpu.step_back());
+    assert_eq!(cpu.get_x(), 0x00);
+    assert_eq!(cpu.get_pc(), 0x0605);
+
+    // PHA
+    assert!(cpu.step_back());
+    assert_eq!(cpu.get_sp(), 0xFD);
+    assert_eq!(cpu.get_memory()[0x01FD], 0x00);
+
+    // STA $10
+    assert!(cpu.step_back());
+    assert_eq!(cpu.get_memory()[0x10], 0x00);
+
+    assert!(cpu.step_back());
+    assert_eq!(cpu.registers(), start);
+    assert_eq!(cpu.cycles(), 7);
+    assert!(!cpu.step_back());
+}
+
+#[test]
+fn replay_after_step_back() {
+    let config = RewindConfig { interval: 8, ..RewindConfig::default() };
+    let mut cpu = TestMachine::from_asm("LDX #$00\nloop: INX\nTXA\nSTA $0300,X\nJMP loop").powered_on().rewind(config).cpu;
+    for _ in 0..100 {
+        cpu.step().unwrap();
+    }
+    let registers = cpu.registers();
+    let memory = cpu.get_memory();
+
+    // Step back across several snapshots, then forward again
+    for _ in 0..50 {
+        assert!(cpu.step_back());
+    }
+    for _ in 0..50 {
+        cpu.step().unwrap();
+    }
+    assert_eq!(cpu.registers(), registers);
+    assert_eq!(cpu.get_memory(), memory);
+}
+
+#[test]
+fn rewind_jumps_to_snapshot() {
+    let config = RewindConfig { interval: 10, ..RewindConfig::default() };
+    let mut cpu = TestMachine::from_asm("LDX #$00\nloop: INX\nJMP loop").powered_on().rewind(config).cpu;
+    for _ in 0..35 {
+        cpu.step().unwrap();
+    }
+    let cycles = cpu.cycles();
+
+    // Snapshots were taken before instructions 0, 10, 20 and 30; 20 cycles back lands on the one at 20
+    assert!(cpu.rewind(20));
+    assert!(cpu.cycles() <= cycles - 20);
+    assert_eq!(cpu.rewind_history().unwrap().len(), 20);
+    let x = cpu.get_x();
+
+    cpu.step().unwrap();
+    assert!(cpu.step_back());
+    assert_eq!(cpu.get_x(), x);
+
+    // Nothing before power on
+    assert!(!cpu.rewind(1_000_000));
+}
+
+#[test]
+fn budget_drops_oldest_history() {
+    // Every snapshot holds all 64K of memory
+    let config = RewindConfig { interval: 10, budget: 200 * 1024 };
+    let mut cpu = TestMachine::from_asm("loop: INX\nJMP loop").powered_on().rewind(config).cpu;
+    for _ in 0..200 {
+        cpu.step().unwrap();
+    }
+
+    let history = cpu.rewind_history().unwrap();
+    assert!(history.memory_used() <= config.budget);
+    assert!(history.len() < 200);
+    assert!(history.len() >= 10);
+
+    let steps = history.len();
+    for _ in 0..steps {
+        assert!(cpu.step_back());
+    }
+    assert!(!cpu.step_back());
+}
+
+#[test]
+fn load_state_clears_history() {
+    let mut cpu = TestMachine::from_asm("INX\nINX\nBRK").powered_on().rewind(RewindConfig::default()).cpu;
+    let state = cpu.save_state();
+    cpu.step().unwrap();
+    cpu.load_state(&state).unwrap();
+    assert!(!cpu.step_back());
+}
+
+#[test]
+fn step_back_stops_at_device_writes() {
+    let writes = Rc::new(Cell::new(0));
+    let mut map = MemoryMap::new();
+    map.map(0x0000, 0x07FF, Ram::new(0x0800));
+    map.map(0xD000, 0xD000, Register { writes: writes.clone() });
+    map.map(0xFFFC, 0xFFFD, Rom::new(vec![0x00, 0x06]));
+
+    let mut cpu = CPU::new(map);
+    cpu.load_program(assemble("LDA #$01\nSTA $10\nSTA $D000\nINX\nSTA $11\nBRK").unwrap().bytes);
+    cpu.power_on();
+    cpu.enable_rewind(RewindConfig::default());
+    for _ in 0..5 {
+        cpu.step().unwrap();
+    }
+    assert_eq!(writes.get(), 1);
+
+    // STA $11 and INX
+    assert!(cpu.step_back());
+    assert_eq!(cpu.read(0x0011), 0x00);
+    assert!(cpu.step_back());
+    assert_eq!(cpu.get_x(), 0x00);
+
+    // The register's write isn't replayed, and nothing changes
+    let registers = cpu.registers();
+    assert!(!cpu.step_back());
+    assert_eq!(cpu.registers(), registers);
+    assert_eq!(writes.get(), 1);
+    assert_eq!(cpu.rewind_history().unwrap().len(), 3);
+
+    // The snapshot from before the first instruction still works
+    assert!(cpu.rewind(1));
+    assert_eq!(cpu.get_pc(), 0x0600);
+    assert_eq!(cpu.read(0x0010), 0x00);
+    assert_eq!(writes.get(), 1);
+}
@@ -0,0 +1,134 @@
+use emulatorr::core::{
+    cpu::{CPU, CpuVariant},
+    memory::{MemoryMap, Ram, Rom},
+    rewind::RewindConfig,
+    state::{self, StateError},
+    bus::Bus,
+};
+
+/// CPU that has run `LDA #$42, STA $10, LDX #$07, PHA`
+fn cpu_after_program() -> CPU {
+    let mut cpu: CPU = CPU::new(Bus::new());
+    cpu.quick_start(vec![0xA9, 0x42, 0x85, 0x10, 0xA2, 0x07, 0x48, 0x00]);
+    cpu
+}
+
+#[test]
+fn round_trip() {
+    let cpu = cpu_after_program();
+    let state = cpu.save_state();
+
+    let mut restored: CPU = CPU::new(Bus::new());
+    restored.load_state(&state).unwrap();
+    assert_eq!(restored.get_state(), cpu.get_state());
+    assert_eq!(restored.cycles(), cpu.cycles());
+    assert_eq!(restored.get_memory(), cpu.get_memory());
+}
+
+#[test]
+fn restore_earlier_point() {
+    let mut cpu = cpu_after_program();
+    let state = cpu.save_state();
+    let registers = cpu.get_state();
+
+    // INX, STX $10
+    cpu.quick_start(vec![0xE8, 0x86, 0x10, 0x00]);
+    assert_ne!(cpu.get_state(), registers);
+
+    cpu.load_state(&state).unwrap();
+    assert_eq!(cpu.get_state(), registers);
+    assert_eq!(cpu.read(0x0010), 0x42);
+}
+
+#[test]
+fn header() {
+    let state = cpu_after_program().save_state();
+    assert_eq!(&state[..4], b"EMSS");
+    assert_eq!(u16::from_le_bytes([state[4], state[5]]), state::VERSION);
+}
+
+#[test]
+fn corrupted_states_are_rejected() {
+    let state = cpu_after_program().save_state();
+    let mut cpu: CPU = CPU::new(Bus::new());
+
+    let mut corrupted = state.clone();
+    corrupted[100] ^= 0x01;
+    assert_eq!(cpu.load_state(&corrupted), Err(StateError::ChecksumMismatch));
+
+    assert_eq!(cpu.load_state(&state[..state.len() - 1]), Err(StateError::UnexpectedEof));
+    assert_eq!(cpu.load_state(b"nope"), Err(StateError::BadMagic));
+
+    let mut future = state.clone();
+    future[4] = 0xFF;
+    future[5] = 0xFF;
+    assert_eq!(cpu.load_state(&future), Err(StateError::UnsupportedVersion(0xFFFF)));
+
+    // Nothing was changed by the failed loads
+    assert_eq!(cpu.get_pc(), 0x0000);
+}
+
+#[test]
+fn variant_must_match() {
+    let state = cpu_after_program().save_state();
+    let mut cpu: CPU = CPU::with_variant(Bus::new(), CpuVariant::Cmos65C02);
+    assert_eq!(cpu.load_state(&state), Err(StateError::Mismatch("CPU variant")));
+}
+
+#[test]
+fn memory_map_state() {
+    let memory_map = || {
+        let mut map = MemoryMap::new();
+        map.map(0x0000, 0x07FF, Ram::new(0x0800));
+        map.map(0xFF00, 0xFFFF, Rom::new(vec![0x06; 0x100]));
+        map
+    };
+
+    let mut cpu = CPU::new(memory_map());
+    cpu.write(0x0123, 0x45);
+    let state = cpu.save_state();
+
+    let mut restored = CPU::new(memory_map());
+    restored.load_state(&state).unwrap();
+    assert_eq!(restored.read(0x0123), 0x45);
+
+    // A differently shaped map can't load it
+    let mut other = MemoryMap::new();
+    other.map(0x0000, 0x07FF, Ram::new(0x0800));
+    assert_eq!(CPU::new(other).load_state(&state), Err(StateError::Mismatch("memory map")));
+}
+
+#[test]
+fn failed_bus_load_changes_nothing() {
+    let memory_map = |size| {
+        let mut map = MemoryMap::new();
+        map.map(0x0000, 0x07FF, Ram::new(0x0800));
+        map.map(0x0800, 0x0FFF, Ram::new(size));
+        map.map(0xFFFC, 0xFFFD, Rom::new(vec![0x00, 0x06]));
+        map
+    };
+
+    let mut saved = CPU::new(memory_map(0x0200));
+    saved.quick_start(vec![0xA9, 0x42, 0x85, 0x10, 0xA2, 0x07, 0x00]);
+    let state = saved.save_state();
+
+    // The first RAM loads, then the second doesn't fit
+    let mut cpu = CPU::new(memory_map(0x0100));
+    cpu.enable_rewind(RewindConfig::default());
+    cpu.quick_start(vec![0xA0, 0x09, 0x84, 0x10, 0x00]);
+    let registers = cpu.get_state();
+    let cycles = cpu.cycles();
+    let history = cpu.rewind_history().unwrap().len();
+    assert_eq!(cpu.load_state(&state), Err(StateError::Mismatch("memory size")));
+
+    assert_eq!(cpu.get_state(), registers);
+    assert_eq!(cpu.cycles(), cycles);
+    assert_eq!(cpu.read(0x0010), 0x09);
+    assert_eq!(cpu.read(0x0600), 0xA0);
+    assert_eq!(cpu.rewind_history().unwrap().len(), history);
+}
+
+#[test]
+fn crc32_check_value() {
+    assert_eq!(state::crc32(b"123456789"), 0xCBF4_3926);
+}
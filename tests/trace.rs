@@ -0,0 +1,90 @@
+mod common;
+
+use common::TestMachine;
+use emulatorr::core::trace::{TraceEntry, TraceFormat, Tracer};
+
+#[test]
+fn records_registers_and_cycles() {
+    let mut cpu = TestMachine::from_asm("LDA #$05\nTAX\nBRK").powered_on().tracer(Tracer::new(16)).cpu;
+    cpu.clock().unwrap();
+
+    let tracer = cpu.tracer().unwrap();
+    let entries: Vec<&TraceEntry> = tracer.entries().collect();
+    assert_eq!(entries.len(), 2);
+
+    let lda = entries[0];
+    assert_eq!(lda.instruction.addr, 0x0600);
+    assert_eq!(lda.instruction.bytes(), vec![0xA9, 0x05]);
+    assert_eq!(lda.before.a, 0x00);
+    assert_eq!(lda.after.a, 0x05);
+    assert_eq!(lda.after.pc, 0x0602);
+    assert_eq!(lda.cycles, 2);
+    assert_eq!(lda.total_cycles, 7);
+
+    let tax = tracer.last().unwrap();
+    assert_eq!(tax.after.x, 0x05);
+    assert_eq!(tax.total_cycles, 9);
+}
+
+#[test]
+fn ring_buffer_keeps_latest() {
+    let mut cpu = TestMachine::from_asm("LDX #10\nloop: DEX\nBNE loop\nBRK").powered_on().tracer(Tracer::new(3)).cpu;
+    cpu.clock().unwrap();
+
+    let addrs: Vec<u16> = cpu.tracer().unwrap().entries().map(|entry| entry.instruction.addr).collect();
+    assert_eq!(addrs, vec![0x0603, 0x0602, 0x0603]);
+    assert_eq!(cpu.tracer().unwrap().last().unwrap().after.x, 0);
+
+    cpu.tracer_mut().unwrap().clear();
+    assert_eq!(cpu.tracer().unwrap().entries().count(), 0);
+}
+
+#[test]
+fn address_filter() {
+    let tracer = Tracer::new(16).with_range(0x0700..=0x07FF);
+    let mut cpu = TestMachine::from_asm("JSR sub\nNOP\nBRK\n.org $0700\nsub: INX\nRTS").powered_on().tracer(tracer).cpu;
+    cpu.clock().unwrap();
+
+    let addrs: Vec<u16> = cpu.tracer().unwrap().entries().map(|entry| entry.instruction.addr).collect();
+    assert_eq!(addrs, vec![0x0700, 0x0701]);
+
+    // Taking the tracer detaches it
+    let tracer = cpu.take_tracer().unwrap();
+    assert_eq!(tracer.entries().count(), 2);
+    assert!(cpu.tracer().is_none());
+}
+
+#[test]
+fn formats() {
+    let mut cpu = TestMachine::from_asm("LDA #$05\nBRK").powered_on().tracer(Tracer::new(1)).cpu;
+    cpu.clock().unwrap();
+    let entry = cpu.tracer().unwrap().last().unwrap();
+
+    assert_eq!(entry.to_string(), "0600  A9 05     LDA #$05         A:00 X:00 Y:00 P:24 SP:FD CYC:7");
+    assert_eq!(
+        entry.format(TraceFormat::Verbose),
+        "0600  A9 05     LDA #$05         A:00 X:00 Y:00 P:24 SP:FD CYC:7 -> A:05 X:00 Y:00 P:24 SP:FD (+2)",
+    );
+    assert_eq!(entry.format(TraceFormat::Custom(|entry| format!("{:04X}", entry.instruction.addr))), "0600");
+}
+
+#[test]
+fn writes_to_file() {
+    let path = std::env::temp_dir().join(format!("emulatorr-trace-{}.log", std::process::id()));
+    let tracer = Tracer::new(0)
+        .with_format(TraceFormat::Custom(|entry| entry.instruction.to_string()))
+        .with_file(&path)
+        .unwrap();
+
+    let mut cpu = TestMachine::from_asm("LDA #$05\nTAX\nBRK").powered_on().tracer(tracer).cpu;
+    cpu.clock().unwrap();
+
+    // A capacity of 0 only writes to the file
+    assert_eq!(cpu.tracer().unwrap().entries().count(), 0);
+
+    // Dropping the tracer flushes it
+    drop(cpu.take_tracer());
+    let text = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(text, "LDA #$05\nTAX\n");
+}
@@ -0,0 +1,203 @@
+use emulatorr::core::{
+    cpu::{
+        CPU,
+        CpuConfig,
+        CpuVariant,
+        Flags,
+        StepOutcome,
+    },
+    bus::Bus,
+};
+
+/// Run `program` on a CPU of the given variant until it reaches a BRK
+fn run(variant: CpuVariant, program: Vec<u8>) -> CPU {
+    let mut cpu: CPU = CPU::with_variant(Bus::new(), variant);
+    cpu.quick_start(program);
+    cpu
+}
+
+#[test]
+fn default_variant_is_nmos() {
+    let cpu: CPU = CPU::new(Bus::new());
+    assert_eq!(cpu.config().variant, CpuVariant::Nmos6502);
+    assert_eq!(CpuConfig::default().variant, CpuVariant::Nmos6502);
+}
+
+#[test]
+fn jmp_indirect_page_wrap() {
+    // JMP ($02FF), with the pointer split over 0x02FF/0x0300 (and 0x0200 for the NMOS bug)
+    let program = vec![0x6C, 0xFF, 0x02];
+    let setup = |variant| {
+        let mut cpu: CPU = CPU::with_variant(Bus::new(), variant);
+        cpu.write(0x02FF, 0x00);
+        cpu.write(0x0300, 0x07);
+        cpu.write(0x0200, 0x08);
+        cpu.quick_start(program.clone());
+        cpu
+    };
+
+    // The jump lands on a zeroed page, so PC stops right after the BRK there
+    assert_eq!(setup(CpuVariant::Nmos6502).get_pc(), 0x0801);
+    assert_eq!(setup(CpuVariant::Ricoh2A03).get_pc(), 0x0801);
+    assert_eq!(setup(CpuVariant::Cmos65C02).get_pc(), 0x0701);
+}
+
+#[test]
+fn ricoh_has_no_decimal_mode() {
+    // SED, CLC, LDA #$09, ADC #$01
+    let mut cpu = run(CpuVariant::Ricoh2A03, vec![0xF8, 0x18, 0xA9, 0x09, 0x69, 0x01, 0x00]);
+    assert_eq!(cpu.get_a(), 0x0A);
+    assert!(cpu.get_flag(Flags::D));
+
+    let cpu = run(CpuVariant::Nmos6502, vec![0xF8, 0x18, 0xA9, 0x09, 0x69, 0x01, 0x00]);
+    assert_eq!(cpu.get_a(), 0x10);
+}
+
+#[test]
+fn cmos_decimal_flags() {
+    // SED, CLC, LDA #$99, ADC #$01: the NMOS takes Z from the binary sum (0x9A), the 65C02 from the result
+    let program = vec![0xF8, 0x18, 0xA9, 0x99, 0x69, 0x01, 0x00];
+
+    let mut cpu = run(CpuVariant::Nmos6502, program.clone());
+    assert_eq!(cpu.get_a(), 0x00);
+    assert!(!cpu.get_flag(Flags::Z));
+
+    let mut cpu = run(CpuVariant::Cmos65C02, program);
+    assert_eq!(cpu.get_a(), 0x00);
+    assert!(cpu.get_flag(Flags::Z));
+    assert!(cpu.get_flag(Flags::C));
+    assert!(!cpu.get_flag(Flags::N));
+
+    // SED, SEC, LDA #$00, SBC #$01
+    let mut cpu = run(CpuVariant::Cmos65C02, vec![0xF8, 0x38, 0xA9, 0x00, 0xE9, 0x01, 0x00]);
+    assert_eq!(cpu.get_a(), 0x99);
+    assert!(cpu.get_flag(Flags::N));
+    assert!(!cpu.get_flag(Flags::C));
+}
+
+#[test]
+fn cmos_decimal_extra_cycle() {
+    // Reset (7) + SED (2) + ADC #$01 (2, +1 on CMOS)
+    let nmos = run(CpuVariant::Nmos6502, vec![0xF8, 0x69, 0x01, 0x00]);
+    let cmos = run(CpuVariant::Cmos65C02, vec![0xF8, 0x69, 0x01, 0x00]);
+    assert_eq!(nmos.cycles(), 11);
+    assert_eq!(cmos.cycles(), 12);
+}
+
+#[test]
+fn stz_and_zero_page_indirect() {
+    // LDA #$42, STA $10, STZ $10, point $20 at 0x3000, LDA #$55, STA ($20), LDA #$00, LDA ($20)
+    let program = vec![
+        0xA9, 0x42, 0x85, 0x10, 0x64, 0x10,
+        0xA9, 0x00, 0x85, 0x20, 0xA9, 0x30, 0x85, 0x21,
+        0xA9, 0x55, 0x92, 0x20,
+        0xA9, 0x00, 0xB2, 0x20,
+        0x00,
+    ];
+    let cpu = run(CpuVariant::Cmos65C02, program);
+    assert_eq!(cpu.get_memory()[0x10], 0x00);
+    assert_eq!(cpu.get_memory()[0x3000], 0x55);
+    assert_eq!(cpu.get_a(), 0x55);
+}
+
+#[test]
+fn push_and_pull_index_registers() {
+    // LDX #$12, LDY #$34, PHX, PHY, PLX, PLY
+    let cpu = run(CpuVariant::Cmos65C02, vec![0xA2, 0x12, 0xA0, 0x34, 0xDA, 0x5A, 0xFA, 0x7A, 0x00]);
+    assert_eq!(cpu.get_x(), 0x34);
+    assert_eq!(cpu.get_y(), 0x12);
+    assert_eq!(cpu.get_sp(), 0xFD);
+}
+
+#[test]
+fn branch_always() {
+    // BRA +2, LDA #$01 (skipped), LDX #$02
+    let cpu = run(CpuVariant::Cmos65C02, vec![0x80, 0x02, 0xA9, 0x01, 0xA2, 0x02, 0x00]);
+    assert_eq!(cpu.get_a(), 0x00);
+    assert_eq!(cpu.get_x(), 0x02);
+    assert_eq!(cpu.cycles(), 7 + 5);
+}
+
+#[test]
+fn test_and_set_reset_bits() {
+    // LDA #$F0, STA $10, LDA #$3C, TSB $10, TRB $10 (A & M != 0 both times)
+    let mut cpu = run(CpuVariant::Cmos65C02, vec![0xA9, 0xF0, 0x85, 0x10, 0xA9, 0x3C, 0x04, 0x10, 0x00]);
+    assert_eq!(cpu.get_memory()[0x10], 0xFC);
+    assert!(!cpu.get_flag(Flags::Z));
+
+    let mut cpu = run(CpuVariant::Cmos65C02, vec![0xA9, 0xF0, 0x85, 0x10, 0xA9, 0x3C, 0x14, 0x10, 0x00]);
+    assert_eq!(cpu.get_memory()[0x10], 0xC0);
+    assert!(!cpu.get_flag(Flags::Z));
+}
+
+#[test]
+fn accumulator_increment_and_bit_immediate() {
+    // LDA #$7F, INC A, BIT #$00 (only Z is affected)
+    let mut cpu = run(CpuVariant::Cmos65C02, vec![0xA9, 0x7F, 0x1A, 0x89, 0x00, 0x00]);
+    assert_eq!(cpu.get_a(), 0x80);
+    assert!(cpu.get_flag(Flags::Z));
+    assert!(cpu.get_flag(Flags::N));
+    assert!(!cpu.get_flag(Flags::V));
+}
+
+#[test]
+fn jmp_absolute_indexed_indirect() {
+    // LDX #$02, JMP ($0300,X) -> pointer at 0x0302 holds 0x0700
+    let mut cpu: CPU = CPU::with_variant(Bus::new(), CpuVariant::Cmos65C02);
+    cpu.write_u16(0x0302, 0x0700);
+    cpu.quick_start(vec![0xA2, 0x02, 0x7C, 0x00, 0x03]);
+    assert_eq!(cpu.get_pc(), 0x0701);
+}
+
+#[test]
+fn unused_cmos_opcodes_are_nops() {
+    // 0x03 and 0x0B are single byte NOPs, 0x02 skips one operand byte, 0x5C two
+    let cpu = run(CpuVariant::Cmos65C02, vec![0x03, 0x0B, 0x02, 0xA9, 0x5C, 0xA9, 0xA9, 0xE8, 0x00]);
+    assert_eq!(cpu.get_a(), 0x00);
+    assert_eq!(cpu.get_x(), 0x01);
+}
+
+#[test]
+fn strict_mode_runs_cmos_nops() {
+    let config = CpuConfig { variant: CpuVariant::Cmos65C02, strict: true, ..Default::default() };
+    let mut cpu: CPU = CPU::with_config(Bus::new(), config);
+    cpu.quick_start(vec![0x03, 0x02, 0xA9, 0x5C, 0xA9, 0xA9, 0xE8, 0x00]);
+    assert_eq!(cpu.get_a(), 0x00);
+    assert_eq!(cpu.get_x(), 0x01);
+}
+
+#[test]
+fn rockwell_bit_instructions() {
+    // SMB3 $10, RMB0 $10 ($10 starts at 0x01), BBS3 $10 +2, LDA #$01 (skipped), BBR0 $10 +2, LDX #$01 (skipped)
+    let mut cpu: CPU = CPU::with_variant(Bus::new(), CpuVariant::Rockwell65C02);
+    cpu.write(0x0010, 0x01);
+    cpu.quick_start(vec![
+        0xB7, 0x10, 0x07, 0x10,
+        0xBF, 0x10, 0x02, 0xA9, 0x01,
+        0x0F, 0x10, 0x02, 0xA2, 0x01,
+        0x00,
+    ]);
+    assert_eq!(cpu.get_memory()[0x10], 0x08);
+    assert_eq!(cpu.get_a(), 0x00);
+    assert_eq!(cpu.get_x(), 0x00);
+
+    // The plain 65C02 treats the same opcode as a single byte NOP
+    let cpu = run(CpuVariant::Cmos65C02, vec![0xB7, 0xE8, 0x00]);
+    assert_eq!(cpu.get_x(), 0x01);
+}
+
+#[test]
+fn wdc_wait_and_stop() {
+    let cpu = run(CpuVariant::Wdc65C02, vec![0xCB, 0xE8, 0x00]);
+    assert!(cpu.is_waiting());
+    assert_eq!(cpu.get_x(), 0x00);
+
+    let mut cpu = run(CpuVariant::Wdc65C02, vec![0xDB, 0xE8, 0x00]);
+    assert!(cpu.is_stopped());
+    assert_eq!(cpu.step(), Ok(StepOutcome::Stopped));
+    assert_eq!(cpu.get_x(), 0x00);
+
+    // Without WAI/STP, the Rockwell part treats these as NOPs
+    let cpu = run(CpuVariant::Rockwell65C02, vec![0xCB, 0xDB, 0xE8, 0x00]);
+    assert_eq!(cpu.get_x(), 0x01);
+}